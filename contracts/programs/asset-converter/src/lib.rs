@@ -1,10 +1,21 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
 use anchor_spl::associated_token::AssociatedToken;
-use std::collections::HashMap;
+use switchboard_v2::AggregatorAccountData;
 
 declare_id!("AssetConv11111111111111111111111111111111");
 
+/// Remaining accounts supplied per batch conversion:
+/// conversion pair, user source, user target, source vault, target vault, admin fee account
+pub const BATCH_ACCOUNTS_PER_CONVERSION: usize = 6;
+/// Number of recent conversions kept in each user's history
+pub const MAX_CONVERSION_HISTORY: usize = 20;
+/// Approximate number of slots in a day, used to reset per-user conversion limits
+pub const SLOTS_PER_DAY: u64 = 216_000;
+
+/// Fixed-point base for conversion rates (1e9 = one whole source token per whole target token)
+pub const RATE_PRECISION: u128 = 1_000_000_000;
+
 #[program]
 pub mod asset_converter {
     use super::*;
@@ -20,8 +31,9 @@ pub mod asset_converter {
         converter_state.conversion_fee_rate = conversion_fee_rate;
         converter_state.total_conversions = 0;
         converter_state.total_volume = 0;
+        converter_state.daily_user_limit = 0;
         converter_state.is_paused = false;
-        converter_state.bump = *ctx.bumps.get("converter_state").unwrap();
+        converter_state.bump = ctx.bumps.converter_state;
         
         msg!("Asset Converter initialized with fee rate: {} bps", conversion_fee_rate);
         Ok(())
@@ -32,7 +44,7 @@ pub mod asset_converter {
         ctx: Context<AddConversionPair>,
         source_mint: Pubkey,
         target_mint: Pubkey,
-        conversion_rate: u64, // Rate in lamports (1e9 = 1:1 ratio)
+        conversion_rate: u64, // Whole-token rate scaled by RATE_PRECISION (1e9 = 1:1)
         min_amount: u64,
         max_amount: u64,
     ) -> Result<()> {
@@ -42,11 +54,28 @@ pub mod asset_converter {
         conversion_pair.source_mint = source_mint;
         conversion_pair.target_mint = target_mint;
         conversion_pair.conversion_rate = conversion_rate;
+        conversion_pair.source_decimals = ctx.accounts.source_mint.decimals;
+        conversion_pair.target_decimals = ctx.accounts.target_mint.decimals;
         conversion_pair.min_amount = min_amount;
         conversion_pair.max_amount = max_amount;
         conversion_pair.is_active = true;
         conversion_pair.total_converted = 0;
-        conversion_pair.bump = *ctx.bumps.get("conversion_pair").unwrap();
+        conversion_pair.source_price_feed = None;
+        conversion_pair.target_price_feed = None;
+        conversion_pair.max_slippage_bps = 0;
+        conversion_pair.max_oracle_staleness = 0;
+        conversion_pair.bump = ctx.bumps.conversion_pair;
+
+        // Record both mints as pair liquidity so they can never be swept as stray tokens
+        let source_registry = &mut ctx.accounts.source_mint_registry;
+        source_registry.mint = source_mint;
+        source_registry.pair_count = source_registry.pair_count.checked_add(1).unwrap();
+        source_registry.bump = ctx.bumps.source_mint_registry;
+
+        let target_registry = &mut ctx.accounts.target_mint_registry;
+        target_registry.mint = target_mint;
+        target_registry.pair_count = target_registry.pair_count.checked_add(1).unwrap();
+        target_registry.bump = ctx.bumps.target_mint_registry;
         
         msg!("Added conversion pair: {} -> {}", source_mint, target_mint);
         Ok(())
@@ -57,114 +86,195 @@ pub mod asset_converter {
         ctx: Context<ConvertAsset>,
         amount: u64,
     ) -> Result<()> {
-        let converter_state = &ctx.accounts.converter_state;
-        let conversion_pair = &mut ctx.accounts.conversion_pair;
-        
-        require!(!converter_state.is_paused, ErrorCode::ProgramPaused);
-        require!(conversion_pair.is_active, ErrorCode::ConversionPairInactive);
-        require!(amount >= conversion_pair.min_amount, ErrorCode::AmountTooSmall);
-        require!(amount <= conversion_pair.max_amount, ErrorCode::AmountTooLarge);
-
-        // Calculate conversion amounts
-        let target_amount = (amount as u128)
-            .checked_mul(conversion_pair.conversion_rate as u128)
-            .unwrap()
-            .checked_div(1_000_000_000) // Normalize from 1e9 base
-            .unwrap() as u64;
-
-        let fee_amount = (target_amount as u128)
-            .checked_mul(converter_state.conversion_fee_rate as u128)
-            .unwrap()
-            .checked_div(10_000) // Basis points
-            .unwrap() as u64;
-
-        let final_amount = target_amount.checked_sub(fee_amount).unwrap();
-
-        // Transfer source tokens from user to program vault
-        let transfer_source_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.user_source_account.to_account_info(),
-                to: ctx.accounts.source_vault.to_account_info(),
-                authority: ctx.accounts.user.to_account_info(),
-            },
+        let conversion_rate = ctx.accounts.conversion_pair.conversion_rate;
+        execute_conversion(ctx.accounts, &ctx.bumps, amount, conversion_rate)
+    }
+
+    /// Convert using a rate derived from the pair's source and target price feeds.
+    /// The oracle rate must be fresh and within the pair's slippage bound of the stored rate.
+    pub fn convert_asset_with_oracle(
+        ctx: Context<ConvertAssetWithOracle>,
+        amount: u64,
+    ) -> Result<()> {
+        let accounts = &mut ctx.accounts.convert;
+        let conversion_pair = &accounts.conversion_pair;
+        require!(
+            conversion_pair.source_price_feed.is_some() && conversion_pair.target_price_feed.is_some(),
+            ErrorCode::PriceFeedNotConfigured
         );
-        token::transfer(transfer_source_ctx, amount)?;
 
-        // Transfer target tokens from program vault to user
-        let seeds = &[
-            b"converter_state",
-            &[converter_state.bump],
-        ];
-        let signer = &[&seeds[..]];
+        let current_time = Clock::get()?.unix_timestamp;
+        let source_price = read_oracle_price(
+            &ctx.accounts.source_price_feed,
+            current_time,
+            conversion_pair.max_oracle_staleness,
+        )?;
+        let target_price = read_oracle_price(
+            &ctx.accounts.target_price_feed,
+            current_time,
+            conversion_pair.max_oracle_staleness,
+        )?;
 
-        let transfer_target_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.target_vault.to_account_info(),
-                to: ctx.accounts.user_target_account.to_account_info(),
-                authority: ctx.accounts.converter_state.to_account_info(),
-            },
-            signer,
+        // Whole-token rate; decimals are applied when the rate is used in `target_amount`
+        let oracle_rate = oracle_rate(source_price, target_price)?;
+        require!(oracle_rate > 0, ErrorCode::InvalidOraclePrice);
+
+        // Guard against feed manipulation by bounding deviation from the stored fallback rate
+        let fallback_rate = conversion_pair.conversion_rate;
+        require!(fallback_rate > 0, ErrorCode::OracleRateDeviation);
+        let deviation_bps = (oracle_rate.abs_diff(fallback_rate) as u128)
+            .checked_mul(10_000)
+            .ok_or(ErrorCode::ConversionOverflow)?
+            / fallback_rate as u128;
+        require!(
+            deviation_bps <= conversion_pair.max_slippage_bps as u128,
+            ErrorCode::OracleRateDeviation
         );
-        token::transfer(transfer_target_ctx, final_amount)?;
-
-        // Transfer fee to admin account if fee > 0
-        if fee_amount > 0 {
-            let transfer_fee_ctx = CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.target_vault.to_account_info(),
-                    to: ctx.accounts.admin_fee_account.to_account_info(),
-                    authority: ctx.accounts.converter_state.to_account_info(),
-                },
-                signer,
-            );
-            token::transfer(transfer_fee_ctx, fee_amount)?;
-        }
 
-        // Update statistics
-        conversion_pair.total_converted = conversion_pair.total_converted
-            .checked_add(amount)
-            .unwrap();
+        execute_conversion(accounts, &ctx.bumps.convert, amount, oracle_rate)
+    }
 
-        // Emit conversion event
-        emit!(AssetConvertedEvent {
-            user: ctx.accounts.user.key(),
+    /// Configure the price feeds used by `convert_asset_with_oracle` (admin only).
+    /// Passing `None` for both feeds leaves the pair on its fixed rate.
+    pub fn set_price_feeds(
+        ctx: Context<UpdateConversionPair>,
+        source_price_feed: Option<Pubkey>,
+        target_price_feed: Option<Pubkey>,
+        max_slippage_bps: u16,
+        max_oracle_staleness: i64,
+    ) -> Result<()> {
+        require!(
+            source_price_feed.is_some() == target_price_feed.is_some(),
+            ErrorCode::PriceFeedNotConfigured
+        );
+        require!(max_slippage_bps <= 10_000, ErrorCode::InvalidOracleConfig);
+        require!(max_oracle_staleness > 0, ErrorCode::InvalidOracleConfig);
+
+        let conversion_pair = &mut ctx.accounts.conversion_pair;
+        conversion_pair.source_price_feed = source_price_feed;
+        conversion_pair.target_price_feed = target_price_feed;
+        conversion_pair.max_slippage_bps = max_slippage_bps;
+        conversion_pair.max_oracle_staleness = max_oracle_staleness;
+
+        emit!(PriceFeedsUpdatedEvent {
             source_mint: conversion_pair.source_mint,
             target_mint: conversion_pair.target_mint,
-            source_amount: amount,
-            target_amount: final_amount,
-            fee_amount,
+            source_price_feed,
+            target_price_feed,
+            max_slippage_bps,
+            max_oracle_staleness,
             timestamp: Clock::get()?.unix_timestamp,
         });
 
-        msg!(
-            "Converted {} {} to {} {} (fee: {})",
-            amount,
-            conversion_pair.source_mint,
-            final_amount,
-            conversion_pair.target_mint,
-            fee_amount
-        );
-
         Ok(())
     }
 
+    /// Return the user's recent conversions, oldest first
+    pub fn get_conversion_history(
+        ctx: Context<GetConversionHistory>,
+    ) -> Result<Vec<ConversionHistoryEntry>> {
+        Ok(ctx.accounts.user_history.entries_in_order())
+    }
+
     /// Batch convert multiple assets in a single transaction
-    pub fn batch_convert_assets(
-        ctx: Context<BatchConvertAssets>,
+    /// Accounts for each conversion are passed through `remaining_accounts` in groups of
+    /// `BATCH_ACCOUNTS_PER_CONVERSION`. Fees go to the admin's target-mint account, as for
+    /// single conversions, and every conversion is recorded in the user's history.
+    pub fn batch_convert_assets<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchConvertAssets<'info>>,
         conversions: Vec<ConversionRequest>,
     ) -> Result<()> {
+        let converter_state = &ctx.accounts.converter_state;
+        let converter_key = converter_state.key();
+        let admin_key = converter_state.admin;
+        let user_key = ctx.accounts.user.key();
+
+        require!(!converter_state.is_paused, ErrorCode::ProgramPaused);
         require!(conversions.len() <= 5, ErrorCode::TooManyConversions);
-        
-        for (i, conversion) in conversions.iter().enumerate() {
-            // Validate each conversion
+        require!(
+            ctx.remaining_accounts.len() == conversions.len() * BATCH_ACCOUNTS_PER_CONVERSION,
+            ErrorCode::BatchAccountMismatch
+        );
+
+        // The whole batch counts towards the user's daily ceiling
+        let batch_volume = conversions
+            .iter()
+            .try_fold(0u64, |total, conversion| total.checked_add(conversion.amount))
+            .ok_or(ErrorCode::ConversionOverflow)?;
+        let user_limit = &mut ctx.accounts.user_limit;
+        if user_limit.user == Pubkey::default() {
+            user_limit.user = user_key;
+            user_limit.bump = ctx.bumps.user_limit;
+        }
+        user_limit.record(batch_volume, converter_state.daily_user_limit, Clock::get()?.slot)?;
+
+        let user_history = &mut ctx.accounts.user_history;
+        if user_history.user == Pubkey::default() {
+            user_history.user = user_key;
+            user_history.bump = ctx.bumps.user_history;
+        }
+
+        for (i, (conversion, accounts)) in conversions
+            .iter()
+            .zip(ctx.remaining_accounts.chunks(BATCH_ACCOUNTS_PER_CONVERSION))
+            .enumerate()
+        {
             require!(conversion.amount > 0, ErrorCode::InvalidAmount);
-            
-            // Process conversion (simplified - in full implementation, 
-            // you'd need to pass the appropriate accounts for each conversion)
-            msg!("Processing conversion {}: {} tokens", i + 1, conversion.amount);
+
+            let mut conversion_pair = Account::<ConversionPair>::try_from(&accounts[0])?;
+            let user_source_account = Account::<TokenAccount>::try_from(&accounts[1])?;
+            let user_target_account = Account::<TokenAccount>::try_from(&accounts[2])?;
+            let source_vault = Account::<TokenAccount>::try_from(&accounts[3])?;
+            let target_vault = Account::<TokenAccount>::try_from(&accounts[4])?;
+            let admin_fee_account = Account::<TokenAccount>::try_from(&accounts[5])?;
+
+            // Validate the supplied accounts belong to this conversion
+            let (expected_pair, _) = Pubkey::find_program_address(
+                &[
+                    b"conversion_pair",
+                    conversion.source_mint.as_ref(),
+                    conversion.target_mint.as_ref(),
+                ],
+                ctx.program_id,
+            );
+            require!(
+                conversion_pair.key() == expected_pair,
+                ErrorCode::BatchAccountMismatch
+            );
+            require!(
+                user_source_account.owner == user_key
+                    && user_source_account.mint == conversion.source_mint
+                    && user_target_account.owner == user_key
+                    && user_target_account.mint == conversion.target_mint
+                    && source_vault.owner == converter_key
+                    && source_vault.mint == conversion.source_mint
+                    && target_vault.owner == converter_key
+                    && target_vault.mint == conversion.target_mint
+                    && admin_fee_account.owner == admin_key
+                    && admin_fee_account.mint == conversion.target_mint,
+                ErrorCode::BatchAccountMismatch
+            );
+
+            let conversion_rate = conversion_pair.conversion_rate;
+            transfer_conversion(
+                ConversionAccounts {
+                    converter_state: &mut ctx.accounts.converter_state,
+                    conversion_pair: &mut conversion_pair,
+                    user: &ctx.accounts.user,
+                    user_source_account: &user_source_account,
+                    user_target_account: &user_target_account,
+                    source_vault: &source_vault,
+                    target_vault: &target_vault,
+                    admin_fee_account: &admin_fee_account,
+                    user_history: &mut ctx.accounts.user_history,
+                    token_program: &ctx.accounts.token_program,
+                },
+                conversion.amount,
+                conversion_rate,
+            )?;
+            conversion_pair.exit(ctx.program_id)?;
+
+            msg!("Processed conversion {}: {} tokens", i + 1, conversion.amount);
         }
 
         emit!(BatchConversionEvent {
@@ -202,6 +312,23 @@ pub mod asset_converter {
         Ok(())
     }
 
+    /// Set the per-user daily conversion ceiling in source token units (0 disables it)
+    pub fn set_daily_user_limit(
+        ctx: Context<AdminAction>,
+        daily_user_limit: u64,
+    ) -> Result<()> {
+        let converter_state = &mut ctx.accounts.converter_state;
+        converter_state.daily_user_limit = daily_user_limit;
+
+        emit!(DailyUserLimitUpdatedEvent {
+            admin: ctx.accounts.admin.key(),
+            daily_user_limit,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
     /// Update conversion rate for a pair
     pub fn update_conversion_rate(
         ctx: Context<UpdateConversionPair>,
@@ -228,7 +355,7 @@ pub mod asset_converter {
         amount: u64,
     ) -> Result<()> {
         let seeds = &[
-            b"converter_state",
+            b"converter_state".as_ref(),
             &[ctx.accounts.converter_state.bump],
         ];
         let signer = &[&seeds[..]];
@@ -252,6 +379,271 @@ pub mod asset_converter {
 
         Ok(())
     }
+
+    /// Recover tokens mistakenly sent to a converter-owned account (admin only).
+    /// Mints used by any conversion pair are rejected.
+    pub fn recover_stray_tokens(
+        ctx: Context<RecoverStrayTokens>,
+        amount: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.mint_registry.data_is_empty(),
+            ErrorCode::PairMintNotRecoverable
+        );
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(
+            ctx.accounts.stray_vault.amount >= amount,
+            ErrorCode::InsufficientVaultBalance
+        );
+
+        let seeds: &[&[u8]] = &[
+            b"converter_state",
+            &[ctx.accounts.converter_state.bump],
+        ];
+        let signer = &[seeds];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.stray_vault.to_account_info(),
+                to: ctx.accounts.admin_account.to_account_info(),
+                authority: ctx.accounts.converter_state.to_account_info(),
+            },
+            signer,
+        );
+        token::transfer(transfer_ctx, amount)?;
+
+        emit!(StrayTokensRecoveredEvent {
+            admin: ctx.accounts.admin.key(),
+            mint: ctx.accounts.stray_mint.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+// Shared conversion path for fixed-rate and oracle-priced conversions
+fn execute_conversion(
+    accounts: &mut ConvertAsset,
+    bumps: &ConvertAssetBumps,
+    amount: u64,
+    conversion_rate: u64,
+) -> Result<()> {
+    let converter_state = &accounts.converter_state;
+    require!(!converter_state.is_paused, ErrorCode::ProgramPaused);
+
+    // Enforce the per-user daily ceiling
+    let user_limit = &mut accounts.user_limit;
+    if user_limit.user == Pubkey::default() {
+        user_limit.user = accounts.user.key();
+        user_limit.bump = bumps.user_limit;
+    }
+    user_limit.record(amount, converter_state.daily_user_limit, Clock::get()?.slot)?;
+
+    let user_history = &mut accounts.user_history;
+    if user_history.user == Pubkey::default() {
+        user_history.user = accounts.user.key();
+        user_history.bump = bumps.user_history;
+    }
+
+    transfer_conversion(
+        ConversionAccounts {
+            converter_state: &mut accounts.converter_state,
+            conversion_pair: &mut accounts.conversion_pair,
+            user: &accounts.user,
+            user_source_account: &accounts.user_source_account,
+            user_target_account: &accounts.user_target_account,
+            source_vault: &accounts.source_vault,
+            target_vault: &accounts.target_vault,
+            admin_fee_account: &accounts.admin_fee_account,
+            user_history: &mut accounts.user_history,
+            token_program: &accounts.token_program,
+        },
+        amount,
+        conversion_rate,
+    )
+}
+
+// Accounts touched by a single conversion, whether from `ConvertAsset` or a batch group
+struct ConversionAccounts<'a, 'info> {
+    converter_state: &'a mut Account<'info, ConverterState>,
+    conversion_pair: &'a mut Account<'info, ConversionPair>,
+    user: &'a Signer<'info>,
+    user_source_account: &'a Account<'info, TokenAccount>,
+    user_target_account: &'a Account<'info, TokenAccount>,
+    source_vault: &'a Account<'info, TokenAccount>,
+    target_vault: &'a Account<'info, TokenAccount>,
+    admin_fee_account: &'a Account<'info, TokenAccount>,
+    user_history: &'a mut Account<'info, UserConversionHistory>,
+    token_program: &'a Program<'info, Token>,
+}
+
+// Check the pair's bounds, move the tokens and fee, and record the conversion in the
+// statistics, the user's history and an `AssetConvertedEvent`
+fn transfer_conversion(
+    accounts: ConversionAccounts,
+    amount: u64,
+    conversion_rate: u64,
+) -> Result<()> {
+    let converter_state = &accounts.converter_state;
+    let conversion_pair = accounts.conversion_pair;
+
+    require!(conversion_pair.is_active, ErrorCode::ConversionPairInactive);
+    require!(amount >= conversion_pair.min_amount, ErrorCode::AmountTooSmall);
+    require!(amount <= conversion_pair.max_amount, ErrorCode::AmountTooLarge);
+
+    // Calculate conversion amounts
+    let target_amount = conversion_pair.target_amount(amount, conversion_rate)?;
+
+    let fee_amount = (target_amount as u128)
+        .checked_mul(converter_state.conversion_fee_rate as u128)
+        .ok_or(ErrorCode::ConversionOverflow)?
+        .checked_div(10_000) // Basis points
+        .ok_or(ErrorCode::ConversionOverflow)? as u64;
+
+    let final_amount = target_amount
+        .checked_sub(fee_amount)
+        .ok_or(ErrorCode::ConversionOverflow)?;
+
+    // Fail clearly if the target vault can't cover the payout and fee
+    let required_vault_balance = final_amount
+        .checked_add(fee_amount)
+        .ok_or(ErrorCode::ConversionOverflow)?;
+    require!(
+        accounts.target_vault.amount >= required_vault_balance,
+        ErrorCode::InsufficientVaultBalance
+    );
+
+    // Transfer source tokens from user to program vault
+    let transfer_source_ctx = CpiContext::new(
+        accounts.token_program.to_account_info(),
+        Transfer {
+            from: accounts.user_source_account.to_account_info(),
+            to: accounts.source_vault.to_account_info(),
+            authority: accounts.user.to_account_info(),
+        },
+    );
+    token::transfer(transfer_source_ctx, amount)?;
+
+    // Transfer target tokens from program vault to user
+    let seeds = &[
+        b"converter_state".as_ref(),
+        &[converter_state.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let transfer_target_ctx = CpiContext::new_with_signer(
+        accounts.token_program.to_account_info(),
+        Transfer {
+            from: accounts.target_vault.to_account_info(),
+            to: accounts.user_target_account.to_account_info(),
+            authority: converter_state.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(transfer_target_ctx, final_amount)?;
+
+    // Transfer fee to admin account if fee > 0
+    if fee_amount > 0 {
+        let transfer_fee_ctx = CpiContext::new_with_signer(
+            accounts.token_program.to_account_info(),
+            Transfer {
+                from: accounts.target_vault.to_account_info(),
+                to: accounts.admin_fee_account.to_account_info(),
+                authority: converter_state.to_account_info(),
+            },
+            signer,
+        );
+        token::transfer(transfer_fee_ctx, fee_amount)?;
+    }
+
+    // Update statistics
+    conversion_pair.total_converted = conversion_pair.total_converted
+        .checked_add(amount)
+        .ok_or(ErrorCode::ConversionOverflow)?;
+
+    let converter_state = accounts.converter_state;
+    converter_state.total_conversions = converter_state.total_conversions
+        .checked_add(1)
+        .ok_or(ErrorCode::ConversionOverflow)?;
+    converter_state.total_volume = converter_state.total_volume
+        .checked_add(amount)
+        .ok_or(ErrorCode::ConversionOverflow)?;
+
+    // Append to the user's conversion history, evicting the oldest entry when full
+    accounts.user_history.push(ConversionHistoryEntry {
+        conversion_pair: conversion_pair.key(),
+        source_amount: amount,
+        target_amount: final_amount,
+        fee_amount,
+        slot: Clock::get()?.slot,
+    });
+
+    // Emit conversion event
+    emit!(AssetConvertedEvent {
+        user: accounts.user.key(),
+        source_mint: conversion_pair.source_mint,
+        target_mint: conversion_pair.target_mint,
+        source_amount: amount,
+        target_amount: final_amount,
+        fee_amount,
+        total_conversions: converter_state.total_conversions,
+        total_volume: converter_state.total_volume,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!(
+        "Converted {} {} to {} {} (fee: {})",
+        amount,
+        conversion_pair.source_mint,
+        final_amount,
+        conversion_pair.target_mint,
+        fee_amount
+    );
+
+    Ok(())
+}
+
+// Read a Switchboard price as `(mantissa, scale)`, rejecting feeds that haven't updated
+// within the staleness window
+fn read_oracle_price(
+    price_feed: &AccountInfo,
+    current_time: i64,
+    max_staleness: i64,
+) -> Result<(u128, u32)> {
+    let aggregator = AggregatorAccountData::new(price_feed)
+        .map_err(|_| error!(ErrorCode::InvalidOraclePrice))?;
+    require!(
+        current_time - aggregator.latest_confirmed_round.round_open_timestamp <= max_staleness,
+        ErrorCode::StaleOracle
+    );
+
+    let price = aggregator.get_result()
+        .map_err(|_| error!(ErrorCode::InvalidOraclePrice))?;
+    require!(price.mantissa > 0, ErrorCode::InvalidOraclePrice);
+
+    Ok((price.mantissa as u128, price.scale))
+}
+
+// Source price over target price in RATE_PRECISION units:
+// rate = source_mantissa * 10^target_scale * RATE_PRECISION / (target_mantissa * 10^source_scale)
+fn oracle_rate(source_price: (u128, u32), target_price: (u128, u32)) -> Result<u64> {
+    let (source_mantissa, source_scale) = source_price;
+    let (target_mantissa, target_scale) = target_price;
+
+    let numerator = 10u128
+        .checked_pow(target_scale)
+        .and_then(|factor| factor.checked_mul(source_mantissa))
+        .and_then(|value| value.checked_mul(RATE_PRECISION))
+        .ok_or(ErrorCode::ConversionOverflow)?;
+    let denominator = 10u128
+        .checked_pow(source_scale)
+        .and_then(|factor| factor.checked_mul(target_mantissa))
+        .ok_or(ErrorCode::ConversionOverflow)?;
+
+    u64::try_from(numerator / denominator).map_err(|_| error!(ErrorCode::ConversionOverflow))
 }
 
 #[derive(Accounts)]
@@ -289,6 +681,24 @@ pub struct AddConversionPair<'info> {
         bump
     )]
     pub conversion_pair: Account<'info, ConversionPair>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + MintRegistry::INIT_SPACE,
+        seeds = [b"mint_registry", source_mint.key().as_ref()],
+        bump
+    )]
+    pub source_mint_registry: Account<'info, MintRegistry>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + MintRegistry::INIT_SPACE,
+        seeds = [b"mint_registry", target_mint.key().as_ref()],
+        bump
+    )]
+    pub target_mint_registry: Account<'info, MintRegistry>,
     
     pub source_mint: Account<'info, Mint>,
     pub target_mint: Account<'info, Mint>,
@@ -302,6 +712,7 @@ pub struct AddConversionPair<'info> {
 #[derive(Accounts)]
 pub struct ConvertAsset<'info> {
     #[account(
+        mut,
         seeds = [b"converter_state"],
         bump = converter_state.bump
     )]
@@ -352,6 +763,24 @@ pub struct ConvertAsset<'info> {
         associated_token::authority = converter_state.admin
     )]
     pub admin_fee_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserConversionHistory::INIT_SPACE,
+        seeds = [b"conversion_history", user.key().as_ref()],
+        bump
+    )]
+    pub user_history: Account<'info, UserConversionHistory>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserConversionLimit::INIT_SPACE,
+        seeds = [b"user_limit", user.key().as_ref()],
+        bump
+    )]
+    pub user_limit: Account<'info, UserConversionLimit>,
     
     #[account(mut)]
     pub user: Signer<'info>,
@@ -361,18 +790,66 @@ pub struct ConvertAsset<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct ConvertAssetWithOracle<'info> {
+    pub convert: ConvertAsset<'info>,
+
+    /// CHECK: Switchboard aggregator, must match the pair's configured source feed
+    #[account(
+        constraint = Some(source_price_feed.key()) == convert.conversion_pair.source_price_feed
+            @ ErrorCode::PriceFeedMismatch
+    )]
+    pub source_price_feed: UncheckedAccount<'info>,
+
+    /// CHECK: Switchboard aggregator, must match the pair's configured target feed
+    #[account(
+        constraint = Some(target_price_feed.key()) == convert.conversion_pair.target_price_feed
+            @ ErrorCode::PriceFeedMismatch
+    )]
+    pub target_price_feed: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetConversionHistory<'info> {
+    #[account(
+        seeds = [b"conversion_history", user_history.user.as_ref()],
+        bump = user_history.bump
+    )]
+    pub user_history: Account<'info, UserConversionHistory>,
+}
+
 #[derive(Accounts)]
 pub struct BatchConvertAssets<'info> {
     #[account(
+        mut,
         seeds = [b"converter_state"],
         bump = converter_state.bump
     )]
     pub converter_state: Account<'info, ConverterState>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserConversionHistory::INIT_SPACE,
+        seeds = [b"conversion_history", user.key().as_ref()],
+        bump
+    )]
+    pub user_history: Account<'info, UserConversionHistory>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserConversionLimit::INIT_SPACE,
+        seeds = [b"user_limit", user.key().as_ref()],
+        bump
+    )]
+    pub user_limit: Account<'info, UserConversionLimit>,
     
     #[account(mut)]
     pub user: Signer<'info>,
     
     pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
@@ -426,6 +903,41 @@ pub struct WithdrawFees<'info> {
     pub token_program: Program<'info, Token>,
 }
 
+#[derive(Accounts)]
+pub struct RecoverStrayTokens<'info> {
+    #[account(
+        seeds = [b"converter_state"],
+        bump = converter_state.bump,
+        has_one = admin
+    )]
+    pub converter_state: Account<'info, ConverterState>,
+
+    pub stray_mint: Account<'info, Mint>,
+
+    /// CHECK: Must be uninitialized; an existing registry means the mint backs a conversion pair
+    #[account(
+        seeds = [b"mint_registry", stray_mint.key().as_ref()],
+        bump
+    )]
+    pub mint_registry: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = stray_mint,
+        token::authority = converter_state
+    )]
+    pub stray_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = stray_mint
+    )]
+    pub admin_account: Account<'info, TokenAccount>,
+
+    pub admin: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct ConverterState {
@@ -433,6 +945,7 @@ pub struct ConverterState {
     pub conversion_fee_rate: u64, // Basis points
     pub total_conversions: u64,
     pub total_volume: u64,
+    pub daily_user_limit: u64, // Per-user daily ceiling in source units, 0 = unlimited
     pub is_paused: bool,
     pub bump: u8,
 }
@@ -442,14 +955,118 @@ pub struct ConverterState {
 pub struct ConversionPair {
     pub source_mint: Pubkey,
     pub target_mint: Pubkey,
-    pub conversion_rate: u64, // Rate in lamports (1e9 = 1:1)
+    pub conversion_rate: u64, // Whole-token rate scaled by RATE_PRECISION (1e9 = 1:1)
+    pub source_decimals: u8,
+    pub target_decimals: u8,
     pub min_amount: u64,
     pub max_amount: u64,
     pub is_active: bool,
     pub total_converted: u64,
+    pub source_price_feed: Option<Pubkey>, // Switchboard feed; None keeps the fixed rate
+    pub target_price_feed: Option<Pubkey>,
+    pub max_slippage_bps: u16, // Max oracle deviation from conversion_rate
+    pub max_oracle_staleness: i64, // Seconds
+    pub bump: u8,
+}
+
+impl ConversionPair {
+    /// Convert raw source units into raw target units at `rate`, rescaling between
+    /// the two mints' decimals
+    pub fn target_amount(&self, amount: u64, rate: u64) -> Result<u64> {
+        let target_scale = 10u128
+            .checked_pow(self.target_decimals as u32)
+            .ok_or(ErrorCode::ConversionOverflow)?;
+        let source_scale = 10u128
+            .checked_pow(self.source_decimals as u32)
+            .ok_or(ErrorCode::ConversionOverflow)?;
+
+        let numerator = (amount as u128)
+            .checked_mul(rate as u128)
+            .and_then(|value| value.checked_mul(target_scale))
+            .ok_or(ErrorCode::ConversionOverflow)?;
+        let denominator = source_scale
+            .checked_mul(RATE_PRECISION)
+            .ok_or(ErrorCode::ConversionOverflow)?;
+
+        u64::try_from(numerator / denominator).map_err(|_| error!(ErrorCode::ConversionOverflow))
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct MintRegistry {
+    pub mint: Pubkey,
+    pub pair_count: u32, // Number of conversion pairs using this mint
     pub bump: u8,
 }
 
+#[account]
+#[derive(InitSpace)]
+pub struct UserConversionHistory {
+    pub user: Pubkey,
+    #[max_len(MAX_CONVERSION_HISTORY)]
+    pub entries: Vec<ConversionHistoryEntry>,
+    pub next_index: u8, // Slot overwritten by the next entry once the buffer is full
+    pub bump: u8,
+}
+
+impl UserConversionHistory {
+    pub fn push(&mut self, entry: ConversionHistoryEntry) {
+        let index = self.next_index as usize;
+        if self.entries.len() < MAX_CONVERSION_HISTORY {
+            self.entries.push(entry);
+        } else {
+            self.entries[index] = entry;
+        }
+        self.next_index = ((index + 1) % MAX_CONVERSION_HISTORY) as u8;
+    }
+
+    pub fn entries_in_order(&self) -> Vec<ConversionHistoryEntry> {
+        let split = (self.next_index as usize).min(self.entries.len());
+        let (newest, oldest) = self.entries.split_at(split);
+        oldest.iter().chain(newest.iter()).cloned().collect()
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct UserConversionLimit {
+    pub user: Pubkey,
+    pub daily_volume: u64,
+    pub last_reset_slot: u64,
+    pub bump: u8,
+}
+
+impl UserConversionLimit {
+    /// Add `amount` to today's volume, resetting the window every `SLOTS_PER_DAY`
+    pub fn record(&mut self, amount: u64, daily_limit: u64, current_slot: u64) -> Result<()> {
+        if current_slot.saturating_sub(self.last_reset_slot) >= SLOTS_PER_DAY {
+            self.daily_volume = 0;
+            self.last_reset_slot = current_slot;
+        }
+
+        let daily_volume = self.daily_volume
+            .checked_add(amount)
+            .ok_or(ErrorCode::ConversionOverflow)?;
+        require!(
+            daily_limit == 0 || daily_volume <= daily_limit,
+            ErrorCode::DailyLimitExceeded
+        );
+        self.daily_volume = daily_volume;
+
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct ConversionHistoryEntry {
+    pub conversion_pair: Pubkey,
+    pub source_amount: u64,
+    pub target_amount: u64,
+    pub fee_amount: u64,
+    pub slot: u64,
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct ConversionRequest {
     pub source_mint: Pubkey,
@@ -465,6 +1082,26 @@ pub struct AssetConvertedEvent {
     pub source_amount: u64,
     pub target_amount: u64,
     pub fee_amount: u64,
+    pub total_conversions: u64,
+    pub total_volume: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DailyUserLimitUpdatedEvent {
+    pub admin: Pubkey,
+    pub daily_user_limit: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PriceFeedsUpdatedEvent {
+    pub source_mint: Pubkey,
+    pub target_mint: Pubkey,
+    pub source_price_feed: Option<Pubkey>,
+    pub target_price_feed: Option<Pubkey>,
+    pub max_slippage_bps: u16,
+    pub max_oracle_staleness: i64,
     pub timestamp: i64,
 }
 
@@ -503,6 +1140,14 @@ pub struct FeesWithdrawnEvent {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct StrayTokensRecoveredEvent {
+    pub admin: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
 #[error_code]
 pub enum ErrorCode {
     #[msg("The conversion program is currently paused")]
@@ -517,8 +1162,26 @@ pub enum ErrorCode {
     InvalidAmount,
     #[msg("Too many conversions in batch (max 5)")]
     TooManyConversions,
-    #[msg("Insufficient vault balance")]
+    #[msg("Vault does not hold enough tokens to cover the transfer")]
     InsufficientVaultBalance,
     #[msg("Conversion rate calculation overflow")]
     ConversionOverflow,
+    #[msg("Token mint backs a conversion pair and cannot be recovered")]
+    PairMintNotRecoverable,
+    #[msg("Batch conversion accounts do not match the requested conversions")]
+    BatchAccountMismatch,
+    #[msg("Price feeds are not configured for this conversion pair")]
+    PriceFeedNotConfigured,
+    #[msg("Price feed does not match the conversion pair configuration")]
+    PriceFeedMismatch,
+    #[msg("Invalid oracle configuration")]
+    InvalidOracleConfig,
+    #[msg("Oracle price is stale")]
+    StaleOracle,
+    #[msg("Oracle returned an invalid price")]
+    InvalidOraclePrice,
+    #[msg("Oracle rate deviates too far from the fallback rate")]
+    OracleRateDeviation,
+    #[msg("Conversion would exceed the daily per-user limit")]
+    DailyLimitExceeded,
 }
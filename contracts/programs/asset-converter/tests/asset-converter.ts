@@ -0,0 +1,499 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, LAMPORTS_PER_SOL, SystemProgram } from "@solana/web3.js";
+import {
+  ACCOUNT_SIZE,
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  AccountLayout,
+  AccountState,
+  MINT_SIZE,
+  MintLayout,
+  TOKEN_PROGRAM_ID,
+  createAssociatedTokenAccount,
+  createMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { startAnchor } from "solana-bankrun";
+import { BankrunProvider } from "anchor-bankrun";
+import { IDL, AssetConverter } from "../target/types/asset_converter";
+import { expect } from "chai";
+
+describe("asset-converter", () => {
+  // Configure the client to use the local cluster.
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.AssetConverter as Program<AssetConverter>;
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+  const payer = (provider.wallet as anchor.Wallet).payer;
+
+  const FEE_RATE_BPS = 100;
+  const RATE_PRECISION = 1_000_000_000;
+  const MAX_AMOUNT = 1_000_000_000_000_000;
+
+  const [converterPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("converter_state")],
+    program.programId
+  );
+
+  const pairPda = (sourceMint: PublicKey, targetMint: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("conversion_pair"), sourceMint.toBuffer(), targetMint.toBuffer()],
+      program.programId
+    )[0];
+
+  const mintRegistryPda = (mint: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_registry"), mint.toBuffer()],
+      program.programId
+    )[0];
+
+  const historyPda = (user: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("conversion_history"), user.toBuffer()],
+      program.programId
+    )[0];
+
+  const limitPda = (user: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("user_limit"), user.toBuffer()],
+      program.programId
+    )[0];
+
+  // Converter-owned token account for `mint`
+  const vaultAddress = (mint: PublicKey) => getAssociatedTokenAddressSync(mint, converterPda, true);
+
+  const fundedKeypair = async () => {
+    const keypair = Keypair.generate();
+    const signature = await provider.connection.requestAirdrop(keypair.publicKey, LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(signature);
+    return keypair;
+  };
+
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    try {
+      await promise;
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal(code);
+      return;
+    }
+    expect.fail(`expected ${code}`);
+  };
+
+  const tokenBalance = async (address: PublicKey) =>
+    Number((await getAccount(provider.connection, address)).amount);
+
+  const createVault = async (mint: PublicKey) =>
+    (await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, converterPda, true)).address;
+
+  const adminTokenAccount = async (mint: PublicKey) =>
+    (await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, payer.publicKey)).address;
+
+  type Pair = { sourceMint: PublicKey; targetMint: PublicKey };
+
+  // A conversion pair between two fresh mints, with `liquidity` target tokens in its vault
+  const createPair = async ({
+    sourceDecimals = 6,
+    targetDecimals = 6,
+    rate = RATE_PRECISION,
+    liquidity = 1_000_000_000,
+  } = {}): Promise<Pair> => {
+    const sourceMint = await createMint(provider.connection, payer, payer.publicKey, null, sourceDecimals);
+    const targetMint = await createMint(provider.connection, payer, payer.publicKey, null, targetDecimals);
+
+    await program.methods
+      .addConversionPair(sourceMint, targetMint, new BN(rate), new BN(1), new BN(MAX_AMOUNT))
+      .accounts({
+        converterState: converterPda,
+        conversionPair: pairPda(sourceMint, targetMint),
+        sourceMintRegistry: mintRegistryPda(sourceMint),
+        targetMintRegistry: mintRegistryPda(targetMint),
+        sourceMint,
+        targetMint,
+        admin: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    await createVault(sourceMint);
+    const targetVault = await createVault(targetMint);
+    await adminTokenAccount(targetMint);
+    if (liquidity > 0) {
+      await mintTo(provider.connection, payer, targetMint, targetVault, payer, liquidity);
+    }
+
+    return { sourceMint, targetMint };
+  };
+
+  // A funded user holding `amount` of the pair's source token
+  const createUser = async (pair: Pair, amount: number) => {
+    const user = await fundedKeypair();
+    const sourceAccount = await createAssociatedTokenAccount(
+      provider.connection,
+      payer,
+      pair.sourceMint,
+      user.publicKey
+    );
+    await mintTo(provider.connection, payer, pair.sourceMint, sourceAccount, payer, amount);
+    return user;
+  };
+
+  const convert = (
+    user: Keypair,
+    { sourceMint, targetMint }: Pair,
+    amount: number,
+    converter = program
+  ) =>
+    converter.methods
+      .convertAsset(new BN(amount))
+      .accounts({
+        converterState: converterPda,
+        conversionPair: pairPda(sourceMint, targetMint),
+        sourceMint,
+        targetMint,
+        userSourceAccount: getAssociatedTokenAddressSync(sourceMint, user.publicKey),
+        userTargetAccount: getAssociatedTokenAddressSync(targetMint, user.publicKey),
+        sourceVault: vaultAddress(sourceMint),
+        targetVault: vaultAddress(targetMint),
+        adminFeeAccount: getAssociatedTokenAddressSync(targetMint, payer.publicKey),
+        userHistory: historyPda(user.publicKey),
+        userLimit: limitPda(user.publicKey),
+        user: user.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+  before(async () => {
+    await program.methods
+      .initialize(new BN(FEE_RATE_BPS), provider.wallet.publicKey)
+      .accounts({
+        converterState: converterPda,
+        payer: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  });
+
+  describe("stray token recovery", () => {
+    const recoverStrayTokens = (strayMint: PublicKey, amount: number) =>
+      program.methods
+        .recoverStrayTokens(new BN(amount))
+        .accounts({
+          converterState: converterPda,
+          strayMint,
+          mintRegistry: mintRegistryPda(strayMint),
+          strayVault: vaultAddress(strayMint),
+          adminAccount: getAssociatedTokenAddressSync(strayMint, payer.publicKey),
+          admin: provider.wallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    it("Recovers a mint no pair uses to the admin", async () => {
+      const strayMint = await createMint(provider.connection, payer, payer.publicKey, null, 6);
+      const strayVault = await createVault(strayMint);
+      const adminAccount = await adminTokenAccount(strayMint);
+      await mintTo(provider.connection, payer, strayMint, strayVault, payer, 5_000);
+
+      await recoverStrayTokens(strayMint, 5_000);
+
+      expect(await tokenBalance(strayVault)).to.equal(0);
+      expect(await tokenBalance(adminAccount)).to.equal(5_000);
+    });
+
+    it("Refuses to drain a conversion pair's liquidity", async () => {
+      const pair = await createPair();
+
+      await expectError(recoverStrayTokens(pair.targetMint, 1_000), "PairMintNotRecoverable");
+      expect(await tokenBalance(vaultAddress(pair.targetMint))).to.equal(1_000_000_000);
+    });
+  });
+
+  describe("vault balance", () => {
+    it("Reports an empty target vault with InsufficientVaultBalance", async () => {
+      const pair = await createPair({ liquidity: 1_000_000 });
+      const user = await createUser(pair, 2_000_000);
+
+      // Payout plus fee takes the whole vault
+      await convert(user, pair, 1_000_000);
+      expect(await tokenBalance(vaultAddress(pair.targetMint))).to.equal(0);
+
+      await expectError(convert(user, pair, 1_000_000), "InsufficientVaultBalance");
+      expect(
+        await tokenBalance(getAssociatedTokenAddressSync(pair.sourceMint, user.publicKey))
+      ).to.equal(1_000_000);
+    });
+
+    it("Rejects a conversion the vault can only partly cover", async () => {
+      const pair = await createPair({ liquidity: 500_000 });
+      const user = await createUser(pair, 1_000_000);
+
+      await expectError(convert(user, pair, 1_000_000), "InsufficientVaultBalance");
+      expect(await tokenBalance(vaultAddress(pair.targetMint))).to.equal(500_000);
+    });
+  });
+
+  describe("conversion history", () => {
+    const MAX_CONVERSION_HISTORY = 20;
+
+    const conversionHistory = (user: PublicKey) =>
+      program.methods.getConversionHistory().accounts({ userHistory: historyPda(user) }).view();
+
+    const sourceAmounts = (entries: { sourceAmount: BN }[]) =>
+      entries.map((entry) => entry.sourceAmount.toNumber());
+
+    it("Records each conversion, oldest first", async () => {
+      const pair = await createPair();
+      const user = await createUser(pair, 1_000_000);
+
+      for (const amount of [10_000, 20_000, 30_000]) {
+        await convert(user, pair, amount);
+      }
+
+      const history = await conversionHistory(user.publicKey);
+      expect(sourceAmounts(history)).to.deep.equal([10_000, 20_000, 30_000]);
+
+      const [first] = history;
+      expect(first.conversionPair.toString()).to.equal(
+        pairPda(pair.sourceMint, pair.targetMint).toString()
+      );
+      expect(first.targetAmount.toNumber()).to.equal(9_900);
+      expect(first.feeAmount.toNumber()).to.equal(100);
+      expect(first.slot.toNumber()).to.be.greaterThan(0);
+      expect(history[2].slot.toNumber()).to.be.at.least(first.slot.toNumber());
+    });
+
+    it("Evicts the oldest conversions past capacity", async () => {
+      const pair = await createPair();
+      const user = await createUser(pair, 1_000_000);
+      const amounts = Array.from({ length: MAX_CONVERSION_HISTORY + 3 }, (_, i) => 1_000 + i);
+
+      for (const amount of amounts) {
+        await convert(user, pair, amount);
+      }
+
+      const history = await conversionHistory(user.publicKey);
+      expect(history).to.have.length(MAX_CONVERSION_HISTORY);
+      expect(sourceAmounts(history)).to.deep.equal(amounts.slice(3));
+    });
+  });
+
+  describe("daily conversion limits", () => {
+    const SLOTS_PER_DAY = 216_000;
+    const DAILY_LIMIT = 1_000_000;
+
+    const expectRejected = async (promise: Promise<unknown>) => {
+      try {
+        await promise;
+      } catch {
+        return;
+      }
+      expect.fail("expected the transaction to fail");
+    };
+
+    // A fresh bank so the clock can move a day ahead. Mints and token accounts are written
+    // directly since the bank has no RPC connection for the spl-token helpers.
+    const startBankrun = async () => {
+      const context = await startAnchor(".", [], []);
+      const setLamports = (address: PublicKey) =>
+        context.setAccount(address, {
+          lamports: 10 * LAMPORTS_PER_SOL,
+          data: Buffer.alloc(0),
+          owner: SystemProgram.programId,
+          executable: false,
+        });
+      setLamports(payer.publicKey);
+
+      const bankrunProgram = new Program<AssetConverter>(
+        IDL,
+        program.programId,
+        new BankrunProvider(context, new anchor.Wallet(payer))
+      );
+      await bankrunProgram.methods
+        .initialize(new BN(FEE_RATE_BPS), payer.publicKey)
+        .accounts({
+          converterState: converterPda,
+          payer: payer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const setMint = (decimals: number) => {
+        const mint = Keypair.generate().publicKey;
+        const data = Buffer.alloc(MINT_SIZE);
+        MintLayout.encode(
+          {
+            mintAuthorityOption: 0,
+            mintAuthority: PublicKey.default,
+            supply: BigInt(0),
+            decimals,
+            isInitialized: true,
+            freezeAuthorityOption: 0,
+            freezeAuthority: PublicKey.default,
+          },
+          data
+        );
+        context.setAccount(mint, { lamports: LAMPORTS_PER_SOL, data, owner: TOKEN_PROGRAM_ID, executable: false });
+        return mint;
+      };
+
+      const setTokenAccount = (mint: PublicKey, owner: PublicKey, amount: number) => {
+        const data = Buffer.alloc(ACCOUNT_SIZE);
+        AccountLayout.encode(
+          {
+            mint,
+            owner,
+            amount: BigInt(amount),
+            delegateOption: 0,
+            delegate: PublicKey.default,
+            state: AccountState.Initialized,
+            isNativeOption: 0,
+            isNative: BigInt(0),
+            delegatedAmount: BigInt(0),
+            closeAuthorityOption: 0,
+            closeAuthority: PublicKey.default,
+          },
+          data
+        );
+        context.setAccount(getAssociatedTokenAddressSync(mint, owner, true), {
+          lamports: LAMPORTS_PER_SOL,
+          data,
+          owner: TOKEN_PROGRAM_ID,
+          executable: false,
+        });
+      };
+
+      const seedPair = async (liquidity: number): Promise<Pair> => {
+        const sourceMint = setMint(6);
+        const targetMint = setMint(6);
+        await bankrunProgram.methods
+          .addConversionPair(sourceMint, targetMint, new BN(RATE_PRECISION), new BN(1), new BN(MAX_AMOUNT))
+          .accounts({
+            converterState: converterPda,
+            conversionPair: pairPda(sourceMint, targetMint),
+            sourceMintRegistry: mintRegistryPda(sourceMint),
+            targetMintRegistry: mintRegistryPda(targetMint),
+            sourceMint,
+            targetMint,
+            admin: payer.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+
+        setTokenAccount(sourceMint, converterPda, 0);
+        setTokenAccount(targetMint, converterPda, liquidity);
+        setTokenAccount(targetMint, payer.publicKey, 0);
+        return { sourceMint, targetMint };
+      };
+
+      const seedUser = ({ sourceMint, targetMint }: Pair, amount: number) => {
+        const user = Keypair.generate();
+        setLamports(user.publicKey);
+        setTokenAccount(sourceMint, user.publicKey, amount);
+        setTokenAccount(targetMint, user.publicKey, 0);
+        return user;
+      };
+
+      return { context, bankrunProgram, seedPair, seedUser };
+    };
+
+    it("Rejects conversions past the daily limit until the window resets", async () => {
+      const { context, bankrunProgram, seedPair, seedUser } = await startBankrun();
+      const pair = await seedPair(1_000_000_000);
+      const user = seedUser(pair, 10_000_000);
+
+      await bankrunProgram.methods
+        .setDailyUserLimit(new BN(DAILY_LIMIT))
+        .accounts({ converterState: converterPda, admin: payer.publicKey })
+        .rpc();
+
+      await convert(user, pair, 600_000, bankrunProgram);
+      await convert(user, pair, 400_000, bankrunProgram);
+      await expectRejected(convert(user, pair, 1, bankrunProgram));
+
+      const exhausted = await bankrunProgram.account.userConversionLimit.fetch(limitPda(user.publicKey));
+      expect(exhausted.user.toString()).to.equal(user.publicKey.toString());
+      expect(exhausted.dailyVolume.toNumber()).to.equal(DAILY_LIMIT);
+
+      const { slot } = await context.banksClient.getClock();
+      context.warpToSlot(slot + BigInt(SLOTS_PER_DAY));
+
+      await convert(user, pair, 500_000, bankrunProgram);
+
+      const reset = await bankrunProgram.account.userConversionLimit.fetch(limitPda(user.publicKey));
+      expect(reset.dailyVolume.toNumber()).to.equal(500_000);
+      expect(reset.lastResetSlot.toNumber()).to.be.at.least(Number(slot) + SLOTS_PER_DAY);
+    });
+
+    it("Leaves conversions unlimited while the limit is zero", async () => {
+      const { bankrunProgram, seedPair, seedUser } = await startBankrun();
+      const pair = await seedPair(1_000_000_000);
+      const user = seedUser(pair, 10_000_000);
+
+      await convert(user, pair, 5_000_000, bankrunProgram);
+      await convert(user, pair, 5_000_000 - 1, bankrunProgram);
+
+      const limit = await bankrunProgram.account.userConversionLimit.fetch(limitPda(user.publicKey));
+      expect(limit.dailyVolume.toNumber()).to.equal(10_000_000 - 1);
+    });
+  });
+
+  describe("decimals-aware conversion", () => {
+    const targetBalance = (user: Keypair, { targetMint }: Pair) =>
+      tokenBalance(getAssociatedTokenAddressSync(targetMint, user.publicKey));
+
+    it("Scales up from a 6-decimal to a 9-decimal mint", async () => {
+      // 1 source = 2 target
+      const pair = await createPair({
+        sourceDecimals: 6,
+        targetDecimals: 9,
+        rate: 2 * RATE_PRECISION,
+        liquidity: 10_000_000_000,
+      });
+      const user = await createUser(pair, 1_500_000);
+
+      const stored = await program.account.conversionPair.fetch(pairPda(pair.sourceMint, pair.targetMint));
+      expect(stored.sourceDecimals).to.equal(6);
+      expect(stored.targetDecimals).to.equal(9);
+
+      // 1.5 source -> 3 target, less the 1% fee
+      await convert(user, pair, 1_500_000);
+
+      expect(await targetBalance(user, pair)).to.equal(2_970_000_000);
+      expect(
+        await tokenBalance(getAssociatedTokenAddressSync(pair.targetMint, payer.publicKey))
+      ).to.equal(30_000_000);
+    });
+
+    it("Scales down from a 9-decimal to a 6-decimal mint", async () => {
+      // 1 source = 0.5 target
+      const pair = await createPair({
+        sourceDecimals: 9,
+        targetDecimals: 6,
+        rate: RATE_PRECISION / 2,
+        liquidity: 10_000_000,
+      });
+      const user = await createUser(pair, 4_000_000_000);
+
+      // 4 source -> 2 target, less the 1% fee
+      await convert(user, pair, 4_000_000_000);
+
+      expect(await targetBalance(user, pair)).to.equal(1_980_000);
+      expect(await tokenBalance(vaultAddress(pair.targetMint))).to.equal(8_000_000);
+    });
+
+    it("Converts 1:1 between mints with the same decimals", async () => {
+      const pair = await createPair({ sourceDecimals: 9, targetDecimals: 9 });
+      const user = await createUser(pair, 1_000_000);
+
+      await convert(user, pair, 1_000_000);
+
+      expect(await targetBalance(user, pair)).to.equal(990_000);
+    });
+  });
+});
@@ -6,6 +6,15 @@ use anchor_spl::{
 
 declare_id!("AssetIndexer1111111111111111111111111111111");
 
+pub const MAX_TOKEN_SYMBOL_LEN: usize = 10;
+pub const MAX_TOKEN_NAME_LEN: usize = 32;
+pub const MAX_LOGO_URI_LEN: usize = 200;
+pub const MAX_GROUP_MEMBERS: usize = 10;
+
+/// Metaplex Token Metadata program
+pub const TOKEN_METADATA_PROGRAM_ID: Pubkey =
+    anchor_lang::solana_program::pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
 #[program]
 pub mod asset_indexer {
     use super::*;
@@ -20,6 +29,7 @@ pub mod asset_indexer {
         indexer.total_assets_indexed = 0;
         indexer.last_update_slot = Clock::get()?.slot;
         indexer.is_active = true;
+        indexer.is_frozen = false;
         indexer.bump = *ctx.bumps.get("indexer").unwrap();
 
         emit!(IndexerInitialized {
@@ -80,7 +90,18 @@ pub mod asset_indexer {
         balance: u64,
         decimals: u8,
         token_symbol: String,
+        token_name: String,
+        logo_uri: String,
     ) -> Result<()> {
+        require!(token_symbol.len() <= MAX_TOKEN_SYMBOL_LEN, AssetIndexerError::TokenSymbolTooLong);
+        require!(token_name.len() <= MAX_TOKEN_NAME_LEN, AssetIndexerError::TokenNameTooLong);
+        require!(logo_uri.len() <= MAX_LOGO_URI_LEN, AssetIndexerError::LogoUriTooLong);
+
+        // Cross-check the supplied name against on-chain Metaplex metadata when provided
+        if let Some(token_metadata) = &ctx.accounts.token_metadata {
+            verify_token_metadata(token_metadata, &mint, &token_name)?;
+        }
+
         let token_index = &mut ctx.accounts.token_index;
         let user_assets = &mut ctx.accounts.user_assets;
 
@@ -89,6 +110,9 @@ pub mod asset_indexer {
         token_index.balance = balance;
         token_index.decimals = decimals;
         token_index.token_symbol = token_symbol;
+        token_index.token_name = token_name;
+        token_index.logo_uri = logo_uri;
+        token_index.metadata_verified = ctx.accounts.token_metadata.is_some();
         token_index.last_updated_slot = Clock::get()?.slot;
         token_index.is_active = true;
         token_index.bump = *ctx.bumps.get("token_index").unwrap();
@@ -101,6 +125,9 @@ pub mod asset_indexer {
             mint,
             balance,
             token_symbol: token_index.token_symbol.clone(),
+            token_name: token_index.token_name.clone(),
+            logo_uri: token_index.logo_uri.clone(),
+            metadata_verified: token_index.metadata_verified,
             slot: token_index.last_updated_slot,
         });
 
@@ -189,14 +216,153 @@ pub mod asset_indexer {
         Ok(())
     }
 
+    /// Block all indexing writes (e.g. while migrating update authorities)
+    pub fn freeze_indexer(ctx: Context<UpdateIndexer>) -> Result<()> {
+        let indexer = &mut ctx.accounts.indexer;
+        require!(!indexer.is_frozen, AssetIndexerError::IndexerFrozen);
+
+        indexer.is_frozen = true;
+
+        emit!(IndexerFreezeToggled {
+            authority: indexer.authority,
+            is_frozen: true,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    pub fn unfreeze_indexer(ctx: Context<UpdateIndexer>) -> Result<()> {
+        let indexer = &mut ctx.accounts.indexer;
+        require!(indexer.is_frozen, AssetIndexerError::IndexerNotFrozen);
+
+        indexer.is_frozen = false;
+
+        emit!(IndexerFreezeToggled {
+            authority: indexer.authority,
+            is_frozen: false,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    pub fn set_update_authority(
+        ctx: Context<UpdateIndexer>,
+        new_update_authority: Pubkey,
+    ) -> Result<()> {
+        let indexer = &mut ctx.accounts.indexer;
+        let old_update_authority = indexer.update_authority;
+
+        indexer.update_authority = new_update_authority;
+        indexer.last_update_slot = Clock::get()?.slot;
+
+        emit!(UpdateAuthorityChanged {
+            old_update_authority,
+            new_update_authority,
+            slot: indexer.last_update_slot,
+        });
+
+        Ok(())
+    }
+
     pub fn get_user_portfolio_value(
         ctx: Context<GetUserPortfolioValue>,
     ) -> Result<u64> {
-        let user_assets = &ctx.accounts.user_assets;
-        
-        // This would calculate total portfolio value in USD
-        // For now, returning SOL balance as a placeholder
-        Ok(user_assets.sol_balance)
+        Ok(portfolio_value(&ctx.accounts.user_assets))
+    }
+
+    pub fn create_portfolio_group(
+        ctx: Context<CreatePortfolioGroup>,
+    ) -> Result<()> {
+        let portfolio_group = &mut ctx.accounts.portfolio_group;
+        portfolio_group.owner = ctx.accounts.owner.key();
+        portfolio_group.members = Vec::new();
+        portfolio_group.created_slot = Clock::get()?.slot;
+        portfolio_group.bump = *ctx.bumps.get("portfolio_group").unwrap();
+
+        emit!(PortfolioGroupCreated {
+            owner: portfolio_group.owner,
+            slot: portfolio_group.created_slot,
+        });
+
+        Ok(())
+    }
+
+    /// Link a wallet's `UserAssets` to the owner's group; the wallet must co-sign
+    pub fn add_group_member(
+        ctx: Context<AddGroupMember>,
+    ) -> Result<()> {
+        let portfolio_group = &mut ctx.accounts.portfolio_group;
+        let member = ctx.accounts.user_assets.key();
+
+        require!(
+            portfolio_group.members.len() < MAX_GROUP_MEMBERS,
+            AssetIndexerError::PortfolioGroupFull
+        );
+        require!(
+            !portfolio_group.members.contains(&member),
+            AssetIndexerError::AlreadyGroupMember
+        );
+
+        portfolio_group.members.push(member);
+
+        emit!(PortfolioGroupMemberChanged {
+            owner: portfolio_group.owner,
+            user_assets: member,
+            added: true,
+            member_count: portfolio_group.members.len() as u8,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    pub fn remove_group_member(
+        ctx: Context<RemoveGroupMember>,
+        user_assets: Pubkey,
+    ) -> Result<()> {
+        let portfolio_group = &mut ctx.accounts.portfolio_group;
+
+        require!(
+            portfolio_group.members.contains(&user_assets),
+            AssetIndexerError::NotGroupMember
+        );
+        portfolio_group.members.retain(|member| *member != user_assets);
+
+        emit!(PortfolioGroupMemberChanged {
+            owner: portfolio_group.owner,
+            user_assets,
+            added: false,
+            member_count: portfolio_group.members.len() as u8,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Sum the portfolio value of every group member. Pass each member's `UserAssets`
+    /// through `remaining_accounts`, in the group's member order.
+    pub fn get_group_portfolio_value(
+        ctx: Context<GetGroupPortfolioValue>,
+    ) -> Result<u64> {
+        let portfolio_group = &ctx.accounts.portfolio_group;
+        require!(
+            ctx.remaining_accounts.len() == portfolio_group.members.len(),
+            AssetIndexerError::GroupMemberMismatch
+        );
+
+        let mut total_value: u64 = 0;
+        for (member_info, member) in ctx.remaining_accounts.iter().zip(portfolio_group.members.iter()) {
+            require!(
+                member_info.key() == *member && member_info.owner == ctx.program_id,
+                AssetIndexerError::GroupMemberMismatch
+            );
+            let user_assets = UserAssets::try_deserialize(&mut &member_info.try_borrow_data()?[..])?;
+            total_value = total_value.saturating_add(portfolio_value(&user_assets));
+        }
+
+        Ok(total_value)
     }
 
     pub fn set_price_oracle(
@@ -222,6 +388,48 @@ pub mod asset_indexer {
     }
 }
 
+// Portfolio value of a single user's indexed assets.
+// This would calculate total portfolio value in USD; for now the SOL balance is a placeholder.
+fn portfolio_value(user_assets: &UserAssets) -> u64 {
+    user_assets.sol_balance
+}
+
+// Helper function to check a Metaplex metadata account matches the indexed mint and name
+fn verify_token_metadata(
+    token_metadata: &AccountInfo,
+    mint: &Pubkey,
+    token_name: &str,
+) -> Result<()> {
+    require!(
+        token_metadata.owner == &TOKEN_METADATA_PROGRAM_ID,
+        AssetIndexerError::InvalidTokenMetadata
+    );
+
+    let (expected_metadata, _) = Pubkey::find_program_address(
+        &[b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &TOKEN_METADATA_PROGRAM_ID,
+    );
+    require!(
+        token_metadata.key() == expected_metadata,
+        AssetIndexerError::InvalidTokenMetadata
+    );
+
+    // Layout: key (1) + update_authority (32) + mint (32) + name (4 + len, null padded)
+    let data = token_metadata.try_borrow_data()?;
+    require!(data.len() >= 69, AssetIndexerError::InvalidTokenMetadata);
+
+    let name_len = u32::from_le_bytes(data[65..69].try_into().unwrap()) as usize;
+    require!(data.len() >= 69 + name_len, AssetIndexerError::InvalidTokenMetadata);
+
+    let on_chain_name = String::from_utf8_lossy(&data[69..69 + name_len]);
+    require!(
+        on_chain_name.trim_end_matches('\0') == token_name,
+        AssetIndexerError::TokenMetadataMismatch
+    );
+
+    Ok(())
+}
+
 #[derive(Accounts)]
 pub struct InitializeIndexer<'info> {
     #[account(
@@ -248,6 +456,12 @@ pub struct RegisterUserAssets<'info> {
         bump
     )]
     pub user_assets: Account<'info, UserAssets>,
+    #[account(
+        seeds = [b"indexer"],
+        bump = indexer.bump,
+        constraint = !indexer.is_frozen @ AssetIndexerError::IndexerFrozen
+    )]
+    pub indexer: Account<'info, AssetIndexer>,
     #[account(mut)]
     pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -261,6 +475,12 @@ pub struct SyncSolBalance<'info> {
         bump = user_assets.bump
     )]
     pub user_assets: Account<'info, UserAssets>,
+    #[account(
+        seeds = [b"indexer"],
+        bump = indexer.bump,
+        constraint = !indexer.is_frozen @ AssetIndexerError::IndexerFrozen
+    )]
+    pub indexer: Account<'info, AssetIndexer>,
     pub authority: Signer<'info>,
 }
 
@@ -281,6 +501,14 @@ pub struct IndexTokenAccount<'info> {
         bump = user_assets.bump
     )]
     pub user_assets: Account<'info, UserAssets>,
+    /// CHECK: Optional Metaplex metadata account, validated in verify_token_metadata
+    pub token_metadata: Option<UncheckedAccount<'info>>,
+    #[account(
+        seeds = [b"indexer"],
+        bump = indexer.bump,
+        constraint = !indexer.is_frozen @ AssetIndexerError::IndexerFrozen
+    )]
+    pub indexer: Account<'info, AssetIndexer>,
     #[account(mut)]
     pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -294,6 +522,12 @@ pub struct UpdateTokenBalance<'info> {
         bump = token_index.bump
     )]
     pub token_index: Account<'info, TokenIndex>,
+    #[account(
+        seeds = [b"indexer"],
+        bump = indexer.bump,
+        constraint = !indexer.is_frozen @ AssetIndexerError::IndexerFrozen
+    )]
+    pub indexer: Account<'info, AssetIndexer>,
     pub authority: Signer<'info>,
 }
 
@@ -314,6 +548,12 @@ pub struct IndexNFTCollection<'info> {
         bump = user_assets.bump
     )]
     pub user_assets: Account<'info, UserAssets>,
+    #[account(
+        seeds = [b"indexer"],
+        bump = indexer.bump,
+        constraint = !indexer.is_frozen @ AssetIndexerError::IndexerFrozen
+    )]
+    pub indexer: Account<'info, AssetIndexer>,
     #[account(mut)]
     pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -327,6 +567,12 @@ pub struct BatchSyncAssets<'info> {
         bump = user_assets.bump
     )]
     pub user_assets: Account<'info, UserAssets>,
+    #[account(
+        seeds = [b"indexer"],
+        bump = indexer.bump,
+        constraint = !indexer.is_frozen @ AssetIndexerError::IndexerFrozen
+    )]
+    pub indexer: Account<'info, AssetIndexer>,
     pub authority: Signer<'info>,
 }
 
@@ -339,6 +585,73 @@ pub struct GetUserPortfolioValue<'info> {
     pub user_assets: Account<'info, UserAssets>,
 }
 
+#[derive(Accounts)]
+pub struct CreatePortfolioGroup<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = PortfolioGroup::LEN,
+        seeds = [b"portfolio_group", owner.key().as_ref()],
+        bump
+    )]
+    pub portfolio_group: Account<'info, PortfolioGroup>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AddGroupMember<'info> {
+    #[account(
+        mut,
+        seeds = [b"portfolio_group", owner.key().as_ref()],
+        bump = portfolio_group.bump,
+        has_one = owner
+    )]
+    pub portfolio_group: Account<'info, PortfolioGroup>,
+    #[account(
+        seeds = [b"user_assets", member.key().as_ref()],
+        bump = user_assets.bump
+    )]
+    pub user_assets: Account<'info, UserAssets>,
+    pub owner: Signer<'info>,
+    /// Wallet whose assets are being linked
+    pub member: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveGroupMember<'info> {
+    #[account(
+        mut,
+        seeds = [b"portfolio_group", owner.key().as_ref()],
+        bump = portfolio_group.bump,
+        has_one = owner
+    )]
+    pub portfolio_group: Account<'info, PortfolioGroup>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetGroupPortfolioValue<'info> {
+    #[account(
+        seeds = [b"portfolio_group", portfolio_group.owner.as_ref()],
+        bump = portfolio_group.bump
+    )]
+    pub portfolio_group: Account<'info, PortfolioGroup>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateIndexer<'info> {
+    #[account(
+        mut,
+        seeds = [b"indexer"],
+        bump = indexer.bump,
+        has_one = authority @ AssetIndexerError::UnauthorizedUpdate
+    )]
+    pub indexer: Account<'info, AssetIndexer>,
+    pub authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 #[instruction(token_mint: Pubkey)]
 pub struct SetPriceOracle<'info> {
@@ -350,6 +663,12 @@ pub struct SetPriceOracle<'info> {
         bump
     )]
     pub price_oracle: Account<'info, PriceOracle>,
+    #[account(
+        seeds = [b"indexer"],
+        bump = indexer.bump,
+        constraint = !indexer.is_frozen @ AssetIndexerError::IndexerFrozen
+    )]
+    pub indexer: Account<'info, AssetIndexer>,
     #[account(mut)]
     pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -362,11 +681,12 @@ pub struct AssetIndexer {
     pub total_assets_indexed: u64,
     pub last_update_slot: u64,
     pub is_active: bool,
+    pub is_frozen: bool,
     pub bump: u8,
 }
 
 impl AssetIndexer {
-    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 1 + 1;
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 1 + 1 + 1;
 }
 
 #[account]
@@ -384,6 +704,18 @@ impl UserAssets {
     pub const LEN: usize = 8 + 32 + 64 + 8 + 4 + 8 + 1 + 1;
 }
 
+#[account]
+pub struct PortfolioGroup {
+    pub owner: Pubkey,
+    pub members: Vec<Pubkey>, // UserAssets accounts
+    pub created_slot: u64,
+    pub bump: u8,
+}
+
+impl PortfolioGroup {
+    pub const LEN: usize = 8 + 32 + 4 + MAX_GROUP_MEMBERS * 32 + 8 + 1;
+}
+
 #[account]
 pub struct TokenIndex {
     pub user: Pubkey,
@@ -391,13 +723,20 @@ pub struct TokenIndex {
     pub balance: u64,
     pub decimals: u8,
     pub token_symbol: String,
+    pub token_name: String,
+    pub logo_uri: String,
+    pub metadata_verified: bool,
     pub last_updated_slot: u64,
     pub is_active: bool,
     pub bump: u8,
 }
 
 impl TokenIndex {
-    pub const LEN: usize = 8 + 32 + 32 + 8 + 1 + 32 + 8 + 1 + 1;
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 1
+        + (4 + MAX_TOKEN_SYMBOL_LEN)
+        + (4 + MAX_TOKEN_NAME_LEN)
+        + (4 + MAX_LOGO_URI_LEN)
+        + 1 + 8 + 1 + 1;
 }
 
 #[account]
@@ -470,6 +809,9 @@ pub struct TokenAccountIndexed {
     pub mint: Pubkey,
     pub balance: u64,
     pub token_symbol: String,
+    pub token_name: String,
+    pub logo_uri: String,
+    pub metadata_verified: bool,
     pub slot: u64,
 }
 
@@ -498,6 +840,35 @@ pub struct BatchSyncCompleted {
     pub slot: u64,
 }
 
+#[event]
+pub struct IndexerFreezeToggled {
+    pub authority: Pubkey,
+    pub is_frozen: bool,
+    pub slot: u64,
+}
+
+#[event]
+pub struct UpdateAuthorityChanged {
+    pub old_update_authority: Pubkey,
+    pub new_update_authority: Pubkey,
+    pub slot: u64,
+}
+
+#[event]
+pub struct PortfolioGroupCreated {
+    pub owner: Pubkey,
+    pub slot: u64,
+}
+
+#[event]
+pub struct PortfolioGroupMemberChanged {
+    pub owner: Pubkey,
+    pub user_assets: Pubkey,
+    pub added: bool,
+    pub member_count: u8,
+    pub slot: u64,
+}
+
 #[event]
 pub struct PriceOracleSet {
     pub token_mint: Pubkey,
@@ -515,4 +886,26 @@ pub enum AssetIndexerError {
     SyncInProgress,
     #[msg("Invalid asset type")]
     InvalidAssetType,
+    #[msg("Token symbol too long")]
+    TokenSymbolTooLong,
+    #[msg("Token name too long")]
+    TokenNameTooLong,
+    #[msg("Logo URI too long")]
+    LogoUriTooLong,
+    #[msg("Invalid token metadata account")]
+    InvalidTokenMetadata,
+    #[msg("Token name does not match on-chain metadata")]
+    TokenMetadataMismatch,
+    #[msg("Indexer is frozen")]
+    IndexerFrozen,
+    #[msg("Indexer is not frozen")]
+    IndexerNotFrozen,
+    #[msg("Portfolio group is full")]
+    PortfolioGroupFull,
+    #[msg("Account is already a member of this group")]
+    AlreadyGroupMember,
+    #[msg("Account is not a member of this group")]
+    NotGroupMember,
+    #[msg("Accounts do not match the group's members")]
+    GroupMemberMismatch,
 }
@@ -0,0 +1,398 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, LAMPORTS_PER_SOL, SystemProgram } from "@solana/web3.js";
+import { createMint } from "@solana/spl-token";
+import {
+  PROGRAM_ID as TOKEN_METADATA_PROGRAM_ID,
+  createCreateMetadataAccountV3Instruction,
+} from "@metaplex-foundation/mpl-token-metadata";
+import { AssetIndexer } from "../target/types/asset_indexer";
+import { expect } from "chai";
+
+describe("asset-indexer", () => {
+  // Configure the client to use the local cluster.
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.AssetIndexer as Program<AssetIndexer>;
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+  const payer = (provider.wallet as anchor.Wallet).payer;
+
+  const [indexerPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("indexer")],
+    program.programId
+  );
+
+  const userAssetsPda = (user: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("user_assets"), user.toBuffer()],
+      program.programId
+    )[0];
+
+  const tokenIndexPda = (user: PublicKey, mint: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("token_index"), user.toBuffer(), mint.toBuffer()],
+      program.programId
+    )[0];
+
+  const metadataPda = (mint: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+      TOKEN_METADATA_PROGRAM_ID
+    )[0];
+
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    try {
+      await promise;
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal(code);
+      return;
+    }
+    expect.fail(`expected ${code}`);
+  };
+
+  // Register a fresh wallet's assets, paid for by the provider wallet
+  const registerUserAssets = async (user = Keypair.generate().publicKey) => {
+    await program.methods
+      .registerUserAssets(user, `${user.toBase58().slice(0, 8)}.sol`)
+      .accounts({
+        userAssets: userAssetsPda(user),
+        indexer: indexerPda,
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    return user;
+  };
+
+  const indexTokenAccount = (
+    user: PublicKey,
+    mint: PublicKey,
+    {
+      tokenSymbol = "EXT",
+      tokenName = "Example Token",
+      logoUri = "https://example.com/logo.png",
+      tokenMetadata = null as PublicKey | null,
+    } = {}
+  ) =>
+    program.methods
+      .indexTokenAccount(mint, new BN(1_000_000), 6, tokenSymbol, tokenName, logoUri)
+      .accounts({
+        tokenIndex: tokenIndexPda(user, mint),
+        userAssets: userAssetsPda(user),
+        tokenMetadata,
+        indexer: indexerPda,
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+  const syncSolBalance = (user: PublicKey, balance: number) =>
+    program.methods
+      .syncSolBalance(new BN(balance))
+      .accounts({
+        userAssets: userAssetsPda(user),
+        indexer: indexerPda,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+  before(async () => {
+    await program.methods
+      .initializeIndexer(provider.wallet.publicKey)
+      .accounts({
+        indexer: indexerPda,
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  });
+
+  describe("token metadata", () => {
+    // A mint with Metaplex metadata named `name`
+    const mintWithMetadata = async (name: string) => {
+      const mint = await createMint(provider.connection, payer, payer.publicKey, null, 6);
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          createCreateMetadataAccountV3Instruction(
+            {
+              metadata: metadataPda(mint),
+              mint,
+              mintAuthority: payer.publicKey,
+              payer: payer.publicKey,
+              updateAuthority: payer.publicKey,
+            },
+            {
+              createMetadataAccountArgsV3: {
+                data: {
+                  name,
+                  symbol: "EXT",
+                  uri: "https://example.com/token.json",
+                  sellerFeeBasisPoints: 0,
+                  creators: null,
+                  collection: null,
+                  uses: null,
+                },
+                isMutable: true,
+                collectionDetails: null,
+              },
+            }
+          )
+        )
+      );
+      return mint;
+    };
+
+    it("Indexes a token with its symbol, name and logo", async () => {
+      const user = await registerUserAssets();
+      const mint = Keypair.generate().publicKey;
+
+      await indexTokenAccount(user, mint);
+
+      const tokenIndex = await program.account.tokenIndex.fetch(tokenIndexPda(user, mint));
+      expect(tokenIndex.tokenSymbol).to.equal("EXT");
+      expect(tokenIndex.tokenName).to.equal("Example Token");
+      expect(tokenIndex.logoUri).to.equal("https://example.com/logo.png");
+      expect(tokenIndex.metadataVerified).to.equal(false);
+      expect(tokenIndex.balance.toNumber()).to.equal(1_000_000);
+      expect(tokenIndex.decimals).to.equal(6);
+    });
+
+    it("Accepts fields at their maximum lengths", async () => {
+      const user = await registerUserAssets();
+      const mint = Keypair.generate().publicKey;
+      const logoUri = `https://example.com/${"l".repeat(200 - "https://example.com/".length)}`;
+
+      await indexTokenAccount(user, mint, {
+        tokenSymbol: "S".repeat(10),
+        tokenName: "N".repeat(32),
+        logoUri,
+      });
+
+      const tokenIndex = await program.account.tokenIndex.fetch(tokenIndexPda(user, mint));
+      expect(tokenIndex.tokenSymbol).to.equal("S".repeat(10));
+      expect(tokenIndex.tokenName).to.equal("N".repeat(32));
+      expect(tokenIndex.logoUri).to.equal(logoUri);
+    });
+
+    it("Rejects fields over their length limits", async () => {
+      const user = await registerUserAssets();
+
+      await expectError(
+        indexTokenAccount(user, Keypair.generate().publicKey, { tokenSymbol: "S".repeat(11) }),
+        "TokenSymbolTooLong"
+      );
+      await expectError(
+        indexTokenAccount(user, Keypair.generate().publicKey, { tokenName: "N".repeat(33) }),
+        "TokenNameTooLong"
+      );
+      await expectError(
+        indexTokenAccount(user, Keypair.generate().publicKey, { logoUri: "l".repeat(201) }),
+        "LogoUriTooLong"
+      );
+    });
+
+    it("Marks the name verified when it matches the Metaplex metadata", async () => {
+      const user = await registerUserAssets();
+      const mint = await mintWithMetadata("Example Token");
+
+      await indexTokenAccount(user, mint, { tokenMetadata: metadataPda(mint) });
+
+      const tokenIndex = await program.account.tokenIndex.fetch(tokenIndexPda(user, mint));
+      expect(tokenIndex.tokenName).to.equal("Example Token");
+      expect(tokenIndex.metadataVerified).to.equal(true);
+    });
+
+    it("Rejects a name that differs from the Metaplex metadata", async () => {
+      const user = await registerUserAssets();
+      const mint = await mintWithMetadata("Example Token");
+
+      await expectError(
+        indexTokenAccount(user, mint, { tokenName: "Another Token", tokenMetadata: metadataPda(mint) }),
+        "TokenMetadataMismatch"
+      );
+    });
+
+    it("Rejects metadata belonging to another mint", async () => {
+      const user = await registerUserAssets();
+      const otherMint = await mintWithMetadata("Example Token");
+
+      await expectError(
+        indexTokenAccount(user, Keypair.generate().publicKey, { tokenMetadata: metadataPda(otherMint) }),
+        "InvalidTokenMetadata"
+      );
+    });
+  });
+
+  describe("freeze", () => {
+    const updateIndexer = (
+      method: "freezeIndexer" | "unfreezeIndexer",
+      authority?: Keypair
+    ) =>
+      program.methods[method]()
+        .accounts({
+          indexer: indexerPda,
+          authority: authority ? authority.publicKey : provider.wallet.publicKey,
+        })
+        .signers(authority ? [authority] : [])
+        .rpc();
+
+    const setUpdateAuthority = (newUpdateAuthority: PublicKey) =>
+      program.methods
+        .setUpdateAuthority(newUpdateAuthority)
+        .accounts({ indexer: indexerPda, authority: provider.wallet.publicKey })
+        .rpc();
+
+    it("Rejects writes while frozen and resumes them after an authority change", async () => {
+      const user = await registerUserAssets();
+      const newUpdateAuthority = Keypair.generate().publicKey;
+
+      await updateIndexer("freezeIndexer");
+      try {
+        await expectError(syncSolBalance(user, LAMPORTS_PER_SOL), "IndexerFrozen");
+        await expectError(registerUserAssets(), "IndexerFrozen");
+        await expectError(indexTokenAccount(user, Keypair.generate().publicKey), "IndexerFrozen");
+
+        // The migration itself goes through while writes are blocked
+        await setUpdateAuthority(newUpdateAuthority);
+      } finally {
+        await updateIndexer("unfreezeIndexer");
+      }
+
+      const indexer = await program.account.assetIndexer.fetch(indexerPda);
+      expect(indexer.isFrozen).to.equal(false);
+      expect(indexer.updateAuthority.toString()).to.equal(newUpdateAuthority.toString());
+
+      await syncSolBalance(user, LAMPORTS_PER_SOL);
+      const userAssets = await program.account.userAssets.fetch(userAssetsPda(user));
+      expect(userAssets.solBalance.toNumber()).to.equal(LAMPORTS_PER_SOL);
+    });
+
+    it("Rejects freezing twice or unfreezing an active indexer", async () => {
+      await expectError(updateIndexer("unfreezeIndexer"), "IndexerNotFrozen");
+
+      await updateIndexer("freezeIndexer");
+      try {
+        await expectError(updateIndexer("freezeIndexer"), "IndexerFrozen");
+      } finally {
+        await updateIndexer("unfreezeIndexer");
+      }
+    });
+
+    it("Only lets the indexer authority freeze it", async () => {
+      const outsider = Keypair.generate();
+      const signature = await provider.connection.requestAirdrop(outsider.publicKey, LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(signature);
+
+      await expectError(updateIndexer("freezeIndexer", outsider), "UnauthorizedUpdate");
+
+      const indexer = await program.account.assetIndexer.fetch(indexerPda);
+      expect(indexer.isFrozen).to.equal(false);
+    });
+  });
+
+  describe("portfolio groups", () => {
+    const portfolioGroupPda = (owner: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("portfolio_group"), owner.toBuffer()],
+        program.programId
+      )[0];
+
+    const fundedKeypair = async () => {
+      const keypair = Keypair.generate();
+      const signature = await provider.connection.requestAirdrop(keypair.publicKey, LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(signature);
+      return keypair;
+    };
+
+    const addGroupMember = (owner: Keypair, member: Keypair) =>
+      program.methods
+        .addGroupMember()
+        .accounts({
+          portfolioGroup: portfolioGroupPda(owner.publicKey),
+          userAssets: userAssetsPda(member.publicKey),
+          owner: owner.publicKey,
+          member: member.publicKey,
+        })
+        .signers([owner, member])
+        .rpc();
+
+    const removeGroupMember = (owner: Keypair, member: PublicKey) =>
+      program.methods
+        .removeGroupMember(userAssetsPda(member))
+        .accounts({ portfolioGroup: portfolioGroupPda(owner.publicKey), owner: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+    const groupValue = (owner: PublicKey, members: PublicKey[]) =>
+      program.methods
+        .getGroupPortfolioValue()
+        .accounts({ portfolioGroup: portfolioGroupPda(owner) })
+        .remainingAccounts(
+          members.map((member) => ({ pubkey: userAssetsPda(member), isWritable: false, isSigner: false }))
+        )
+        .view();
+
+    // A wallet whose indexed assets hold `solBalance` lamports
+    const indexedWallet = async (solBalance: number) => {
+      const wallet = Keypair.generate();
+      await registerUserAssets(wallet.publicKey);
+      await syncSolBalance(wallet.publicKey, solBalance);
+      return wallet;
+    };
+
+    let owner: Keypair;
+    let first: Keypair;
+    let second: Keypair;
+
+    before(async () => {
+      owner = await fundedKeypair();
+      first = await indexedWallet(LAMPORTS_PER_SOL);
+      second = await indexedWallet(2 * LAMPORTS_PER_SOL);
+
+      await program.methods
+        .createPortfolioGroup()
+        .accounts({
+          portfolioGroup: portfolioGroupPda(owner.publicKey),
+          owner: owner.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([owner])
+        .rpc();
+    });
+
+    it("Values a group at the sum of its members", async () => {
+      await addGroupMember(owner, first);
+      await addGroupMember(owner, second);
+
+      const group = await program.account.portfolioGroup.fetch(portfolioGroupPda(owner.publicKey));
+      expect(group.members.map((member) => member.toString())).to.deep.equal([
+        userAssetsPda(first.publicKey).toString(),
+        userAssetsPda(second.publicKey).toString(),
+      ]);
+
+      const value = await groupValue(owner.publicKey, [first.publicKey, second.publicKey]);
+      expect(value.toNumber()).to.equal(3 * LAMPORTS_PER_SOL);
+    });
+
+    it("Rejects member accounts that don't match the group", async () => {
+      await expectError(groupValue(owner.publicKey, [first.publicKey]), "GroupMemberMismatch");
+      await expectError(
+        groupValue(owner.publicKey, [second.publicKey, first.publicKey]),
+        "GroupMemberMismatch"
+      );
+    });
+
+    it("Rejects adding a member twice", async () => {
+      await expectError(addGroupMember(owner, first), "AlreadyGroupMember");
+    });
+
+    it("Drops a removed member from the group value", async () => {
+      await removeGroupMember(owner, first.publicKey);
+
+      const value = await groupValue(owner.publicKey, [second.publicKey]);
+      expect(value.toNumber()).to.equal(2 * LAMPORTS_PER_SOL);
+
+      await expectError(removeGroupMember(owner, first.publicKey), "NotGroupMember");
+    });
+  });
+});
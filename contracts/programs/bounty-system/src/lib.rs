@@ -6,6 +6,14 @@ use mpl_token_metadata::state::{DataV2, Creator};
 
 declare_id!("BountySystem111111111111111111111111111111111");
 
+/// Maximum number of workers that can share a single bounty's reward
+pub const MAX_BOUNTY_WINNERS: usize = 10;
+
+/// Ratings exchanged after a bounty completes range from 1 to 5
+pub const MIN_RATING: u8 = 1;
+pub const MAX_RATING: u8 = 5;
+pub const MAX_RATING_FEEDBACK_LEN: usize = 200;
+
 #[program]
 pub mod bounty_system {
     use super::*;
@@ -23,6 +31,7 @@ pub mod bounty_system {
         bounty_config.total_bounties_created = 0;
         bounty_config.total_bounties_completed = 0;
         bounty_config.total_rewards_distributed = 0;
+        bounty_config.bounty_count = 0;
         bounty_config.is_active = true;
         bounty_config.bump = *ctx.bumps.get("bounty_config").unwrap();
 
@@ -47,7 +56,7 @@ pub mod bounty_system {
         max_participants: u8,
     ) -> Result<()> {
         let bounty = &mut ctx.accounts.bounty;
-        let bounty_config = &ctx.accounts.bounty_config;
+        let bounty_config = &mut ctx.accounts.bounty_config;
         let current_slot = Clock::get()?.slot;
         let current_timestamp = Clock::get()?.unix_timestamp;
 
@@ -56,6 +65,9 @@ pub mod bounty_system {
         require!(deadline > current_timestamp, BountyError::InvalidDeadline);
         require!(max_participants > 0 && max_participants <= 100, BountyError::InvalidMaxParticipants);
 
+        bounty.bounty_id = bounty_config.bounty_count;
+        bounty_config.bounty_count += 1;
+
         bounty.creator = ctx.accounts.creator.key();
         bounty.title = title;
         bounty.description = description;
@@ -68,8 +80,9 @@ pub mod bounty_system {
         bounty.status = BountyStatus::Open;
         bounty.created_at = current_timestamp;
         bounty.completed_at = 0;
-        bounty.winner = None;
+        bounty.winners = Vec::new();
         bounty.submissions_count = 0;
+        bounty.is_native = false;
         bounty.bump = *ctx.bumps.get("bounty").unwrap();
 
         // Transfer reward to escrow
@@ -83,6 +96,94 @@ pub mod bounty_system {
         );
         transfer(transfer_ctx, reward_amount)?;
 
+        // Update creator stats
+        let creator_stats = &mut ctx.accounts.creator_stats;
+        if creator_stats.creator == Pubkey::default() {
+            creator_stats.creator = bounty.creator;
+            creator_stats.bump = *ctx.bumps.get("creator_stats").unwrap();
+        }
+        creator_stats.bounties_created += 1;
+        creator_stats.total_escrowed += reward_amount;
+        creator_stats.last_activity = current_timestamp;
+
+        emit!(BountyCreated {
+            bounty_id: bounty.key(),
+            creator: bounty.creator,
+            title: bounty.title.clone(),
+            reward_amount,
+            deadline,
+            category,
+            slot: current_slot,
+        });
+
+        emit_creator_stats(creator_stats);
+
+        Ok(())
+    }
+
+    /// Create a bounty whose reward is escrowed as lamports in the bounty account
+    pub fn create_sol_bounty(
+        ctx: Context<CreateSolBounty>,
+        title: String,
+        description: String,
+        reward_amount: u64,
+        deadline: i64,
+        category: BountyCategory,
+        required_skills: Vec<String>,
+        max_participants: u8,
+    ) -> Result<()> {
+        let bounty = &mut ctx.accounts.bounty;
+        let bounty_config = &mut ctx.accounts.bounty_config;
+        let current_slot = Clock::get()?.slot;
+        let current_timestamp = Clock::get()?.unix_timestamp;
+
+        require!(bounty_config.is_active, BountyError::ProgramNotActive);
+        require!(reward_amount >= bounty_config.min_bounty_amount, BountyError::RewardTooLow);
+        require!(deadline > current_timestamp, BountyError::InvalidDeadline);
+        require!(max_participants > 0 && max_participants <= 100, BountyError::InvalidMaxParticipants);
+
+        bounty.bounty_id = bounty_config.bounty_count;
+        bounty_config.bounty_count += 1;
+
+        bounty.creator = ctx.accounts.creator.key();
+        bounty.title = title;
+        bounty.description = description;
+        bounty.reward_amount = reward_amount;
+        bounty.deadline = deadline;
+        bounty.category = category.clone();
+        bounty.required_skills = required_skills;
+        bounty.max_participants = max_participants;
+        bounty.current_participants = 0;
+        bounty.status = BountyStatus::Open;
+        bounty.created_at = current_timestamp;
+        bounty.completed_at = 0;
+        bounty.winners = Vec::new();
+        bounty.submissions_count = 0;
+        bounty.is_native = true;
+        bounty.bump = *ctx.bumps.get("bounty").unwrap();
+
+        // Escrow reward lamports on top of the bounty account's rent
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.creator.to_account_info(),
+                    to: bounty.to_account_info(),
+                },
+            ),
+            reward_amount,
+        )?;
+
+        // Update creator stats
+        let creator_stats = &mut ctx.accounts.creator_stats;
+        if creator_stats.creator == Pubkey::default() {
+            creator_stats.creator = bounty.creator;
+            creator_stats.bump = *ctx.bumps.get("creator_stats").unwrap();
+        }
+        creator_stats.bounties_created += 1;
+        creator_stats.total_escrowed += reward_amount;
+        creator_stats.last_activity = current_timestamp;
+
         emit!(BountyCreated {
             bounty_id: bounty.key(),
             creator: bounty.creator,
@@ -93,6 +194,8 @@ pub mod bounty_system {
             slot: current_slot,
         });
 
+        emit_creator_stats(creator_stats);
+
         Ok(())
     }
 
@@ -100,11 +203,18 @@ pub mod bounty_system {
         ctx: Context<SubmitWork>,
         submission_data: String,
         submission_hash: String,
+        declared_skills: Option<Vec<String>>,
     ) -> Result<()> {
         let bounty = &mut ctx.accounts.bounty;
         let submission = &mut ctx.accounts.submission;
         let current_timestamp = Clock::get()?.unix_timestamp;
 
+        // The submission PDA is per (bounty, worker); an initialized one means the worker already submitted
+        require!(submission.worker == Pubkey::default(), BountyError::AlreadySubmitted);
+        require!(
+            skills_match(&bounty.required_skills, declared_skills.as_deref().unwrap_or_default()),
+            BountyError::SkillsNotMatched
+        );
         require!(bounty.status == BountyStatus::Open, BountyError::BountyNotOpen);
         require!(current_timestamp < bounty.deadline, BountyError::DeadlinePassed);
         require!(bounty.current_participants < bounty.max_participants, BountyError::MaxParticipantsReached);
@@ -119,9 +229,22 @@ pub mod bounty_system {
         submission.review_notes = String::new();
         submission.bump = *ctx.bumps.get("submission").unwrap();
 
+        // The marker is created with `init`, so a hash already submitted to this bounty is rejected
+        let hash_marker = &mut ctx.accounts.submission_hash_marker;
+        hash_marker.bounty = bounty.key();
+        hash_marker.submission = submission.key();
+        hash_marker.worker = submission.worker;
+        hash_marker.created_at = current_timestamp;
+        hash_marker.bump = *ctx.bumps.get("submission_hash_marker").unwrap();
+
         bounty.current_participants += 1;
         bounty.submissions_count += 1;
 
+        let worker_stats = &mut ctx.accounts.worker_stats;
+        load_worker_stats(worker_stats, ctx.accounts.worker.key(), *ctx.bumps.get("worker_stats").unwrap())?;
+        worker_stats.submissions_made += 1;
+        worker_stats.last_activity = current_timestamp;
+
         emit!(WorkSubmitted {
             bounty_id: bounty.key(),
             worker: ctx.accounts.worker.key(),
@@ -147,6 +270,7 @@ pub mod bounty_system {
         require!(bounty.creator == ctx.accounts.creator.key(), BountyError::NotBountyCreator);
         require!(submission.status == SubmissionStatus::Pending, BountyError::SubmissionAlreadyReviewed);
         require!(bounty.status == BountyStatus::Open, BountyError::BountyNotOpen);
+        require!(!bounty.is_native, BountyError::RewardTypeMismatch);
 
         // Update submission
         submission.status = SubmissionStatus::Approved;
@@ -154,9 +278,16 @@ pub mod bounty_system {
 
         // Update bounty
         bounty.status = BountyStatus::Completed;
-        bounty.winner = Some(submission.worker);
+        bounty.winners = vec![submission.worker];
         bounty.completed_at = current_timestamp;
 
+        // Update worker reputation
+        let worker_stats = &mut ctx.accounts.worker_stats;
+        load_worker_stats(worker_stats, submission.worker, *ctx.bumps.get("worker_stats").unwrap())?;
+        worker_stats.approvals += 1;
+        worker_stats.last_activity = current_timestamp;
+        worker_stats.refresh_reputation();
+
         // Calculate platform fee
         let platform_fee = (bounty.reward_amount * bounty_config.platform_fee_bps as u64) / 10000;
         let worker_reward = bounty.reward_amount - platform_fee;
@@ -165,7 +296,7 @@ pub mod bounty_system {
         let bounty_seeds = &[
             b"bounty",
             bounty.creator.as_ref(),
-            &bounty.created_at.to_le_bytes(),
+            &bounty.bounty_id.to_le_bytes(),
             &[bounty.bump],
         ];
         let signer = &[&bounty_seeds[..]];
@@ -252,6 +383,13 @@ pub mod bounty_system {
         bounty_config.total_bounties_completed += 1;
         bounty_config.total_rewards_distributed += bounty.reward_amount;
 
+        // Update creator stats
+        let creator_stats = &mut ctx.accounts.creator_stats;
+        creator_stats.bounties_completed += 1;
+        creator_stats.total_paid += worker_reward;
+        creator_stats.total_fees_paid += platform_fee;
+        creator_stats.last_activity = current_timestamp;
+
         emit!(BountyCompleted {
             bounty_id: bounty.key(),
             winner: submission.worker,
@@ -261,6 +399,235 @@ pub mod bounty_system {
             completed_at: current_timestamp,
         });
 
+        emit_creator_stats(creator_stats);
+
+        Ok(())
+    }
+
+    /// Approve several submissions on a token bounty and split the reward (minus platform fee)
+    /// by basis-point shares. `remaining_accounts` holds a (submission, worker token account,
+    /// worker stats) triple for each entry in `winner_shares`, in the same order.
+    pub fn approve_multiple_submissions<'info>(
+        ctx: Context<'_, '_, '_, 'info, ApproveMultipleSubmissions<'info>>,
+        winner_shares: Vec<WinnerShare>,
+    ) -> Result<()> {
+        let bounty = &mut ctx.accounts.bounty;
+        let bounty_config = &mut ctx.accounts.bounty_config;
+        let current_timestamp = Clock::get()?.unix_timestamp;
+
+        require!(bounty.creator == ctx.accounts.creator.key(), BountyError::NotBountyCreator);
+        require!(bounty.status == BountyStatus::Open, BountyError::BountyNotOpen);
+        require!(!bounty.is_native, BountyError::RewardTypeMismatch);
+        require!(
+            !winner_shares.is_empty() && winner_shares.len() <= MAX_BOUNTY_WINNERS,
+            BountyError::InvalidWinnerCount
+        );
+        require!(
+            winner_shares.iter().map(|share| share.share_bps as u64).sum::<u64>() == 10000,
+            BountyError::InvalidWinnerShares
+        );
+        require!(
+            ctx.remaining_accounts.len() == winner_shares.len() * 3,
+            BountyError::WinnerAccountMismatch
+        );
+
+        // Calculate platform fee
+        let platform_fee = (bounty.reward_amount * bounty_config.platform_fee_bps as u64) / 10000;
+        let distributable = bounty.reward_amount - platform_fee;
+
+        let bounty_seeds = &[
+            b"bounty",
+            bounty.creator.as_ref(),
+            &bounty.bounty_id.to_le_bytes(),
+            &[bounty.bump],
+        ];
+        let signer = &[&bounty_seeds[..]];
+
+        let mut payouts = Vec::with_capacity(winner_shares.len());
+        let mut paid_out = 0u64;
+        for (i, (share, accounts)) in winner_shares
+            .iter()
+            .zip(ctx.remaining_accounts.chunks(3))
+            .enumerate()
+        {
+            let (submission_info, worker_token_info, worker_stats_info) =
+                (&accounts[0], &accounts[1], &accounts[2]);
+            require!(
+                !payouts.iter().any(|payout: &WinnerPayout| payout.worker == share.worker),
+                BountyError::DuplicateWinner
+            );
+
+            // Each winner needs a pending submission on this bounty
+            let (expected_submission, _) = Pubkey::find_program_address(
+                &[b"submission", bounty.key().as_ref(), share.worker.as_ref()],
+                ctx.program_id,
+            );
+            require!(
+                submission_info.key() == expected_submission && submission_info.owner == ctx.program_id,
+                BountyError::WinnerAccountMismatch
+            );
+            let mut submission = Submission::try_deserialize(&mut &submission_info.try_borrow_data()?[..])?;
+            require!(submission.status == SubmissionStatus::Pending, BountyError::SubmissionAlreadyReviewed);
+
+            require!(worker_token_info.owner == &anchor_spl::token::ID, BountyError::WinnerAccountMismatch);
+            let worker_token_account = TokenAccount::try_deserialize(&mut &worker_token_info.try_borrow_data()?[..])?;
+            require!(
+                worker_token_account.owner == share.worker
+                    && worker_token_account.mint == ctx.accounts.escrow_token_account.mint,
+                BountyError::WinnerAccountMismatch
+            );
+
+            // The last winner absorbs rounding dust so the escrow is fully distributed
+            let amount = if i == winner_shares.len() - 1 {
+                distributable - paid_out
+            } else {
+                distributable * share.share_bps as u64 / 10000
+            };
+            paid_out += amount;
+
+            let transfer_to_worker_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    to: worker_token_info.clone(),
+                    authority: bounty.to_account_info(),
+                },
+                signer,
+            );
+            transfer(transfer_to_worker_ctx, amount)?;
+
+            submission.status = SubmissionStatus::Approved;
+            submission.try_serialize(&mut &mut submission_info.try_borrow_mut_data()?[..])?;
+
+            // Stats were created when the worker submitted
+            let (expected_worker_stats, _) = Pubkey::find_program_address(
+                &[b"worker_stats", share.worker.as_ref()],
+                ctx.program_id,
+            );
+            require!(
+                worker_stats_info.key() == expected_worker_stats
+                    && worker_stats_info.owner == ctx.program_id
+                    && worker_stats_info.is_writable,
+                BountyError::WinnerAccountMismatch
+            );
+            let mut worker_stats = WorkerStats::try_deserialize(&mut &worker_stats_info.try_borrow_data()?[..])?;
+            require!(worker_stats.worker == share.worker, BountyError::WorkerStatsMismatch);
+            worker_stats.approvals += 1;
+            worker_stats.last_activity = current_timestamp;
+            worker_stats.refresh_reputation();
+            worker_stats.try_serialize(&mut &mut worker_stats_info.try_borrow_mut_data()?[..])?;
+
+            payouts.push(WinnerPayout {
+                worker: share.worker,
+                share_bps: share.share_bps,
+                amount,
+            });
+        }
+
+        // Transfer platform fee
+        if platform_fee > 0 {
+            let transfer_fee_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    to: ctx.accounts.platform_fee_account.to_account_info(),
+                    authority: bounty.to_account_info(),
+                },
+                signer,
+            );
+            transfer(transfer_fee_ctx, platform_fee)?;
+        }
+
+        // Update bounty
+        bounty.status = BountyStatus::Completed;
+        bounty.winners = winner_shares.iter().map(|share| share.worker).collect();
+        bounty.completed_at = current_timestamp;
+
+        // Update global stats
+        bounty_config.total_bounties_completed += 1;
+        bounty_config.total_rewards_distributed += bounty.reward_amount;
+
+        // Update creator stats
+        let creator_stats = &mut ctx.accounts.creator_stats;
+        creator_stats.bounties_completed += 1;
+        creator_stats.total_paid += distributable;
+        creator_stats.total_fees_paid += platform_fee;
+        creator_stats.last_activity = current_timestamp;
+
+        emit!(BountySplitCompleted {
+            bounty_id: bounty.key(),
+            payouts,
+            platform_fee,
+            completed_at: current_timestamp,
+        });
+
+        emit_creator_stats(creator_stats);
+
+        Ok(())
+    }
+
+    /// Approve a submission on a SOL bounty, paying the worker and platform fee in lamports
+    pub fn approve_sol_submission(
+        ctx: Context<ApproveSolSubmission>,
+        review_notes: String,
+    ) -> Result<()> {
+        let bounty = &mut ctx.accounts.bounty;
+        let submission = &mut ctx.accounts.submission;
+        let bounty_config = &mut ctx.accounts.bounty_config;
+        let current_timestamp = Clock::get()?.unix_timestamp;
+
+        require!(bounty.creator == ctx.accounts.creator.key(), BountyError::NotBountyCreator);
+        require!(submission.status == SubmissionStatus::Pending, BountyError::SubmissionAlreadyReviewed);
+        require!(bounty.status == BountyStatus::Open, BountyError::BountyNotOpen);
+        require!(bounty.is_native, BountyError::RewardTypeMismatch);
+
+        // Update submission
+        submission.status = SubmissionStatus::Approved;
+        submission.review_notes = review_notes;
+
+        // Update bounty
+        bounty.status = BountyStatus::Completed;
+        bounty.winners = vec![submission.worker];
+        bounty.completed_at = current_timestamp;
+
+        // Calculate platform fee
+        let platform_fee = (bounty.reward_amount * bounty_config.platform_fee_bps as u64) / 10000;
+        let worker_reward = bounty.reward_amount - platform_fee;
+
+        // Pay out of the escrowed lamports; the bounty keeps its rent
+        **bounty.to_account_info().try_borrow_mut_lamports()? -= bounty.reward_amount;
+        **ctx.accounts.worker.to_account_info().try_borrow_mut_lamports()? += worker_reward;
+        **ctx.accounts.platform_authority.to_account_info().try_borrow_mut_lamports()? += platform_fee;
+
+        // Update worker stats
+        let worker_stats = &mut ctx.accounts.worker_stats;
+        load_worker_stats(worker_stats, submission.worker, *ctx.bumps.get("worker_stats").unwrap())?;
+        worker_stats.approvals += 1;
+        worker_stats.last_activity = current_timestamp;
+        worker_stats.refresh_reputation();
+
+        // Update global stats
+        bounty_config.total_bounties_completed += 1;
+        bounty_config.total_rewards_distributed += bounty.reward_amount;
+
+        // Update creator stats
+        let creator_stats = &mut ctx.accounts.creator_stats;
+        creator_stats.bounties_completed += 1;
+        creator_stats.total_paid += worker_reward;
+        creator_stats.total_fees_paid += platform_fee;
+        creator_stats.last_activity = current_timestamp;
+
+        emit!(BountyCompleted {
+            bounty_id: bounty.key(),
+            winner: submission.worker,
+            reward_amount: worker_reward,
+            platform_fee,
+            nft_mint: Pubkey::default(), // No completion NFT for SOL bounties
+            completed_at: current_timestamp,
+        });
+
+        emit_creator_stats(creator_stats);
+
         Ok(())
     }
 
@@ -277,6 +644,13 @@ pub mod bounty_system {
         submission.status = SubmissionStatus::Rejected;
         submission.review_notes = review_notes;
 
+        // Update worker reputation
+        let worker_stats = &mut ctx.accounts.worker_stats;
+        load_worker_stats(worker_stats, submission.worker, *ctx.bumps.get("worker_stats").unwrap())?;
+        worker_stats.rejections += 1;
+        worker_stats.last_activity = Clock::get()?.unix_timestamp;
+        worker_stats.refresh_reputation();
+
         emit!(SubmissionRejected {
             bounty_id: bounty.key(),
             worker: submission.worker,
@@ -287,6 +661,27 @@ pub mod bounty_system {
         Ok(())
     }
 
+    /// Push back the deadline of an open bounty, keeping its existing submissions
+    pub fn extend_deadline(ctx: Context<ExtendDeadline>, new_deadline: i64) -> Result<()> {
+        let bounty = &mut ctx.accounts.bounty;
+
+        require!(bounty.creator == ctx.accounts.creator.key(), BountyError::NotBountyCreator);
+        require!(bounty.status == BountyStatus::Open, BountyError::BountyNotOpen);
+        require!(new_deadline > bounty.deadline, BountyError::InvalidDeadline);
+
+        let old_deadline = bounty.deadline;
+        bounty.deadline = new_deadline;
+
+        emit!(DeadlineExtended {
+            bounty_id: bounty.key(),
+            old_deadline,
+            new_deadline,
+            extended_at: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
     pub fn cancel_bounty(ctx: Context<CancelBounty>) -> Result<()> {
         let bounty = &mut ctx.accounts.bounty;
         let current_timestamp = Clock::get()?.unix_timestamp;
@@ -294,6 +689,7 @@ pub mod bounty_system {
         require!(bounty.creator == ctx.accounts.creator.key(), BountyError::NotBountyCreator);
         require!(bounty.status == BountyStatus::Open, BountyError::BountyNotOpen);
         require!(bounty.submissions_count == 0, BountyError::HasSubmissions);
+        require!(!bounty.is_native, BountyError::RewardTypeMismatch);
 
         bounty.status = BountyStatus::Cancelled;
 
@@ -301,7 +697,7 @@ pub mod bounty_system {
         let bounty_seeds = &[
             b"bounty",
             bounty.creator.as_ref(),
-            &bounty.created_at.to_le_bytes(),
+            &bounty.bounty_id.to_le_bytes(),
             &[bounty.bump],
         ];
         let signer = &[&bounty_seeds[..]];
@@ -317,6 +713,12 @@ pub mod bounty_system {
         );
         transfer(refund_ctx, bounty.reward_amount)?;
 
+        // Update creator stats
+        let creator_stats = &mut ctx.accounts.creator_stats;
+        creator_stats.bounties_cancelled += 1;
+        creator_stats.total_refunded += bounty.reward_amount;
+        creator_stats.last_activity = current_timestamp;
+
         emit!(BountyCancelled {
             bounty_id: bounty.key(),
             creator: bounty.creator,
@@ -324,80 +726,372 @@ pub mod bounty_system {
             cancelled_at: current_timestamp,
         });
 
+        emit_creator_stats(creator_stats);
+
         Ok(())
     }
-}
 
-// Account structures
-#[derive(Accounts)]
-pub struct InitializeBountyProgram<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = BountyConfig::LEN,
-        seeds = [b"bounty_config"],
-        bump
-    )]
-    pub bounty_config: Account<'info, BountyConfig>,
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+    /// Cancel a SOL bounty with no submissions, refunding the escrowed lamports to the creator
+    pub fn cancel_sol_bounty(ctx: Context<CancelSolBounty>) -> Result<()> {
+        let bounty = &mut ctx.accounts.bounty;
+        let current_timestamp = Clock::get()?.unix_timestamp;
 
-#[derive(Accounts)]
-#[instruction(title: String, description: String, reward_amount: u64, deadline: i64)]
-pub struct CreateBounty<'info> {
-    #[account(
-        init,
-        payer = creator,
-        space = Bounty::LEN,
-        seeds = [b"bounty", creator.key().as_ref(), &Clock::get().unwrap().unix_timestamp.to_le_bytes()],
-        bump
-    )]
-    pub bounty: Account<'info, Bounty>,
-    #[account(
-        seeds = [b"bounty_config"],
-        bump = bounty_config.bump
-    )]
-    pub bounty_config: Account<'info, BountyConfig>,
-    #[account(
-        init,
-        payer = creator,
-        associated_token::mint = reward_mint,
-        associated_token::authority = bounty,
-    )]
-    pub escrow_token_account: Account<'info, TokenAccount>,
-    #[account(
-        mut,
-        associated_token::mint = reward_mint,
-        associated_token::authority = creator,
-    )]
-    pub creator_token_account: Account<'info, TokenAccount>,
-    pub reward_mint: Account<'info, Mint>,
-    #[account(mut)]
-    pub creator: Signer<'info>,
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub system_program: Program<'info, System>,
-}
+        require!(bounty.creator == ctx.accounts.creator.key(), BountyError::NotBountyCreator);
+        require!(bounty.status == BountyStatus::Open, BountyError::BountyNotOpen);
+        require!(bounty.submissions_count == 0, BountyError::HasSubmissions);
+        require!(bounty.is_native, BountyError::RewardTypeMismatch);
 
-#[derive(Accounts)]
-#[instruction(submission_data: String)]
-pub struct SubmitWork<'info> {
-    #[account(
-        mut,
-        seeds = [b"bounty", bounty.creator.as_ref(), &bounty.created_at.to_le_bytes()],
-        bump = bounty.bump
+        bounty.status = BountyStatus::Cancelled;
+
+        // Refund creator
+        **bounty.to_account_info().try_borrow_mut_lamports()? -= bounty.reward_amount;
+        **ctx.accounts.creator.to_account_info().try_borrow_mut_lamports()? += bounty.reward_amount;
+
+        // Update creator stats
+        let creator_stats = &mut ctx.accounts.creator_stats;
+        creator_stats.bounties_cancelled += 1;
+        creator_stats.total_refunded += bounty.reward_amount;
+        creator_stats.last_activity = current_timestamp;
+
+        emit!(BountyCancelled {
+            bounty_id: bounty.key(),
+            creator: bounty.creator,
+            refund_amount: bounty.reward_amount,
+            cancelled_at: current_timestamp,
+        });
+
+        emit_creator_stats(creator_stats);
+
+        Ok(())
+    }
+
+    /// Expire an open token bounty past its deadline, refunding the escrow to the creator.
+    /// Callable by anyone.
+    pub fn expire_bounty(ctx: Context<ExpireBounty>) -> Result<()> {
+        let bounty = &mut ctx.accounts.bounty;
+        let current_timestamp = Clock::get()?.unix_timestamp;
+
+        require!(bounty.status == BountyStatus::Open, BountyError::BountyNotOpen);
+        require!(current_timestamp > bounty.deadline, BountyError::DeadlineNotReached);
+        require!(bounty.winners.is_empty(), BountyError::ApprovedSubmissionPending);
+        require!(!bounty.is_native, BountyError::RewardTypeMismatch);
+
+        bounty.status = BountyStatus::Expired;
+
+        // Refund creator
+        let bounty_seeds = &[
+            b"bounty",
+            bounty.creator.as_ref(),
+            &bounty.bounty_id.to_le_bytes(),
+            &[bounty.bump],
+        ];
+        let signer = &[&bounty_seeds[..]];
+
+        let refund_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                to: ctx.accounts.creator_token_account.to_account_info(),
+                authority: bounty.to_account_info(),
+            },
+            signer,
+        );
+        transfer(refund_ctx, bounty.reward_amount)?;
+
+        // Update creator stats
+        let creator_stats = &mut ctx.accounts.creator_stats;
+        creator_stats.total_refunded += bounty.reward_amount;
+        creator_stats.last_activity = current_timestamp;
+
+        emit!(BountyExpired {
+            bounty_id: bounty.key(),
+            creator: bounty.creator,
+            refund_amount: bounty.reward_amount,
+            expired_at: current_timestamp,
+        });
+
+        emit_creator_stats(creator_stats);
+
+        Ok(())
+    }
+
+    /// Expire an open SOL bounty past its deadline, refunding the escrowed lamports to the creator.
+    /// Callable by anyone.
+    pub fn expire_sol_bounty(ctx: Context<ExpireSolBounty>) -> Result<()> {
+        let bounty = &mut ctx.accounts.bounty;
+        let current_timestamp = Clock::get()?.unix_timestamp;
+
+        require!(bounty.status == BountyStatus::Open, BountyError::BountyNotOpen);
+        require!(current_timestamp > bounty.deadline, BountyError::DeadlineNotReached);
+        require!(bounty.winners.is_empty(), BountyError::ApprovedSubmissionPending);
+        require!(bounty.is_native, BountyError::RewardTypeMismatch);
+
+        bounty.status = BountyStatus::Expired;
+
+        // Refund creator
+        **bounty.to_account_info().try_borrow_mut_lamports()? -= bounty.reward_amount;
+        **ctx.accounts.creator.to_account_info().try_borrow_mut_lamports()? += bounty.reward_amount;
+
+        // Update creator stats
+        let creator_stats = &mut ctx.accounts.creator_stats;
+        creator_stats.total_refunded += bounty.reward_amount;
+        creator_stats.last_activity = current_timestamp;
+
+        emit!(BountyExpired {
+            bounty_id: bounty.key(),
+            creator: bounty.creator,
+            refund_amount: bounty.reward_amount,
+            expired_at: current_timestamp,
+        });
+
+        emit_creator_stats(creator_stats);
+
+        Ok(())
+    }
+
+    /// Rate the other side of a completed bounty: the creator rates a winner, or a winner rates
+    /// the creator. The rating record PDA allows one rating per bounty in each direction.
+    pub fn rate_counterparty(
+        ctx: Context<RateCounterparty>,
+        rating: u8,
+        feedback: String,
+    ) -> Result<()> {
+        let bounty = &ctx.accounts.bounty;
+        let rater = ctx.accounts.rater.key();
+        let worker = ctx.accounts.worker.key();
+        let current_timestamp = Clock::get()?.unix_timestamp;
+
+        require!(bounty.status == BountyStatus::Completed, BountyError::BountyNotCompleted);
+        require!(bounty.winners.contains(&worker), BountyError::NotBountyWinner);
+        require!((MIN_RATING..=MAX_RATING).contains(&rating), BountyError::InvalidRating);
+        require!(feedback.len() <= MAX_RATING_FEEDBACK_LEN, BountyError::FeedbackTooLong);
+
+        let worker_stats = &mut ctx.accounts.worker_stats;
+        load_worker_stats(worker_stats, worker, *ctx.bumps.get("worker_stats").unwrap())?;
+
+        let (ratee, ratings) = if rater == bounty.creator {
+            worker_stats.ratings.record(rating);
+            (worker, worker_stats.ratings.clone())
+        } else if rater == worker {
+            let creator_stats = &mut ctx.accounts.creator_stats;
+            creator_stats.ratings.record(rating);
+            (bounty.creator, creator_stats.ratings.clone())
+        } else {
+            return err!(BountyError::NotBountyParticipant);
+        };
+
+        let rating_record = &mut ctx.accounts.rating_record;
+        rating_record.bounty = bounty.key();
+        rating_record.rater = rater;
+        rating_record.ratee = ratee;
+        rating_record.rating = rating;
+        rating_record.feedback = feedback;
+        rating_record.rated_at = current_timestamp;
+        rating_record.bump = *ctx.bumps.get("rating_record").unwrap();
+
+        emit!(CounterpartyRated {
+            bounty_id: bounty.key(),
+            rater,
+            ratee,
+            rating,
+            ratings_received: ratings.count,
+            average_rating: ratings.average_rating,
+            rated_at: current_timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn get_worker_reputation(ctx: Context<GetWorkerReputation>) -> Result<WorkerStats> {
+        Ok((*ctx.accounts.worker_stats).clone())
+    }
+
+    pub fn get_creator_stats(ctx: Context<GetCreatorStats>) -> Result<CreatorStatsSummary> {
+        let creator_stats = &ctx.accounts.creator_stats;
+
+        let completion_rate_bps = if creator_stats.bounties_created > 0 {
+            (creator_stats.bounties_completed * 10000 / creator_stats.bounties_created) as u16
+        } else {
+            0
+        };
+
+        Ok(CreatorStatsSummary {
+            creator: creator_stats.creator,
+            bounties_created: creator_stats.bounties_created,
+            bounties_completed: creator_stats.bounties_completed,
+            bounties_cancelled: creator_stats.bounties_cancelled,
+            total_escrowed: creator_stats.total_escrowed,
+            total_paid: creator_stats.total_paid,
+            total_fees_paid: creator_stats.total_fees_paid,
+            total_refunded: creator_stats.total_refunded,
+            completion_rate_bps,
+            ratings: creator_stats.ratings.clone(),
+            last_activity: creator_stats.last_activity,
+        })
+    }
+}
+
+// Bounties without required skills accept anyone; otherwise at least one declared skill must match
+fn skills_match(required_skills: &[String], declared_skills: &[String]) -> bool {
+    required_skills.is_empty()
+        || declared_skills.iter().any(|declared| {
+            required_skills
+                .iter()
+                .any(|required| required.eq_ignore_ascii_case(declared))
+        })
+}
+
+// Fill in a lazily created worker stats account; existing ones must belong to the worker
+fn load_worker_stats(worker_stats: &mut WorkerStats, worker: Pubkey, bump: u8) -> Result<()> {
+    if worker_stats.worker == Pubkey::default() {
+        worker_stats.worker = worker;
+        worker_stats.bump = bump;
+    }
+    require!(worker_stats.worker == worker, BountyError::WorkerStatsMismatch);
+    Ok(())
+}
+
+// Helper function to emit the current aggregate for a creator
+fn emit_creator_stats(creator_stats: &CreatorStats) {
+    emit!(CreatorStatsUpdated {
+        creator: creator_stats.creator,
+        bounties_created: creator_stats.bounties_created,
+        bounties_completed: creator_stats.bounties_completed,
+        bounties_cancelled: creator_stats.bounties_cancelled,
+        total_paid: creator_stats.total_paid,
+        last_activity: creator_stats.last_activity,
+    });
+}
+
+// Account structures
+#[derive(Accounts)]
+pub struct InitializeBountyProgram<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = BountyConfig::LEN,
+        seeds = [b"bounty_config"],
+        bump
+    )]
+    pub bounty_config: Account<'info, BountyConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(title: String, description: String, reward_amount: u64, deadline: i64)]
+pub struct CreateBounty<'info> {
+    #[account(
+        init,
+        payer = creator,
+        space = Bounty::LEN,
+        seeds = [b"bounty", creator.key().as_ref(), &bounty_config.bounty_count.to_le_bytes()],
+        bump
     )]
     pub bounty: Account<'info, Bounty>,
+    #[account(
+        mut,
+        seeds = [b"bounty_config"],
+        bump = bounty_config.bump
+    )]
+    pub bounty_config: Account<'info, BountyConfig>,
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = CreatorStats::LEN,
+        seeds = [b"creator_stats", creator.key().as_ref()],
+        bump
+    )]
+    pub creator_stats: Account<'info, CreatorStats>,
+    #[account(
+        init,
+        payer = creator,
+        associated_token::mint = reward_mint,
+        associated_token::authority = bounty,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = creator,
+    )]
+    pub creator_token_account: Account<'info, TokenAccount>,
+    pub reward_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateSolBounty<'info> {
     #[account(
         init,
+        payer = creator,
+        space = Bounty::LEN,
+        seeds = [b"bounty", creator.key().as_ref(), &bounty_config.bounty_count.to_le_bytes()],
+        bump
+    )]
+    pub bounty: Account<'info, Bounty>,
+    #[account(
+        mut,
+        seeds = [b"bounty_config"],
+        bump = bounty_config.bump
+    )]
+    pub bounty_config: Account<'info, BountyConfig>,
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = CreatorStats::LEN,
+        seeds = [b"creator_stats", creator.key().as_ref()],
+        bump
+    )]
+    pub creator_stats: Account<'info, CreatorStats>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(submission_data: String, submission_hash: String)]
+pub struct SubmitWork<'info> {
+    #[account(
+        mut,
+        seeds = [b"bounty", bounty.creator.as_ref(), &bounty.bounty_id.to_le_bytes()],
+        bump = bounty.bump
+    )]
+    pub bounty: Account<'info, Bounty>,
+    #[account(
+        init_if_needed,
         payer = worker,
         space = Submission::LEN,
         seeds = [b"submission", bounty.key().as_ref(), worker.key().as_ref()],
         bump
     )]
     pub submission: Account<'info, Submission>,
+    // Submission hashes can exceed the 32-byte seed limit, so the seed is their SHA-256
+    #[account(
+        init,
+        payer = worker,
+        space = SubmissionHashMarker::LEN,
+        seeds = [
+            b"submission_hash",
+            bounty.key().as_ref(),
+            anchor_lang::solana_program::hash::hash(submission_hash.as_bytes()).as_ref(),
+        ],
+        bump
+    )]
+    pub submission_hash_marker: Account<'info, SubmissionHashMarker>,
+    #[account(
+        init_if_needed,
+        payer = worker,
+        space = WorkerStats::LEN,
+        seeds = [b"worker_stats", worker.key().as_ref()],
+        bump
+    )]
+    pub worker_stats: Account<'info, WorkerStats>,
     #[account(mut)]
     pub worker: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -407,7 +1101,7 @@ pub struct SubmitWork<'info> {
 pub struct ApproveSubmissionAndMintNFT<'info> {
     #[account(
         mut,
-        seeds = [b"bounty", bounty.creator.as_ref(), &bounty.created_at.to_le_bytes()],
+        seeds = [b"bounty", bounty.creator.as_ref(), &bounty.bounty_id.to_le_bytes()],
         bump = bounty.bump
     )]
     pub bounty: Account<'info, Bounty>,
@@ -423,6 +1117,20 @@ pub struct ApproveSubmissionAndMintNFT<'info> {
         bump = bounty_config.bump
     )]
     pub bounty_config: Account<'info, BountyConfig>,
+    #[account(
+        mut,
+        seeds = [b"creator_stats", bounty.creator.as_ref()],
+        bump = creator_stats.bump
+    )]
+    pub creator_stats: Account<'info, CreatorStats>,
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = WorkerStats::LEN,
+        seeds = [b"worker_stats", submission.worker.as_ref()],
+        bump
+    )]
+    pub worker_stats: Account<'info, WorkerStats>,
     #[account(
         mut,
         associated_token::mint = reward_mint,
@@ -460,6 +1168,7 @@ pub struct ApproveSubmissionAndMintNFT<'info> {
     #[account(mut)]
     pub nft_metadata: UncheckedAccount<'info>,
     pub reward_mint: Account<'info, Mint>,
+    #[account(mut)]
     pub creator: Signer<'info>,
     /// CHECK: Metadata program
     pub metadata_program: UncheckedAccount<'info>,
@@ -469,10 +1178,98 @@ pub struct ApproveSubmissionAndMintNFT<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
+#[derive(Accounts)]
+pub struct ApproveMultipleSubmissions<'info> {
+    #[account(
+        mut,
+        seeds = [b"bounty", bounty.creator.as_ref(), &bounty.bounty_id.to_le_bytes()],
+        bump = bounty.bump
+    )]
+    pub bounty: Account<'info, Bounty>,
+    #[account(
+        mut,
+        seeds = [b"bounty_config"],
+        bump = bounty_config.bump
+    )]
+    pub bounty_config: Account<'info, BountyConfig>,
+    #[account(
+        mut,
+        seeds = [b"creator_stats", bounty.creator.as_ref()],
+        bump = creator_stats.bump
+    )]
+    pub creator_stats: Account<'info, CreatorStats>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = bounty,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = bounty_config.authority,
+    )]
+    pub platform_fee_account: Account<'info, TokenAccount>,
+    pub reward_mint: Account<'info, Mint>,
+    pub creator: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveSolSubmission<'info> {
+    #[account(
+        mut,
+        seeds = [b"bounty", bounty.creator.as_ref(), &bounty.bounty_id.to_le_bytes()],
+        bump = bounty.bump
+    )]
+    pub bounty: Account<'info, Bounty>,
+    #[account(
+        mut,
+        seeds = [b"submission", bounty.key().as_ref(), submission.worker.as_ref()],
+        bump = submission.bump
+    )]
+    pub submission: Account<'info, Submission>,
+    #[account(
+        mut,
+        seeds = [b"bounty_config"],
+        bump = bounty_config.bump
+    )]
+    pub bounty_config: Account<'info, BountyConfig>,
+    #[account(
+        mut,
+        seeds = [b"creator_stats", bounty.creator.as_ref()],
+        bump = creator_stats.bump
+    )]
+    pub creator_stats: Account<'info, CreatorStats>,
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = WorkerStats::LEN,
+        seeds = [b"worker_stats", submission.worker.as_ref()],
+        bump
+    )]
+    pub worker_stats: Account<'info, WorkerStats>,
+    /// CHECK: Worker receiving the SOL reward
+    #[account(
+        mut,
+        constraint = worker.key() == submission.worker
+    )]
+    pub worker: UncheckedAccount<'info>,
+    /// CHECK: Platform authority receiving the SOL fee
+    #[account(
+        mut,
+        constraint = platform_authority.key() == bounty_config.authority
+    )]
+    pub platform_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct RejectSubmission<'info> {
     #[account(
-        seeds = [b"bounty", bounty.creator.as_ref(), &bounty.created_at.to_le_bytes()],
+        seeds = [b"bounty", bounty.creator.as_ref(), &bounty.bounty_id.to_le_bytes()],
         bump = bounty.bump
     )]
     pub bounty: Account<'info, Bounty>,
@@ -482,6 +1279,27 @@ pub struct RejectSubmission<'info> {
         bump = submission.bump
     )]
     pub submission: Account<'info, Submission>,
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = WorkerStats::LEN,
+        seeds = [b"worker_stats", submission.worker.as_ref()],
+        bump
+    )]
+    pub worker_stats: Account<'info, WorkerStats>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExtendDeadline<'info> {
+    #[account(
+        mut,
+        seeds = [b"bounty", bounty.creator.as_ref(), &bounty.bounty_id.to_le_bytes()],
+        bump = bounty.bump
+    )]
+    pub bounty: Account<'info, Bounty>,
     pub creator: Signer<'info>,
 }
 
@@ -489,10 +1307,16 @@ pub struct RejectSubmission<'info> {
 pub struct CancelBounty<'info> {
     #[account(
         mut,
-        seeds = [b"bounty", bounty.creator.as_ref(), &bounty.created_at.to_le_bytes()],
+        seeds = [b"bounty", bounty.creator.as_ref(), &bounty.bounty_id.to_le_bytes()],
         bump = bounty.bump
     )]
     pub bounty: Account<'info, Bounty>,
+    #[account(
+        mut,
+        seeds = [b"creator_stats", bounty.creator.as_ref()],
+        bump = creator_stats.bump
+    )]
+    pub creator_stats: Account<'info, CreatorStats>,
     #[account(
         mut,
         associated_token::mint = reward_mint,
@@ -510,6 +1334,130 @@ pub struct CancelBounty<'info> {
     pub token_program: Program<'info, Token>,
 }
 
+#[derive(Accounts)]
+pub struct CancelSolBounty<'info> {
+    #[account(
+        mut,
+        seeds = [b"bounty", bounty.creator.as_ref(), &bounty.bounty_id.to_le_bytes()],
+        bump = bounty.bump
+    )]
+    pub bounty: Account<'info, Bounty>,
+    #[account(
+        mut,
+        seeds = [b"creator_stats", bounty.creator.as_ref()],
+        bump = creator_stats.bump
+    )]
+    pub creator_stats: Account<'info, CreatorStats>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExpireBounty<'info> {
+    #[account(
+        mut,
+        seeds = [b"bounty", bounty.creator.as_ref(), &bounty.bounty_id.to_le_bytes()],
+        bump = bounty.bump
+    )]
+    pub bounty: Account<'info, Bounty>,
+    #[account(
+        mut,
+        seeds = [b"creator_stats", bounty.creator.as_ref()],
+        bump = creator_stats.bump
+    )]
+    pub creator_stats: Account<'info, CreatorStats>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = bounty,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = bounty.creator,
+    )]
+    pub creator_token_account: Account<'info, TokenAccount>,
+    pub reward_mint: Account<'info, Mint>,
+    pub caller: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ExpireSolBounty<'info> {
+    #[account(
+        mut,
+        seeds = [b"bounty", bounty.creator.as_ref(), &bounty.bounty_id.to_le_bytes()],
+        bump = bounty.bump
+    )]
+    pub bounty: Account<'info, Bounty>,
+    #[account(
+        mut,
+        seeds = [b"creator_stats", bounty.creator.as_ref()],
+        bump = creator_stats.bump
+    )]
+    pub creator_stats: Account<'info, CreatorStats>,
+    /// CHECK: receives the refund; constrained to the bounty creator
+    #[account(mut, address = bounty.creator)]
+    pub creator: UncheckedAccount<'info>,
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RateCounterparty<'info> {
+    #[account(
+        seeds = [b"bounty", bounty.creator.as_ref(), &bounty.bounty_id.to_le_bytes()],
+        bump = bounty.bump
+    )]
+    pub bounty: Account<'info, Bounty>,
+    // Seeded by rater and winner, so the creator-to-winner and winner-to-creator ratings differ
+    #[account(
+        init,
+        payer = rater,
+        space = RatingRecord::LEN,
+        seeds = [b"rating", bounty.key().as_ref(), rater.key().as_ref(), worker.key().as_ref()],
+        bump
+    )]
+    pub rating_record: Account<'info, RatingRecord>,
+    #[account(
+        mut,
+        seeds = [b"creator_stats", bounty.creator.as_ref()],
+        bump = creator_stats.bump
+    )]
+    pub creator_stats: Account<'info, CreatorStats>,
+    #[account(
+        init_if_needed,
+        payer = rater,
+        space = WorkerStats::LEN,
+        seeds = [b"worker_stats", worker.key().as_ref()],
+        bump
+    )]
+    pub worker_stats: Account<'info, WorkerStats>,
+    /// CHECK: The winner being rated or rating; checked against the bounty's winners
+    pub worker: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub rater: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GetCreatorStats<'info> {
+    #[account(
+        seeds = [b"creator_stats", creator_stats.creator.as_ref()],
+        bump = creator_stats.bump
+    )]
+    pub creator_stats: Account<'info, CreatorStats>,
+}
+
+#[derive(Accounts)]
+pub struct GetWorkerReputation<'info> {
+    #[account(
+        seeds = [b"worker_stats", worker_stats.worker.as_ref()],
+        bump = worker_stats.bump
+    )]
+    pub worker_stats: Account<'info, WorkerStats>,
+}
+
 // Data structures
 #[account]
 pub struct BountyConfig {
@@ -519,16 +1467,18 @@ pub struct BountyConfig {
     pub total_bounties_created: u64,
     pub total_bounties_completed: u64,
     pub total_rewards_distributed: u64,
+    pub bounty_count: u64,               // Next bounty index, used in bounty PDA seeds
     pub is_active: bool,
     pub bump: u8,
 }
 
 impl BountyConfig {
-    pub const LEN: usize = 8 + 32 + 2 + 8 + 8 + 8 + 8 + 1 + 1;
+    pub const LEN: usize = 8 + 32 + 2 + 8 + 8 + 8 + 8 + 8 + 1 + 1;
 }
 
 #[account]
 pub struct Bounty {
+    pub bounty_id: u64,
     pub creator: Pubkey,
     pub title: String,
     pub description: String,
@@ -541,13 +1491,14 @@ pub struct Bounty {
     pub status: BountyStatus,
     pub created_at: i64,
     pub completed_at: i64,
-    pub winner: Option<Pubkey>,
+    pub winners: Vec<Pubkey>,
     pub submissions_count: u32,
+    pub is_native: bool,             // Reward escrowed as SOL rather than an SPL token
     pub bump: u8,
 }
 
 impl Bounty {
-    pub const LEN: usize = 8 + 32 + 128 + 512 + 8 + 8 + 1 + 256 + 1 + 1 + 1 + 8 + 8 + 33 + 4 + 1;
+    pub const LEN: usize = 8 + 8 + 32 + 128 + 512 + 8 + 8 + 1 + 256 + 1 + 1 + 1 + 8 + 8 + 4 + 32 * MAX_BOUNTY_WINNERS + 4 + 1 + 1;
 }
 
 #[account]
@@ -566,6 +1517,113 @@ impl Submission {
     pub const LEN: usize = 8 + 32 + 32 + 1024 + 64 + 8 + 1 + 256 + 1;
 }
 
+/// Claims a submission hash within a bounty so the same work can't be submitted twice
+#[account]
+pub struct SubmissionHashMarker {
+    pub bounty: Pubkey,
+    pub submission: Pubkey,
+    pub worker: Pubkey,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl SubmissionHashMarker {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 8 + 1;
+}
+
+#[account]
+pub struct CreatorStats {
+    pub creator: Pubkey,
+    pub bounties_created: u64,
+    pub bounties_completed: u64,
+    pub bounties_cancelled: u64,
+    pub total_escrowed: u64,
+    pub total_paid: u64,
+    pub total_fees_paid: u64,
+    pub total_refunded: u64,
+    pub last_activity: i64,
+    pub ratings: RatingSummary,      // Ratings received from the bounties' winners
+    pub bump: u8,
+}
+
+impl CreatorStats {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + RatingSummary::LEN + 1;
+}
+
+#[account]
+pub struct WorkerStats {
+    pub worker: Pubkey,
+    pub submissions_made: u64,
+    pub approvals: u64,
+    pub rejections: u64,
+    pub reputation_score: u16,       // Approval rate of reviewed submissions, in basis points
+    pub last_activity: i64,
+    pub ratings: RatingSummary,      // Ratings received from bounty creators
+    pub bump: u8,
+}
+
+impl WorkerStats {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 2 + 8 + RatingSummary::LEN + 1;
+
+    pub fn refresh_reputation(&mut self) {
+        let reviewed = self.approvals + self.rejections;
+        self.reputation_score = if reviewed > 0 {
+            (self.approvals * 10000 / reviewed) as u16
+        } else {
+            0
+        };
+    }
+}
+
+/// One party's rating of the other after a bounty completes
+#[account]
+pub struct RatingRecord {
+    pub bounty: Pubkey,
+    pub rater: Pubkey,
+    pub ratee: Pubkey,
+    pub rating: u8,
+    pub feedback: String,
+    pub rated_at: i64,
+    pub bump: u8,
+}
+
+impl RatingRecord {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 1 + 4 + MAX_RATING_FEEDBACK_LEN + 8 + 1;
+}
+
+/// Running tally of ratings received; `average_rating` is in hundredths (450 = 4.50)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct RatingSummary {
+    pub count: u32,
+    pub total: u64,
+    pub average_rating: u16,
+}
+
+impl RatingSummary {
+    pub const LEN: usize = 4 + 8 + 2;
+
+    pub fn record(&mut self, rating: u8) {
+        self.count += 1;
+        self.total += rating as u64;
+        self.average_rating = (self.total * 100 / self.count as u64) as u16;
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CreatorStatsSummary {
+    pub creator: Pubkey,
+    pub bounties_created: u64,
+    pub bounties_completed: u64,
+    pub bounties_cancelled: u64,
+    pub total_escrowed: u64,
+    pub total_paid: u64,
+    pub total_fees_paid: u64,
+    pub total_refunded: u64,
+    pub completion_rate_bps: u16,
+    pub ratings: RatingSummary,
+    pub last_activity: i64,
+}
+
 // Enums
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
 pub enum BountyCategory {
@@ -622,6 +1680,17 @@ pub struct WorkSubmitted {
     pub submitted_at: i64,
 }
 
+#[event]
+pub struct CounterpartyRated {
+    pub bounty_id: Pubkey,
+    pub rater: Pubkey,
+    pub ratee: Pubkey,
+    pub rating: u8,
+    pub ratings_received: u32,
+    pub average_rating: u16,
+    pub rated_at: i64,
+}
+
 #[event]
 pub struct BountyCompleted {
     pub bounty_id: Pubkey,
@@ -632,6 +1701,27 @@ pub struct BountyCompleted {
     pub completed_at: i64,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct WinnerShare {
+    pub worker: Pubkey,
+    pub share_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct WinnerPayout {
+    pub worker: Pubkey,
+    pub share_bps: u16,
+    pub amount: u64,
+}
+
+#[event]
+pub struct BountySplitCompleted {
+    pub bounty_id: Pubkey,
+    pub payouts: Vec<WinnerPayout>,
+    pub platform_fee: u64,
+    pub completed_at: i64,
+}
+
 #[event]
 pub struct SubmissionRejected {
     pub bounty_id: Pubkey,
@@ -648,6 +1738,32 @@ pub struct BountyCancelled {
     pub cancelled_at: i64,
 }
 
+#[event]
+pub struct DeadlineExtended {
+    pub bounty_id: Pubkey,
+    pub old_deadline: i64,
+    pub new_deadline: i64,
+    pub extended_at: i64,
+}
+
+#[event]
+pub struct BountyExpired {
+    pub bounty_id: Pubkey,
+    pub creator: Pubkey,
+    pub refund_amount: u64,
+    pub expired_at: i64,
+}
+
+#[event]
+pub struct CreatorStatsUpdated {
+    pub creator: Pubkey,
+    pub bounties_created: u64,
+    pub bounties_completed: u64,
+    pub bounties_cancelled: u64,
+    pub total_paid: u64,
+    pub last_activity: i64,
+}
+
 // Errors
 #[error_code]
 pub enum BountyError {
@@ -673,4 +1789,34 @@ pub enum BountyError {
     SubmissionAlreadyReviewed,
     #[msg("Bounty has submissions")]
     HasSubmissions,
+    #[msg("Instruction does not match the bounty's reward type")]
+    RewardTypeMismatch,
+    #[msg("Invalid number of winners")]
+    InvalidWinnerCount,
+    #[msg("Winner shares must sum to 10000 basis points")]
+    InvalidWinnerShares,
+    #[msg("Winner accounts do not match the listed winners")]
+    WinnerAccountMismatch,
+    #[msg("Worker listed more than once")]
+    DuplicateWinner,
+    #[msg("Bounty deadline has not passed yet")]
+    DeadlineNotReached,
+    #[msg("Worker has already submitted to this bounty")]
+    AlreadySubmitted,
+    #[msg("Declared skills do not match any of the bounty's required skills")]
+    SkillsNotMatched,
+    #[msg("Worker stats account does not belong to the submission's worker")]
+    WorkerStatsMismatch,
+    #[msg("Bounty has an approved submission pending reward")]
+    ApprovedSubmissionPending,
+    #[msg("Bounty has not been completed")]
+    BountyNotCompleted,
+    #[msg("Worker is not a winner of this bounty")]
+    NotBountyWinner,
+    #[msg("Only the bounty creator or a winner can rate")]
+    NotBountyParticipant,
+    #[msg("Rating must be between 1 and 5")]
+    InvalidRating,
+    #[msg("Feedback is too long")]
+    FeedbackTooLong,
 }
@@ -0,0 +1,577 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, LAMPORTS_PER_SOL, SystemProgram } from "@solana/web3.js";
+import { createHash } from "crypto";
+import { BountySystem } from "../target/types/bounty_system";
+import { expect } from "chai";
+
+describe("bounty-system", () => {
+  // Configure the client to use the local cluster.
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.BountySystem as Program<BountySystem>;
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+
+  const PLATFORM_FEE_BPS = 250;
+  const REWARD = 0.1 * LAMPORTS_PER_SOL;
+  const PLATFORM_FEE = (REWARD * PLATFORM_FEE_BPS) / 10000;
+
+  // Receives platform fees; kept separate from the provider wallet, which pays transaction fees
+  const platform = Keypair.generate();
+
+  const [configPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("bounty_config")],
+    program.programId
+  );
+
+  const bountyPda = (creator: PublicKey, index: BN) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("bounty"), creator.toBuffer(), index.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+
+  const creatorStatsPda = (creator: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("creator_stats"), creator.toBuffer()],
+      program.programId
+    )[0];
+
+  const workerStatsPda = (worker: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("worker_stats"), worker.toBuffer()],
+      program.programId
+    )[0];
+
+  const submissionPda = (bounty: PublicKey, worker: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("submission"), bounty.toBuffer(), worker.toBuffer()],
+      program.programId
+    )[0];
+
+  const submissionHashPda = (bounty: PublicKey, submissionHash: string) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("submission_hash"),
+        bounty.toBuffer(),
+        createHash("sha256").update(submissionHash).digest(),
+      ],
+      program.programId
+    )[0];
+
+  const fundedKeypair = async (sol = 2) => {
+    const keypair = Keypair.generate();
+    const signature = await provider.connection.requestAirdrop(keypair.publicKey, sol * LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(signature);
+    return keypair;
+  };
+
+  const balance = (account: PublicKey) => provider.connection.getBalance(account);
+
+  const chainTime = async () =>
+    (await provider.connection.getBlockTime(await provider.connection.getSlot())) as number;
+
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    try {
+      await promise;
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal(code);
+      return;
+    }
+    expect.fail(`expected ${code}`);
+  };
+
+  // Accounts created with `init` fail in the system program when they already exist
+  const expectAlreadyInUse = async (promise: Promise<unknown>) => {
+    try {
+      await promise;
+    } catch (err) {
+      expect(((err as { logs?: string[] }).logs ?? []).join("\n")).to.match(/already in use/);
+      return;
+    }
+    expect.fail("expected the account to already be in use");
+  };
+
+  const createSolBountyInstruction = (
+    creator: Keypair,
+    index: BN,
+    { reward = REWARD, deadline, requiredSkills = [] as string[] }: {
+      reward?: number;
+      deadline: number;
+      requiredSkills?: string[];
+    }
+  ) =>
+    program.methods
+      .createSolBounty(
+        "Fix the docs",
+        "Tidy up the README",
+        new BN(reward),
+        new BN(deadline),
+        { development: {} },
+        requiredSkills,
+        5
+      )
+      .accounts({
+        bounty: bountyPda(creator.publicKey, index),
+        bountyConfig: configPda,
+        creatorStats: creatorStatsPda(creator.publicKey),
+        creator: creator.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([creator]);
+
+  const createSolBounty = async (
+    creator: Keypair,
+    options: { reward?: number; deadline?: number; requiredSkills?: string[] } = {}
+  ) => {
+    const { bountyCount } = await program.account.bountyConfig.fetch(configPda);
+    const deadline = options.deadline ?? (await chainTime()) + 3600;
+    await createSolBountyInstruction(creator, bountyCount, { ...options, deadline }).rpc();
+    return bountyPda(creator.publicKey, bountyCount);
+  };
+
+  const submitWork = (
+    bounty: PublicKey,
+    worker: Keypair,
+    submissionHash: string,
+    declaredSkills: string[] | null = null
+  ) =>
+    program.methods
+      .submitWork("https://example.com/pr/1", submissionHash, declaredSkills)
+      .accounts({
+        bounty,
+        submission: submissionPda(bounty, worker.publicKey),
+        submissionHashMarker: submissionHashPda(bounty, submissionHash),
+        workerStats: workerStatsPda(worker.publicKey),
+        worker: worker.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([worker])
+      .rpc();
+
+  const approveSolSubmission = (bounty: PublicKey, creator: Keypair, worker: PublicKey) =>
+    program.methods
+      .approveSolSubmission("Looks good")
+      .accounts({
+        bounty,
+        submission: submissionPda(bounty, worker),
+        bountyConfig: configPda,
+        creatorStats: creatorStatsPda(creator.publicKey),
+        workerStats: workerStatsPda(worker),
+        worker,
+        platformAuthority: platform.publicKey,
+        creator: creator.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([creator])
+      .rpc();
+
+  const cancelSolBounty = (bounty: PublicKey, creator: Keypair) =>
+    program.methods
+      .cancelSolBounty()
+      .accounts({
+        bounty,
+        creatorStats: creatorStatsPda(creator.publicKey),
+        creator: creator.publicKey,
+      })
+      .signers([creator])
+      .rpc();
+
+  // Create a SOL bounty, have a fresh worker submit to it and approve the submission
+  const completeSolBounty = async (creator: Keypair, worker?: Keypair) => {
+    const bounty = await createSolBounty(creator);
+    const winner = worker ?? (await fundedKeypair());
+    await submitWork(bounty, winner, `hash-${bounty.toBase58()}`);
+    await approveSolSubmission(bounty, creator, winner.publicKey);
+    return { bounty, worker: winner };
+  };
+
+  before(async () => {
+    // The platform account must exist and be rent exempt to receive fees
+    const signature = await provider.connection.requestAirdrop(platform.publicKey, LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(signature);
+
+    await program.methods
+      .initializeBountyProgram(platform.publicKey, PLATFORM_FEE_BPS, new BN(1000))
+      .accounts({
+        bountyConfig: configPda,
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  });
+
+  describe("creator stats", () => {
+    it("Aggregates created, completed and cancelled bounties per creator", async () => {
+      const creator = await fundedKeypair();
+
+      await completeSolBounty(creator);
+      await completeSolBounty(creator);
+      const cancelled = await createSolBounty(creator);
+      await cancelSolBounty(cancelled, creator);
+
+      const stats = await program.account.creatorStats.fetch(creatorStatsPda(creator.publicKey));
+      expect(stats.creator.toString()).to.equal(creator.publicKey.toString());
+      expect(stats.bountiesCreated.toNumber()).to.equal(3);
+      expect(stats.bountiesCompleted.toNumber()).to.equal(2);
+      expect(stats.bountiesCancelled.toNumber()).to.equal(1);
+      expect(stats.totalEscrowed.toNumber()).to.equal(3 * REWARD);
+      expect(stats.totalPaid.toNumber()).to.equal(2 * (REWARD - PLATFORM_FEE));
+      expect(stats.totalFeesPaid.toNumber()).to.equal(2 * PLATFORM_FEE);
+      expect(stats.totalRefunded.toNumber()).to.equal(REWARD);
+    });
+
+    it("Reports the completion rate through get_creator_stats", async () => {
+      const creator = await fundedKeypair();
+
+      await completeSolBounty(creator);
+      await cancelSolBounty(await createSolBounty(creator), creator);
+
+      const summary = await program.methods
+        .getCreatorStats()
+        .accounts({ creatorStats: creatorStatsPda(creator.publicKey) })
+        .view();
+      expect(summary.bountiesCreated.toNumber()).to.equal(2);
+      expect(summary.bountiesCompleted.toNumber()).to.equal(1);
+      expect(summary.bountiesCancelled.toNumber()).to.equal(1);
+      expect(summary.completionRateBps).to.equal(5000);
+    });
+  });
+
+  describe("SOL bounties", () => {
+    it("Escrows the reward lamports in the bounty account", async () => {
+      const creator = await fundedKeypair();
+      const creatorBefore = await balance(creator.publicKey);
+
+      const bounty = await createSolBounty(creator);
+
+      const rentOf = async (account: PublicKey) =>
+        provider.connection.getMinimumBalanceForRentExemption(
+          (await provider.connection.getAccountInfo(account))!.data.length
+        );
+      const bountyRent = await rentOf(bounty);
+      const statsRent = await rentOf(creatorStatsPda(creator.publicKey));
+      expect(await balance(bounty)).to.equal(bountyRent + REWARD);
+      // The creator also paid for their stats account on this first bounty
+      expect(creatorBefore - (await balance(creator.publicKey))).to.equal(
+        bountyRent + statsRent + REWARD
+      );
+
+      const account = await program.account.bounty.fetch(bounty);
+      expect(account.isNative).to.equal(true);
+      expect(account.rewardAmount.toNumber()).to.equal(REWARD);
+    });
+
+    it("Pays the worker and the platform fee in SOL on approval", async () => {
+      const creator = await fundedKeypair();
+      const worker = await fundedKeypair();
+      const bounty = await createSolBounty(creator);
+      await submitWork(bounty, worker, "sol-approval");
+
+      const bountyBefore = await balance(bounty);
+      const workerBefore = await balance(worker.publicKey);
+      const platformBefore = await balance(platform.publicKey);
+
+      await approveSolSubmission(bounty, creator, worker.publicKey);
+
+      expect(bountyBefore - (await balance(bounty))).to.equal(REWARD);
+      expect((await balance(worker.publicKey)) - workerBefore).to.equal(REWARD - PLATFORM_FEE);
+      expect((await balance(platform.publicKey)) - platformBefore).to.equal(PLATFORM_FEE);
+
+      const account = await program.account.bounty.fetch(bounty);
+      expect(account.status).to.deep.equal({ completed: {} });
+      expect(account.winners.map((winner) => winner.toString())).to.deep.equal([
+        worker.publicKey.toString(),
+      ]);
+    });
+
+    it("Refunds the escrowed lamports when a SOL bounty is cancelled", async () => {
+      const creator = await fundedKeypair();
+      const bounty = await createSolBounty(creator);
+
+      const creatorBefore = await balance(creator.publicKey);
+      const bountyBefore = await balance(bounty);
+
+      await cancelSolBounty(bounty, creator);
+
+      expect((await balance(creator.publicKey)) - creatorBefore).to.equal(REWARD);
+      expect(bountyBefore - (await balance(bounty))).to.equal(REWARD);
+      const account = await program.account.bounty.fetch(bounty);
+      expect(account.status).to.deep.equal({ cancelled: {} });
+    });
+  });
+
+  describe("bounty addresses", () => {
+    it("Creates two bounties from the same creator in one transaction", async () => {
+      const creator = await fundedKeypair();
+      const { bountyCount } = await program.account.bountyConfig.fetch(configPda);
+      const deadline = (await chainTime()) + 3600;
+
+      // Both instructions land in the same slot and second, which used to collide
+      const first = await createSolBountyInstruction(creator, bountyCount, { deadline }).instruction();
+      const second = await createSolBountyInstruction(creator, bountyCount.addn(1), { deadline }).instruction();
+      await provider.sendAndConfirm(new anchor.web3.Transaction().add(first, second), [creator]);
+
+      const firstBounty = await program.account.bounty.fetch(bountyPda(creator.publicKey, bountyCount));
+      const secondBounty = await program.account.bounty.fetch(
+        bountyPda(creator.publicKey, bountyCount.addn(1))
+      );
+      expect(firstBounty.bountyId.toNumber()).to.equal(bountyCount.toNumber());
+      expect(secondBounty.bountyId.toNumber()).to.equal(bountyCount.toNumber() + 1);
+
+      const config = await program.account.bountyConfig.fetch(configPda);
+      expect(config.bountyCount.toNumber()).to.equal(bountyCount.toNumber() + 2);
+    });
+
+    it("Accepts submissions addressed by the stored bounty index", async () => {
+      const creator = await fundedKeypair();
+      const worker = await fundedKeypair();
+      const bounty = await createSolBounty(creator);
+
+      await submitWork(bounty, worker, "indexed-bounty");
+
+      const account = await program.account.bounty.fetch(bounty);
+      expect(account.submissionsCount).to.equal(1);
+    });
+  });
+
+  describe("submission rules", () => {
+    it("Rejects a second submission from the same worker", async () => {
+      const creator = await fundedKeypair();
+      const worker = await fundedKeypair();
+      const bounty = await createSolBounty(creator);
+      await submitWork(bounty, worker, "first-attempt");
+
+      // A fresh hash gets past the hash marker, so the explicit check is what rejects it
+      await expectError(submitWork(bounty, worker, "second-attempt"), "AlreadySubmitted");
+    });
+
+    it("Rejects a worker whose declared skills miss every required skill", async () => {
+      const creator = await fundedKeypair();
+      const worker = await fundedKeypair();
+      const bounty = await createSolBounty(creator, { requiredSkills: ["rust", "anchor"] });
+
+      await expectError(submitWork(bounty, worker, "wrong-skills", ["design"]), "SkillsNotMatched");
+    });
+
+    it("Rejects a worker who declares no skills for a bounty that requires some", async () => {
+      const creator = await fundedKeypair();
+      const worker = await fundedKeypair();
+      const bounty = await createSolBounty(creator, { requiredSkills: ["rust"] });
+
+      await expectError(submitWork(bounty, worker, "no-skills"), "SkillsNotMatched");
+    });
+
+    it("Accepts a worker with at least one matching skill", async () => {
+      const creator = await fundedKeypair();
+      const worker = await fundedKeypair();
+      const bounty = await createSolBounty(creator, { requiredSkills: ["rust", "anchor"] });
+
+      await submitWork(bounty, worker, "matching-skills", ["Anchor", "typescript"]);
+
+      const submission = await program.account.submission.fetch(submissionPda(bounty, worker.publicKey));
+      expect(submission.worker.toString()).to.equal(worker.publicKey.toString());
+    });
+  });
+
+  describe("deadline extension", () => {
+    const extendDeadline = (bounty: PublicKey, creator: Keypair, newDeadline: number) =>
+      program.methods
+        .extendDeadline(new BN(newDeadline))
+        .accounts({ bounty, creator: creator.publicKey })
+        .signers([creator])
+        .rpc();
+
+    it("Lets a submission that was too late succeed after the deadline is extended", async () => {
+      const creator = await fundedKeypair();
+      const worker = await fundedKeypair();
+      const bounty = await createSolBounty(creator, { deadline: (await chainTime()) + 2 });
+
+      // Let the validator clock move past the original deadline
+      await new Promise((resolve) => setTimeout(resolve, 4000));
+      await expectError(submitWork(bounty, worker, "late-work"), "DeadlinePassed");
+
+      const newDeadline = (await chainTime()) + 3600;
+      await extendDeadline(bounty, creator, newDeadline);
+
+      const account = await program.account.bounty.fetch(bounty);
+      expect(account.deadline.toNumber()).to.equal(newDeadline);
+
+      await submitWork(bounty, worker, "late-work");
+      const submission = await program.account.submission.fetch(submissionPda(bounty, worker.publicKey));
+      expect(submission.worker.toString()).to.equal(worker.publicKey.toString());
+    });
+
+    it("Only lets the creator extend the deadline", async () => {
+      const creator = await fundedKeypair();
+      const bounty = await createSolBounty(creator);
+
+      await expectError(
+        extendDeadline(bounty, await fundedKeypair(), (await chainTime()) + 7200),
+        "NotBountyCreator"
+      );
+    });
+
+    it("Rejects a deadline that is not later than the current one", async () => {
+      const creator = await fundedKeypair();
+      const bounty = await createSolBounty(creator);
+      const { deadline } = await program.account.bounty.fetch(bounty);
+
+      await expectError(extendDeadline(bounty, creator, deadline.toNumber()), "InvalidDeadline");
+    });
+
+    it("Rejects extending a bounty that is no longer open", async () => {
+      const creator = await fundedKeypair();
+      const bounty = await createSolBounty(creator);
+      await cancelSolBounty(bounty, creator);
+
+      await expectError(
+        extendDeadline(bounty, creator, (await chainTime()) + 7200),
+        "BountyNotOpen"
+      );
+    });
+  });
+
+  describe("submission hash uniqueness", () => {
+    it("Records a marker for a unique submission hash", async () => {
+      const creator = await fundedKeypair();
+      const worker = await fundedKeypair();
+      const bounty = await createSolBounty(creator);
+
+      await submitWork(bounty, worker, "unique-hash");
+
+      const marker = await program.account.submissionHashMarker.fetch(
+        submissionHashPda(bounty, "unique-hash")
+      );
+      expect(marker.bounty.toString()).to.equal(bounty.toString());
+      expect(marker.worker.toString()).to.equal(worker.publicKey.toString());
+      expect(marker.submission.toString()).to.equal(submissionPda(bounty, worker.publicKey).toString());
+    });
+
+    it("Rejects another worker submitting the same hash to the bounty", async () => {
+      const creator = await fundedKeypair();
+      const bounty = await createSolBounty(creator);
+      await submitWork(bounty, await fundedKeypair(), "copied-work");
+
+      const copier = await fundedKeypair();
+      // The marker is created with `init`, so a reused hash fails in the system program
+      await expectAlreadyInUse(submitWork(bounty, copier, "copied-work"));
+
+      const account = await program.account.bounty.fetch(bounty);
+      expect(account.submissionsCount).to.equal(1);
+    });
+
+    it("Accepts distinct hashes from different workers", async () => {
+      const creator = await fundedKeypair();
+      const bounty = await createSolBounty(creator);
+
+      await submitWork(bounty, await fundedKeypair(), "work-a");
+      await submitWork(bounty, await fundedKeypair(), "work-b");
+
+      const account = await program.account.bounty.fetch(bounty);
+      expect(account.submissionsCount).to.equal(2);
+    });
+
+    it("Allows the same hash on a different bounty", async () => {
+      const creator = await fundedKeypair();
+      const worker = await fundedKeypair();
+      const first = await createSolBounty(creator);
+      const second = await createSolBounty(creator);
+
+      await submitWork(first, worker, "shared-hash");
+      await submitWork(second, worker, "shared-hash");
+    });
+  });
+
+  describe("counterparty ratings", () => {
+    const rateCounterparty = (
+      bounty: PublicKey,
+      rater: Keypair,
+      creator: PublicKey,
+      worker: PublicKey,
+      rating: number
+    ) =>
+      program.methods
+        .rateCounterparty(rating, "Great to work with")
+        .accounts({
+          bounty,
+          ratingRecord: PublicKey.findProgramAddressSync(
+            [Buffer.from("rating"), bounty.toBuffer(), rater.publicKey.toBuffer(), worker.toBuffer()],
+            program.programId
+          )[0],
+          creatorStats: creatorStatsPda(creator),
+          workerStats: workerStatsPda(worker),
+          worker,
+          rater: rater.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([rater])
+        .rpc();
+
+    it("Lets both parties rate each other after completion and updates the averages", async () => {
+      const creator = await fundedKeypair();
+      const { bounty, worker } = await completeSolBounty(creator);
+
+      await rateCounterparty(bounty, creator, creator.publicKey, worker.publicKey, 4);
+      await rateCounterparty(bounty, worker, creator.publicKey, worker.publicKey, 5);
+
+      const workerStats = await program.account.workerStats.fetch(workerStatsPda(worker.publicKey));
+      expect(workerStats.ratings.count).to.equal(1);
+      expect(workerStats.ratings.averageRating).to.equal(400);
+
+      const creatorStats = await program.account.creatorStats.fetch(creatorStatsPda(creator.publicKey));
+      expect(creatorStats.ratings.count).to.equal(1);
+      expect(creatorStats.ratings.averageRating).to.equal(500);
+
+      // A second completed bounty between the same pair moves the worker's average
+      const second = await completeSolBounty(creator, worker);
+      await rateCounterparty(second.bounty, creator, creator.publicKey, worker.publicKey, 5);
+
+      const updated = await program.account.workerStats.fetch(workerStatsPda(worker.publicKey));
+      expect(updated.ratings.count).to.equal(2);
+      expect(updated.ratings.averageRating).to.equal(450);
+    });
+
+    it("Rejects rating the same bounty twice", async () => {
+      const creator = await fundedKeypair();
+      const { bounty, worker } = await completeSolBounty(creator);
+      await rateCounterparty(bounty, creator, creator.publicKey, worker.publicKey, 3);
+
+      await expectAlreadyInUse(rateCounterparty(bounty, creator, creator.publicKey, worker.publicKey, 5));
+
+      const workerStats = await program.account.workerStats.fetch(workerStatsPda(worker.publicKey));
+      expect(workerStats.ratings.count).to.equal(1);
+    });
+
+    it("Rejects ratings before the bounty completes", async () => {
+      const creator = await fundedKeypair();
+      const worker = await fundedKeypair();
+      const bounty = await createSolBounty(creator);
+      await submitWork(bounty, worker, "not-yet-approved");
+
+      await expectError(
+        rateCounterparty(bounty, creator, creator.publicKey, worker.publicKey, 4),
+        "BountyNotCompleted"
+      );
+    });
+
+    it("Rejects ratings from someone outside the bounty", async () => {
+      const creator = await fundedKeypair();
+      const { bounty, worker } = await completeSolBounty(creator);
+
+      await expectError(
+        rateCounterparty(bounty, await fundedKeypair(), creator.publicKey, worker.publicKey, 4),
+        "NotBountyParticipant"
+      );
+    });
+
+    it("Rejects ratings outside 1 to 5", async () => {
+      const creator = await fundedKeypair();
+      const { bounty, worker } = await completeSolBounty(creator);
+
+      await expectError(
+        rateCounterparty(bounty, creator, creator.publicKey, worker.publicKey, 6),
+        "InvalidRating"
+      );
+    });
+  });
+});
@@ -6,6 +6,9 @@ use anchor_spl::{
 
 declare_id!("CoffeeShopPayment11111111111111111111111111");
 
+pub const MAX_LINE_ITEMS: usize = 10;
+pub const MAX_TAB_CONTRIBUTORS: usize = 8;
+
 #[program]
 pub mod coffee_shop {
     use super::*;
@@ -23,6 +26,7 @@ pub mod coffee_shop {
         merchant.fee_percentage = fee_percentage;
         merchant.total_sales = 0;
         merchant.total_transactions = 0;
+        merchant.product_count = 0;
         merchant.is_active = true;
         merchant.created_at = Clock::get()?.unix_timestamp;
         
@@ -35,15 +39,44 @@ pub mod coffee_shop {
         price_usdc: u64, // in lamports (6 decimals for USDC)
         description: String,
     ) -> Result<()> {
+        let merchant = &mut ctx.accounts.merchant;
         let product = &mut ctx.accounts.product;
-        product.merchant = ctx.accounts.merchant.key();
+        product.merchant = merchant.key();
+        product.product_id = merchant.product_count;
         product.name = name;
         product.price_usdc = price_usdc;
         product.description = description;
         product.is_available = true;
         product.total_sold = 0;
+        product.stock_remaining = None;
         product.created_at = Clock::get()?.unix_timestamp;
         
+        merchant.product_count += 1;
+        
+        Ok(())
+    }
+
+    pub fn restock_product(
+        ctx: Context<RestockProduct>,
+        quantity: u32,
+    ) -> Result<()> {
+        let product = &mut ctx.accounts.product;
+        
+        require!(quantity > 0, CoffeeShopError::InvalidAmount);
+        
+        // Restocking switches an unlimited product to tracked inventory
+        let stock = product.stock_remaining.unwrap_or(0)
+            .checked_add(quantity)
+            .ok_or(CoffeeShopError::InvalidAmount)?;
+        product.stock_remaining = Some(stock);
+        
+        emit!(ProductRestocked {
+            product: product.key(),
+            quantity,
+            stock_remaining: stock,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
         Ok(())
     }
 
@@ -51,16 +84,68 @@ pub mod coffee_shop {
         ctx: Context<ProcessPayment>,
         amount: u64,
         tip_amount: u64,
+        line_items: Vec<LineItem>,
     ) -> Result<()> {
         let merchant = &mut ctx.accounts.merchant;
         let payment = &mut ctx.accounts.payment;
         
         require!(merchant.is_active, CoffeeShopError::MerchantInactive);
         require!(amount > 0, CoffeeShopError::InvalidAmount);
+        require!(line_items.len() <= MAX_LINE_ITEMS, CoffeeShopError::TooManyLineItems);
+        
+        // Itemized receipts must add up to the charged amount (tips excluded)
+        if !line_items.is_empty() {
+            let mut items_total: u64 = 0;
+            for item in line_items.iter() {
+                require!(item.quantity > 0, CoffeeShopError::InvalidAmount);
+                let line_total = item.unit_price
+                    .checked_mul(item.quantity as u64)
+                    .ok_or(CoffeeShopError::InvalidAmount)?;
+                items_total = items_total
+                    .checked_add(line_total)
+                    .ok_or(CoffeeShopError::InvalidAmount)?;
+            }
+            require!(items_total == amount, CoffeeShopError::LineItemTotalMismatch);
+        }
         
-        let total_amount = amount + tip_amount;
-        let fee_amount = (amount * merchant.fee_percentage as u64) / 10000;
-        let merchant_payout = total_amount - fee_amount;
+        // Per-SKU sales tracking when the purchased product is supplied
+        let product_key = match ctx.accounts.product.as_mut() {
+            Some(product) => {
+                require!(product.merchant == merchant.key(), CoffeeShopError::ProductMerchantMismatch);
+                require!(product.is_available, CoffeeShopError::ProductNotAvailable);
+                require!(amount >= product.price_usdc, CoffeeShopError::InvalidAmount);
+                // Units of this product on the receipt; a payment without line items sells one
+                let quantity = if line_items.is_empty() {
+                    1
+                } else {
+                    line_items.iter()
+                        .filter(|item| item.product == product.key())
+                        .try_fold(0u32, |total, item| total.checked_add(item.quantity))
+                        .ok_or(CoffeeShopError::InvalidAmount)?
+                };
+                require!(quantity > 0, CoffeeShopError::ProductNotInLineItems);
+                if let Some(stock) = product.stock_remaining {
+                    require!(stock >= quantity, CoffeeShopError::OutOfStock);
+                    product.stock_remaining = Some(stock - quantity);
+                }
+                product.total_sold = product.total_sold
+                    .checked_add(quantity as u64)
+                    .ok_or(CoffeeShopError::InvalidAmount)?;
+                Some(product.key())
+            }
+            None => None,
+        };
+        
+        // Fee applies to the order amount only; the tip passes through to the merchant in full.
+        // Customer is debited `amount + tip`, split as merchant `amount - fee + tip` and platform `fee`.
+        let total_amount = amount
+            .checked_add(tip_amount)
+            .ok_or(CoffeeShopError::InvalidAmount)?;
+        let fee_amount = amount
+            .checked_mul(merchant.fee_percentage as u64)
+            .ok_or(CoffeeShopError::InvalidAmount)?
+            / 10000;
+        let merchant_payout = (amount - fee_amount) + tip_amount;
         
         // Transfer USDC from customer to merchant
         let transfer_to_merchant = Transfer {
@@ -103,6 +188,7 @@ pub mod coffee_shop {
         payment.total_amount = total_amount;
         payment.timestamp = Clock::get()?.unix_timestamp;
         payment.status = PaymentStatus::Completed;
+        payment.line_items = line_items;
         
         // Update merchant stats
         merchant.total_sales += merchant_payout;
@@ -113,6 +199,7 @@ pub mod coffee_shop {
             customer: ctx.accounts.customer.key(),
             amount: total_amount,
             fee_amount,
+            product: product_key,
             timestamp: payment.timestamp,
         });
         
@@ -151,6 +238,281 @@ pub mod coffee_shop {
         
         Ok(())
     }
+
+    pub fn refund_payment(
+        ctx: Context<RefundPayment>,
+        amount: u64,
+    ) -> Result<()> {
+        let merchant = &mut ctx.accounts.merchant;
+        let payment = &mut ctx.accounts.payment;
+        
+        require!(amount > 0, CoffeeShopError::InvalidAmount);
+        require!(payment.status == PaymentStatus::Completed, CoffeeShopError::PaymentNotRefundable);
+        require!(amount <= payment.amount, CoffeeShopError::RefundExceedsPayment);
+        
+        // Return USDC from the merchant's business account to the paying customer
+        let transfer_refund = Transfer {
+            from: ctx.accounts.merchant_token_account.to_account_info(),
+            to: ctx.accounts.customer_token_account.to_account_info(),
+            authority: ctx.accounts.merchant_authority.to_account_info(),
+        };
+        
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                transfer_refund,
+            ),
+            amount,
+        )?;
+        
+        payment.status = PaymentStatus::Refunded;
+        merchant.total_sales = merchant.total_sales.saturating_sub(amount);
+        
+        emit!(PaymentRefunded {
+            merchant: merchant.key(),
+            customer: payment.customer,
+            payment: payment.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Open a tab that several customers split; `shares` lists what each contributor owes
+    pub fn open_group_tab(
+        ctx: Context<OpenGroupTab>,
+        tab_id: u64,
+        total_amount: u64,
+        shares: Vec<TabShare>,
+    ) -> Result<()> {
+        let merchant = &ctx.accounts.merchant;
+        let group_tab = &mut ctx.accounts.group_tab;
+        
+        require!(merchant.is_active, CoffeeShopError::MerchantInactive);
+        require!(total_amount > 0, CoffeeShopError::InvalidAmount);
+        require!(
+            !shares.is_empty() && shares.len() <= MAX_TAB_CONTRIBUTORS,
+            CoffeeShopError::InvalidTabShares
+        );
+        
+        let mut shares_total: u64 = 0;
+        for (i, share) in shares.iter().enumerate() {
+            require!(share.share > 0 && share.contributed == 0, CoffeeShopError::InvalidTabShares);
+            require!(
+                !shares[..i].iter().any(|s| s.contributor == share.contributor),
+                CoffeeShopError::InvalidTabShares
+            );
+            shares_total = shares_total
+                .checked_add(share.share)
+                .ok_or(CoffeeShopError::InvalidAmount)?;
+        }
+        require!(shares_total == total_amount, CoffeeShopError::InvalidTabShares);
+        
+        group_tab.merchant = merchant.key();
+        group_tab.opener = ctx.accounts.opener.key();
+        group_tab.tab_id = tab_id;
+        group_tab.usdc_mint = ctx.accounts.usdc_mint.key();
+        group_tab.total_amount = total_amount;
+        group_tab.funded_amount = 0;
+        group_tab.shares = shares;
+        group_tab.status = TabStatus::Open;
+        group_tab.created_at = Clock::get()?.unix_timestamp;
+        group_tab.settled_at = None;
+        group_tab.bump = ctx.bumps.group_tab;
+        
+        emit!(GroupTabOpened {
+            merchant: group_tab.merchant,
+            group_tab: group_tab.key(),
+            opener: group_tab.opener,
+            total_amount,
+            contributors: group_tab.shares.len() as u8,
+            timestamp: group_tab.created_at,
+        });
+        
+        Ok(())
+    }
+
+    /// Pay toward the caller's share of a tab; partial payments are allowed, overpaying is not
+    pub fn contribute_to_tab(
+        ctx: Context<ContributeToTab>,
+        amount: u64,
+    ) -> Result<()> {
+        let group_tab = &mut ctx.accounts.group_tab;
+        let contributor = ctx.accounts.contributor.key();
+        
+        require!(group_tab.status == TabStatus::Open, CoffeeShopError::TabNotOpen);
+        require!(amount > 0, CoffeeShopError::InvalidAmount);
+        
+        let share = group_tab.shares.iter_mut()
+            .find(|s| s.contributor == contributor)
+            .ok_or(CoffeeShopError::NotTabContributor)?;
+        let contributed = share.contributed
+            .checked_add(amount)
+            .ok_or(CoffeeShopError::InvalidAmount)?;
+        require!(contributed <= share.share, CoffeeShopError::TabOverContribution);
+        share.contributed = contributed;
+        let share_remaining = share.share - contributed;
+        
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.contributor_token_account.to_account_info(),
+                    to: ctx.accounts.tab_token_account.to_account_info(),
+                    authority: ctx.accounts.contributor.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        
+        group_tab.funded_amount += amount;
+        
+        emit!(TabContributionMade {
+            group_tab: group_tab.key(),
+            contributor,
+            amount,
+            share_remaining,
+            tab_remaining: group_tab.total_amount - group_tab.funded_amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Pay a fully funded tab out to the merchant, less the platform fee (merchant only)
+    pub fn settle_group_tab(ctx: Context<SettleGroupTab>) -> Result<()> {
+        let merchant = &mut ctx.accounts.merchant;
+        let group_tab = &mut ctx.accounts.group_tab;
+        
+        require!(group_tab.status == TabStatus::Open, CoffeeShopError::TabNotOpen);
+        require!(group_tab.funded_amount == group_tab.total_amount, CoffeeShopError::TabNotFullyFunded);
+        
+        let amount = group_tab.total_amount;
+        let fee_amount = amount
+            .checked_mul(merchant.fee_percentage as u64)
+            .ok_or(CoffeeShopError::InvalidAmount)?
+            / 10000;
+        let merchant_payout = amount - fee_amount;
+        
+        let tab_id_bytes = group_tab.tab_id.to_le_bytes();
+        let seeds = &[
+            b"group_tab".as_ref(),
+            group_tab.merchant.as_ref(),
+            group_tab.opener.as_ref(),
+            tab_id_bytes.as_ref(),
+            &[group_tab.bump],
+        ];
+        let signer = &[&seeds[..]];
+        
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.tab_token_account.to_account_info(),
+                    to: ctx.accounts.merchant_token_account.to_account_info(),
+                    authority: group_tab.to_account_info(),
+                },
+                signer,
+            ),
+            merchant_payout,
+        )?;
+        
+        if fee_amount > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.tab_token_account.to_account_info(),
+                        to: ctx.accounts.platform_fee_account.to_account_info(),
+                        authority: group_tab.to_account_info(),
+                    },
+                    signer,
+                ),
+                fee_amount,
+            )?;
+        }
+        
+        group_tab.status = TabStatus::Settled;
+        group_tab.settled_at = Some(Clock::get()?.unix_timestamp);
+        
+        merchant.total_sales += merchant_payout;
+        merchant.total_transactions += 1;
+        
+        emit!(GroupTabSettled {
+            merchant: merchant.key(),
+            group_tab: group_tab.key(),
+            amount,
+            fee_amount,
+            timestamp: group_tab.settled_at.unwrap(),
+        });
+        
+        Ok(())
+    }
+
+    /// Cancel an underfunded tab; contributors then withdraw what they paid in
+    pub fn cancel_group_tab(ctx: Context<CancelGroupTab>) -> Result<()> {
+        let group_tab = &mut ctx.accounts.group_tab;
+        
+        require!(group_tab.status == TabStatus::Open, CoffeeShopError::TabNotOpen);
+        
+        group_tab.status = TabStatus::Cancelled;
+        
+        emit!(GroupTabCancelled {
+            group_tab: group_tab.key(),
+            funded_amount: group_tab.funded_amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Return a contributor's payments from a cancelled tab
+    pub fn withdraw_tab_contribution(ctx: Context<WithdrawTabContribution>) -> Result<()> {
+        let group_tab = &mut ctx.accounts.group_tab;
+        let contributor = ctx.accounts.contributor.key();
+        
+        require!(group_tab.status == TabStatus::Cancelled, CoffeeShopError::TabNotCancelled);
+        
+        let share = group_tab.shares.iter_mut()
+            .find(|s| s.contributor == contributor)
+            .ok_or(CoffeeShopError::NotTabContributor)?;
+        let amount = share.contributed;
+        require!(amount > 0, CoffeeShopError::InvalidAmount);
+        share.contributed = 0;
+        group_tab.funded_amount -= amount;
+        
+        let tab_id_bytes = group_tab.tab_id.to_le_bytes();
+        let seeds = &[
+            b"group_tab".as_ref(),
+            group_tab.merchant.as_ref(),
+            group_tab.opener.as_ref(),
+            tab_id_bytes.as_ref(),
+            &[group_tab.bump],
+        ];
+        
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.tab_token_account.to_account_info(),
+                    to: ctx.accounts.contributor_token_account.to_account_info(),
+                    authority: group_tab.to_account_info(),
+                },
+                &[&seeds[..]],
+            ),
+            amount,
+        )?;
+        
+        emit!(TabContributionWithdrawn {
+            group_tab: group_tab.key(),
+            contributor,
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
 }
 
 #[derive(Accounts)]
@@ -176,7 +538,7 @@ pub struct CreateProduct<'info> {
         init,
         payer = authority,
         space = 8 + Product::INIT_SPACE,
-        seeds = [b"product", merchant.key().as_ref(), authority.key().as_ref()],
+        seeds = [b"product", merchant.key().as_ref(), merchant.product_count.to_le_bytes().as_ref()],
         bump
     )]
     pub product: Account<'info, Product>,
@@ -194,6 +556,20 @@ pub struct CreateProduct<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct RestockProduct<'info> {
+    #[account(
+        mut,
+        constraint = product.merchant == merchant.key() @ CoffeeShopError::ProductMerchantMismatch
+    )]
+    pub product: Account<'info, Product>,
+    
+    #[account(has_one = authority)]
+    pub merchant: Account<'info, Merchant>,
+    
+    pub authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct ProcessPayment<'info> {
     #[account(
@@ -208,6 +584,9 @@ pub struct ProcessPayment<'info> {
     #[account(mut)]
     pub merchant: Account<'info, Merchant>,
     
+    #[account(mut)]
+    pub product: Option<Account<'info, Product>>,
+    
     #[account(mut)]
     pub customer: Signer<'info>,
     
@@ -267,6 +646,179 @@ pub struct InstantPayout<'info> {
     pub token_program: Program<'info, Token>,
 }
 
+#[derive(Accounts)]
+pub struct RefundPayment<'info> {
+    #[account(
+        mut,
+        constraint = payment.merchant == merchant.key()
+    )]
+    pub payment: Account<'info, Payment>,
+    
+    #[account(
+        mut,
+        constraint = merchant.authority == merchant_authority.key()
+    )]
+    pub merchant: Account<'info, Merchant>,
+    
+    pub merchant_authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = merchant_authority
+    )]
+    pub merchant_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = payment.customer
+    )]
+    pub customer_token_account: Account<'info, TokenAccount>,
+    
+    pub usdc_mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(tab_id: u64)]
+pub struct OpenGroupTab<'info> {
+    #[account(
+        init,
+        payer = opener,
+        space = 8 + GroupTab::INIT_SPACE,
+        seeds = [b"group_tab", merchant.key().as_ref(), opener.key().as_ref(), tab_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub group_tab: Account<'info, GroupTab>,
+    
+    #[account(
+        init,
+        payer = opener,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = group_tab
+    )]
+    pub tab_token_account: Account<'info, TokenAccount>,
+    
+    pub merchant: Account<'info, Merchant>,
+    
+    #[account(mut)]
+    pub opener: Signer<'info>,
+    
+    pub usdc_mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ContributeToTab<'info> {
+    #[account(mut)]
+    pub group_tab: Account<'info, GroupTab>,
+    
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = group_tab
+    )]
+    pub tab_token_account: Account<'info, TokenAccount>,
+    
+    pub contributor: Signer<'info>,
+    
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = contributor
+    )]
+    pub contributor_token_account: Account<'info, TokenAccount>,
+    
+    #[account(address = group_tab.usdc_mint)]
+    pub usdc_mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SettleGroupTab<'info> {
+    #[account(
+        mut,
+        constraint = group_tab.merchant == merchant.key()
+    )]
+    pub group_tab: Account<'info, GroupTab>,
+    
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = group_tab
+    )]
+    pub tab_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = merchant.authority == merchant_authority.key()
+    )]
+    pub merchant: Account<'info, Merchant>,
+    
+    pub merchant_authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = merchant.payout_address
+    )]
+    pub merchant_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = platform_authority
+    )]
+    pub platform_fee_account: Account<'info, TokenAccount>,
+    
+    #[account(address = group_tab.usdc_mint)]
+    pub usdc_mint: Account<'info, Mint>,
+    /// CHECK: Platform authority for fee collection
+    pub platform_authority: AccountInfo<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CancelGroupTab<'info> {
+    #[account(
+        mut,
+        constraint = group_tab.opener == opener.key()
+    )]
+    pub group_tab: Account<'info, GroupTab>,
+    
+    pub opener: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTabContribution<'info> {
+    #[account(mut)]
+    pub group_tab: Account<'info, GroupTab>,
+    
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = group_tab
+    )]
+    pub tab_token_account: Account<'info, TokenAccount>,
+    
+    pub contributor: Signer<'info>,
+    
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = contributor
+    )]
+    pub contributor_token_account: Account<'info, TokenAccount>,
+    
+    #[account(address = group_tab.usdc_mint)]
+    pub usdc_mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct Merchant {
@@ -277,6 +829,7 @@ pub struct Merchant {
     pub fee_percentage: u16,
     pub total_sales: u64,
     pub total_transactions: u64,
+    pub product_count: u64,
     pub is_active: bool,
     pub created_at: i64,
 }
@@ -285,6 +838,7 @@ pub struct Merchant {
 #[derive(InitSpace)]
 pub struct Product {
     pub merchant: Pubkey,
+    pub product_id: u64,
     #[max_len(50)]
     pub name: String,
     pub price_usdc: u64,
@@ -292,6 +846,7 @@ pub struct Product {
     pub description: String,
     pub is_available: bool,
     pub total_sold: u64,
+    pub stock_remaining: Option<u32>, // None means unlimited
     pub created_at: i64,
 }
 
@@ -306,9 +861,49 @@ pub struct Payment {
     pub total_amount: u64,
     pub timestamp: i64,
     pub status: PaymentStatus,
+    #[max_len(MAX_LINE_ITEMS)]
+    pub line_items: Vec<LineItem>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct LineItem {
+    pub product: Pubkey,
+    pub quantity: u32,
+    pub unit_price: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct GroupTab {
+    pub merchant: Pubkey,
+    pub opener: Pubkey,
+    pub tab_id: u64,
+    pub usdc_mint: Pubkey,
+    pub total_amount: u64,
+    pub funded_amount: u64, // held by the tab's token account until settlement
+    #[max_len(MAX_TAB_CONTRIBUTORS)]
+    pub shares: Vec<TabShare>,
+    pub status: TabStatus,
+    pub created_at: i64,
+    pub settled_at: Option<i64>,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct TabShare {
+    pub contributor: Pubkey,
+    pub share: u64,
+    pub contributed: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, InitSpace)]
+pub enum TabStatus {
+    Open,
+    Settled,
+    Cancelled,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, InitSpace)]
 pub enum PaymentStatus {
     Pending,
     Completed,
@@ -322,6 +917,7 @@ pub struct PaymentProcessed {
     pub customer: Pubkey,
     pub amount: u64,
     pub fee_amount: u64,
+    pub product: Option<Pubkey>,
     pub timestamp: i64,
 }
 
@@ -332,6 +928,67 @@ pub struct InstantPayoutProcessed {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct ProductRestocked {
+    pub product: Pubkey,
+    pub quantity: u32,
+    pub stock_remaining: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PaymentRefunded {
+    pub merchant: Pubkey,
+    pub customer: Pubkey,
+    pub payment: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GroupTabOpened {
+    pub merchant: Pubkey,
+    pub group_tab: Pubkey,
+    pub opener: Pubkey,
+    pub total_amount: u64,
+    pub contributors: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TabContributionMade {
+    pub group_tab: Pubkey,
+    pub contributor: Pubkey,
+    pub amount: u64,
+    pub share_remaining: u64,
+    pub tab_remaining: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GroupTabSettled {
+    pub merchant: Pubkey,
+    pub group_tab: Pubkey,
+    pub amount: u64,
+    pub fee_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GroupTabCancelled {
+    pub group_tab: Pubkey,
+    pub funded_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TabContributionWithdrawn {
+    pub group_tab: Pubkey,
+    pub contributor: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
 #[error_code]
 pub enum CoffeeShopError {
     #[msg("Merchant is not active")]
@@ -342,4 +999,30 @@ pub enum CoffeeShopError {
     InsufficientBalance,
     #[msg("Product not available")]
     ProductNotAvailable,
+    #[msg("Too many line items")]
+    TooManyLineItems,
+    #[msg("Line items do not add up to the payment amount")]
+    LineItemTotalMismatch,
+    #[msg("Only completed payments can be refunded")]
+    PaymentNotRefundable,
+    #[msg("Refund exceeds the original payment amount")]
+    RefundExceedsPayment,
+    #[msg("Product does not belong to this merchant")]
+    ProductMerchantMismatch,
+    #[msg("Product is out of stock")]
+    OutOfStock,
+    #[msg("Tab shares must be non-empty, unique, and add up to the tab total")]
+    InvalidTabShares,
+    #[msg("Tab is not open")]
+    TabNotOpen,
+    #[msg("Tab is not cancelled")]
+    TabNotCancelled,
+    #[msg("Signer is not a contributor on this tab")]
+    NotTabContributor,
+    #[msg("Contribution exceeds the remaining share")]
+    TabOverContribution,
+    #[msg("Tab is not fully funded")]
+    TabNotFullyFunded,
+    #[msg("Product does not appear in the line items")]
+    ProductNotInLineItems,
 }
@@ -0,0 +1,514 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, LAMPORTS_PER_SOL, SystemProgram } from "@solana/web3.js";
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  createAssociatedTokenAccount,
+  createMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  mintTo,
+} from "@solana/spl-token";
+import { CoffeeShop } from "../target/types/coffee_shop";
+import { expect } from "chai";
+
+describe("coffee-shop", () => {
+  // Configure the client to use the local cluster.
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.CoffeeShop as Program<CoffeeShop>;
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+  const payer = (provider.wallet as anchor.Wallet).payer;
+
+  const FEE_BPS = 250;
+  const platformAuthority = Keypair.generate().publicKey;
+  let usdcMint: PublicKey;
+
+  const merchantPda = (authority: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("merchant"), authority.toBuffer()],
+      program.programId
+    )[0];
+
+  const productPda = (merchant: PublicKey, productId: number) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("product"), merchant.toBuffer(), new BN(productId).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+
+  const paymentPda = (merchant: PublicKey, customer: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("payment"), merchant.toBuffer(), customer.toBuffer()],
+      program.programId
+    )[0];
+
+  const fundedKeypair = async () => {
+    const keypair = Keypair.generate();
+    const signature = await provider.connection.requestAirdrop(keypair.publicKey, LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(signature);
+    return keypair;
+  };
+
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    try {
+      await promise;
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal(code);
+      return;
+    }
+    expect.fail(`expected ${code}`);
+  };
+
+  const usdcAccount = (owner: PublicKey) => getAssociatedTokenAddressSync(usdcMint, owner, true);
+
+  const usdcBalance = async (owner: PublicKey) =>
+    Number((await getAccount(provider.connection, usdcAccount(owner))).amount);
+
+  type Shop = { authority: Keypair; merchant: PublicKey; payout: PublicKey };
+
+  // A merchant whose payouts go to a fresh address with an empty USDC account
+  const createShop = async (feeBps = FEE_BPS): Promise<Shop> => {
+    const authority = await fundedKeypair();
+    const payout = Keypair.generate().publicKey;
+    await createAssociatedTokenAccount(provider.connection, payer, usdcMint, payout);
+
+    const merchant = merchantPda(authority.publicKey);
+    await program.methods
+      .initializeMerchant("Corner Coffee", payout, feeBps)
+      .accounts({
+        merchant,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+
+    return { authority, merchant, payout };
+  };
+
+  const createProduct = async ({ authority, merchant }: Shop, name: string, price: number) => {
+    const { productCount } = await program.account.merchant.fetch(merchant);
+    const product = productPda(merchant, productCount.toNumber());
+    await program.methods
+      .createProduct(name, new BN(price), `${name}, made to order`)
+      .accounts({
+        product,
+        merchant,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    return product;
+  };
+
+  // A funded customer holding `usdc` in their token account
+  const createCustomer = async (usdc = 100_000) => {
+    const customer = await fundedKeypair();
+    const account = await createAssociatedTokenAccount(provider.connection, payer, usdcMint, customer.publicKey);
+    await mintTo(provider.connection, payer, usdcMint, account, payer, usdc);
+    return customer;
+  };
+
+  type LineItem = { product: PublicKey; quantity: number; unitPrice: BN };
+
+  const processPayment = (
+    shop: Shop,
+    customer: Keypair,
+    amount: number,
+    { tip = 0, lineItems = [] as LineItem[], product = null as PublicKey | null } = {}
+  ) =>
+    program.methods
+      .processPayment(new BN(amount), new BN(tip), lineItems)
+      .accounts({
+        payment: paymentPda(shop.merchant, customer.publicKey),
+        merchant: shop.merchant,
+        product,
+        customer: customer.publicKey,
+        customerTokenAccount: usdcAccount(customer.publicKey),
+        merchantTokenAccount: usdcAccount(shop.payout),
+        platformFeeAccount: usdcAccount(platformAuthority),
+        usdcMint,
+        platformAuthority,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([customer])
+      .rpc();
+
+  before(async () => {
+    usdcMint = await createMint(provider.connection, payer, payer.publicKey, null, 6);
+    await createAssociatedTokenAccount(provider.connection, payer, usdcMint, platformAuthority);
+  });
+
+  describe("itemized receipts", () => {
+    let shop: Shop;
+    let espresso: PublicKey;
+    let latte: PublicKey;
+    let coldBrew: PublicKey;
+
+    const lineItem = (product: PublicKey, quantity: number, unitPrice: number): LineItem => ({
+      product,
+      quantity,
+      unitPrice: new BN(unitPrice),
+    });
+
+    before(async () => {
+      shop = await createShop();
+      espresso = await createProduct(shop, "Espresso", 3_000);
+      latte = await createProduct(shop, "Latte", 4_500);
+      coldBrew = await createProduct(shop, "Cold Brew", 5_000);
+    });
+
+    it("Stores line items that add up to the charged amount", async () => {
+      const customer = await createCustomer();
+      const lineItems = [lineItem(espresso, 2, 3_000), lineItem(latte, 1, 4_500), lineItem(coldBrew, 1, 5_000)];
+
+      await processPayment(shop, customer, 15_500, { lineItems });
+
+      const payment = await program.account.payment.fetch(paymentPda(shop.merchant, customer.publicKey));
+      expect(payment.amount.toNumber()).to.equal(15_500);
+      expect(payment.lineItems.map((item) => item.product.toString())).to.deep.equal(
+        [espresso, latte, coldBrew].map((product) => product.toString())
+      );
+      expect(payment.lineItems.map((item) => item.quantity)).to.deep.equal([2, 1, 1]);
+      const itemsTotal = payment.lineItems.reduce(
+        (total, item) => total + item.quantity * item.unitPrice.toNumber(),
+        0
+      );
+      expect(itemsTotal).to.equal(payment.amount.toNumber());
+    });
+
+    it("Rejects line items that don't add up to the amount", async () => {
+      const customer = await createCustomer();
+      const lineItems = [lineItem(espresso, 2, 3_000), lineItem(latte, 1, 4_500), lineItem(coldBrew, 1, 5_000)];
+
+      await expectError(processPayment(shop, customer, 15_000, { lineItems }), "LineItemTotalMismatch");
+    });
+
+    it("Rejects more line items than a receipt holds", async () => {
+      const customer = await createCustomer();
+      const lineItems = Array.from({ length: 11 }, () => lineItem(espresso, 1, 3_000));
+
+      await expectError(processPayment(shop, customer, 33_000, { lineItems }), "TooManyLineItems");
+    });
+
+    it("Counts every unit of the product on the receipt as sold", async () => {
+      const customer = await createCustomer();
+      const lineItems = [lineItem(espresso, 2, 3_000), lineItem(latte, 1, 4_500), lineItem(espresso, 1, 3_000)];
+      const { totalSold } = await program.account.product.fetch(espresso);
+
+      await processPayment(shop, customer, 13_500, { lineItems, product: espresso });
+
+      const sold = await program.account.product.fetch(espresso);
+      expect(sold.totalSold.toNumber()).to.equal(totalSold.toNumber() + 3);
+    });
+
+    it("Rejects a product that isn't on the receipt", async () => {
+      const customer = await createCustomer();
+      const lineItems = [lineItem(latte, 1, 4_500)];
+
+      await expectError(
+        processPayment(shop, customer, 4_500, { lineItems, product: coldBrew }),
+        "ProductNotInLineItems"
+      );
+    });
+  });
+
+  describe("menu", () => {
+    it("Lists several products under one merchant", async () => {
+      const shop = await createShop();
+
+      const espresso = await createProduct(shop, "Espresso", 3_000);
+      const latte = await createProduct(shop, "Latte", 4_500);
+      const coldBrew = await createProduct(shop, "Cold Brew", 5_000);
+
+      const menu = await Promise.all(
+        [espresso, latte, coldBrew].map((product) => program.account.product.fetch(product))
+      );
+      expect(menu.map((product) => product.productId.toNumber())).to.deep.equal([0, 1, 2]);
+      expect(menu.map((product) => product.name)).to.deep.equal(["Espresso", "Latte", "Cold Brew"]);
+      expect(menu.map((product) => product.priceUsdc.toNumber())).to.deep.equal([3_000, 4_500, 5_000]);
+      menu.forEach((product) => expect(product.merchant.toString()).to.equal(shop.merchant.toString()));
+
+      const merchant = await program.account.merchant.fetch(shop.merchant);
+      expect(merchant.productCount.toNumber()).to.equal(3);
+    });
+
+    it("Only lets the merchant authority add products", async () => {
+      const shop = await createShop();
+
+      await expectError(
+        createProduct({ ...shop, authority: await fundedKeypair() }, "Espresso", 3_000),
+        "ConstraintHasOne"
+      );
+    });
+  });
+
+  describe("tips", () => {
+    it("Passes the tip through to the merchant without a fee", async () => {
+      const shop = await createShop();
+      const customer = await createCustomer(100_000);
+      const platformBefore = await usdcBalance(platformAuthority);
+
+      // 2.5% of the 10_000 order; the 2_000 tip is fee-free
+      await processPayment(shop, customer, 10_000, { tip: 2_000 });
+
+      expect(await usdcBalance(shop.payout)).to.equal(10_000 - 250 + 2_000);
+      expect(await usdcBalance(platformAuthority)).to.equal(platformBefore + 250);
+      expect(await usdcBalance(customer.publicKey)).to.equal(100_000 - 12_000);
+
+      const payment = await program.account.payment.fetch(paymentPda(shop.merchant, customer.publicKey));
+      expect(payment.tipAmount.toNumber()).to.equal(2_000);
+      expect(payment.feeAmount.toNumber()).to.equal(250);
+      expect(payment.totalAmount.toNumber()).to.equal(12_000);
+
+      const merchant = await program.account.merchant.fetch(shop.merchant);
+      expect(merchant.totalSales.toNumber()).to.equal(11_750);
+    });
+
+    it("Charges the fee on the order alone when there's no tip", async () => {
+      const shop = await createShop();
+      const customer = await createCustomer(100_000);
+
+      await processPayment(shop, customer, 10_000);
+
+      expect(await usdcBalance(shop.payout)).to.equal(9_750);
+      expect(await usdcBalance(customer.publicKey)).to.equal(90_000);
+    });
+  });
+
+  describe("inventory", () => {
+    const restockProduct = ({ authority, merchant }: Shop, product: PublicKey, quantity: number) =>
+      program.methods
+        .restockProduct(quantity)
+        .accounts({ product, merchant, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+
+    it("Leaves new products unlimited", async () => {
+      const shop = await createShop();
+      const latte = await createProduct(shop, "Latte", 4_500);
+
+      expect((await program.account.product.fetch(latte)).stockRemaining).to.equal(null);
+    });
+
+    it("Sells the last unit and rejects the next sale", async () => {
+      const shop = await createShop();
+      const croissant = await createProduct(shop, "Croissant", 3_000);
+      await restockProduct(shop, croissant, 1);
+      expect((await program.account.product.fetch(croissant)).stockRemaining).to.equal(1);
+
+      await processPayment(shop, await createCustomer(), 3_000, { product: croissant });
+
+      const soldOut = await program.account.product.fetch(croissant);
+      expect(soldOut.stockRemaining).to.equal(0);
+      expect(soldOut.totalSold.toNumber()).to.equal(1);
+
+      const lateCustomer = await createCustomer();
+      await expectError(processPayment(shop, lateCustomer, 3_000, { product: croissant }), "OutOfStock");
+
+      // Restocking makes the product sellable again
+      await restockProduct(shop, croissant, 2);
+      await processPayment(shop, lateCustomer, 3_000, { product: croissant });
+      expect((await program.account.product.fetch(croissant)).stockRemaining).to.equal(1);
+    });
+
+    it("Takes every unit on the receipt out of stock", async () => {
+      const shop = await createShop();
+      const croissant = await createProduct(shop, "Croissant", 3_000);
+      await restockProduct(shop, croissant, 5);
+      const lineItems = [{ product: croissant, quantity: 3, unitPrice: new BN(3_000) }];
+
+      await processPayment(shop, await createCustomer(), 9_000, { lineItems, product: croissant });
+      expect((await program.account.product.fetch(croissant)).stockRemaining).to.equal(2);
+
+      // Two left can't cover another three
+      await expectError(
+        processPayment(shop, await createCustomer(), 9_000, { lineItems, product: croissant }),
+        "OutOfStock"
+      );
+      expect((await program.account.product.fetch(croissant)).stockRemaining).to.equal(2);
+    });
+
+    it("Only lets the merchant authority restock", async () => {
+      const shop = await createShop();
+      const croissant = await createProduct(shop, "Croissant", 3_000);
+
+      await expectError(
+        restockProduct({ ...shop, authority: await fundedKeypair() }, croissant, 5),
+        "ConstraintHasOne"
+      );
+    });
+  });
+
+  describe("group tabs", () => {
+    const TAB_TOTAL = 10_000;
+
+    const groupTabPda = (merchant: PublicKey, opener: PublicKey, tabId: number) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("group_tab"), merchant.toBuffer(), opener.toBuffer(), new BN(tabId).toArrayLike(Buffer, "le", 8)],
+        program.programId
+      )[0];
+
+    let nextTabId = 0;
+
+    // Open a tab at `shop` split between `shares`; the first contributor opens it
+    const openTab = async (shop: Shop, shares: [Keypair, number][], total = TAB_TOTAL) => {
+      const opener = shares[0][0];
+      const tabId = nextTabId++;
+      const groupTab = groupTabPda(shop.merchant, opener.publicKey, tabId);
+      await program.methods
+        .openGroupTab(
+          new BN(tabId),
+          new BN(total),
+          shares.map(([contributor, share]) => ({
+            contributor: contributor.publicKey,
+            share: new BN(share),
+            contributed: new BN(0),
+          }))
+        )
+        .accounts({
+          groupTab,
+          tabTokenAccount: usdcAccount(groupTab),
+          merchant: shop.merchant,
+          opener: opener.publicKey,
+          usdcMint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([opener])
+        .rpc();
+      return groupTab;
+    };
+
+    const contribute = (groupTab: PublicKey, contributor: Keypair, amount: number) =>
+      program.methods
+        .contributeToTab(new BN(amount))
+        .accounts({
+          groupTab,
+          tabTokenAccount: usdcAccount(groupTab),
+          contributor: contributor.publicKey,
+          contributorTokenAccount: usdcAccount(contributor.publicKey),
+          usdcMint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([contributor])
+        .rpc();
+
+    const settleTab = (shop: Shop, groupTab: PublicKey) =>
+      program.methods
+        .settleGroupTab()
+        .accounts({
+          groupTab,
+          tabTokenAccount: usdcAccount(groupTab),
+          merchant: shop.merchant,
+          merchantAuthority: shop.authority.publicKey,
+          merchantTokenAccount: usdcAccount(shop.payout),
+          platformFeeAccount: usdcAccount(platformAuthority),
+          usdcMint,
+          platformAuthority,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([shop.authority])
+        .rpc();
+
+    it("Settles to the merchant once both customers pay their shares", async () => {
+      const shop = await createShop();
+      const alice = await createCustomer();
+      const bob = await createCustomer();
+      const groupTab = await openTab(shop, [[alice, 6_000], [bob, 4_000]]);
+
+      await contribute(groupTab, alice, 6_000);
+      await contribute(groupTab, bob, 4_000);
+
+      const funded = await program.account.groupTab.fetch(groupTab);
+      expect(funded.fundedAmount.toNumber()).to.equal(TAB_TOTAL);
+      expect(funded.shares.map((share) => share.contributed.toNumber())).to.deep.equal([6_000, 4_000]);
+      expect(await usdcBalance(groupTab)).to.equal(TAB_TOTAL);
+
+      await settleTab(shop, groupTab);
+
+      expect(await usdcBalance(shop.payout)).to.equal(TAB_TOTAL - 250);
+      expect(await usdcBalance(groupTab)).to.equal(0);
+      expect(await usdcBalance(alice.publicKey)).to.equal(94_000);
+      expect(await usdcBalance(bob.publicKey)).to.equal(96_000);
+
+      const settled = await program.account.groupTab.fetch(groupTab);
+      expect(settled.status).to.deep.equal({ settled: {} });
+      expect(settled.settledAt).to.not.equal(null);
+    });
+
+    it("Refuses to settle an underfunded tab", async () => {
+      const shop = await createShop();
+      const alice = await createCustomer();
+      const bob = await createCustomer();
+      const groupTab = await openTab(shop, [[alice, 6_000], [bob, 4_000]]);
+
+      // Partial payments are allowed but don't fund the tab
+      await contribute(groupTab, alice, 6_000);
+      await contribute(groupTab, bob, 1_000);
+
+      await expectError(settleTab(shop, groupTab), "TabNotFullyFunded");
+    });
+
+    it("Rejects contributions beyond a customer's share", async () => {
+      const shop = await createShop();
+      const alice = await createCustomer();
+      const bob = await createCustomer();
+      const groupTab = await openTab(shop, [[alice, 6_000], [bob, 4_000]]);
+
+      await expectError(contribute(groupTab, bob, 5_000), "TabOverContribution");
+      await contribute(groupTab, bob, 3_000);
+      await expectError(contribute(groupTab, bob, 1_001), "TabOverContribution");
+    });
+
+    it("Rejects contributions from customers without a share", async () => {
+      const shop = await createShop();
+      const alice = await createCustomer();
+      const groupTab = await openTab(shop, [[alice, TAB_TOTAL]]);
+
+      await expectError(contribute(groupTab, await createCustomer(), 1_000), "NotTabContributor");
+    });
+
+    it("Rejects shares that don't add up to the tab total", async () => {
+      const shop = await createShop();
+      const alice = await createCustomer();
+      const bob = await createCustomer();
+
+      await expectError(openTab(shop, [[alice, 6_000], [bob, 3_000]]), "InvalidTabShares");
+    });
+
+    it("Returns contributions from a cancelled tab", async () => {
+      const shop = await createShop();
+      const alice = await createCustomer();
+      const bob = await createCustomer();
+      const groupTab = await openTab(shop, [[alice, 6_000], [bob, 4_000]]);
+      await contribute(groupTab, alice, 6_000);
+
+      await program.methods
+        .cancelGroupTab()
+        .accounts({ groupTab, opener: alice.publicKey })
+        .signers([alice])
+        .rpc();
+      await program.methods
+        .withdrawTabContribution()
+        .accounts({
+          groupTab,
+          tabTokenAccount: usdcAccount(groupTab),
+          contributor: alice.publicKey,
+          contributorTokenAccount: usdcAccount(alice.publicKey),
+          usdcMint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([alice])
+        .rpc();
+
+      expect(await usdcBalance(alice.publicKey)).to.equal(100_000);
+      expect((await program.account.groupTab.fetch(groupTab)).fundedAmount.toNumber()).to.equal(0);
+      await expectError(settleTab(shop, groupTab), "TabNotOpen");
+    });
+  });
+});
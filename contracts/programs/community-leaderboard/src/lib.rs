@@ -6,12 +6,27 @@ use mpl_token_metadata::state::{DataV2, Creator};
 
 declare_id!("COMMxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx");
 
+/// Default prior completions at lower difficulties required for Easy/Medium/Hard/Expert
+pub const DEFAULT_DIFFICULTY_REQUIREMENTS: [u32; 4] = [0, 3, 10, 25];
+
+/// Maximum number of signers allowed to record scoring activity
+pub const MAX_TRUSTED_RECORDERS: usize = 10;
+
 #[program]
 pub mod community_leaderboard {
     use super::*;
 
     /// Initialize the community leaderboard program
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        point_formula: Option<PointFormula>,
+        achievement_thresholds: Option<AchievementThresholds>,
+    ) -> Result<()> {
+        let point_formula = point_formula.unwrap_or_default();
+        require!(point_formula.is_valid(), ErrorCode::InvalidPointFormula);
+        let achievement_thresholds = achievement_thresholds.unwrap_or_default();
+        require!(achievement_thresholds.is_valid(), ErrorCode::InvalidAchievementThresholds);
+
         let config = &mut ctx.accounts.config;
         config.authority = ctx.accounts.authority.key();
         config.total_users = 0;
@@ -22,6 +37,12 @@ pub mod community_leaderboard {
         config.season_start = Clock::get()?.unix_timestamp;
         config.season_end = Clock::get()?.unix_timestamp + (30 * 24 * 60 * 60); // 30 days
         config.is_paused = false;
+        config.difficulty_requirements = DEFAULT_DIFFICULTY_REQUIREMENTS;
+        config.point_formula = point_formula;
+        config.achievement_thresholds = achievement_thresholds;
+        config.trusted_recorders = vec![];
+        // Existing clients record from arbitrary signers until the authority opts out
+        config.allow_legacy_recording = true;
 
         emit!(ProgramInitialized {
             authority: config.authority,
@@ -55,6 +76,7 @@ pub mod community_leaderboard {
         user_profile.total_transactions = 0;
         user_profile.total_volume = 0;
         user_profile.tasks_completed = 0;
+        user_profile.tasks_by_difficulty = [0; 4];
         user_profile.rewards_earned = 0;
         user_profile.contribution_score = 0;
         user_profile.tier = UserTier::Bronze;
@@ -62,6 +84,7 @@ pub mod community_leaderboard {
         user_profile.joined_at = Clock::get()?.unix_timestamp;
         user_profile.last_activity = Clock::get()?.unix_timestamp;
         user_profile.is_active = true;
+        user_profile.is_private = false;
 
         config.total_users += 1;
 
@@ -86,12 +109,13 @@ pub mod community_leaderboard {
         let user_profile = &mut ctx.accounts.user_profile;
         let config = &mut ctx.accounts.config;
 
+        require!(config.can_record(&ctx.accounts.authority.key()), ErrorCode::UnauthorizedRecorder);
         require!(user_profile.is_active, ErrorCode::UserInactive);
         require!(amount > 0, ErrorCode::InvalidAmount);
         require!(transaction_hash.len() <= 100, ErrorCode::HashTooLong);
 
         // Calculate contribution points based on transaction type and amount
-        let points = calculate_transaction_points(transaction_type, amount);
+        let points = calculate_transaction_points(&config.point_formula, &transaction_type, amount);
 
         // Update user stats
         user_profile.total_transactions += 1;
@@ -105,12 +129,15 @@ pub mod community_leaderboard {
         // Check for tier upgrade
         update_user_tier(user_profile);
 
+        // Private profiles keep their amounts and points out of the event stream
+        let is_private = user_profile.is_private;
         emit!(TransactionRecorded {
             user_id: user_profile.key(),
             transaction_type,
-            amount,
-            points_earned: points,
+            amount: if is_private { 0 } else { amount },
+            points_earned: if is_private { 0 } else { points },
             transaction_hash,
+            is_private,
             timestamp: Clock::get()?.unix_timestamp,
         });
 
@@ -128,14 +155,24 @@ pub mod community_leaderboard {
         let user_profile = &mut ctx.accounts.user_profile;
         let config = &mut ctx.accounts.config;
 
+        require!(config.can_record(&ctx.accounts.authority.key()), ErrorCode::UnauthorizedRecorder);
         require!(user_profile.is_active, ErrorCode::UserInactive);
         require!(task_id.len() <= 100, ErrorCode::TaskIdTooLong);
 
+        // Enforce progression: harder tasks need prior completions at lower difficulties
+        let difficulty_index = difficulty.index();
+        let prior_lower_completions: u64 = user_profile.tasks_by_difficulty[..difficulty_index].iter().sum();
+        require!(
+            prior_lower_completions >= config.difficulty_requirements[difficulty_index] as u64,
+            ErrorCode::DifficultyLocked
+        );
+
         // Calculate contribution points based on task type and difficulty
-        let points = calculate_task_points(task_type, difficulty, reward_amount);
+        let points = calculate_task_points(&config.point_formula, &task_type, &difficulty, reward_amount);
 
         // Update user stats
         user_profile.tasks_completed += 1;
+        user_profile.tasks_by_difficulty[difficulty_index] += 1;
         user_profile.rewards_earned += reward_amount;
         user_profile.contribution_score += points;
         user_profile.last_activity = Clock::get()?.unix_timestamp;
@@ -148,13 +185,15 @@ pub mod community_leaderboard {
         update_user_tier(user_profile);
         check_and_award_badges(user_profile, task_type);
 
+        let is_private = user_profile.is_private;
         emit!(TaskCompleted {
             user_id: user_profile.key(),
             task_type,
             difficulty,
-            points_earned: points,
-            reward_amount,
+            points_earned: if is_private { 0 } else { points },
+            reward_amount: if is_private { 0 } else { reward_amount },
             task_id,
+            is_private,
             timestamp: Clock::get()?.unix_timestamp,
         });
 
@@ -165,6 +204,21 @@ pub mod community_leaderboard {
     pub fn get_user_rank(ctx: Context<GetUserRank>) -> Result<UserRankInfo> {
         let user_profile = &ctx.accounts.user_profile;
         
+        // Private profiles only expose their tier as a rank band
+        if user_profile.is_private {
+            return Ok(UserRankInfo {
+                user_id: user_profile.key(),
+                contribution_score: 0,
+                tier: user_profile.tier.clone(),
+                total_transactions: 0,
+                tasks_completed: 0,
+                rewards_earned: 0,
+                badges_count: 0,
+                estimated_rank: 0,
+                is_private: true,
+            });
+        }
+
         // This would typically require iterating through all users to calculate rank
         // For efficiency, this should be done off-chain or with a separate ranking system
         let rank_info = UserRankInfo {
@@ -176,11 +230,28 @@ pub mod community_leaderboard {
             rewards_earned: user_profile.rewards_earned,
             badges_count: user_profile.badges.len() as u32,
             estimated_rank: 0, // Would be calculated off-chain
+            is_private: false,
         };
 
         Ok(rank_info)
     }
 
+    /// Toggle privacy mode; private profiles still score, but `get_user_rank` and the
+    /// recording events leave out their stats. This is not confidentiality: the profile
+    /// account itself stays readable on-chain by anyone.
+    pub fn set_privacy(ctx: Context<SetPrivacy>, is_private: bool) -> Result<()> {
+        let user_profile = &mut ctx.accounts.user_profile;
+        user_profile.is_private = is_private;
+
+        emit!(PrivacyModeUpdated {
+            user_id: user_profile.key(),
+            is_private,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
     /// Award special badge to user
     pub fn award_badge(
         ctx: Context<AwardBadge>,
@@ -253,6 +324,151 @@ pub mod community_leaderboard {
         Ok(())
     }
 
+    /// Configure prior completions required to unlock each task difficulty
+    pub fn set_difficulty_requirements(
+        ctx: Context<UpdateConfig>,
+        difficulty_requirements: [u32; 4],
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(
+            difficulty_requirements[0] == 0
+                && difficulty_requirements.windows(2).all(|w| w[0] <= w[1]),
+            ErrorCode::InvalidDifficultyRequirements
+        );
+
+        config.difficulty_requirements = difficulty_requirements;
+
+        emit!(DifficultyRequirementsUpdated {
+            difficulty_requirements,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Update the base points, multipliers, and caps used to score activity
+    pub fn set_point_formula(
+        ctx: Context<UpdateConfig>,
+        point_formula: PointFormula,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(point_formula.is_valid(), ErrorCode::InvalidPointFormula);
+
+        config.point_formula = point_formula.clone();
+
+        emit!(PointFormulaUpdated {
+            point_formula,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Update the per-achievement qualification thresholds checked when minting
+    pub fn set_achievement_thresholds(
+        ctx: Context<UpdateConfig>,
+        achievement_thresholds: AchievementThresholds,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(achievement_thresholds.is_valid(), ErrorCode::InvalidAchievementThresholds);
+
+        config.achievement_thresholds = achievement_thresholds.clone();
+
+        emit!(AchievementThresholdsUpdated {
+            achievement_thresholds,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Allow a signer to record scoring activity. To accept CPI recordings from
+    /// another program, allowlist the PDA that program signs with.
+    pub fn add_trusted_recorder(ctx: Context<UpdateConfig>, recorder: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(
+            !config.trusted_recorders.contains(&recorder),
+            ErrorCode::RecorderAlreadyTrusted
+        );
+        require!(
+            config.trusted_recorders.len() < MAX_TRUSTED_RECORDERS,
+            ErrorCode::TrustedRecordersFull
+        );
+
+        config.trusted_recorders.push(recorder);
+
+        emit!(TrustedRecorderUpdated {
+            recorder,
+            is_trusted: true,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Revoke a signer's permission to record scoring activity
+    pub fn remove_trusted_recorder(ctx: Context<UpdateConfig>, recorder: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(
+            config.trusted_recorders.contains(&recorder),
+            ErrorCode::RecorderNotTrusted
+        );
+
+        config.trusted_recorders.retain(|trusted| *trusted != recorder);
+
+        emit!(TrustedRecorderUpdated {
+            recorder,
+            is_trusted: false,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Toggle the legacy mode in which any signer may record scoring activity
+    pub fn set_legacy_recording(ctx: Context<UpdateConfig>, enabled: bool) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+
+        config.allow_legacy_recording = enabled;
+
+        emit!(LegacyRecordingUpdated {
+            enabled,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
     /// Mint achievement NFT for top contributors
     pub fn mint_achievement_nft(
         ctx: Context<MintAchievementNft>,
@@ -265,15 +481,10 @@ pub mod community_leaderboard {
         require!(metadata_uri.len() <= 200, ErrorCode::UriTooLong);
 
         // Verify user qualifies for achievement
-        let qualifies = match achievement_type {
-            AchievementType::Top10Overall => user_profile.contribution_score >= 10000,
-            AchievementType::Top100Transactions => user_profile.total_transactions >= 100,
-            AchievementType::TaskCompletionist => user_profile.tasks_completed >= 50,
-            AchievementType::VolumeLeader => user_profile.total_volume >= 1000000000, // 1 SOL
-            AchievementType::SeasonWinner => user_profile.tier == UserTier::Platinum,
-        };
-
-        require!(qualifies, ErrorCode::NotQualified);
+        require!(
+            ctx.accounts.config.achievement_thresholds.qualifies(&achievement_type, user_profile),
+            ErrorCode::NotQualified
+        );
 
         // Create NFT metadata
         let achievement_name = match achievement_type {
@@ -320,41 +531,33 @@ pub mod community_leaderboard {
 }
 
 // Helper functions
-fn calculate_transaction_points(transaction_type: TransactionType, amount: u64) -> u64 {
-    let base_points = match transaction_type {
-        TransactionType::Payment => 10,
-        TransactionType::Reward => 5,
-        TransactionType::Staking => 15,
-        TransactionType::Trading => 8,
-        TransactionType::Donation => 20,
-    };
-
-    // Bonus points based on amount (1 point per 0.01 SOL)
-    let amount_bonus = amount / 10_000_000; // 0.01 SOL in lamports
+fn calculate_transaction_points(
+    formula: &PointFormula,
+    transaction_type: &TransactionType,
+    amount: u64,
+) -> u64 {
+    let base_points = formula.transaction_base_points[transaction_type.index()];
+
+    // Bonus points based on amount (1 point per `transaction_amount_unit` lamports)
+    let amount_bonus = amount / formula.transaction_amount_unit;
     
-    base_points + amount_bonus.min(100) // Cap bonus at 100 points
+    base_points.saturating_add(amount_bonus.min(formula.transaction_bonus_cap))
 }
 
-fn calculate_task_points(task_type: TaskType, difficulty: TaskDifficulty, reward_amount: u64) -> u64 {
-    let base_points = match task_type {
-        TaskType::Survey => 50,
-        TaskType::Testing => 100,
-        TaskType::Development => 200,
-        TaskType::Community => 75,
-        TaskType::Education => 125,
-        TaskType::Marketing => 150,
-    };
+fn calculate_task_points(
+    formula: &PointFormula,
+    task_type: &TaskType,
+    difficulty: &TaskDifficulty,
+    reward_amount: u64,
+) -> u64 {
+    let base_points = formula.task_base_points[task_type.index()];
+    let difficulty_multiplier = formula.difficulty_multipliers[difficulty.index()];
 
-    let difficulty_multiplier = match difficulty {
-        TaskDifficulty::Easy => 1,
-        TaskDifficulty::Medium => 2,
-        TaskDifficulty::Hard => 3,
-        TaskDifficulty::Expert => 5,
-    };
-
-    let reward_bonus = (reward_amount / 1_000_000).min(500); // Max 500 bonus points
+    let reward_bonus = (reward_amount / formula.task_reward_unit).min(formula.task_bonus_cap);
     
-    (base_points * difficulty_multiplier) + reward_bonus
+    base_points
+        .saturating_mul(difficulty_multiplier)
+        .saturating_add(reward_bonus)
 }
 
 fn update_user_tier(user_profile: &mut UserProfile) {
@@ -472,6 +675,19 @@ pub struct GetUserRank<'info> {
     pub user_profile: Account<'info, UserProfile>,
 }
 
+#[derive(Accounts)]
+pub struct SetPrivacy<'info> {
+    #[account(
+        mut,
+        seeds = [b"user", owner.key().as_ref()],
+        bump,
+        has_one = owner
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    
+    pub owner: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct AwardBadge<'info> {
     #[account(
@@ -502,6 +718,18 @@ pub struct StartNewSeason<'info> {
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, LeaderboardConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct MintAchievementNft<'info> {
     #[account(
@@ -558,10 +786,102 @@ pub struct LeaderboardConfig {
     pub season_start: i64,
     pub season_end: i64,
     pub is_paused: bool,
+    pub difficulty_requirements: [u32; 4],
+    pub point_formula: PointFormula,
+    pub achievement_thresholds: AchievementThresholds,
+    pub trusted_recorders: Vec<Pubkey>,
+    pub allow_legacy_recording: bool,    // Any signer may record scoring activity
 }
 
 impl LeaderboardConfig {
-    pub const INIT_SPACE: usize = 32 + 8 + 8 + 8 + 8 + 4 + 8 + 8 + 1;
+    pub const INIT_SPACE: usize = 32 + 8 + 8 + 8 + 8 + 4 + 8 + 8 + 1 + 16 + PointFormula::SIZE
+        + AchievementThresholds::SIZE + 4 + 32 * MAX_TRUSTED_RECORDERS + 1;
+
+    /// Whether `recorder` may submit transactions and task completions for scoring
+    pub fn can_record(&self, recorder: &Pubkey) -> bool {
+        self.allow_legacy_recording
+            || *recorder == self.authority
+            || self.trusted_recorders.contains(recorder)
+    }
+}
+
+/// Tunable scoring parameters; arrays are indexed by `TransactionType::index`,
+/// `TaskType::index`, and `TaskDifficulty::index`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub struct PointFormula {
+    pub transaction_base_points: [u64; 5],
+    pub transaction_amount_unit: u64, // Lamports per bonus point
+    pub transaction_bonus_cap: u64,
+    pub task_base_points: [u64; 6],
+    pub difficulty_multipliers: [u64; 4],
+    pub task_reward_unit: u64,        // Reward lamports per bonus point
+    pub task_bonus_cap: u64,
+}
+
+impl PointFormula {
+    pub const SIZE: usize = 8 * 5 + 8 + 8 + 8 * 6 + 8 * 4 + 8 + 8;
+
+    pub fn is_valid(&self) -> bool {
+        self.transaction_amount_unit > 0 && self.task_reward_unit > 0
+    }
+}
+
+impl Default for PointFormula {
+    fn default() -> Self {
+        Self {
+            transaction_base_points: [10, 5, 15, 8, 20],
+            transaction_amount_unit: 10_000_000, // 0.01 SOL
+            transaction_bonus_cap: 100,
+            task_base_points: [50, 100, 200, 75, 125, 150],
+            difficulty_multipliers: [1, 2, 3, 5],
+            task_reward_unit: 1_000_000,
+            task_bonus_cap: 500,
+        }
+    }
+}
+
+/// Minimum stats a user needs to mint each `AchievementType`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub struct AchievementThresholds {
+    pub top10_overall_score: u64,
+    pub top100_transactions: u64,
+    pub task_completionist_tasks: u64,
+    pub volume_leader_volume: u64,   // Lamports
+    pub season_winner_tier: UserTier,
+}
+
+impl AchievementThresholds {
+    pub const SIZE: usize = 8 + 8 + 8 + 8 + 1;
+
+    /// A zero threshold would let every user mint that achievement
+    pub fn is_valid(&self) -> bool {
+        self.top10_overall_score > 0
+            && self.top100_transactions > 0
+            && self.task_completionist_tasks > 0
+            && self.volume_leader_volume > 0
+    }
+
+    pub fn qualifies(&self, achievement_type: &AchievementType, user_profile: &UserProfile) -> bool {
+        match achievement_type {
+            AchievementType::Top10Overall => user_profile.contribution_score >= self.top10_overall_score,
+            AchievementType::Top100Transactions => user_profile.total_transactions >= self.top100_transactions,
+            AchievementType::TaskCompletionist => user_profile.tasks_completed >= self.task_completionist_tasks,
+            AchievementType::VolumeLeader => user_profile.total_volume >= self.volume_leader_volume,
+            AchievementType::SeasonWinner => user_profile.tier == self.season_winner_tier,
+        }
+    }
+}
+
+impl Default for AchievementThresholds {
+    fn default() -> Self {
+        Self {
+            top10_overall_score: 10000,
+            top100_transactions: 100,
+            task_completionist_tasks: 50,
+            volume_leader_volume: 1_000_000_000, // 1 SOL
+            season_winner_tier: UserTier::Platinum,
+        }
+    }
 }
 
 #[account]
@@ -572,6 +892,7 @@ pub struct UserProfile {
     pub total_transactions: u64,
     pub total_volume: u64,
     pub tasks_completed: u64,
+    pub tasks_by_difficulty: [u64; 4],
     pub rewards_earned: u64,
     pub contribution_score: u64,
     pub tier: UserTier,
@@ -579,10 +900,13 @@ pub struct UserProfile {
     pub joined_at: i64,
     pub last_activity: i64,
     pub is_active: bool,
+    /// Owner-controlled; redacts the rank view and recording events but still counts
+    /// toward aggregates. The fields of this account remain publicly readable.
+    pub is_private: bool,
 }
 
 impl UserProfile {
-    pub const INIT_SPACE: usize = 32 + 50 + 100 + 8 + 8 + 8 + 8 + 8 + 1 + 100 + 8 + 8 + 1;
+    pub const INIT_SPACE: usize = 32 + 50 + 100 + 8 + 8 + 8 + 32 + 8 + 8 + 1 + 100 + 8 + 8 + 1 + 1;
 }
 
 #[account]
@@ -617,6 +941,18 @@ pub enum TransactionType {
     Donation,
 }
 
+impl TransactionType {
+    pub fn index(&self) -> usize {
+        match self {
+            TransactionType::Payment => 0,
+            TransactionType::Reward => 1,
+            TransactionType::Staking => 2,
+            TransactionType::Trading => 3,
+            TransactionType::Donation => 4,
+        }
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
 pub enum TaskType {
     Survey,
@@ -627,6 +963,19 @@ pub enum TaskType {
     Marketing,
 }
 
+impl TaskType {
+    pub fn index(&self) -> usize {
+        match self {
+            TaskType::Survey => 0,
+            TaskType::Testing => 1,
+            TaskType::Development => 2,
+            TaskType::Community => 3,
+            TaskType::Education => 4,
+            TaskType::Marketing => 5,
+        }
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
 pub enum TaskDifficulty {
     Easy,
@@ -635,6 +984,17 @@ pub enum TaskDifficulty {
     Expert,
 }
 
+impl TaskDifficulty {
+    pub fn index(&self) -> usize {
+        match self {
+            TaskDifficulty::Easy => 0,
+            TaskDifficulty::Medium => 1,
+            TaskDifficulty::Hard => 2,
+            TaskDifficulty::Expert => 3,
+        }
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
 pub enum BadgeType {
     EarlyAdopter,
@@ -665,6 +1025,8 @@ pub struct UserRankInfo {
     pub rewards_earned: u64,
     pub badges_count: u32,
     pub estimated_rank: u32,
+    /// When set, only `tier` is populated as a rank band
+    pub is_private: bool,
 }
 
 // Events
@@ -691,6 +1053,8 @@ pub struct TransactionRecorded {
     pub amount: u64,
     pub points_earned: u64,
     pub transaction_hash: String,
+    /// When set, `amount` and `points_earned` are redacted to 0
+    pub is_private: bool,
     pub timestamp: i64,
 }
 
@@ -702,6 +1066,8 @@ pub struct TaskCompleted {
     pub points_earned: u64,
     pub reward_amount: u64,
     pub task_id: String,
+    /// When set, `points_earned` and `reward_amount` are redacted to 0
+    pub is_private: bool,
     pub timestamp: i64,
 }
 
@@ -722,6 +1088,44 @@ pub struct NewSeasonStarted {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct DifficultyRequirementsUpdated {
+    pub difficulty_requirements: [u32; 4],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PointFormulaUpdated {
+    pub point_formula: PointFormula,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AchievementThresholdsUpdated {
+    pub achievement_thresholds: AchievementThresholds,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TrustedRecorderUpdated {
+    pub recorder: Pubkey,
+    pub is_trusted: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PrivacyModeUpdated {
+    pub user_id: Pubkey,
+    pub is_private: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LegacyRecordingUpdated {
+    pub enabled: bool,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct AchievementNftMinted {
     pub user_id: Pubkey,
@@ -763,4 +1167,20 @@ pub enum ErrorCode {
     UriTooLong,
     #[msg("User not qualified for achievement")]
     NotQualified,
+    #[msg("Not enough lower-difficulty completions for this task")]
+    DifficultyLocked,
+    #[msg("Invalid difficulty requirements")]
+    InvalidDifficultyRequirements,
+    #[msg("Invalid point formula")]
+    InvalidPointFormula,
+    #[msg("Invalid achievement thresholds")]
+    InvalidAchievementThresholds,
+    #[msg("Signer is not a trusted recorder")]
+    UnauthorizedRecorder,
+    #[msg("Trusted recorder list is full")]
+    TrustedRecordersFull,
+    #[msg("Recorder is already trusted")]
+    RecorderAlreadyTrusted,
+    #[msg("Recorder is not trusted")]
+    RecorderNotTrusted,
 }
@@ -0,0 +1,610 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import {
+  Keypair,
+  PublicKey,
+  LAMPORTS_PER_SOL,
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+} from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { CommunityLeaderboard } from "../target/types/community_leaderboard";
+import { expect } from "chai";
+
+describe("community-leaderboard", () => {
+  // Configure the client to use the local cluster.
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.CommunityLeaderboard as Program<CommunityLeaderboard>;
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+
+  const DEFAULT_DIFFICULTY_REQUIREMENTS = [0, 3, 10, 25];
+
+  const [configPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    program.programId
+  );
+
+  const userPda = (owner: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("user"), owner.toBuffer()],
+      program.programId
+    )[0];
+
+  const fundedKeypair = async (sol = 2) => {
+    const keypair = Keypair.generate();
+    const signature = await provider.connection.requestAirdrop(keypair.publicKey, sol * LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(signature);
+    return keypair;
+  };
+
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    try {
+      await promise;
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal(code);
+      return;
+    }
+    expect.fail(`expected ${code}`);
+  };
+
+  // Transaction hashes and task ids only need to differ between calls
+  const uniqueId = () => Keypair.generate().publicKey.toBase58().slice(0, 16);
+
+  const registerUser = async () => {
+    const owner = await fundedKeypair();
+    await program.methods
+      .registerUser(`user-${uniqueId()}`, null)
+      .accounts({
+        userProfile: userPda(owner.publicKey),
+        config: configPda,
+        owner: owner.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+    return owner;
+  };
+
+  // Recorded by the config authority unless another recorder is given
+  const recordTask = (
+    owner: PublicKey,
+    difficulty: object,
+    { taskType = { survey: {} } as object, rewardAmount = 0, recorder }: {
+      taskType?: object;
+      rewardAmount?: number;
+      recorder?: Keypair;
+    } = {}
+  ) =>
+    program.methods
+      .recordTaskCompletion(taskType, difficulty, new BN(rewardAmount), uniqueId())
+      .accounts({
+        userProfile: userPda(owner),
+        config: configPda,
+        authority: recorder ? recorder.publicKey : provider.wallet.publicKey,
+      })
+      .signers(recorder ? [recorder] : [])
+      .rpc();
+
+  const recordTasks = async (owner: PublicKey, difficulty: object, count: number) => {
+    for (let i = 0; i < count; i++) {
+      await recordTask(owner, difficulty);
+    }
+  };
+
+  const setDifficultyRequirements = (requirements: number[], authority?: Keypair) =>
+    program.methods
+      .setDifficultyRequirements(requirements)
+      .accounts({
+        config: configPda,
+        authority: authority ? authority.publicKey : provider.wallet.publicKey,
+      })
+      .signers(authority ? [authority] : [])
+      .rpc();
+
+  const recordTransaction = (
+    owner: PublicKey,
+    amount: number,
+    { transactionType = { payment: {} } as object, recorder }: { transactionType?: object; recorder?: Keypair } = {}
+  ) =>
+    program.methods
+      .recordTransaction(new BN(amount), transactionType, uniqueId())
+      .accounts({
+        userProfile: userPda(owner),
+        config: configPda,
+        authority: recorder ? recorder.publicKey : provider.wallet.publicKey,
+      })
+      .signers(recorder ? [recorder] : [])
+      .rpc();
+
+  // Events emitted by a confirmed transaction
+  const emittedEvents = async (signature: string) => {
+    await provider.connection.confirmTransaction(signature, "confirmed");
+    const tx = await provider.connection.getTransaction(signature, { commitment: "confirmed" });
+    const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+    return [...parser.parseLogs(tx.meta.logMessages)];
+  };
+
+  const contributionScore = async (owner: PublicKey) =>
+    (await program.account.userProfile.fetch(userPda(owner))).contributionScore.toNumber();
+
+  before(async () => {
+    await program.methods
+      .initialize(null, null)
+      .accounts({
+        config: configPda,
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  });
+
+  describe("difficulty progression", () => {
+    it("Rejects an Expert task for a new user", async () => {
+      const owner = await registerUser();
+
+      await expectError(recordTask(owner.publicKey, { expert: {} }), "DifficultyLocked");
+    });
+
+    it("Unlocks Medium tasks after three Easy completions", async () => {
+      const owner = await registerUser();
+      await recordTasks(owner.publicKey, { easy: {} }, 2);
+
+      await expectError(recordTask(owner.publicKey, { medium: {} }), "DifficultyLocked");
+
+      await recordTask(owner.publicKey, { easy: {} });
+      await recordTask(owner.publicKey, { medium: {} });
+
+      const profile = await program.account.userProfile.fetch(userPda(owner.publicKey));
+      expect(profile.tasksByDifficulty.map((count) => count.toNumber())).to.deep.equal([3, 1, 0, 0]);
+    });
+
+    it("Allows an Expert task once the configured prerequisites are met", async () => {
+      const owner = await registerUser();
+
+      await setDifficultyRequirements([0, 1, 2, 3]);
+      try {
+        await recordTask(owner.publicKey, { easy: {} });
+        await recordTask(owner.publicKey, { medium: {} });
+        await expectError(recordTask(owner.publicKey, { expert: {} }), "DifficultyLocked");
+
+        // Completions at any lower difficulty count toward the requirement
+        await recordTask(owner.publicKey, { hard: {} });
+        await recordTask(owner.publicKey, { expert: {} });
+      } finally {
+        await setDifficultyRequirements(DEFAULT_DIFFICULTY_REQUIREMENTS);
+      }
+
+      const profile = await program.account.userProfile.fetch(userPda(owner.publicKey));
+      expect(profile.tasksByDifficulty.map((count) => count.toNumber())).to.deep.equal([1, 1, 1, 1]);
+      expect(profile.tasksCompleted.toNumber()).to.equal(4);
+    });
+
+    it("Rejects requirements that gate Easy tasks or decrease", async () => {
+      await expectError(setDifficultyRequirements([1, 3, 10, 25]), "InvalidDifficultyRequirements");
+      await expectError(setDifficultyRequirements([0, 10, 3, 25]), "InvalidDifficultyRequirements");
+    });
+
+    it("Only lets the config authority change requirements", async () => {
+      await expectError(setDifficultyRequirements([0, 0, 0, 0], await fundedKeypair()), "Unauthorized");
+
+      const config = await program.account.leaderboardConfig.fetch(configPda);
+      expect(config.difficultyRequirements).to.deep.equal(DEFAULT_DIFFICULTY_REQUIREMENTS);
+    });
+  });
+
+  describe("point formula", () => {
+    const setPointFormula = (pointFormula: object, authority?: Keypair) =>
+      program.methods
+        .setPointFormula(pointFormula)
+        .accounts({
+          config: configPda,
+          authority: authority ? authority.publicKey : provider.wallet.publicKey,
+        })
+        .signers(authority ? [authority] : [])
+        .rpc();
+
+    const bns = (values: number[]) => values.map((value) => new BN(value));
+
+    it("Initializes with the previously hardcoded formula", async () => {
+      const { pointFormula } = await program.account.leaderboardConfig.fetch(configPda);
+
+      expect(pointFormula.transactionBasePoints.map((p) => p.toNumber())).to.deep.equal([10, 5, 15, 8, 20]);
+      expect(pointFormula.transactionAmountUnit.toNumber()).to.equal(10_000_000);
+      expect(pointFormula.transactionBonusCap.toNumber()).to.equal(100);
+      expect(pointFormula.taskBasePoints.map((p) => p.toNumber())).to.deep.equal([50, 100, 200, 75, 125, 150]);
+      expect(pointFormula.difficultyMultipliers.map((m) => m.toNumber())).to.deep.equal([1, 2, 3, 5]);
+      expect(pointFormula.taskRewardUnit.toNumber()).to.equal(1_000_000);
+      expect(pointFormula.taskBonusCap.toNumber()).to.equal(500);
+    });
+
+    it("Scores activity with the default formula", async () => {
+      const owner = await registerUser();
+
+      // 10 base points plus one per 0.01 SOL, capped at 100
+      await recordTransaction(owner.publicKey, 2 * LAMPORTS_PER_SOL);
+      expect(await contributionScore(owner.publicKey)).to.equal(110);
+
+      // 200 base points for development at the Easy multiplier, plus one per 0.001 SOL of reward
+      await recordTask(owner.publicKey, { easy: {} }, { taskType: { development: {} }, rewardAmount: 2_000_000 });
+      expect(await contributionScore(owner.publicKey)).to.equal(110 + 202);
+    });
+
+    it("Scores activity with a formula updated by the authority", async () => {
+      const owner = await registerUser();
+      const { pointFormula: defaults } = await program.account.leaderboardConfig.fetch(configPda);
+
+      await setPointFormula({
+        transactionBasePoints: bns([40, 5, 15, 8, 20]),
+        transactionAmountUnit: new BN(100_000_000),
+        transactionBonusCap: new BN(5),
+        taskBasePoints: bns([50, 100, 300, 75, 125, 150]),
+        difficultyMultipliers: bns([2, 2, 3, 5]),
+        taskRewardUnit: new BN(1_000_000),
+        taskBonusCap: new BN(1),
+      });
+      try {
+        // 40 base points plus one per 0.1 SOL, now capped at 5
+        await recordTransaction(owner.publicKey, 2 * LAMPORTS_PER_SOL);
+        expect(await contributionScore(owner.publicKey)).to.equal(45);
+
+        // 300 base points doubled at Easy, plus a reward bonus capped at 1
+        await recordTask(owner.publicKey, { easy: {} }, { taskType: { development: {} }, rewardAmount: 2_000_000 });
+        expect(await contributionScore(owner.publicKey)).to.equal(45 + 601);
+      } finally {
+        await setPointFormula(defaults);
+      }
+
+      await recordTransaction(owner.publicKey, 2 * LAMPORTS_PER_SOL);
+      expect(await contributionScore(owner.publicKey)).to.equal(45 + 601 + 110);
+    });
+
+    it("Rejects a formula with a zero unit", async () => {
+      const { pointFormula } = await program.account.leaderboardConfig.fetch(configPda);
+
+      await expectError(
+        setPointFormula({ ...pointFormula, transactionAmountUnit: new BN(0) }),
+        "InvalidPointFormula"
+      );
+      await expectError(setPointFormula({ ...pointFormula, taskRewardUnit: new BN(0) }), "InvalidPointFormula");
+    });
+
+    it("Only lets the config authority change the formula", async () => {
+      const { pointFormula } = await program.account.leaderboardConfig.fetch(configPda);
+
+      await expectError(
+        setPointFormula({ ...pointFormula, transactionBonusCap: new BN(1_000_000) }, await fundedKeypair()),
+        "Unauthorized"
+      );
+    });
+  });
+
+  describe("trusted recorders", () => {
+    const updateRecorders = (method: "addTrustedRecorder" | "removeTrustedRecorder", recorder: PublicKey) =>
+      program.methods[method](recorder)
+        .accounts({ config: configPda, authority: provider.wallet.publicKey })
+        .rpc();
+
+    const setLegacyRecording = (enabled: boolean) =>
+      program.methods
+        .setLegacyRecording(enabled)
+        .accounts({ config: configPda, authority: provider.wallet.publicKey })
+        .rpc();
+
+    let owner: Keypair;
+    let legacyRecordingAtInit: boolean;
+
+    // Recording is restricted for these tests and reopened to legacy callers afterwards
+    before(async () => {
+      owner = await registerUser();
+      ({ allowLegacyRecording: legacyRecordingAtInit } = await program.account.leaderboardConfig.fetch(configPda));
+      await setLegacyRecording(false);
+    });
+
+    after(async () => {
+      await setLegacyRecording(true);
+    });
+
+    it("Initializes with legacy recording enabled so existing callers keep working", () => {
+      expect(legacyRecordingAtInit).to.equal(true);
+    });
+
+    it("Rejects an arbitrary signer recording activity", async () => {
+      const recorder = await fundedKeypair();
+
+      await expectError(
+        recordTransaction(owner.publicKey, LAMPORTS_PER_SOL, { recorder }),
+        "UnauthorizedRecorder"
+      );
+      await expectError(recordTask(owner.publicKey, { easy: {} }, { recorder }), "UnauthorizedRecorder");
+      expect(await contributionScore(owner.publicKey)).to.equal(0);
+    });
+
+    it("Accepts an allowlisted recorder until it is removed", async () => {
+      const recorder = await fundedKeypair();
+      await updateRecorders("addTrustedRecorder", recorder.publicKey);
+
+      await recordTransaction(owner.publicKey, LAMPORTS_PER_SOL, { recorder });
+      await recordTask(owner.publicKey, { easy: {} }, { recorder });
+
+      const profile = await program.account.userProfile.fetch(userPda(owner.publicKey));
+      expect(profile.totalTransactions.toNumber()).to.equal(1);
+      expect(profile.tasksCompleted.toNumber()).to.equal(1);
+
+      await updateRecorders("removeTrustedRecorder", recorder.publicKey);
+
+      await expectError(
+        recordTransaction(owner.publicKey, LAMPORTS_PER_SOL, { recorder }),
+        "UnauthorizedRecorder"
+      );
+      const config = await program.account.leaderboardConfig.fetch(configPda);
+      expect(config.trustedRecorders.map((key) => key.toString())).to.not.include(recorder.publicKey.toString());
+    });
+
+    it("Accepts any signer while legacy recording is enabled", async () => {
+      const recorder = await fundedKeypair();
+      const scoreBefore = await contributionScore(owner.publicKey);
+
+      await setLegacyRecording(true);
+      try {
+        await recordTransaction(owner.publicKey, LAMPORTS_PER_SOL, { recorder });
+      } finally {
+        await setLegacyRecording(false);
+      }
+      expect(await contributionScore(owner.publicKey)).to.be.greaterThan(scoreBefore);
+
+      await expectError(
+        recordTransaction(owner.publicKey, LAMPORTS_PER_SOL, { recorder }),
+        "UnauthorizedRecorder"
+      );
+    });
+
+    it("Rejects adding a recorder twice or removing an unknown one", async () => {
+      const recorder = Keypair.generate().publicKey;
+      await updateRecorders("addTrustedRecorder", recorder);
+
+      await expectError(updateRecorders("addTrustedRecorder", recorder), "RecorderAlreadyTrusted");
+
+      await updateRecorders("removeTrustedRecorder", recorder);
+      await expectError(updateRecorders("removeTrustedRecorder", recorder), "RecorderNotTrusted");
+    });
+
+    it("Only lets the config authority manage recorders", async () => {
+      const outsider = await fundedKeypair();
+
+      await expectError(
+        program.methods
+          .addTrustedRecorder(outsider.publicKey)
+          .accounts({ config: configPda, authority: outsider.publicKey })
+          .signers([outsider])
+          .rpc(),
+        "Unauthorized"
+      );
+      await expectError(
+        program.methods
+          .setLegacyRecording(true)
+          .accounts({ config: configPda, authority: outsider.publicKey })
+          .signers([outsider])
+          .rpc(),
+        "Unauthorized"
+      );
+    });
+  });
+
+  describe("achievement thresholds", () => {
+    const TOKEN_METADATA_PROGRAM_ID = new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+    const achievementPda = (userProfile: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("achievement"), userProfile.toBuffer()],
+        program.programId
+      )[0];
+
+    const setAchievementThresholds = (thresholds: object, authority?: Keypair) =>
+      program.methods
+        .setAchievementThresholds(thresholds)
+        .accounts({
+          config: configPda,
+          authority: authority ? authority.publicKey : provider.wallet.publicKey,
+        })
+        .signers(authority ? [authority] : [])
+        .rpc();
+
+    const mintAchievement = (owner: PublicKey, achievementType: object) => {
+      const mint = Keypair.generate();
+      return program.methods
+        .mintAchievementNft(achievementType, "https://example.com/achievement.json")
+        .accounts({
+          achievement: achievementPda(userPda(owner)),
+          userProfile: userPda(owner),
+          config: configPda,
+          mint: mint.publicKey,
+          metadata: Keypair.generate().publicKey,
+          recipient: owner,
+          authority: provider.wallet.publicKey,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([mint])
+        .rpc();
+    };
+
+    // Run `body` with some thresholds overridden, restoring the previous ones afterwards
+    const withThresholds = async (overrides: object, body: () => Promise<void>) => {
+      const { achievementThresholds } = await program.account.leaderboardConfig.fetch(configPda);
+      await setAchievementThresholds({ ...achievementThresholds, ...overrides });
+      try {
+        await body();
+      } finally {
+        await setAchievementThresholds(achievementThresholds);
+      }
+    };
+
+    it("Initializes with the previously hardcoded thresholds", async () => {
+      const { achievementThresholds } = await program.account.leaderboardConfig.fetch(configPda);
+
+      expect(achievementThresholds.top10OverallScore.toNumber()).to.equal(10000);
+      expect(achievementThresholds.top100Transactions.toNumber()).to.equal(100);
+      expect(achievementThresholds.taskCompletionistTasks.toNumber()).to.equal(50);
+      expect(achievementThresholds.volumeLeaderVolume.toNumber()).to.equal(LAMPORTS_PER_SOL);
+      expect(achievementThresholds.seasonWinnerTier).to.deep.equal({ platinum: {} });
+    });
+
+    it("Qualifies a user once they reach a configured transaction count", async () => {
+      const owner = await registerUser();
+      await recordTransaction(owner.publicKey, LAMPORTS_PER_SOL);
+
+      await withThresholds({ top100Transactions: new BN(2) }, async () => {
+        await expectError(mintAchievement(owner.publicKey, { top100Transactions: {} }), "NotQualified");
+
+        await recordTransaction(owner.publicKey, LAMPORTS_PER_SOL);
+        await mintAchievement(owner.publicKey, { top100Transactions: {} });
+      });
+
+      const achievement = await program.account.achievement.fetch(achievementPda(userPda(owner.publicKey)));
+      expect(achievement.achievementType).to.deep.equal({ top100Transactions: {} });
+      expect(achievement.userId.toString()).to.equal(userPda(owner.publicKey).toString());
+    });
+
+    it("Flips score qualification exactly at the configured boundary", async () => {
+      const owner = await registerUser();
+      await recordTransaction(owner.publicKey, 2 * LAMPORTS_PER_SOL);
+      const score = await contributionScore(owner.publicKey);
+
+      await withThresholds({ top10OverallScore: new BN(score + 1) }, async () => {
+        await expectError(mintAchievement(owner.publicKey, { top10Overall: {} }), "NotQualified");
+      });
+      await withThresholds({ top10OverallScore: new BN(score) }, async () => {
+        await mintAchievement(owner.publicKey, { top10Overall: {} });
+      });
+    });
+
+    it("Checks the season winner against the configured tier", async () => {
+      const owner = await registerUser();
+
+      await expectError(mintAchievement(owner.publicKey, { seasonWinner: {} }), "NotQualified");
+
+      await withThresholds({ seasonWinnerTier: { bronze: {} } }, async () => {
+        await mintAchievement(owner.publicKey, { seasonWinner: {} });
+      });
+    });
+
+    it("Rejects a zero threshold", async () => {
+      const { achievementThresholds } = await program.account.leaderboardConfig.fetch(configPda);
+
+      for (const field of ["top10OverallScore", "top100Transactions", "taskCompletionistTasks", "volumeLeaderVolume"]) {
+        await expectError(
+          setAchievementThresholds({ ...achievementThresholds, [field]: new BN(0) }),
+          "InvalidAchievementThresholds"
+        );
+      }
+
+      const config = await program.account.leaderboardConfig.fetch(configPda);
+      expect(config.achievementThresholds.top10OverallScore.toNumber()).to.equal(
+        achievementThresholds.top10OverallScore.toNumber()
+      );
+    });
+
+    it("Only lets the config authority change thresholds", async () => {
+      const { achievementThresholds } = await program.account.leaderboardConfig.fetch(configPda);
+
+      await expectError(
+        setAchievementThresholds(
+          { ...achievementThresholds, top10OverallScore: new BN(0) },
+          await fundedKeypair()
+        ),
+        "Unauthorized"
+      );
+    });
+  });
+
+  describe("privacy mode", () => {
+    const setPrivacy = (owner: Keypair, isPrivate: boolean, profileOwner = owner.publicKey) =>
+      program.methods
+        .setPrivacy(isPrivate)
+        .accounts({ userProfile: userPda(profileOwner), owner: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+    const userRank = (owner: PublicKey) =>
+      program.methods.getUserRank().accounts({ userProfile: userPda(owner) }).view();
+
+    it("Redacts the rank of a private profile to its tier", async () => {
+      const owner = await registerUser();
+      await recordTransaction(owner.publicKey, 2 * LAMPORTS_PER_SOL);
+
+      await setPrivacy(owner, true);
+
+      const rank = await userRank(owner.publicKey);
+      expect(rank.isPrivate).to.equal(true);
+      expect(rank.tier).to.deep.equal({ bronze: {} });
+      expect(rank.contributionScore.toNumber()).to.equal(0);
+      expect(rank.totalTransactions.toNumber()).to.equal(0);
+      expect(rank.tasksCompleted.toNumber()).to.equal(0);
+      expect(rank.rewardsEarned.toNumber()).to.equal(0);
+      expect(rank.badgesCount).to.equal(0);
+    });
+
+    it("Keeps scoring a private profile", async () => {
+      const owner = await registerUser();
+      await setPrivacy(owner, true);
+      const { totalTransactions } = await program.account.leaderboardConfig.fetch(configPda);
+
+      await recordTransaction(owner.publicKey, 2 * LAMPORTS_PER_SOL);
+
+      expect(await contributionScore(owner.publicKey)).to.equal(110);
+      const config = await program.account.leaderboardConfig.fetch(configPda);
+      expect(config.totalTransactions.toNumber()).to.equal(totalTransactions.toNumber() + 1);
+    });
+
+    it("Leaves a private profile's amounts and points out of recording events", async () => {
+      const owner = await registerUser();
+      await setPrivacy(owner, true);
+
+      const [recorded] = await emittedEvents(await recordTransaction(owner.publicKey, 2 * LAMPORTS_PER_SOL));
+      expect(recorded.name).to.equal("TransactionRecorded");
+      expect(recorded.data.isPrivate).to.equal(true);
+      expect(recorded.data.amount.toNumber()).to.equal(0);
+      expect(recorded.data.pointsEarned.toNumber()).to.equal(0);
+
+      const [completed] = await emittedEvents(
+        await recordTask(owner.publicKey, { easy: {} }, { rewardAmount: LAMPORTS_PER_SOL })
+      );
+      expect(completed.name).to.equal("TaskCompleted");
+      expect(completed.data.isPrivate).to.equal(true);
+      expect(completed.data.pointsEarned.toNumber()).to.equal(0);
+      expect(completed.data.rewardAmount.toNumber()).to.equal(0);
+
+      // Public profiles still report them
+      await setPrivacy(owner, false);
+      const [publicRecord] = await emittedEvents(await recordTransaction(owner.publicKey, 2 * LAMPORTS_PER_SOL));
+      expect(publicRecord.data.isPrivate).to.equal(false);
+      expect(publicRecord.data.amount.toNumber()).to.equal(2 * LAMPORTS_PER_SOL);
+      expect(publicRecord.data.pointsEarned.toNumber()).to.equal(110);
+    });
+
+    it("Shows full stats again once privacy is turned off", async () => {
+      const owner = await registerUser();
+      await setPrivacy(owner, true);
+      await recordTransaction(owner.publicKey, 2 * LAMPORTS_PER_SOL);
+
+      await setPrivacy(owner, false);
+
+      const rank = await userRank(owner.publicKey);
+      expect(rank.isPrivate).to.equal(false);
+      expect(rank.contributionScore.toNumber()).to.equal(110);
+      expect(rank.totalTransactions.toNumber()).to.equal(1);
+    });
+
+    it("Only lets the profile owner toggle privacy", async () => {
+      const owner = await registerUser();
+
+      await expectError(setPrivacy(await fundedKeypair(), true, owner.publicKey), "ConstraintSeeds");
+
+      const profile = await program.account.userProfile.fetch(userPda(owner.publicKey));
+      expect(profile.isPrivate).to.equal(false);
+    });
+  });
+});
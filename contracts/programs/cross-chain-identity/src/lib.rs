@@ -2,17 +2,50 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Mint};
 use anchor_spl::associated_token::AssociatedToken;
 use solana_program::{
-    keccak::hash,
+    keccak::{hash, hashv},
     secp256k1_recover::{secp256k1_recover},
     pubkey::Pubkey,
 };
 
 declare_id!("CCIDxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx");
 
+/// EIP-712 domain the typed-data signatures are bound to
+pub const EIP712_DOMAIN_NAME: &str = "SolanaPay Cross-Chain Identity";
+pub const EIP712_DOMAIN_VERSION: &str = "1";
+const EIP712_DOMAIN_TYPE: &str =
+    "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+const LINK_WALLET_TYPE: &str = "LinkWallet(bytes32 solanaWallet)";
+const GENERATE_WALLET_TYPE: &str = "GenerateWallet(address evmAddress)";
+
 #[program]
 pub mod cross_chain_identity {
     use super::*;
 
+    /// Create the program config holding the EIP-712 chain id and the plaintext signature switch
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        chain_id: u64,
+        allow_plaintext_signatures: bool,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.authority = ctx.accounts.authority.key();
+        config.chain_id = chain_id;
+        config.allow_plaintext_signatures = allow_plaintext_signatures;
+        config.bump = ctx.bumps.config;
+
+        Ok(())
+    }
+
+    /// Enable or disable legacy plaintext-message signatures (authority only)
+    pub fn set_plaintext_signatures(
+        ctx: Context<UpdateConfig>,
+        allowed: bool,
+    ) -> Result<()> {
+        ctx.accounts.config.allow_plaintext_signatures = allowed;
+
+        Ok(())
+    }
+
     /// Initialize a new cross-chain identity linking EVM and Solana wallets
     pub fn initialize_identity(
         ctx: Context<InitializeIdentity>,
@@ -20,26 +53,24 @@ pub mod cross_chain_identity {
         signature: [u8; 64],
         recovery_id: u8,
     ) -> Result<()> {
+        let config = &ctx.accounts.config;
         let identity = &mut ctx.accounts.identity;
         let user = ctx.accounts.user.key();
 
         // Verify EVM signature to prove ownership
-        let message = format!("Link Solana wallet {} to EVM", user);
-        let message_hash = hash(message.as_bytes());
-        
-        // Recover EVM address from signature
-        let recovered_pubkey = secp256k1_recover(
-            &message_hash.to_bytes(),
+        let typed_data_hash = eip712_digest(config, hashv(&[
+            hash(LINK_WALLET_TYPE.as_bytes()).as_ref(),
+            user.as_ref(),
+        ]).to_bytes());
+        let plaintext_hash = hash(format!("Link Solana wallet {} to EVM", user).as_bytes()).to_bytes();
+        verify_evm_signature(
+            config,
+            &typed_data_hash,
+            &plaintext_hash,
             recovery_id,
             &signature,
-        ).map_err(|_| ErrorCode::InvalidSignature)?;
-
-        // Convert recovered pubkey to EVM address (last 20 bytes of keccak hash)
-        let recovered_address = &hash(&recovered_pubkey.to_bytes()).to_bytes()[12..32];
-        
-        if recovered_address != evm_address {
-            return Err(ErrorCode::SignatureVerificationFailed.into());
-        }
+            &evm_address,
+        )?;
 
         // Initialize identity account
         identity.user = user;
@@ -67,24 +98,26 @@ pub mod cross_chain_identity {
         recovery_id: u8,
         seed: String,
     ) -> Result<()> {
+        let config = &ctx.accounts.config;
         let identity = &mut ctx.accounts.identity;
         let new_wallet = ctx.accounts.new_wallet.key();
 
         // Verify EVM signature
-        let message = format!("Generate Solana wallet for EVM {}", hex::encode(evm_address));
-        let message_hash = hash(message.as_bytes());
-        
-        let recovered_pubkey = secp256k1_recover(
-            &message_hash.to_bytes(),
+        let typed_data_hash = eip712_digest(config, hashv(&[
+            hash(GENERATE_WALLET_TYPE.as_bytes()).as_ref(),
+            &abi_encode_address(&evm_address),
+        ]).to_bytes());
+        let plaintext_hash = hash(
+            format!("Generate Solana wallet for EVM {}", hex::encode(evm_address)).as_bytes()
+        ).to_bytes();
+        verify_evm_signature(
+            config,
+            &typed_data_hash,
+            &plaintext_hash,
             recovery_id,
             &signature,
-        ).map_err(|_| ErrorCode::InvalidSignature)?;
-
-        let recovered_address = &hash(&recovered_pubkey.to_bytes()).to_bytes()[12..32];
-        
-        if recovered_address != evm_address {
-            return Err(ErrorCode::SignatureVerificationFailed.into());
-        }
+            &evm_address,
+        )?;
 
         // Create deterministic wallet from EVM address and seed
         let wallet_seed = format!("{}{}", hex::encode(evm_address), seed);
@@ -171,10 +204,163 @@ pub mod cross_chain_identity {
             verification_count: identity.verification_count,
         })
     }
+
+    /// Authorize a session key to act on behalf of a linked identity until expiry
+    pub fn create_session_key(
+        ctx: Context<CreateSessionKey>,
+        session_key: Pubkey,
+        expires_at: i64,
+    ) -> Result<()> {
+        let session = &mut ctx.accounts.session;
+        let identity = &ctx.accounts.identity;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        if expires_at <= current_time {
+            return Err(ErrorCode::InvalidSessionExpiry.into());
+        }
+
+        if expires_at - current_time > MAX_SESSION_DURATION {
+            return Err(ErrorCode::SessionDurationTooLong.into());
+        }
+
+        session.identity = identity.key();
+        session.owner = identity.user;
+        session.session_key = session_key;
+        session.created_at = current_time;
+        session.expires_at = expires_at;
+        session.is_revoked = false;
+
+        emit!(SessionKeyCreated {
+            identity: session.identity,
+            owner: session.owner,
+            session_key,
+            expires_at,
+            timestamp: current_time,
+        });
+
+        Ok(())
+    }
+
+    /// Revoke a session key before its expiry
+    pub fn revoke_session_key(ctx: Context<RevokeSessionKey>) -> Result<()> {
+        let session = &mut ctx.accounts.session;
+
+        session.is_revoked = true;
+
+        emit!(SessionKeyRevoked {
+            identity: session.identity,
+            session_key: session.session_key,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Verify that a session key is currently authorized for an identity
+    pub fn verify_session_key(ctx: Context<VerifySessionKey>) -> Result<()> {
+        let session = &ctx.accounts.session;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        session.verify(&ctx.accounts.session_signer.key(), current_time)
+    }
+}
+
+// EVM-style address standing in for this program as the EIP-712 `verifyingContract`
+pub fn verifying_contract() -> [u8; 20] {
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash(crate::ID.as_ref()).to_bytes()[12..32]);
+    address
+}
+
+// ABI-encoded `address` is left-padded to 32 bytes
+fn abi_encode_address(address: &[u8; 20]) -> [u8; 32] {
+    let mut encoded = [0u8; 32];
+    encoded[12..].copy_from_slice(address);
+    encoded
+}
+
+// EIP-712 signing digest: keccak256("\x19\x01" || domainSeparator || hashStruct(message))
+fn eip712_digest(config: &IdentityConfig, struct_hash: [u8; 32]) -> [u8; 32] {
+    let mut chain_id = [0u8; 32];
+    chain_id[24..].copy_from_slice(&config.chain_id.to_be_bytes());
+
+    let domain_separator = hashv(&[
+        hash(EIP712_DOMAIN_TYPE.as_bytes()).as_ref(),
+        hash(EIP712_DOMAIN_NAME.as_bytes()).as_ref(),
+        hash(EIP712_DOMAIN_VERSION.as_bytes()).as_ref(),
+        &chain_id,
+        &abi_encode_address(&verifying_contract()),
+    ]);
+    hashv(&[b"\x19\x01", domain_separator.as_ref(), &struct_hash]).to_bytes()
+}
+
+// Accept an EIP-712 signature, or a plaintext-message one while the config still allows them
+fn verify_evm_signature(
+    config: &IdentityConfig,
+    typed_data_hash: &[u8; 32],
+    plaintext_hash: &[u8; 32],
+    recovery_id: u8,
+    signature: &[u8; 64],
+    evm_address: &[u8; 20],
+) -> Result<()> {
+    if recover_evm_address(typed_data_hash, recovery_id, signature)? == *evm_address {
+        return Ok(());
+    }
+
+    if config.allow_plaintext_signatures
+        && recover_evm_address(plaintext_hash, recovery_id, signature)? == *evm_address
+    {
+        return Ok(());
+    }
+
+    Err(ErrorCode::SignatureVerificationFailed.into())
+}
+
+// Recover the EVM address (last 20 bytes of the keccak hash of the public key) that signed `message_hash`
+fn recover_evm_address(message_hash: &[u8; 32], recovery_id: u8, signature: &[u8; 64]) -> Result<[u8; 20]> {
+    let recovered_pubkey = secp256k1_recover(message_hash, recovery_id, signature)
+        .map_err(|_| ErrorCode::InvalidSignature)?;
+
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash(&recovered_pubkey.to_bytes()).to_bytes()[12..32]);
+    Ok(address)
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + IdentityConfig::INIT_SPACE,
+        seeds = [b"identity_config"],
+        bump
+    )]
+    pub config: Account<'info, IdentityConfig>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"identity_config"],
+        bump = config.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, IdentityConfig>,
+
+    pub authority: Signer<'info>,
 }
 
 #[derive(Accounts)]
 pub struct InitializeIdentity<'info> {
+    #[account(seeds = [b"identity_config"], bump = config.bump)]
+    pub config: Account<'info, IdentityConfig>,
+
     #[account(
         init,
         payer = user,
@@ -192,6 +378,9 @@ pub struct InitializeIdentity<'info> {
 
 #[derive(Accounts)]
 pub struct GenerateLinkedWallet<'info> {
+    #[account(seeds = [b"identity_config"], bump = config.bump)]
+    pub config: Account<'info, IdentityConfig>,
+
     #[account(
         init,
         payer = payer,
@@ -244,6 +433,68 @@ pub struct GetIdentity<'info> {
     pub identity: Account<'info, CrossChainIdentity>,
 }
 
+#[derive(Accounts)]
+#[instruction(session_key: Pubkey)]
+pub struct CreateSessionKey<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = 8 + SessionKey::INIT_SPACE,
+        seeds = [b"session", identity.key().as_ref(), session_key.as_ref()],
+        bump
+    )]
+    pub session: Account<'info, SessionKey>,
+
+    #[account(
+        seeds = [b"identity", user.key().as_ref()],
+        bump,
+        has_one = user,
+        constraint = identity.is_verified @ ErrorCode::IdentityNotVerified
+    )]
+    pub identity: Account<'info, CrossChainIdentity>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeSessionKey<'info> {
+    #[account(
+        mut,
+        seeds = [b"session", session.identity.as_ref(), session.session_key.as_ref()],
+        bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub session: Account<'info, SessionKey>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VerifySessionKey<'info> {
+    #[account(
+        seeds = [b"session", session.identity.as_ref(), session.session_key.as_ref()],
+        bump
+    )]
+    pub session: Account<'info, SessionKey>,
+
+    pub session_signer: Signer<'info>,
+}
+
+#[account]
+pub struct IdentityConfig {
+    pub authority: Pubkey,               // Can toggle plaintext signatures
+    pub chain_id: u64,                   // EIP-712 domain chainId
+    pub allow_plaintext_signatures: bool, // Accept legacy raw-message signatures
+    pub bump: u8,
+}
+
+impl IdentityConfig {
+    pub const INIT_SPACE: usize = 32 + 8 + 1 + 1;
+}
+
 #[account]
 pub struct CrossChainIdentity {
     pub user: Pubkey,                    // Solana wallet address
@@ -263,6 +514,41 @@ impl CrossChainIdentity {
     pub const INIT_SPACE: usize = 32 + 20 + 32 + 1 + 8 + 8 + 8 + 4 + 4 + 32 + 256;
 }
 
+/// Maximum lifetime of a session key (7 days)
+pub const MAX_SESSION_DURATION: i64 = 7 * 24 * 60 * 60;
+
+#[account]
+pub struct SessionKey {
+    pub identity: Pubkey,               // Identity account this session acts for
+    pub owner: Pubkey,                  // Identity owner that created the session
+    pub session_key: Pubkey,            // Delegated signer
+    pub created_at: i64,                // Creation timestamp
+    pub expires_at: i64,                // Expiry timestamp
+    pub is_revoked: bool,               // Revoked by owner
+}
+
+impl SessionKey {
+    pub const INIT_SPACE: usize = 32 + 32 + 32 + 8 + 8 + 1;
+
+    /// Check that `signer` is this session's key and the session is still live.
+    /// Programs reading a `SessionKey` account can call this directly instead of CPI.
+    pub fn verify(&self, signer: &Pubkey, current_time: i64) -> Result<()> {
+        if self.session_key != *signer {
+            return Err(ErrorCode::InvalidSessionKey.into());
+        }
+
+        if self.is_revoked {
+            return Err(ErrorCode::SessionKeyRevoked.into());
+        }
+
+        if current_time >= self.expires_at {
+            return Err(ErrorCode::SessionKeyExpired.into());
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct IdentityData {
     pub user: Pubkey,
@@ -298,6 +584,22 @@ pub struct IdentityVerified {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct SessionKeyCreated {
+    pub identity: Pubkey,
+    pub owner: Pubkey,
+    pub session_key: Pubkey,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SessionKeyRevoked {
+    pub identity: Pubkey,
+    pub session_key: Pubkey,
+    pub timestamp: i64,
+}
+
 #[error_code]
 pub enum ErrorCode {
     #[msg("Invalid signature provided")]
@@ -312,4 +614,16 @@ pub enum ErrorCode {
     IdentityNotFound,
     #[msg("Unauthorized access")]
     Unauthorized,
+    #[msg("Identity is not verified")]
+    IdentityNotVerified,
+    #[msg("Session expiry must be in the future")]
+    InvalidSessionExpiry,
+    #[msg("Session duration exceeds maximum allowed")]
+    SessionDurationTooLong,
+    #[msg("Signer is not the session key")]
+    InvalidSessionKey,
+    #[msg("Session key has been revoked")]
+    SessionKeyRevoked,
+    #[msg("Session key has expired")]
+    SessionKeyExpired,
 }
@@ -0,0 +1,335 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, LAMPORTS_PER_SOL, SystemProgram } from "@solana/web3.js";
+import { CrossChainIdentity } from "../target/types/cross_chain_identity";
+import { expect } from "chai";
+import { ethers } from "ethers";
+
+describe("cross-chain-identity", () => {
+  // Configure the client to use the local cluster.
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.CrossChainIdentity as Program<CrossChainIdentity>;
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+
+  const CHAIN_ID = 1;
+  const [configPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("identity_config")],
+    program.programId
+  );
+
+  // Mirrors the program's EIP-712 domain; verifyingContract is the tail of keccak256(program id)
+  const domain = {
+    name: "SolanaPay Cross-Chain Identity",
+    version: "1",
+    chainId: CHAIN_ID,
+    verifyingContract: ethers.getAddress(
+      ethers.dataSlice(ethers.keccak256(program.programId.toBytes()), 12)
+    ),
+  };
+
+  const identityPda = (wallet: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("identity"), wallet.toBuffer()],
+      program.programId
+    )[0];
+
+  const fundedKeypair = async () => {
+    const keypair = Keypair.generate();
+    const signature = await provider.connection.requestAirdrop(keypair.publicKey, LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(signature);
+    return keypair;
+  };
+
+  const chainTime = async () =>
+    (await provider.connection.getBlockTime(await provider.connection.getSlot())) as number;
+
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    try {
+      await promise;
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal(code);
+      return;
+    }
+    expect.fail(`expected ${code}`);
+  };
+
+  // Split a 65-byte EVM signature into the (r || s, recovery id) pair the program takes
+  const splitSignature = (signature: string) => {
+    const { r, s, yParity } = ethers.Signature.from(signature);
+    return {
+      signature: Array.from(ethers.getBytes(ethers.concat([r, s]))),
+      recoveryId: yParity,
+    };
+  };
+
+  const signLinkWallet = async (evmWallet: ethers.BaseWallet, solanaWallet: PublicKey) =>
+    splitSignature(
+      await evmWallet.signTypedData(
+        domain,
+        { LinkWallet: [{ name: "solanaWallet", type: "bytes32" }] },
+        { solanaWallet: solanaWallet.toBytes() }
+      )
+    );
+
+  const evmAddressBytes = (evmWallet: ethers.BaseWallet) =>
+    Array.from(ethers.getBytes(evmWallet.address));
+
+  const linkIdentity = async (user: Keypair, evmWallet = ethers.Wallet.createRandom()) => {
+    const { signature, recoveryId } = await signLinkWallet(evmWallet, user.publicKey);
+    await program.methods
+      .initializeIdentity(evmAddressBytes(evmWallet), signature, recoveryId)
+      .accounts({
+        config: configPda,
+        identity: identityPda(user.publicKey),
+        user: user.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+    return evmWallet;
+  };
+
+  before(async () => {
+    await program.methods
+      .initializeConfig(new BN(CHAIN_ID), false)
+      .accounts({
+        config: configPda,
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  });
+
+  describe("session keys", () => {
+    let owner: Keypair;
+    let ownerIdentity: PublicKey;
+
+    const sessionPda = (sessionKey: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("session"), ownerIdentity.toBuffer(), sessionKey.toBuffer()],
+        program.programId
+      )[0];
+
+    const createSession = (sessionKey: PublicKey, expiresAt: number) =>
+      program.methods
+        .createSessionKey(sessionKey, new BN(expiresAt))
+        .accounts({
+          session: sessionPda(sessionKey),
+          identity: ownerIdentity,
+          user: owner.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([owner])
+        .rpc();
+
+    const verifySession = (sessionKey: PublicKey, signer: Keypair) =>
+      program.methods
+        .verifySessionKey()
+        .accounts({
+          session: sessionPda(sessionKey),
+          sessionSigner: signer.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+
+    before(async () => {
+      owner = await fundedKeypair();
+      await linkIdentity(owner);
+      ownerIdentity = identityPda(owner.publicKey);
+    });
+
+    it("Creates a session key for the identity owner", async () => {
+      const sessionKey = Keypair.generate();
+      const expiresAt = (await chainTime()) + 3600;
+
+      await createSession(sessionKey.publicKey, expiresAt);
+
+      const session = await program.account.sessionKey.fetch(sessionPda(sessionKey.publicKey));
+      expect(session.identity.toString()).to.equal(ownerIdentity.toString());
+      expect(session.owner.toString()).to.equal(owner.publicKey.toString());
+      expect(session.sessionKey.toString()).to.equal(sessionKey.publicKey.toString());
+      expect(session.expiresAt.toNumber()).to.equal(expiresAt);
+      expect(session.isRevoked).to.equal(false);
+    });
+
+    it("Accepts the session key within its validity", async () => {
+      const sessionKey = Keypair.generate();
+      await createSession(sessionKey.publicKey, (await chainTime()) + 3600);
+
+      await verifySession(sessionKey.publicKey, sessionKey);
+    });
+
+    it("Rejects a signer that is not the session key", async () => {
+      const sessionKey = Keypair.generate();
+      await createSession(sessionKey.publicKey, (await chainTime()) + 3600);
+
+      await expectError(
+        verifySession(sessionKey.publicKey, Keypair.generate()),
+        "InvalidSessionKey"
+      );
+    });
+
+    it("Rejects a revoked session key", async () => {
+      const sessionKey = Keypair.generate();
+      await createSession(sessionKey.publicKey, (await chainTime()) + 3600);
+
+      await program.methods
+        .revokeSessionKey()
+        .accounts({
+          session: sessionPda(sessionKey.publicKey),
+          owner: owner.publicKey,
+        })
+        .signers([owner])
+        .rpc();
+
+      await expectError(verifySession(sessionKey.publicKey, sessionKey), "SessionKeyRevoked");
+    });
+
+    it("Rejects an expired session key", async () => {
+      const sessionKey = Keypair.generate();
+      await createSession(sessionKey.publicKey, (await chainTime()) + 2);
+
+      // Let the validator clock move past the expiry
+      await new Promise((resolve) => setTimeout(resolve, 4000));
+
+      await expectError(verifySession(sessionKey.publicKey, sessionKey), "SessionKeyExpired");
+    });
+
+    it("Rejects a session longer than the maximum duration", async () => {
+      const sessionKey = Keypair.generate();
+      const eightDays = 8 * 24 * 60 * 60;
+
+      await expectError(
+        createSession(sessionKey.publicKey, (await chainTime()) + eightDays),
+        "SessionDurationTooLong"
+      );
+    });
+  });
+
+  describe("signatures", () => {
+    // Fixed key so the typed-data signatures below are a reproducible vector
+    const evmWallet = new ethers.Wallet(
+      "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"
+    );
+
+    const signPlaintext = (message: string) => {
+      // The program hashes the raw message, without the EIP-191 prefix
+      const { r, s, yParity } = evmWallet.signingKey.sign(
+        ethers.keccak256(ethers.toUtf8Bytes(message))
+      );
+      return {
+        signature: Array.from(ethers.getBytes(ethers.concat([r, s]))),
+        recoveryId: yParity,
+      };
+    };
+
+    const initializeIdentity = (
+      user: Keypair,
+      { signature, recoveryId }: { signature: number[]; recoveryId: number }
+    ) =>
+      program.methods
+        .initializeIdentity(evmAddressBytes(evmWallet), signature, recoveryId)
+        .accounts({
+          config: configPda,
+          identity: identityPda(user.publicKey),
+          user: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+
+    const setPlaintextSignatures = (allowed: boolean, authority?: Keypair) =>
+      program.methods
+        .setPlaintextSignatures(allowed)
+        .accounts({
+          config: configPda,
+          authority: authority ? authority.publicKey : provider.wallet.publicKey,
+        })
+        .signers(authority ? [authority] : [])
+        .rpc();
+
+    it("Links an identity from an EIP-712 LinkWallet signature", async () => {
+      const user = await fundedKeypair();
+
+      await initializeIdentity(user, await signLinkWallet(evmWallet, user.publicKey));
+
+      const identity = await program.account.crossChainIdentity.fetch(identityPda(user.publicKey));
+      expect(Buffer.from(identity.evmAddress).toString("hex")).to.equal(
+        evmWallet.address.slice(2).toLowerCase()
+      );
+      expect(identity.isVerified).to.equal(true);
+    });
+
+    it("Generates a linked wallet from an EIP-712 GenerateWallet signature", async () => {
+      const newWallet = Keypair.generate();
+      const { signature, recoveryId } = splitSignature(
+        await evmWallet.signTypedData(
+          domain,
+          { GenerateWallet: [{ name: "evmAddress", type: "address" }] },
+          { evmAddress: evmWallet.address }
+        )
+      );
+
+      await program.methods
+        .generateLinkedWallet(evmAddressBytes(evmWallet), signature, recoveryId, "seed-1")
+        .accounts({
+          config: configPda,
+          identity: identityPda(newWallet.publicKey),
+          newWallet: newWallet.publicKey,
+          payer: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const identity = await program.account.crossChainIdentity.fetch(identityPda(newWallet.publicKey));
+      expect(identity.solanaAddress.toString()).to.equal(newWallet.publicKey.toString());
+    });
+
+    it("Rejects a typed-data signature made for another chain", async () => {
+      const user = await fundedKeypair();
+      const { signature, recoveryId } = splitSignature(
+        await evmWallet.signTypedData(
+          { ...domain, chainId: CHAIN_ID + 1 },
+          { LinkWallet: [{ name: "solanaWallet", type: "bytes32" }] },
+          { solanaWallet: user.publicKey.toBytes() }
+        )
+      );
+
+      await expectError(
+        initializeIdentity(user, { signature, recoveryId }),
+        "SignatureVerificationFailed"
+      );
+    });
+
+    it("Rejects plaintext signatures while the config disallows them", async () => {
+      const user = await fundedKeypair();
+
+      await expectError(
+        initializeIdentity(user, signPlaintext(`Link Solana wallet ${user.publicKey.toBase58()} to EVM`)),
+        "SignatureVerificationFailed"
+      );
+    });
+
+    it("Only lets the config authority toggle plaintext signatures", async () => {
+      await expectError(setPlaintextSignatures(true, await fundedKeypair()), "Unauthorized");
+    });
+
+    it("Accepts plaintext signatures once the authority enables them", async () => {
+      const user = await fundedKeypair();
+      await setPlaintextSignatures(true);
+
+      try {
+        await initializeIdentity(
+          user,
+          signPlaintext(`Link Solana wallet ${user.publicKey.toBase58()} to EVM`)
+        );
+      } finally {
+        await setPlaintextSignatures(false);
+      }
+
+      const identity = await program.account.crossChainIdentity.fetch(identityPda(user.publicKey));
+      expect(identity.isVerified).to.equal(true);
+    });
+  });
+});
@@ -1,9 +1,15 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
 use anchor_spl::associated_token::AssociatedToken;
 
 declare_id!("ESCRxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx");
 
+pub const MAX_MILESTONES: usize = 5;
+pub const MAX_ARBITER_CANDIDATES: usize = 20;
+pub const SLASH_REPUTATION_PENALTY: u32 = 20;
+// Slots between opening (or appealing) a dispute and the slot whose hash seeds the arbiter draw
+pub const ARBITER_DRAW_DELAY_SLOTS: u64 = 10;
+
 #[program]
 pub mod escrow_arbitration {
     use super::*;
@@ -14,11 +20,24 @@ pub mod escrow_arbitration {
         config.authority = ctx.accounts.authority.key();
         config.treasury = ctx.accounts.treasury.key();
         config.arbitration_fee = 1000000; // 0.001 SOL
+        config.dispute_fee_rebate_bps = 5000; // 50% of the fee to a winner who didn't file
         config.dispute_timeout = 7 * 24 * 60 * 60; // 7 days
+        config.escrow_creation_fee = 0; // Escrows are free until the authority sets a fee
         config.total_escrows = 0;
+        config.escrow_count = 0;
+        config.appeal_deposit = 0; // Appeals require no deposit until the authority sets one
+        config.appeal_window = 0; // Rulings are final until the authority opens an appeal window
+        config.slash_bps = 1000; // 10% of stake lost when a ruling is overturned on appeal
+        config.max_escrow_amount = 0; // No cap until the authority sets one
+        config.kyc_threshold = 0; // No KYC requirement until the authority sets one
+        config.kyc_mint = Pubkey::default();
         config.total_disputes = 0;
+        config.disputes_by_reason = [0; 4];
         config.is_paused = false;
 
+        ctx.accounts.arbiter_registry.arbiters = Vec::new();
+        ctx.accounts.arbiter_registry.bump = *ctx.bumps.get("arbiter_registry").unwrap();
+
         emit!(ProgramInitialized {
             authority: config.authority,
             timestamp: Clock::get()?.unix_timestamp,
@@ -40,6 +59,13 @@ pub mod escrow_arbitration {
         require!(!config.is_paused, ErrorCode::ProgramPaused);
         require!(amount > 0, ErrorCode::InvalidAmount);
         require!(description.len() <= 200, ErrorCode::DescriptionTooLong);
+        check_escrow_limits(
+            config,
+            None,
+            amount,
+            &ctx.accounts.buyer.key(),
+            &ctx.accounts.kyc_credential,
+        )?;
 
         // Initialize escrow
         escrow.buyer = ctx.accounts.buyer.key();
@@ -50,11 +76,35 @@ pub mod escrow_arbitration {
         escrow.created_at = Clock::get()?.unix_timestamp;
         escrow.auto_release_time = auto_release_time;
         escrow.is_disputed = false;
+        escrow.milestones = Vec::new();
+        escrow.released_amount = 0;
+        escrow.refunded_amount = 0;
+        escrow.escrow_id = config.escrow_count;
+        escrow.mint = None;
+        escrow.bump = *ctx.bumps.get("escrow").unwrap();
+        config.escrow_count += 1;
 
         // Lock funds in escrow
-        **ctx.accounts.buyer.to_account_info().try_borrow_mut_lamports()? -= amount;
-        **escrow.to_account_info().try_borrow_mut_lamports()? += amount;
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.buyer.to_account_info(),
+                    to: escrow.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
 
+        let creation_fee = charge_escrow_creation_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.buyer,
+            &ctx.accounts.treasury,
+            &ctx.accounts.system_program,
+        )?;
+
+        let escrow = &ctx.accounts.escrow;
+        let config = &mut ctx.accounts.config;
         config.total_escrows += 1;
 
         emit!(EscrowCreated {
@@ -62,46 +112,270 @@ pub mod escrow_arbitration {
             buyer: escrow.buyer,
             seller: escrow.seller,
             amount,
+            mint: None,
+            creation_fee,
             timestamp: escrow.created_at,
         });
 
         Ok(())
     }
 
-    /// Release escrow funds to seller
-    pub fn release_escrow(ctx: Context<ReleaseEscrow>) -> Result<()> {
+    /// Create a draft escrow that records agreed terms without locking funds
+    pub fn create_draft_escrow(
+        ctx: Context<CreateEscrow>,
+        amount: u64,
+        description: String,
+        auto_release_time: Option<i64>,
+    ) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        let config = &mut ctx.accounts.config;
+
+        require!(!config.is_paused, ErrorCode::ProgramPaused);
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(description.len() <= 200, ErrorCode::DescriptionTooLong);
+        // KYC is checked when the draft is funded; only the cap applies here
+        require!(
+            config.max_escrow_amount == 0 || amount <= config.max_escrow_amount,
+            ErrorCode::EscrowAmountTooLarge
+        );
+
+        escrow.buyer = ctx.accounts.buyer.key();
+        escrow.seller = ctx.accounts.seller.key();
+        escrow.amount = amount;
+        escrow.status = EscrowStatus::Draft;
+        escrow.description = description;
+        escrow.created_at = Clock::get()?.unix_timestamp;
+        escrow.auto_release_time = auto_release_time;
+        escrow.is_disputed = false;
+        escrow.milestones = Vec::new();
+        escrow.released_amount = 0;
+        escrow.refunded_amount = 0;
+        escrow.escrow_id = config.escrow_count;
+        escrow.mint = None;
+        escrow.bump = *ctx.bumps.get("escrow").unwrap();
+        config.escrow_count += 1;
+
+        // Charged up front so funding the draft later doesn't bypass the fee
+        let creation_fee = charge_escrow_creation_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.buyer,
+            &ctx.accounts.treasury,
+            &ctx.accounts.system_program,
+        )?;
+
+        let escrow = &ctx.accounts.escrow;
+        emit!(EscrowDraftCreated {
+            escrow_id: escrow.key(),
+            buyer: escrow.buyer,
+            seller: escrow.seller,
+            amount,
+            creation_fee,
+            timestamp: escrow.created_at,
+        });
+
+        Ok(())
+    }
+
+    /// Lock the agreed amount into a draft escrow, making it active
+    pub fn fund_escrow(ctx: Context<FundEscrow>) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        let config = &mut ctx.accounts.config;
+
+        require!(!config.is_paused, ErrorCode::ProgramPaused);
+        require!(escrow.status == EscrowStatus::Draft, ErrorCode::InvalidEscrowStatus);
+
+        let amount = escrow.amount;
+        check_escrow_limits(
+            config,
+            None,
+            amount,
+            &ctx.accounts.buyer.key(),
+            &ctx.accounts.kyc_credential,
+        )?;
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.buyer.to_account_info(),
+                    to: escrow.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        escrow.status = EscrowStatus::Active;
+        config.total_escrows += 1;
+
+        emit!(EscrowCreated {
+            escrow_id: escrow.key(),
+            buyer: escrow.buyer,
+            seller: escrow.seller,
+            amount,
+            mint: None,
+            creation_fee: 0, // Already charged when the draft was created
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Discard an unfunded draft escrow (buyer or seller); rent returns to the buyer
+    pub fn cancel_draft(ctx: Context<CancelDraft>) -> Result<()> {
+        let escrow = &ctx.accounts.escrow;
+
+        require!(escrow.status == EscrowStatus::Draft, ErrorCode::InvalidEscrowStatus);
+        require!(
+            escrow.buyer == ctx.accounts.authority.key() ||
+            escrow.seller == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+
+        emit!(EscrowDraftCancelled {
+            escrow_id: escrow.key(),
+            buyer: escrow.buyer,
+            seller: escrow.seller,
+            amount: escrow.amount,
+            cancelled_by: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Create escrow whose funds are released to the seller in milestones
+    pub fn create_milestone_escrow(
+        ctx: Context<CreateEscrow>,
+        description: String,
+        milestones: Vec<MilestoneInput>,
+    ) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        let config = &mut ctx.accounts.config;
+
+        require!(!config.is_paused, ErrorCode::ProgramPaused);
+        require!(description.len() <= 200, ErrorCode::DescriptionTooLong);
+        require!(
+            !milestones.is_empty() && milestones.len() <= MAX_MILESTONES,
+            ErrorCode::InvalidMilestoneCount
+        );
+
+        let mut amount: u64 = 0;
+        for milestone in milestones.iter() {
+            require!(milestone.amount > 0, ErrorCode::InvalidAmount);
+            amount = amount.checked_add(milestone.amount).ok_or(ErrorCode::InvalidAmount)?;
+        }
+        check_escrow_limits(
+            config,
+            None,
+            amount,
+            &ctx.accounts.buyer.key(),
+            &ctx.accounts.kyc_credential,
+        )?;
+
+        // Initialize escrow
+        escrow.buyer = ctx.accounts.buyer.key();
+        escrow.seller = ctx.accounts.seller.key();
+        escrow.amount = amount;
+        escrow.status = EscrowStatus::Active;
+        escrow.description = description;
+        escrow.created_at = Clock::get()?.unix_timestamp;
+        escrow.auto_release_time = None;
+        escrow.is_disputed = false;
+        escrow.milestones = milestones.iter().map(|m| Milestone {
+            amount: m.amount,
+            release_time: m.release_time,
+            status: MilestoneStatus::Pending,
+            completed_at: None,
+        }).collect();
+        escrow.released_amount = 0;
+        escrow.refunded_amount = 0;
+        escrow.escrow_id = config.escrow_count;
+        escrow.mint = None;
+        escrow.bump = *ctx.bumps.get("escrow").unwrap();
+        config.escrow_count += 1;
+
+        // Lock funds in escrow
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.buyer.to_account_info(),
+                    to: escrow.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let creation_fee = charge_escrow_creation_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.buyer,
+            &ctx.accounts.treasury,
+            &ctx.accounts.system_program,
+        )?;
+
+        let escrow = &ctx.accounts.escrow;
+        let config = &mut ctx.accounts.config;
+        config.total_escrows += 1;
+
+        emit!(EscrowCreated {
+            escrow_id: escrow.key(),
+            buyer: escrow.buyer,
+            seller: escrow.seller,
+            amount,
+            mint: None,
+            creation_fee,
+            timestamp: escrow.created_at,
+        });
+
+        Ok(())
+    }
+
+    /// Release a single milestone to the seller (buyer approval or after its release time)
+    pub fn release_milestone(ctx: Context<ReleaseMilestone>, milestone_index: u8) -> Result<()> {
         let escrow = &mut ctx.accounts.escrow;
+        let clock = Clock::get()?;
 
         require!(escrow.status == EscrowStatus::Active, ErrorCode::InvalidEscrowStatus);
         require!(!escrow.is_disputed, ErrorCode::EscrowDisputed);
 
-        // Check authorization
-        let clock = Clock::get()?;
+        let milestone = escrow.milestones.get(milestone_index as usize)
+            .ok_or(ErrorCode::InvalidMilestoneIndex)?;
+        require!(milestone.status == MilestoneStatus::Pending, ErrorCode::InvalidMilestoneStatus);
+
         let is_authorized = escrow.buyer == ctx.accounts.authority.key() ||
-            (escrow.auto_release_time.is_some() && 
-             clock.unix_timestamp >= escrow.auto_release_time.unwrap());
+            (milestone.release_time.is_some() &&
+             clock.unix_timestamp >= milestone.release_time.unwrap());
 
         require!(is_authorized, ErrorCode::Unauthorized);
 
-        // Release funds to seller
-        escrow.status = EscrowStatus::Completed;
-        escrow.completed_at = Some(clock.unix_timestamp);
+        let amount = milestone.amount;
+        escrow.milestones[milestone_index as usize].status = MilestoneStatus::Released;
+        escrow.milestones[milestone_index as usize].completed_at = Some(clock.unix_timestamp);
+        escrow.released_amount += amount;
 
-        **escrow.to_account_info().try_borrow_mut_lamports()? -= escrow.amount;
-        **ctx.accounts.seller.to_account_info().try_borrow_mut_lamports()? += escrow.amount;
+        **escrow.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.seller.to_account_info().try_borrow_mut_lamports()? += amount;
 
-        emit!(EscrowReleased {
+        escrow.finalize_if_settled(clock.unix_timestamp);
+
+        emit!(MilestoneReleased {
             escrow_id: escrow.key(),
+            buyer: escrow.buyer,
             seller: escrow.seller,
-            amount: escrow.amount,
+            milestone_index,
+            amount,
             timestamp: clock.unix_timestamp,
         });
 
         Ok(())
     }
 
-    /// Create dispute for escrow
-    pub fn create_dispute(ctx: Context<CreateDispute>, reason: String) -> Result<()> {
+    /// Dispute a single pending milestone
+    pub fn dispute_milestone(
+        ctx: Context<DisputeMilestone>,
+        milestone_index: u8,
+        reason_code: ReasonCode,
+        reason: String,
+    ) -> Result<()> {
         let escrow = &mut ctx.accounts.escrow;
         let dispute = &mut ctx.accounts.dispute;
         let config = &mut ctx.accounts.config;
@@ -109,157 +383,1676 @@ pub mod escrow_arbitration {
         require!(escrow.status == EscrowStatus::Active, ErrorCode::InvalidEscrowStatus);
         require!(!escrow.is_disputed, ErrorCode::AlreadyDisputed);
         require!(reason.len() <= 500, ErrorCode::ReasonTooLong);
-
-        // Only buyer or seller can create dispute
         require!(
             escrow.buyer == ctx.accounts.disputer.key() ||
             escrow.seller == ctx.accounts.disputer.key(),
             ErrorCode::Unauthorized
         );
 
-        // Initialize dispute
+        let milestone = escrow.milestones.get_mut(milestone_index as usize)
+            .ok_or(ErrorCode::InvalidMilestoneIndex)?;
+        require!(milestone.status == MilestoneStatus::Pending, ErrorCode::InvalidMilestoneStatus);
+        milestone.status = MilestoneStatus::Disputed;
+        let amount = milestone.amount;
+
         dispute.escrow = escrow.key();
         dispute.disputer = ctx.accounts.disputer.key();
         dispute.reason = reason.clone();
+        dispute.reason_code = reason_code;
+        dispute.resolution_code = None;
         dispute.status = DisputeStatus::Open;
         dispute.created_at = Clock::get()?.unix_timestamp;
         dispute.assigned_arbiter = None;
+        dispute.milestone_index = Some(milestone_index);
+        dispute.fee_paid = config.arbitration_fee;
+        dispute.appellant = None;
+        dispute.appeal_reason = None;
+        dispute.appeal_deposit = 0;
+        dispute.appeal_min_reputation = 0;
+        dispute.original_arbiter = None;
+        dispute.arbiter_draw_slot = Clock::get()?.slot + ARBITER_DRAW_DELAY_SLOTS;
+        dispute.appeal_upheld = None;
+        dispute.ruling_executed = false;
+
+        charge_arbitration_fee(
+            dispute,
+            &ctx.accounts.disputer,
+            &ctx.accounts.system_program,
+        )?;
 
-        escrow.is_disputed = true;
         config.total_disputes += 1;
+        config.disputes_by_reason[reason_code.index()] += 1;
 
         emit!(DisputeCreated {
             dispute_id: dispute.key(),
             escrow_id: escrow.key(),
+            buyer: escrow.buyer,
+            seller: escrow.seller,
+            amount,
             disputer: dispute.disputer,
+            reason_code,
             reason,
+            arbitration_fee: dispute.fee_paid,
             timestamp: dispute.created_at,
         });
 
         Ok(())
     }
 
-    /// Add arbiter to DAO
-    pub fn add_arbiter(ctx: Context<AddArbiter>, stake_amount: u64) -> Result<()> {
-        let arbiter = &mut ctx.accounts.arbiter;
-        let config = &ctx.accounts.config;
-
-        require!(
-            ctx.accounts.authority.key() == config.authority,
-            ErrorCode::Unauthorized
-        );
-        require!(stake_amount >= 10_000_000, ErrorCode::InsufficientStake); // 0.01 SOL minimum
-
-        arbiter.pubkey = ctx.accounts.arbiter_account.key();
-        arbiter.stake = stake_amount;
-        arbiter.reputation = 100; // Starting reputation
-        arbiter.cases_resolved = 0;
-        arbiter.is_active = true;
-        arbiter.joined_at = Clock::get()?.unix_timestamp;
-
-        // Lock stake
-        **ctx.accounts.arbiter_account.to_account_info().try_borrow_mut_lamports()? -= stake_amount;
-        **arbiter.to_account_info().try_borrow_mut_lamports()? += stake_amount;
-
-        emit!(ArbiterAdded {
-            arbiter: arbiter.pubkey,
-            stake: stake_amount,
-            timestamp: arbiter.joined_at,
-        });
-
-        Ok(())
-    }
-
-    /// Resolve dispute by arbiter
-    pub fn resolve_dispute(
-        ctx: Context<ResolveDispute>,
+    /// Resolve a milestone dispute by arbiter, settling only the disputed milestone
+    pub fn resolve_milestone_dispute(
+        ctx: Context<ResolveMilestoneDispute>,
         decision: DisputeDecision,
+        resolution_code: ReasonCode,
         reasoning: String,
     ) -> Result<()> {
         let dispute = &mut ctx.accounts.dispute;
         let escrow = &mut ctx.accounts.escrow;
         let arbiter = &mut ctx.accounts.arbiter;
+        let current_time = Clock::get()?.unix_timestamp;
 
         require!(dispute.status == DisputeStatus::Open, ErrorCode::InvalidDisputeStatus);
         require!(arbiter.is_active, ErrorCode::ArbiterInactive);
         require!(reasoning.len() <= 1000, ErrorCode::ReasoningTooLong);
-
-        // Assign arbiter if not already assigned
-        if dispute.assigned_arbiter.is_none() {
-            dispute.assigned_arbiter = Some(arbiter.pubkey);
-        }
-
         require!(
-            dispute.assigned_arbiter.unwrap() == arbiter.pubkey,
+            dispute.assigned_arbiter.ok_or(ErrorCode::ArbiterNotAssigned)? == arbiter.pubkey,
             ErrorCode::UnauthorizedArbiter
         );
 
-        // Execute decision
+        let milestone_index = dispute.milestone_index.ok_or(ErrorCode::InvalidMilestoneIndex)? as usize;
+        let amount = escrow.milestones[milestone_index].amount;
+        require!(
+            escrow.milestones[milestone_index].status == MilestoneStatus::Disputed,
+            ErrorCode::InvalidMilestoneStatus
+        );
+
         match decision {
             DisputeDecision::FavorBuyer => {
-                // Refund to buyer
-                **escrow.to_account_info().try_borrow_mut_lamports()? -= escrow.amount;
-                **ctx.accounts.buyer.to_account_info().try_borrow_mut_lamports()? += escrow.amount;
-                escrow.status = EscrowStatus::Refunded;
+                **escrow.to_account_info().try_borrow_mut_lamports()? -= amount;
+                **ctx.accounts.buyer.to_account_info().try_borrow_mut_lamports()? += amount;
+                escrow.milestones[milestone_index].status = MilestoneStatus::Refunded;
+                escrow.refunded_amount += amount;
             }
             DisputeDecision::FavorSeller => {
-                // Release to seller
-                **escrow.to_account_info().try_borrow_mut_lamports()? -= escrow.amount;
-                **ctx.accounts.seller.to_account_info().try_borrow_mut_lamports()? += escrow.amount;
-                escrow.status = EscrowStatus::Completed;
+                **escrow.to_account_info().try_borrow_mut_lamports()? -= amount;
+                **ctx.accounts.seller.to_account_info().try_borrow_mut_lamports()? += amount;
+                escrow.milestones[milestone_index].status = MilestoneStatus::Released;
+                escrow.released_amount += amount;
             }
         }
+        escrow.milestones[milestone_index].completed_at = Some(current_time);
+        escrow.finalize_if_settled(current_time);
 
         dispute.status = DisputeStatus::Resolved;
         dispute.decision = Some(decision);
-        dispute.reasoning = Some(reasoning.clone());
-        dispute.resolved_at = Some(Clock::get()?.unix_timestamp);
+        dispute.resolution_code = Some(resolution_code);
+        dispute.reasoning = Some(reasoning);
+        dispute.resolved_at = Some(current_time);
+
+        settle_arbitration_fee(
+            dispute,
+            escrow,
+            decision,
+            &ctx.accounts.buyer,
+            &ctx.accounts.seller,
+            &ctx.accounts.treasury,
+            ctx.accounts.config.dispute_fee_rebate_bps,
+        )?;
 
-        // Update arbiter stats
         arbiter.cases_resolved += 1;
-        arbiter.reputation += 10; // Increase reputation for resolving case
+        arbiter.open_cases = arbiter.open_cases.saturating_sub(1);
+        arbiter.reputation += 10;
 
         emit!(DisputeResolved {
             dispute_id: dispute.key(),
             escrow_id: escrow.key(),
+            buyer: escrow.buyer,
+            seller: escrow.seller,
+            amount,
             arbiter: arbiter.pubkey,
             decision,
-            timestamp: dispute.resolved_at.unwrap(),
+            resolution_code,
+            timestamp: current_time,
         });
 
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-pub struct Initialize<'info> {
+    /// Release escrow funds to seller
+    pub fn release_escrow(ctx: Context<ReleaseEscrow>) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+
+        require!(escrow.status == EscrowStatus::Active, ErrorCode::InvalidEscrowStatus);
+        require!(!escrow.is_disputed, ErrorCode::EscrowDisputed);
+        require!(escrow.milestones.is_empty(), ErrorCode::MilestoneEscrow);
+        require!(escrow.mint.is_none(), ErrorCode::TokenEscrow);
+
+        // Check authorization
+        let clock = Clock::get()?;
+        let is_authorized = escrow.buyer == ctx.accounts.authority.key() ||
+            (escrow.auto_release_time.is_some() && 
+             clock.unix_timestamp >= escrow.auto_release_time.unwrap());
+
+        require!(is_authorized, ErrorCode::Unauthorized);
+
+        // Release funds to seller
+        escrow.status = EscrowStatus::Completed;
+        escrow.completed_at = Some(clock.unix_timestamp);
+
+        **escrow.to_account_info().try_borrow_mut_lamports()? -= escrow.amount;
+        **ctx.accounts.seller.to_account_info().try_borrow_mut_lamports()? += escrow.amount;
+
+        emit!(EscrowReleased {
+            escrow_id: escrow.key(),
+            buyer: escrow.buyer,
+            seller: escrow.seller,
+            amount: escrow.amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Create escrow with SPL tokens locked in an escrow-owned token account
+    pub fn create_token_escrow(
+        ctx: Context<CreateTokenEscrow>,
+        amount: u64,
+        description: String,
+        auto_release_time: Option<i64>,
+    ) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        let config = &mut ctx.accounts.config;
+
+        require!(!config.is_paused, ErrorCode::ProgramPaused);
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(description.len() <= 200, ErrorCode::DescriptionTooLong);
+        check_escrow_limits(
+            config,
+            Some(&ctx.accounts.mint_limit),
+            amount,
+            &ctx.accounts.buyer.key(),
+            &ctx.accounts.kyc_credential,
+        )?;
+
+        escrow.buyer = ctx.accounts.buyer.key();
+        escrow.seller = ctx.accounts.seller.key();
+        escrow.amount = amount;
+        escrow.status = EscrowStatus::Active;
+        escrow.description = description;
+        escrow.created_at = Clock::get()?.unix_timestamp;
+        escrow.auto_release_time = auto_release_time;
+        escrow.is_disputed = false;
+        escrow.milestones = Vec::new();
+        escrow.released_amount = 0;
+        escrow.refunded_amount = 0;
+        escrow.escrow_id = config.escrow_count;
+        escrow.mint = Some(ctx.accounts.mint.key());
+        escrow.bump = *ctx.bumps.get("escrow").unwrap();
+        config.escrow_count += 1;
+
+        // Lock tokens in the escrow's token account
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.buyer_token_account.to_account_info(),
+                    to: ctx.accounts.escrow_token_account.to_account_info(),
+                    authority: ctx.accounts.buyer.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        // The creation fee is charged in SOL, as for SOL escrows
+        let creation_fee = charge_escrow_creation_fee(
+            &ctx.accounts.config,
+            &ctx.accounts.buyer,
+            &ctx.accounts.treasury,
+            &ctx.accounts.system_program,
+        )?;
+
+        let escrow = &ctx.accounts.escrow;
+        let config = &mut ctx.accounts.config;
+        config.total_escrows += 1;
+
+        emit!(EscrowCreated {
+            escrow_id: escrow.key(),
+            buyer: escrow.buyer,
+            seller: escrow.seller,
+            amount,
+            mint: escrow.mint,
+            creation_fee,
+            timestamp: escrow.created_at,
+        });
+
+        Ok(())
+    }
+
+    /// Release a token escrow to the seller (buyer approval or after auto-release time)
+    pub fn release_token_escrow(ctx: Context<ReleaseTokenEscrow>) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+
+        require!(escrow.status == EscrowStatus::Active, ErrorCode::InvalidEscrowStatus);
+        require!(!escrow.is_disputed, ErrorCode::EscrowDisputed);
+
+        let clock = Clock::get()?;
+        let is_authorized = escrow.buyer == ctx.accounts.authority.key() ||
+            (escrow.auto_release_time.is_some() &&
+             clock.unix_timestamp >= escrow.auto_release_time.unwrap());
+
+        require!(is_authorized, ErrorCode::Unauthorized);
+
+        let amount = escrow.remaining_amount();
+        transfer_from_escrow_vault(
+            escrow,
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.seller_token_account,
+            &ctx.accounts.token_program,
+            amount,
+        )?;
+
+        escrow.released_amount += amount;
+        escrow.status = EscrowStatus::Completed;
+        escrow.completed_at = Some(clock.unix_timestamp);
+
+        emit!(EscrowReleased {
+            escrow_id: escrow.key(),
+            buyer: escrow.buyer,
+            seller: escrow.seller,
+            amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Create dispute for escrow
+    pub fn create_dispute(
+        ctx: Context<CreateDispute>,
+        reason_code: ReasonCode,
+        reason: String,
+    ) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        let dispute = &mut ctx.accounts.dispute;
+        let config = &mut ctx.accounts.config;
+
+        require!(escrow.status == EscrowStatus::Active, ErrorCode::InvalidEscrowStatus);
+        require!(!escrow.is_disputed, ErrorCode::AlreadyDisputed);
+        require!(reason.len() <= 500, ErrorCode::ReasonTooLong);
+        require!(
+            !escrow.milestones.iter().any(|m| m.status == MilestoneStatus::Disputed),
+            ErrorCode::AlreadyDisputed
+        );
+
+        // Only buyer or seller can create dispute
+        require!(
+            escrow.buyer == ctx.accounts.disputer.key() ||
+            escrow.seller == ctx.accounts.disputer.key(),
+            ErrorCode::Unauthorized
+        );
+
+        // Initialize dispute
+        dispute.escrow = escrow.key();
+        dispute.disputer = ctx.accounts.disputer.key();
+        dispute.reason = reason.clone();
+        dispute.reason_code = reason_code;
+        dispute.resolution_code = None;
+        dispute.status = DisputeStatus::Open;
+        dispute.created_at = Clock::get()?.unix_timestamp;
+        dispute.assigned_arbiter = None;
+        dispute.milestone_index = None;
+        dispute.fee_paid = config.arbitration_fee;
+        dispute.appellant = None;
+        dispute.appeal_reason = None;
+        dispute.appeal_deposit = 0;
+        dispute.appeal_min_reputation = 0;
+        dispute.original_arbiter = None;
+        dispute.arbiter_draw_slot = Clock::get()?.slot + ARBITER_DRAW_DELAY_SLOTS;
+        dispute.appeal_upheld = None;
+        dispute.ruling_executed = false;
+
+        charge_arbitration_fee(
+            dispute,
+            &ctx.accounts.disputer,
+            &ctx.accounts.system_program,
+        )?;
+
+        escrow.is_disputed = true;
+        config.total_disputes += 1;
+        config.disputes_by_reason[reason_code.index()] += 1;
+
+        emit!(DisputeCreated {
+            dispute_id: dispute.key(),
+            escrow_id: escrow.key(),
+            buyer: escrow.buyer,
+            seller: escrow.seller,
+            amount: escrow.remaining_amount(),
+            disputer: dispute.disputer,
+            reason_code,
+            reason,
+            arbitration_fee: dispute.fee_paid,
+            timestamp: dispute.created_at,
+        });
+
+        Ok(())
+    }
+
+    /// Add arbiter to DAO
+    pub fn add_arbiter(ctx: Context<AddArbiter>, stake_amount: u64) -> Result<()> {
+        let arbiter = &mut ctx.accounts.arbiter;
+        let config = &ctx.accounts.config;
+
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(stake_amount >= 10_000_000, ErrorCode::InsufficientStake); // 0.01 SOL minimum
+
+        let registry = &mut ctx.accounts.arbiter_registry;
+        require!(registry.arbiters.len() < MAX_ARBITER_CANDIDATES, ErrorCode::ArbiterRegistryFull);
+        registry.arbiters.push(ctx.accounts.arbiter_account.key());
+
+        arbiter.pubkey = ctx.accounts.arbiter_account.key();
+        arbiter.stake = stake_amount;
+        arbiter.reputation = 100; // Starting reputation
+        arbiter.cases_resolved = 0;
+        arbiter.open_cases = 0;
+        arbiter.is_active = true;
+        arbiter.joined_at = Clock::get()?.unix_timestamp;
+
+        // Lock stake
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.arbiter_account.to_account_info(),
+                    to: arbiter.to_account_info(),
+                },
+            ),
+            stake_amount,
+        )?;
+
+        emit!(ArbiterAdded {
+            arbiter: arbiter.pubkey,
+            stake: stake_amount,
+            timestamp: arbiter.joined_at,
+        });
+
+        Ok(())
+    }
+
+    /// Deactivate an arbiter and return their locked stake. The record is kept, zeroed, so
+    /// past rulings can still be settled on appeal.
+    pub fn withdraw_arbiter_stake(ctx: Context<WithdrawArbiterStake>) -> Result<()> {
+        let arbiter = &mut ctx.accounts.arbiter;
+
+        require!(arbiter.is_active, ErrorCode::ArbiterInactive);
+        require!(arbiter.open_cases == 0, ErrorCode::ArbiterHasOpenCases);
+
+        let stake = arbiter.stake;
+        **arbiter.to_account_info().try_borrow_mut_lamports()? -= stake;
+        **ctx.accounts.arbiter_account.to_account_info().try_borrow_mut_lamports()? += stake;
+
+        arbiter.stake = 0;
+        arbiter.is_active = false;
+
+        let pubkey = arbiter.pubkey;
+        ctx.accounts.arbiter_registry.arbiters.retain(|registered| *registered != pubkey);
+
+        emit!(ArbiterExited {
+            arbiter: arbiter.pubkey,
+            stake_returned: stake,
+            cases_resolved: arbiter.cases_resolved,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Resolve dispute by arbiter. An appealed dispute is re-heard by a higher-reputation arbiter
+    /// and that ruling is final.
+    pub fn resolve_dispute(
+        ctx: Context<ResolveDispute>,
+        decision: DisputeDecision,
+        resolution_code: ReasonCode,
+        reasoning: String,
+    ) -> Result<()> {
+        let dispute = &mut ctx.accounts.dispute;
+        let escrow = &mut ctx.accounts.escrow;
+        let arbiter = &mut ctx.accounts.arbiter;
+
+        require!(
+            dispute.status == DisputeStatus::Open || dispute.status == DisputeStatus::Appealed,
+            ErrorCode::InvalidDisputeStatus
+        );
+        require!(arbiter.is_active, ErrorCode::ArbiterInactive);
+        require!(reasoning.len() <= 1000, ErrorCode::ReasoningTooLong);
+
+        // Only the arbiter picked by assign_arbiter can rule; for an appeal that is a new,
+        // higher-reputation arbiter
+        require!(
+            dispute.assigned_arbiter.ok_or(ErrorCode::ArbiterNotAssigned)? == arbiter.pubkey,
+            ErrorCode::UnauthorizedArbiter
+        );
+
+        let is_appeal = dispute.status == DisputeStatus::Appealed;
+        // Captured before the ruling moves the funds out
+        let amount = escrow.remaining_amount();
+
+        let original_decision = dispute.decision;
+        dispute.status = DisputeStatus::Resolved;
+        dispute.decision = Some(decision);
+        dispute.resolution_code = Some(resolution_code);
+        dispute.reasoning = Some(reasoning.clone());
+        dispute.resolved_at = Some(Clock::get()?.unix_timestamp);
+
+        // With an appeal window configured, the first ruling holds the funds until the window
+        // closes (see finalize_dispute); an appellate ruling is executed immediately
+        if is_appeal || ctx.accounts.config.appeal_window == 0 {
+            require!(!dispute.ruling_executed, ErrorCode::RulingAlreadyExecuted);
+            let tokens = escrow_token_accounts(
+                escrow,
+                &ctx.accounts.escrow_token_account,
+                &ctx.accounts.buyer_token_account,
+                &ctx.accounts.seller_token_account,
+                &ctx.accounts.token_program,
+            )?;
+            execute_ruling(escrow, &ctx.accounts.buyer, &ctx.accounts.seller, tokens, decision)?;
+            dispute.ruling_executed = true;
+        }
+
+        if is_appeal {
+            // Forfeit the appeal deposit if the original decision stands, return it otherwise
+            let upheld = original_decision == Some(decision);
+            let deposit = dispute.appeal_deposit;
+            let recipient = if upheld {
+                ctx.accounts.treasury.to_account_info()
+            } else if dispute.appellant == Some(escrow.buyer) {
+                ctx.accounts.buyer.to_account_info()
+            } else {
+                ctx.accounts.seller.to_account_info()
+            };
+            if deposit > 0 {
+                **dispute.to_account_info().try_borrow_mut_lamports()? -= deposit;
+                **recipient.try_borrow_mut_lamports()? += deposit;
+            }
+            dispute.appeal_upheld = Some(upheld);
+            dispute.appeal_deposit = 0;
+
+            // The appeal settles the original arbiter's case; an overturned ruling also costs
+            // them part of their stake
+            let original_arbiter = ctx.accounts.original_arbiter.as_mut()
+                .ok_or(ErrorCode::OriginalArbiterRequired)?;
+            require!(
+                dispute.original_arbiter == Some(original_arbiter.pubkey),
+                ErrorCode::OriginalArbiterRequired
+            );
+            original_arbiter.open_cases = original_arbiter.open_cases.saturating_sub(1);
+            if !upheld {
+                slash_arbiter(
+                    original_arbiter,
+                    &ctx.accounts.treasury,
+                    dispute.key(),
+                    ctx.accounts.config.slash_bps,
+                )?;
+            }
+
+            emit!(AppealResolved {
+                dispute_id: dispute.key(),
+                escrow_id: escrow.key(),
+                buyer: escrow.buyer,
+                seller: escrow.seller,
+                amount,
+                arbiter: arbiter.pubkey,
+                upheld,
+                deposit,
+                deposit_recipient: recipient.key(),
+                timestamp: dispute.resolved_at.unwrap(),
+            });
+        } else {
+            settle_arbitration_fee(
+                dispute,
+                escrow,
+                decision,
+                &ctx.accounts.buyer,
+                &ctx.accounts.seller,
+                &ctx.accounts.treasury,
+                ctx.accounts.config.dispute_fee_rebate_bps,
+            )?;
+        }
+
+        // Update arbiter stats. An appealable ruling stays an open case until it is finalized or
+        // the appeal is heard, so the arbiter can't withdraw ahead of a slash.
+        arbiter.cases_resolved += 1;
+        if dispute.ruling_executed {
+            arbiter.open_cases = arbiter.open_cases.saturating_sub(1);
+        }
+        arbiter.reputation += 10; // Increase reputation for resolving case
+
+        emit!(DisputeResolved {
+            dispute_id: dispute.key(),
+            escrow_id: escrow.key(),
+            buyer: escrow.buyer,
+            seller: escrow.seller,
+            amount,
+            arbiter: arbiter.pubkey,
+            decision,
+            resolution_code,
+            timestamp: dispute.resolved_at.unwrap(),
+        });
+
+        Ok(())
+    }
+
+    /// Appeal a resolved dispute within the appeal window. Either party can appeal by posting the
+    /// configured deposit, held by the dispute until the appellate ruling.
+    pub fn appeal_dispute(ctx: Context<AppealDispute>, reason: String) -> Result<()> {
+        let dispute = &mut ctx.accounts.dispute;
+        let escrow = &ctx.accounts.escrow;
+        let appellant = &ctx.accounts.appellant;
+        let config = &ctx.accounts.config;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        require!(dispute.status == DisputeStatus::Resolved, ErrorCode::InvalidDisputeStatus);
+        require!(dispute.milestone_index.is_none(), ErrorCode::InvalidDisputeStatus);
+        require!(dispute.appellant.is_none(), ErrorCode::AlreadyAppealed);
+        require!(reason.len() <= 500, ErrorCode::ReasonTooLong);
+        require!(
+            escrow.buyer == appellant.key() || escrow.seller == appellant.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            !dispute.ruling_executed &&
+            current_time <= dispute.resolved_at.unwrap_or(0) + config.appeal_window,
+            ErrorCode::AppealWindowClosed
+        );
+
+        let deposit = config.appeal_deposit;
+        require!(appellant.lamports() >= deposit, ErrorCode::InsufficientAppealDeposit);
+        if deposit > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: appellant.to_account_info(),
+                        to: dispute.to_account_info(),
+                    },
+                ),
+                deposit,
+            )?;
+        }
+
+        dispute.status = DisputeStatus::Appealed;
+        dispute.appellant = Some(appellant.key());
+        dispute.appeal_reason = Some(reason.clone());
+        dispute.appeal_deposit = deposit;
+        // The appellate arbiter must out-rank the one who made the original decision and is
+        // picked afresh by assign_arbiter
+        dispute.appeal_min_reputation = ctx.accounts.original_arbiter.reputation;
+        dispute.original_arbiter = dispute.assigned_arbiter;
+        dispute.assigned_arbiter = None;
+        dispute.arbiter_draw_slot = Clock::get()?.slot + ARBITER_DRAW_DELAY_SLOTS;
+
+        emit!(DisputeAppealed {
+            dispute_id: dispute.key(),
+            escrow_id: escrow.key(),
+            buyer: escrow.buyer,
+            seller: escrow.seller,
+            amount: escrow.remaining_amount(),
+            appellant: appellant.key(),
+            reason,
+            deposit,
+            timestamp: current_time,
+        });
+
+        Ok(())
+    }
+
+    /// Assign an arbiter to an open or appealed dispute (anyone can call). Candidates are the
+    /// `Arbiter` accounts passed as remaining accounts; one is picked at random, weighted by
+    /// stake and reputation, seeded from the hash of the first block at or after the dispute's
+    /// draw slot, which was still in the future when the dispute was opened or appealed.
+    pub fn assign_arbiter(ctx: Context<AssignArbiter>) -> Result<()> {
+        let dispute = &mut ctx.accounts.dispute;
+        let escrow = &ctx.accounts.escrow;
+
+        require!(
+            dispute.status == DisputeStatus::Open || dispute.status == DisputeStatus::Appealed,
+            ErrorCode::InvalidDisputeStatus
+        );
+        require!(dispute.assigned_arbiter.is_none(), ErrorCode::ArbiterAlreadyAssigned);
+        // Every registered arbiter must be passed, in registry order, so the caller can't
+        // narrow the draw to a hand-picked arbiter
+        let registry = &ctx.accounts.arbiter_registry;
+        require!(
+            !registry.arbiters.is_empty() &&
+            ctx.remaining_accounts.len() == registry.arbiters.len(),
+            ErrorCode::InvalidArbiterCandidates
+        );
+        let is_appeal = dispute.status == DisputeStatus::Appealed;
+
+        let mut candidates: Vec<(Pubkey, u128, usize)> = Vec::new();
+        let mut total_weight: u128 = 0;
+        for (index, info) in ctx.remaining_accounts.iter().enumerate() {
+            require!(info.owner == ctx.program_id, ErrorCode::InvalidArbiterCandidates);
+            let arbiter = Arbiter::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+            require!(
+                arbiter.pubkey == registry.arbiters[index],
+                ErrorCode::InvalidArbiterCandidates
+            );
+
+            // Skip arbiters who can't hear this case
+            if !arbiter.is_active
+                || arbiter.pubkey == escrow.buyer
+                || arbiter.pubkey == escrow.seller
+                || (is_appeal && (
+                    Some(arbiter.pubkey) == dispute.original_arbiter
+                    || arbiter.reputation <= dispute.appeal_min_reputation
+                ))
+            {
+                continue;
+            }
+
+            let weight = arbiter.stake as u128 * (arbiter.reputation as u128 + 1);
+            if weight == 0 {
+                continue;
+            }
+            total_weight += weight;
+            candidates.push((arbiter.pubkey, weight, index));
+        }
+        require!(total_weight > 0, ErrorCode::NoEligibleArbiter);
+
+        // SlotHashes sysvar: [len: u64] then [slot: u64][hash: 32 bytes] entries, newest first.
+        // The seed is the hash of the oldest entry at or after the draw slot; if the entry
+        // before it has already rolled out of the sysvar, that block can't be confirmed as the
+        // first one and the draw is moved to a new future slot
+        let clock = Clock::get()?;
+        require!(clock.slot > dispute.arbiter_draw_slot, ErrorCode::ArbiterDrawPending);
+        let slot_hashes = ctx.accounts.slot_hashes.try_borrow_data()?;
+        require!(slot_hashes.len() >= 8, ErrorCode::InvalidArbiterCandidates);
+        let entries = (u64::from_le_bytes(slot_hashes[..8].try_into().unwrap()) as usize)
+            .min((slot_hashes.len() - 8) / 40);
+        let mut draw_hash: Option<&[u8]> = None;
+        for entry in 0..entries {
+            let offset = 8 + entry * 40;
+            let slot = u64::from_le_bytes(slot_hashes[offset..offset + 8].try_into().unwrap());
+            if slot < dispute.arbiter_draw_slot {
+                break;
+            }
+            draw_hash = if entry + 1 < entries || slot == dispute.arbiter_draw_slot {
+                Some(&slot_hashes[offset + 8..offset + 40])
+            } else {
+                None
+            };
+        }
+        let draw_hash = match draw_hash {
+            Some(hash) => hash,
+            None => {
+                let expired_slot = dispute.arbiter_draw_slot;
+                dispute.arbiter_draw_slot = clock.slot + ARBITER_DRAW_DELAY_SLOTS;
+                emit!(ArbiterDrawRescheduled {
+                    dispute_id: dispute.key(),
+                    expired_slot,
+                    draw_slot: dispute.arbiter_draw_slot,
+                    timestamp: clock.unix_timestamp,
+                });
+                return Ok(());
+            }
+        };
+        let seed = anchor_lang::solana_program::hash::hashv(&[
+            draw_hash,
+            dispute.key().as_ref(),
+        ]);
+        let mut pick_bytes = [0u8; 16];
+        pick_bytes.copy_from_slice(&seed.to_bytes()[..16]);
+        let mut pick = u128::from_le_bytes(pick_bytes) % total_weight;
+
+        let (mut selected, _, mut selected_index) = candidates[candidates.len() - 1];
+        for (pubkey, weight, index) in candidates.iter() {
+            if pick < *weight {
+                selected = *pubkey;
+                selected_index = *index;
+                break;
+            }
+            pick -= *weight;
+        }
+
+        dispute.assigned_arbiter = Some(selected);
+
+        // Count the case against the arbiter so they can't exit while it is open
+        let selected_info = &ctx.remaining_accounts[selected_index];
+        require!(selected_info.is_writable, ErrorCode::InvalidArbiterCandidates);
+        let mut selected_arbiter = Arbiter::try_deserialize(&mut &selected_info.try_borrow_data()?[..])?;
+        selected_arbiter.open_cases += 1;
+        selected_arbiter.try_serialize(&mut &mut selected_info.try_borrow_mut_data()?[..])?;
+
+        emit!(ArbiterAssigned {
+            dispute_id: dispute.key(),
+            escrow_id: escrow.key(),
+            buyer: escrow.buyer,
+            seller: escrow.seller,
+            amount: escrow.disputed_amount(dispute.milestone_index),
+            arbiter: selected,
+            candidates: candidates.len() as u8,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Execute a ruling once its appeal window has closed without an appeal (anyone can call)
+    pub fn finalize_dispute(ctx: Context<FinalizeDispute>) -> Result<()> {
+        let dispute = &mut ctx.accounts.dispute;
+        let escrow = &mut ctx.accounts.escrow;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        require!(dispute.status == DisputeStatus::Resolved, ErrorCode::InvalidDisputeStatus);
+        require!(!dispute.ruling_executed, ErrorCode::RulingAlreadyExecuted);
+        require!(
+            current_time > dispute.resolved_at.unwrap_or(0) + ctx.accounts.config.appeal_window,
+            ErrorCode::AppealWindowOpen
+        );
+
+        let decision = dispute.decision.ok_or(ErrorCode::InvalidDisputeStatus)?;
+        let amount = escrow.remaining_amount();
+        let tokens = escrow_token_accounts(
+            escrow,
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.buyer_token_account,
+            &ctx.accounts.seller_token_account,
+            &ctx.accounts.token_program,
+        )?;
+        execute_ruling(escrow, &ctx.accounts.buyer, &ctx.accounts.seller, tokens, decision)?;
+        dispute.ruling_executed = true;
+
+        // The ruling can no longer be appealed, so it stops counting against the arbiter
+        let arbiter = &mut ctx.accounts.arbiter;
+        arbiter.open_cases = arbiter.open_cases.saturating_sub(1);
+
+        emit!(DisputeFinalized {
+            dispute_id: dispute.key(),
+            escrow_id: escrow.key(),
+            buyer: escrow.buyer,
+            seller: escrow.seller,
+            amount,
+            decision,
+            timestamp: current_time,
+        });
+
+        Ok(())
+    }
+
+    /// Set how long (in seconds) after a ruling either party may appeal; 0 disables appeals
+    pub fn set_appeal_window(ctx: Context<UpdateEscrowConfig>, appeal_window: i64) -> Result<()> {
+        require!(appeal_window >= 0, ErrorCode::InvalidAppealWindow);
+
+        ctx.accounts.config.appeal_window = appeal_window;
+
+        Ok(())
+    }
+
+    /// Set the share of stake (in basis points) an arbiter loses when their ruling is overturned
+    pub fn set_slash_rate(ctx: Context<UpdateEscrowConfig>, slash_bps: u16) -> Result<()> {
+        require!(slash_bps <= 10000, ErrorCode::InvalidSlashRate);
+
+        ctx.accounts.config.slash_bps = slash_bps;
+
+        Ok(())
+    }
+
+    /// Set the deposit (in lamports) a party must post to appeal a decision
+    pub fn set_appeal_deposit(ctx: Context<UpdateEscrowConfig>, deposit: u64) -> Result<()> {
+        ctx.accounts.config.appeal_deposit = deposit;
+
+        Ok(())
+    }
+
+    /// Set the share of the arbitration fee rebated to a winning party who didn't file (authority only)
+    pub fn set_dispute_fee_rebate(ctx: Context<UpdateEscrowConfig>, rebate_bps: u16) -> Result<()> {
+        require!(rebate_bps <= 10000, ErrorCode::InvalidRebateRate);
+
+        ctx.accounts.config.dispute_fee_rebate_bps = rebate_bps;
+
+        Ok(())
+    }
+
+    /// Set the fee (in lamports) charged to the buyer when an escrow is created
+    pub fn set_escrow_creation_fee(ctx: Context<UpdateEscrowConfig>, fee: u64) -> Result<()> {
+        ctx.accounts.config.escrow_creation_fee = fee;
+
+        Ok(())
+    }
+
+    /// Set the largest amount (in lamports) a SOL escrow may lock; 0 removes the cap
+    pub fn set_max_escrow_amount(ctx: Context<UpdateEscrowConfig>, max_amount: u64) -> Result<()> {
+        ctx.accounts.config.max_escrow_amount = max_amount;
+
+        Ok(())
+    }
+
+    /// Require a KYC SBT from `kyc_mint` for SOL escrows above `threshold` lamports; 0 disables
+    pub fn set_kyc_requirement(
+        ctx: Context<UpdateEscrowConfig>,
+        kyc_mint: Pubkey,
+        threshold: u64,
+    ) -> Result<()> {
+        require!(
+            threshold == 0 || kyc_mint != Pubkey::default(),
+            ErrorCode::InvalidKycMint
+        );
+
+        ctx.accounts.config.kyc_mint = kyc_mint;
+        ctx.accounts.config.kyc_threshold = threshold;
+
+        Ok(())
+    }
+
+    /// Set the cap and KYC threshold (in base units) for token escrows of one mint; 0 disables
+    /// either. The KYC SBT mint is the one set by `set_kyc_requirement`.
+    pub fn set_mint_escrow_limits(
+        ctx: Context<SetMintEscrowLimits>,
+        max_amount: u64,
+        kyc_threshold: u64,
+    ) -> Result<()> {
+        require!(
+            kyc_threshold == 0 || ctx.accounts.config.kyc_mint != Pubkey::default(),
+            ErrorCode::InvalidKycMint
+        );
+
+        let mint_limit = &mut ctx.accounts.mint_limit;
+        mint_limit.mint = ctx.accounts.mint.key();
+        mint_limit.max_amount = max_amount;
+        mint_limit.kyc_threshold = kyc_threshold;
+        mint_limit.bump = *ctx.bumps.get("mint_limit").unwrap();
+
+        Ok(())
+    }
+}
+
+// Enforce an escrow cap, and require the buyer's KYC SBT above the threshold; 0 disables either.
+// SOL escrows use the config's limits, token escrows the limits stored for their mint
+fn check_escrow_limits(
+    config: &EscrowConfig,
+    mint_limit: Option<&UncheckedAccount>,
+    amount: u64,
+    buyer: &Pubkey,
+    kyc_credential: &Option<Account<TokenAccount>>,
+) -> Result<()> {
+    // Token amounts aren't comparable to the lamport limits, so each mint has its own
+    let (max_amount, kyc_threshold) = match mint_limit {
+        None => (config.max_escrow_amount, config.kyc_threshold),
+        Some(mint_limit) if mint_limit.owner == &crate::ID => {
+            let limit = MintEscrowLimit::try_deserialize(&mut &mint_limit.try_borrow_data()?[..])?;
+            (limit.max_amount, limit.kyc_threshold)
+        }
+        Some(_) => (0, 0),
+    };
+    require!(
+        max_amount == 0 || amount <= max_amount,
+        ErrorCode::EscrowAmountTooLarge
+    );
+
+    if kyc_threshold > 0 && amount > kyc_threshold {
+        let credential = kyc_credential.as_ref().ok_or(ErrorCode::KycRequired)?;
+        require!(
+            credential.mint == config.kyc_mint &&
+            credential.owner == *buyer &&
+            credential.amount > 0,
+            ErrorCode::InvalidKycCredential
+        );
+    }
+
+    Ok(())
+}
+
+// Buyer pays the configured creation fee to the treasury, separate from the escrowed amount
+fn charge_escrow_creation_fee<'info>(
+    config: &EscrowConfig,
+    buyer: &Signer<'info>,
+    treasury: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<u64> {
+    let creation_fee = config.escrow_creation_fee;
+    if creation_fee > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: buyer.to_account_info(),
+                    to: treasury.clone(),
+                },
+            ),
+            creation_fee,
+        )?;
+    }
+
+    Ok(creation_fee)
+}
+
+/// Token accounts needed to settle a token escrow
+struct EscrowTokenAccounts<'a, 'info> {
+    vault: &'a Account<'info, TokenAccount>,
+    buyer: &'a Account<'info, TokenAccount>,
+    seller: &'a Account<'info, TokenAccount>,
+    token_program: &'a Program<'info, Token>,
+}
+
+// Check the optional token accounts against a token escrow; SOL escrows need none
+fn escrow_token_accounts<'a, 'info>(
+    escrow: &Account<'info, Escrow>,
+    vault: &'a Option<Account<'info, TokenAccount>>,
+    buyer: &'a Option<Account<'info, TokenAccount>>,
+    seller: &'a Option<Account<'info, TokenAccount>>,
+    token_program: &'a Option<Program<'info, Token>>,
+) -> Result<Option<EscrowTokenAccounts<'a, 'info>>> {
+    let mint = match escrow.mint {
+        Some(mint) => mint,
+        None => return Ok(None),
+    };
+
+    let (vault, buyer, seller, token_program) = match (vault, buyer, seller, token_program) {
+        (Some(vault), Some(buyer), Some(seller), Some(token_program)) => (vault, buyer, seller, token_program),
+        _ => return err!(ErrorCode::TokenAccountsRequired),
+    };
+    require!(
+        vault.mint == mint && vault.owner == escrow.key(),
+        ErrorCode::InvalidTokenAccount
+    );
+    require!(
+        buyer.mint == mint && buyer.owner == escrow.buyer,
+        ErrorCode::InvalidTokenAccount
+    );
+    require!(
+        seller.mint == mint && seller.owner == escrow.seller,
+        ErrorCode::InvalidTokenAccount
+    );
+
+    Ok(Some(EscrowTokenAccounts { vault, buyer, seller, token_program }))
+}
+
+// Move tokens out of the escrow's token account, signed by the escrow PDA
+fn transfer_from_escrow_vault<'info>(
+    escrow: &Account<'info, Escrow>,
+    vault: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    amount: u64,
+) -> Result<()> {
+    let escrow_id_bytes = escrow.escrow_id.to_le_bytes();
+    let seeds = &[
+        b"escrow".as_ref(),
+        escrow.buyer.as_ref(),
+        escrow_id_bytes.as_ref(),
+        &[escrow.bump],
+    ];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                from: vault.to_account_info(),
+                to: to.to_account_info(),
+                authority: escrow.to_account_info(),
+            },
+            &[&seeds[..]],
+        ),
+        amount,
+    )
+}
+
+// Filer pays the arbitration fee, held by the dispute until it is settled on resolution
+fn charge_arbitration_fee<'info>(
+    dispute: &Account<'info, Dispute>,
+    disputer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    require!(
+        disputer.lamports() >= dispute.fee_paid,
+        ErrorCode::InsufficientArbitrationFee
+    );
+    if dispute.fee_paid > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: disputer.to_account_info(),
+                    to: dispute.to_account_info(),
+                },
+            ),
+            dispute.fee_paid,
+        )?;
+    }
+
+    Ok(())
+}
+
+// Settle the arbitration fee: a winner who didn't file gets a rebate, the rest goes to the
+// treasury so nothing is left behind in the dispute account
+fn settle_arbitration_fee<'info>(
+    dispute: &Account<'info, Dispute>,
+    escrow: &Account<'info, Escrow>,
+    decision: DisputeDecision,
+    buyer: &AccountInfo<'info>,
+    seller: &AccountInfo<'info>,
+    treasury: &AccountInfo<'info>,
+    rebate_bps: u16,
+) -> Result<()> {
+    if dispute.fee_paid == 0 {
+        return Ok(());
+    }
+
+    let winner = match decision {
+        DisputeDecision::FavorBuyer => buyer,
+        DisputeDecision::FavorSeller => seller,
+    };
+    let rebate = if winner.key() != dispute.disputer {
+        dispute.fee_paid * rebate_bps as u64 / 10000
+    } else {
+        0
+    };
+    let treasury_share = dispute.fee_paid - rebate;
+
+    **dispute.to_account_info().try_borrow_mut_lamports()? -= dispute.fee_paid;
+    **winner.try_borrow_mut_lamports()? += rebate;
+    **treasury.try_borrow_mut_lamports()? += treasury_share;
+
+    emit!(ArbitrationFeePaid {
+        dispute_id: dispute.key(),
+        buyer: escrow.buyer,
+        seller: escrow.seller,
+        treasury: treasury.key(),
+        amount: treasury_share,
+        timestamp: dispute.resolved_at.unwrap(),
+    });
+
+    if rebate > 0 {
+        emit!(DisputeFeeRebated {
+            dispute_id: dispute.key(),
+            buyer: escrow.buyer,
+            seller: escrow.seller,
+            recipient: winner.key(),
+            amount: rebate,
+            timestamp: dispute.resolved_at.unwrap(),
+        });
+    }
+
+    Ok(())
+}
+
+// Move `bps` of the arbiter's stake to the treasury and dock their reputation. Only reachable
+// from resolve_dispute when an appeal overturns the arbiter's ruling.
+fn slash_arbiter<'info>(
+    arbiter: &mut Account<'info, Arbiter>,
+    treasury: &AccountInfo<'info>,
+    dispute_id: Pubkey,
+    bps: u16,
+) -> Result<()> {
+    let amount = (arbiter.stake as u128 * bps as u128 / 10000) as u64;
+    if amount > 0 {
+        **arbiter.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **treasury.try_borrow_mut_lamports()? += amount;
+        arbiter.stake -= amount;
+    }
+    arbiter.reputation = arbiter.reputation.saturating_sub(SLASH_REPUTATION_PENALTY);
+
+    emit!(ArbiterSlashed {
+        arbiter: arbiter.pubkey,
+        dispute_id,
+        amount,
+        remaining_stake: arbiter.stake,
+        new_reputation: arbiter.reputation,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// Pay out whatever the escrow still holds according to the ruling, in lamports or tokens
+fn execute_ruling<'info>(
+    escrow: &mut Account<'info, Escrow>,
+    buyer: &AccountInfo<'info>,
+    seller: &AccountInfo<'info>,
+    tokens: Option<EscrowTokenAccounts<'_, 'info>>,
+    decision: DisputeDecision,
+) -> Result<()> {
+    let remaining = escrow.remaining_amount();
+    match decision {
+        DisputeDecision::FavorBuyer => {
+            // Refund to buyer
+            match tokens {
+                Some(t) => transfer_from_escrow_vault(escrow, t.vault, t.buyer, t.token_program, remaining)?,
+                None => {
+                    **escrow.to_account_info().try_borrow_mut_lamports()? -= remaining;
+                    **buyer.try_borrow_mut_lamports()? += remaining;
+                }
+            }
+            escrow.refunded_amount += remaining;
+            escrow.status = EscrowStatus::Refunded;
+        }
+        DisputeDecision::FavorSeller => {
+            // Release to seller
+            match tokens {
+                Some(t) => transfer_from_escrow_vault(escrow, t.vault, t.seller, t.token_program, remaining)?,
+                None => {
+                    **escrow.to_account_info().try_borrow_mut_lamports()? -= remaining;
+                    **seller.try_borrow_mut_lamports()? += remaining;
+                }
+            }
+            escrow.released_amount += remaining;
+            escrow.status = EscrowStatus::Completed;
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + EscrowConfig::INIT_SPACE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, EscrowConfig>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ArbiterRegistry::INIT_SPACE,
+        seeds = [b"arbiter_registry"],
+        bump
+    )]
+    pub arbiter_registry: Account<'info, ArbiterRegistry>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Treasury account
+    pub treasury: AccountInfo<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateEscrow<'info> {
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + Escrow::INIT_SPACE,
+        seeds = [b"escrow", buyer.key().as_ref(), config.escrow_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, EscrowConfig>,
+    
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    
+    /// CHECK: Seller account
+    pub seller: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury @ ErrorCode::Unauthorized
+    )]
+    /// CHECK: Treasury receiving the escrow creation fee
+    pub treasury: AccountInfo<'info>,
+
+    /// Buyer's KYC SBT account; required above the configured KYC threshold
+    pub kyc_credential: Option<Account<'info, TokenAccount>>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", buyer.key().as_ref(), escrow.escrow_id.to_le_bytes().as_ref()],
+        bump,
+        has_one = buyer @ ErrorCode::Unauthorized
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, EscrowConfig>,
+    
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// Buyer's KYC SBT account; required above the configured KYC threshold
+    pub kyc_credential: Option<Account<'info, TokenAccount>>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelDraft<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.buyer.as_ref(), escrow.escrow_id.to_le_bytes().as_ref()],
+        bump,
+        close = buyer
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        constraint = buyer.key() == escrow.buyer @ ErrorCode::Unauthorized
+    )]
+    /// CHECK: Buyer account receiving the draft's rent
+    pub buyer: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.buyer.as_ref(), escrow.escrow_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    pub authority: Signer<'info>,
+    
+    #[account(mut)]
+    /// CHECK: Seller account
+    pub seller: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateTokenEscrow<'info> {
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + Escrow::INIT_SPACE,
+        seeds = [b"escrow", buyer.key().as_ref(), config.escrow_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        init,
+        payer = buyer,
+        associated_token::mint = mint,
+        associated_token::authority = escrow
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, EscrowConfig>,
+    
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = buyer
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Seller account
+    pub seller: AccountInfo<'info>,
+    
+    pub mint: Account<'info, Mint>,
+    
+    #[account(
+        seeds = [b"mint_limit", mint.key().as_ref()],
+        bump
+    )]
+    /// CHECK: Per-mint escrow limits; uncapped while the authority hasn't set any
+    pub mint_limit: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury @ ErrorCode::Unauthorized
+    )]
+    /// CHECK: Treasury receiving the escrow creation fee
+    pub treasury: AccountInfo<'info>,
+    
+    /// Buyer's KYC SBT account; required above the mint's KYC threshold
+    pub kyc_credential: Option<Account<'info, TokenAccount>>,
+    
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseTokenEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.buyer.as_ref(), escrow.escrow_id.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.mint.is_some() @ ErrorCode::InvalidEscrowStatus
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = escrow
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = escrow.seller
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+    
+    #[account(constraint = escrow.mint == Some(mint.key()) @ ErrorCode::InvalidTokenAccount)]
+    pub mint: Account<'info, Mint>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseMilestone<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.buyer.as_ref(), escrow.escrow_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        constraint = seller.key() == escrow.seller @ ErrorCode::Unauthorized
+    )]
+    /// CHECK: Seller account
+    pub seller: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(milestone_index: u8)]
+pub struct DisputeMilestone<'info> {
+    #[account(
+        init,
+        payer = disputer,
+        space = 8 + Dispute::INIT_SPACE,
+        seeds = [b"milestone_dispute", escrow.key().as_ref(), &[milestone_index]],
+        bump
+    )]
+    pub dispute: Account<'info, Dispute>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.buyer.as_ref(), escrow.escrow_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, EscrowConfig>,
+    
+    #[account(mut)]
+    pub disputer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveMilestoneDispute<'info> {
+    #[account(
+        mut,
+        seeds = [b"milestone_dispute", dispute.escrow.as_ref(), &[dispute.milestone_index.unwrap_or_default()]],
+        bump
+    )]
+    pub dispute: Account<'info, Dispute>,
+    
+    #[account(
+        mut,
+        constraint = escrow.key() == dispute.escrow @ ErrorCode::InvalidDisputeStatus,
+        seeds = [b"escrow", escrow.buyer.as_ref(), escrow.escrow_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"arbiter", arbiter.pubkey.as_ref()],
+        bump
+    )]
+    pub arbiter: Account<'info, Arbiter>,
+    
+    #[account(
+        mut,
+        constraint = buyer.key() == escrow.buyer @ ErrorCode::Unauthorized
+    )]
+    /// CHECK: Buyer account
+    pub buyer: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        constraint = seller.key() == escrow.seller @ ErrorCode::Unauthorized
+    )]
+    /// CHECK: Seller account
+    pub seller: AccountInfo<'info>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, EscrowConfig>,
+    
+    #[account(
+        constraint = arbiter_account.key() == arbiter.pubkey @ ErrorCode::UnauthorizedArbiter
+    )]
+    pub arbiter_account: Signer<'info>,
+    
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury @ ErrorCode::Unauthorized
+    )]
+    /// CHECK: Treasury receiving the arbitration fee
+    pub treasury: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateDispute<'info> {
+    #[account(
+        init,
+        payer = disputer,
+        space = 8 + Dispute::INIT_SPACE,
+        seeds = [b"dispute", escrow.key().as_ref()],
+        bump
+    )]
+    pub dispute: Account<'info, Dispute>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.buyer.as_ref(), escrow.escrow_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, EscrowConfig>,
+    
+    #[account(mut)]
+    pub disputer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AddArbiter<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Arbiter::INIT_SPACE,
+        seeds = [b"arbiter", arbiter_account.key().as_ref()],
+        bump
+    )]
+    pub arbiter: Account<'info, Arbiter>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, EscrowConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"arbiter_registry"],
+        bump = arbiter_registry.bump
+    )]
+    pub arbiter_registry: Account<'info, ArbiterRegistry>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// Arbiter wallet; signs to lock its stake
+    #[account(mut)]
+    pub arbiter_account: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawArbiterStake<'info> {
+    #[account(
+        mut,
+        seeds = [b"arbiter", arbiter_account.key().as_ref()],
+        bump
+    )]
+    pub arbiter: Account<'info, Arbiter>,
+    
+    #[account(
+        mut,
+        seeds = [b"arbiter_registry"],
+        bump = arbiter_registry.bump
+    )]
+    pub arbiter_registry: Account<'info, ArbiterRegistry>,
+    
+    #[account(mut)]
+    pub arbiter_account: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    #[account(
+        mut,
+        seeds = [b"dispute", dispute.escrow.as_ref()],
+        bump
+    )]
+    pub dispute: Account<'info, Dispute>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.buyer.as_ref(), escrow.escrow_id.to_le_bytes().as_ref()],
+        bump,
+        constraint = escrow.key() == dispute.escrow @ ErrorCode::InvalidDisputeStatus
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"arbiter", arbiter.pubkey.as_ref()],
+        bump
+    )]
+    pub arbiter: Account<'info, Arbiter>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, EscrowConfig>,
+    
+    #[account(
+        mut,
+        constraint = buyer.key() == escrow.buyer @ ErrorCode::Unauthorized
+    )]
+    /// CHECK: Buyer account
+    pub buyer: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        constraint = seller.key() == escrow.seller @ ErrorCode::Unauthorized
+    )]
+    /// CHECK: Seller account
+    pub seller: AccountInfo<'info>,
+    
+    #[account(
+        constraint = arbiter_account.key() == arbiter.pubkey @ ErrorCode::UnauthorizedArbiter
+    )]
+    /// Assigned arbiter's wallet; signs the ruling
+    pub arbiter_account: Signer<'info>,
+    
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury @ ErrorCode::Unauthorized
+    )]
+    /// CHECK: Treasury receiving the arbitration fee, a forfeited appeal deposit or slashed stake
+    pub treasury: AccountInfo<'info>,
+    
+    /// Arbiter of the appealed ruling; required to settle an appeal
     #[account(
-        init,
-        payer = authority,
-        space = 8 + EscrowConfig::INIT_SPACE,
-        seeds = [b"config"],
+        mut,
+        seeds = [b"arbiter", original_arbiter.pubkey.as_ref()],
         bump
     )]
-    pub config: Account<'info, EscrowConfig>,
+    pub original_arbiter: Option<Account<'info, Arbiter>>,    
+    /// Token escrows only: the escrow's token account and the parties' token accounts
+    #[account(mut)]
+    pub escrow_token_account: Option<Account<'info, TokenAccount>>,
     
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub buyer_token_account: Option<Account<'info, TokenAccount>>,
     
-    /// CHECK: Treasury account
-    pub treasury: AccountInfo<'info>,
+    #[account(mut)]
+    pub seller_token_account: Option<Account<'info, TokenAccount>>,
     
-    pub system_program: Program<'info, System>,
+    pub token_program: Option<Program<'info, Token>>,
 }
 
 #[derive(Accounts)]
-pub struct CreateEscrow<'info> {
+pub struct AppealDispute<'info> {
     #[account(
-        init,
-        payer = buyer,
-        space = 8 + Escrow::INIT_SPACE,
-        seeds = [b"escrow", buyer.key().as_ref()],
+        mut,
+        seeds = [b"dispute", dispute.escrow.as_ref()],
         bump
     )]
+    pub dispute: Account<'info, Dispute>,
+    
+    #[account(
+        seeds = [b"escrow", escrow.buyer.as_ref(), escrow.escrow_id.to_le_bytes().as_ref()],
+        bump,
+        constraint = escrow.key() == dispute.escrow @ ErrorCode::InvalidDisputeStatus
+    )]
     pub escrow: Account<'info, Escrow>,
     
     #[account(
@@ -268,119 +2061,137 @@ pub struct CreateEscrow<'info> {
     )]
     pub config: Account<'info, EscrowConfig>,
     
-    #[account(mut)]
-    pub buyer: Signer<'info>,
+    #[account(
+        seeds = [b"arbiter", original_arbiter.pubkey.as_ref()],
+        bump,
+        constraint = dispute.assigned_arbiter == Some(original_arbiter.pubkey) @ ErrorCode::UnauthorizedArbiter
+    )]
+    pub original_arbiter: Account<'info, Arbiter>,
     
-    /// CHECK: Seller account
-    pub seller: AccountInfo<'info>,
+    #[account(mut)]
+    pub appellant: Signer<'info>,
     
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct ReleaseEscrow<'info> {
+pub struct AssignArbiter<'info> {
+    #[account(mut)]
+    pub dispute: Account<'info, Dispute>,
+    
     #[account(
-        mut,
-        seeds = [b"escrow", escrow.buyer.as_ref()],
-        bump
+        seeds = [b"escrow", escrow.buyer.as_ref(), escrow.escrow_id.to_le_bytes().as_ref()],
+        bump,
+        constraint = escrow.key() == dispute.escrow @ ErrorCode::InvalidDisputeStatus
     )]
     pub escrow: Account<'info, Escrow>,
     
-    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"arbiter_registry"],
+        bump = arbiter_registry.bump
+    )]
+    pub arbiter_registry: Account<'info, ArbiterRegistry>,
     
-    #[account(mut)]
-    /// CHECK: Seller account
-    pub seller: AccountInfo<'info>,
+    #[account(address = anchor_lang::solana_program::sysvar::slot_hashes::ID)]
+    /// CHECK: SlotHashes sysvar, read for the assignment seed
+    pub slot_hashes: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
-pub struct CreateDispute<'info> {
+pub struct FinalizeDispute<'info> {
     #[account(
-        init,
-        payer = disputer,
-        space = 8 + Dispute::INIT_SPACE,
-        seeds = [b"dispute", escrow.key().as_ref()],
+        mut,
+        seeds = [b"dispute", dispute.escrow.as_ref()],
         bump
     )]
     pub dispute: Account<'info, Dispute>,
     
     #[account(
         mut,
-        seeds = [b"escrow", escrow.buyer.as_ref()],
-        bump
+        seeds = [b"escrow", escrow.buyer.as_ref(), escrow.escrow_id.to_le_bytes().as_ref()],
+        bump,
+        constraint = escrow.key() == dispute.escrow @ ErrorCode::InvalidDisputeStatus
     )]
     pub escrow: Account<'info, Escrow>,
     
     #[account(
-        mut,
         seeds = [b"config"],
         bump
     )]
     pub config: Account<'info, EscrowConfig>,
     
-    #[account(mut)]
-    pub disputer: Signer<'info>,
-    
-    pub system_program: Program<'info, System>,
-}
-
-#[derive(Accounts)]
-pub struct AddArbiter<'info> {
     #[account(
-        init,
-        payer = authority,
-        space = 8 + Arbiter::INIT_SPACE,
-        seeds = [b"arbiter", arbiter_account.key().as_ref()],
-        bump
+        mut,
+        seeds = [b"arbiter", arbiter.pubkey.as_ref()],
+        bump,
+        constraint = dispute.assigned_arbiter == Some(arbiter.pubkey) @ ErrorCode::UnauthorizedArbiter
     )]
     pub arbiter: Account<'info, Arbiter>,
     
     #[account(
-        seeds = [b"config"],
-        bump
+        mut,
+        constraint = buyer.key() == escrow.buyer @ ErrorCode::Unauthorized
     )]
-    pub config: Account<'info, EscrowConfig>,
+    /// CHECK: Buyer account
+    pub buyer: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        constraint = seller.key() == escrow.seller @ ErrorCode::Unauthorized
+    )]
+    /// CHECK: Seller account
+    pub seller: AccountInfo<'info>,
     
+    /// Token escrows only: the escrow's token account and the parties' token accounts
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub escrow_token_account: Option<Account<'info, TokenAccount>>,
     
     #[account(mut)]
-    /// CHECK: Arbiter account
-    pub arbiter_account: AccountInfo<'info>,
+    pub buyer_token_account: Option<Account<'info, TokenAccount>>,
     
-    pub system_program: Program<'info, System>,
+    #[account(mut)]
+    pub seller_token_account: Option<Account<'info, TokenAccount>>,
+    
+    pub token_program: Option<Program<'info, Token>>,
 }
 
 #[derive(Accounts)]
-pub struct ResolveDispute<'info> {
+pub struct UpdateEscrowConfig<'info> {
     #[account(
         mut,
-        seeds = [b"dispute", dispute.escrow.as_ref()],
-        bump
+        seeds = [b"config"],
+        bump,
+        has_one = authority @ ErrorCode::Unauthorized
     )]
-    pub dispute: Account<'info, Dispute>,
+    pub config: Account<'info, EscrowConfig>,
     
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMintEscrowLimits<'info> {
     #[account(
-        mut,
-        seeds = [b"escrow", escrow.buyer.as_ref()],
+        init_if_needed,
+        payer = authority,
+        space = 8 + MintEscrowLimit::INIT_SPACE,
+        seeds = [b"mint_limit", mint.key().as_ref()],
         bump
     )]
-    pub escrow: Account<'info, Escrow>,
+    pub mint_limit: Account<'info, MintEscrowLimit>,
     
     #[account(
-        mut,
-        seeds = [b"arbiter", arbiter.pubkey.as_ref()],
-        bump
+        seeds = [b"config"],
+        bump,
+        has_one = authority @ ErrorCode::Unauthorized
     )]
-    pub arbiter: Account<'info, Arbiter>,
+    pub config: Account<'info, EscrowConfig>,
     
-    #[account(mut)]
-    /// CHECK: Buyer account
-    pub buyer: AccountInfo<'info>,
+    pub mint: Account<'info, Mint>,
     
     #[account(mut)]
-    /// CHECK: Seller account
-    pub seller: AccountInfo<'info>,
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
 }
 
 #[account]
@@ -392,10 +2203,20 @@ pub struct EscrowConfig {
     pub total_escrows: u64,
     pub total_disputes: u64,
     pub is_paused: bool,
+    pub dispute_fee_rebate_bps: u16,
+    pub disputes_by_reason: [u64; 4],  // Filed disputes, indexed by `ReasonCode::index`
+    pub escrow_creation_fee: u64,      // Lamports charged to the buyer when an escrow is created
+    pub escrow_count: u64,             // Next escrow id; part of each escrow's PDA seeds
+    pub appeal_deposit: u64,           // Lamports a party posts to appeal a decision
+    pub appeal_window: i64,            // Seconds after a ruling during which it can be appealed
+    pub slash_bps: u16,                // Share of stake slashed when a ruling is overturned
+    pub max_escrow_amount: u64,        // Largest SOL escrow in lamports; 0 means uncapped
+    pub kyc_threshold: u64,            // SOL escrows above this need a KYC SBT; 0 disables the check
+    pub kyc_mint: Pubkey,              // SBT mint issued by kyc-verification
 }
 
 impl EscrowConfig {
-    pub const INIT_SPACE: usize = 32 + 32 + 8 + 8 + 8 + 8 + 1;
+    pub const INIT_SPACE: usize = 32 + 32 + 8 + 8 + 8 + 8 + 1 + 2 + 8 * 4 + 8 + 8 + 8 + 8 + 2 + 8 + 8 + 32;
 }
 
 #[account]
@@ -409,10 +2230,62 @@ pub struct Escrow {
     pub completed_at: Option<i64>,
     pub auto_release_time: Option<i64>,
     pub is_disputed: bool,
+    pub milestones: Vec<Milestone>,
+    pub released_amount: u64,
+    pub refunded_amount: u64,
+    pub escrow_id: u64,              // Per-program counter so a buyer can hold many escrows
+    pub mint: Option<Pubkey>,        // SPL mint for token escrows; None for SOL
+    pub bump: u8,
 }
 
 impl Escrow {
-    pub const INIT_SPACE: usize = 32 + 32 + 8 + 1 + 200 + 8 + 9 + 9 + 1;
+    pub const INIT_SPACE: usize = 32 + 32 + 8 + 1 + 200 + 8 + 9 + 9 + 1
+        + 4 + MAX_MILESTONES * Milestone::SIZE + 8 + 8 + 8 + 33 + 1;
+
+    /// Funds still held by the escrow
+    pub fn remaining_amount(&self) -> u64 {
+        self.amount - self.released_amount - self.refunded_amount
+    }
+
+    /// Funds at stake in a dispute: the disputed milestone, or everything still held
+    pub fn disputed_amount(&self, milestone_index: Option<u8>) -> u64 {
+        milestone_index
+            .and_then(|index| self.milestones.get(index as usize))
+            .map_or_else(|| self.remaining_amount(), |milestone| milestone.amount)
+    }
+
+    /// Close out a milestone escrow once every milestone is released or refunded
+    pub fn finalize_if_settled(&mut self, timestamp: i64) {
+        let settled = self.milestones.iter().all(|m| {
+            m.status == MilestoneStatus::Released || m.status == MilestoneStatus::Refunded
+        });
+        if settled {
+            self.status = if self.released_amount > 0 {
+                EscrowStatus::Completed
+            } else {
+                EscrowStatus::Refunded
+            };
+            self.completed_at = Some(timestamp);
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Milestone {
+    pub amount: u64,
+    pub release_time: Option<i64>,
+    pub status: MilestoneStatus,
+    pub completed_at: Option<i64>,
+}
+
+impl Milestone {
+    pub const SIZE: usize = 8 + 9 + 1 + 9;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MilestoneInput {
+    pub amount: u64,
+    pub release_time: Option<i64>,
 }
 
 #[account]
@@ -426,10 +2299,22 @@ pub struct Dispute {
     pub assigned_arbiter: Option<Pubkey>,
     pub decision: Option<DisputeDecision>,
     pub reasoning: Option<String>,
+    pub milestone_index: Option<u8>,
+    pub fee_paid: u64,
+    pub reason_code: ReasonCode,
+    pub resolution_code: Option<ReasonCode>,
+    pub appellant: Option<Pubkey>,
+    pub appeal_reason: Option<String>,
+    pub appeal_deposit: u64,           // Held by the dispute until the appeal is decided
+    pub appeal_min_reputation: u32,    // Reputation the appellate arbiter must exceed
+    pub original_arbiter: Option<Pubkey>, // Arbiter of the appealed ruling, excluded from the appeal
+    pub appeal_upheld: Option<bool>,
+    pub ruling_executed: bool,         // Escrow funds paid out per the decision
+    pub arbiter_draw_slot: u64,        // Slot whose hash seeds the arbiter draw
 }
 
 impl Dispute {
-    pub const INIT_SPACE: usize = 32 + 32 + 500 + 1 + 8 + 9 + 33 + 2 + 1000;
+    pub const INIT_SPACE: usize = 32 + 32 + 500 + 1 + 8 + 9 + 33 + 2 + 1000 + 2 + 8 + 1 + 2 + 33 + 505 + 8 + 4 + 33 + 2 + 1 + 8;
 }
 
 #[account]
@@ -440,20 +2325,52 @@ pub struct Arbiter {
     pub cases_resolved: u32,
     pub is_active: bool,
     pub joined_at: i64,
+    pub open_cases: u32,               // Disputes assigned whose ruling isn't final yet
 }
 
 impl Arbiter {
-    pub const INIT_SPACE: usize = 32 + 8 + 4 + 4 + 1 + 8;
+    pub const INIT_SPACE: usize = 32 + 8 + 4 + 4 + 1 + 8 + 4;
+}
+
+#[account]
+pub struct ArbiterRegistry {
+    pub arbiters: Vec<Pubkey>,         // Active arbiter wallets, in the order assign_arbiter expects
+    pub bump: u8,
+}
+
+impl ArbiterRegistry {
+    pub const INIT_SPACE: usize = 4 + 32 * MAX_ARBITER_CANDIDATES + 1;
+}
+
+#[account]
+pub struct MintEscrowLimit {
+    pub mint: Pubkey,
+    pub max_amount: u64,               // Largest token escrow in base units; 0 means uncapped
+    pub kyc_threshold: u64,            // Token escrows above this need a KYC SBT; 0 disables the check
+    pub bump: u8,
+}
+
+impl MintEscrowLimit {
+    pub const INIT_SPACE: usize = 32 + 8 + 8 + 1;
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
 pub enum EscrowStatus {
+    Draft,
     Active,
     Completed,
     Refunded,
     Cancelled,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum MilestoneStatus {
+    Pending,
+    Released,
+    Disputed,
+    Refunded,
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
 pub enum DisputeStatus {
     Open,
@@ -467,6 +2384,26 @@ pub enum DisputeDecision {
     FavorSeller,
 }
 
+/// Structured reason recorded alongside the free-text dispute reason and arbiter reasoning
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Copy)]
+pub enum ReasonCode {
+    NotDelivered,
+    NotAsDescribed,
+    Fraud,
+    Other,
+}
+
+impl ReasonCode {
+    pub fn index(&self) -> usize {
+        match self {
+            ReasonCode::NotDelivered => 0,
+            ReasonCode::NotAsDescribed => 1,
+            ReasonCode::Fraud => 2,
+            ReasonCode::Other => 3,
+        }
+    }
+}
+
 #[event]
 pub struct ProgramInitialized {
     pub authority: Pubkey,
@@ -479,13 +2416,46 @@ pub struct EscrowCreated {
     pub buyer: Pubkey,
     pub seller: Pubkey,
     pub amount: u64,
+    pub mint: Option<Pubkey>,
+    pub creation_fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EscrowDraftCreated {
+    pub escrow_id: Pubkey,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub amount: u64,
+    pub creation_fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EscrowDraftCancelled {
+    pub escrow_id: Pubkey,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub amount: u64,
+    pub cancelled_by: Pubkey,
     pub timestamp: i64,
 }
 
 #[event]
 pub struct EscrowReleased {
     pub escrow_id: Pubkey,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MilestoneReleased {
+    pub escrow_id: Pubkey,
+    pub buyer: Pubkey,
     pub seller: Pubkey,
+    pub milestone_index: u8,
     pub amount: u64,
     pub timestamp: i64,
 }
@@ -494,8 +2464,13 @@ pub struct EscrowReleased {
 pub struct DisputeCreated {
     pub dispute_id: Pubkey,
     pub escrow_id: Pubkey,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub amount: u64,
     pub disputer: Pubkey,
+    pub reason_code: ReasonCode,
     pub reason: String,
+    pub arbitration_fee: u64,
     pub timestamp: i64,
 }
 
@@ -503,11 +2478,103 @@ pub struct DisputeCreated {
 pub struct DisputeResolved {
     pub dispute_id: Pubkey,
     pub escrow_id: Pubkey,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub amount: u64,
+    pub arbiter: Pubkey,
+    pub decision: DisputeDecision,
+    pub resolution_code: ReasonCode,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DisputeAppealed {
+    pub dispute_id: Pubkey,
+    pub escrow_id: Pubkey,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub amount: u64,
+    pub appellant: Pubkey,
+    pub reason: String,
+    pub deposit: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ArbiterAssigned {
+    pub dispute_id: Pubkey,
+    pub escrow_id: Pubkey,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub amount: u64,
+    pub arbiter: Pubkey,
+    pub candidates: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ArbiterDrawRescheduled {
+    pub dispute_id: Pubkey,
+    pub expired_slot: u64,
+    pub draw_slot: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ArbiterSlashed {
     pub arbiter: Pubkey,
+    pub dispute_id: Pubkey,
+    pub amount: u64,
+    pub remaining_stake: u64,
+    pub new_reputation: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DisputeFinalized {
+    pub dispute_id: Pubkey,
+    pub escrow_id: Pubkey,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub amount: u64,
     pub decision: DisputeDecision,
     pub timestamp: i64,
 }
 
+#[event]
+pub struct AppealResolved {
+    pub dispute_id: Pubkey,
+    pub escrow_id: Pubkey,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub amount: u64,
+    pub arbiter: Pubkey,
+    pub upheld: bool,
+    pub deposit: u64,
+    pub deposit_recipient: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DisputeFeeRebated {
+    pub dispute_id: Pubkey,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ArbitrationFeePaid {
+    pub dispute_id: Pubkey,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub treasury: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct ArbiterAdded {
     pub arbiter: Pubkey,
@@ -515,6 +2582,14 @@ pub struct ArbiterAdded {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct ArbiterExited {
+    pub arbiter: Pubkey,
+    pub stake_returned: u64,
+    pub cases_resolved: u32,
+    pub timestamp: i64,
+}
+
 #[error_code]
 pub enum ErrorCode {
     #[msg("Program is currently paused")]
@@ -543,4 +2618,60 @@ pub enum ErrorCode {
     ReasoningTooLong,
     #[msg("Insufficient stake")]
     InsufficientStake,
+    #[msg("Disputer cannot cover the arbitration fee")]
+    InsufficientArbitrationFee,
+    #[msg("Invalid number of milestones")]
+    InvalidMilestoneCount,
+    #[msg("Invalid milestone index")]
+    InvalidMilestoneIndex,
+    #[msg("Invalid milestone status")]
+    InvalidMilestoneStatus,
+    #[msg("Milestone escrows must be released per milestone")]
+    MilestoneEscrow,
+    #[msg("Rebate rate must not exceed 10000 basis points")]
+    InvalidRebateRate,
+    #[msg("Dispute has already been appealed")]
+    AlreadyAppealed,
+    #[msg("Appellant cannot cover the appeal deposit")]
+    InsufficientAppealDeposit,
+    #[msg("Appeal window has closed")]
+    AppealWindowClosed,
+    #[msg("Appeal window is still open")]
+    AppealWindowOpen,
+    #[msg("Appeal window must not be negative")]
+    InvalidAppealWindow,
+    #[msg("Ruling has already been executed")]
+    RulingAlreadyExecuted,
+    #[msg("No arbiter has been assigned to this dispute")]
+    ArbiterNotAssigned,
+    #[msg("An arbiter is already assigned to this dispute")]
+    ArbiterAlreadyAssigned,
+    #[msg("Invalid arbiter candidate accounts")]
+    InvalidArbiterCandidates,
+    #[msg("No eligible arbiter among the candidates")]
+    NoEligibleArbiter,
+    #[msg("Arbiter registry is full")]
+    ArbiterRegistryFull,
+    #[msg("Original arbiter account is required to settle an appeal")]
+    OriginalArbiterRequired,
+    #[msg("Slash rate must not exceed 10000 basis points")]
+    InvalidSlashRate,
+    #[msg("Arbiter still has open disputes assigned")]
+    ArbiterHasOpenCases,
+    #[msg("Token escrows must be released with release_token_escrow")]
+    TokenEscrow,
+    #[msg("Token accounts are required to settle a token escrow")]
+    TokenAccountsRequired,
+    #[msg("Token account does not match the escrow")]
+    InvalidTokenAccount,
+    #[msg("Escrow amount exceeds the configured maximum")]
+    EscrowAmountTooLarge,
+    #[msg("A KYC credential is required for an escrow of this size")]
+    KycRequired,
+    #[msg("KYC credential is not a valid SBT held by the buyer")]
+    InvalidKycCredential,
+    #[msg("A KYC mint must be set when the KYC threshold is enabled")]
+    InvalidKycMint,
+    #[msg("The arbiter draw slot has not passed yet")]
+    ArbiterDrawPending,
 }
@@ -0,0 +1,1362 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import {
+  Keypair,
+  PublicKey,
+  LAMPORTS_PER_SOL,
+  SystemProgram,
+  SYSVAR_SLOT_HASHES_PUBKEY,
+} from "@solana/web3.js";
+import { ACCOUNT_SIZE, AccountLayout, AccountState, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { Clock, ProgramTestContext, startAnchor } from "solana-bankrun";
+import { BankrunProvider } from "anchor-bankrun";
+import { IDL, EscrowArbitration } from "../target/types/escrow_arbitration";
+import { expect } from "chai";
+
+// Every test runs on a fresh bank: the arbiter registry is global and assignment is random, so
+// each case registers exactly the arbiters it needs
+describe("escrow-arbitration", () => {
+  // Configure the client to use the local cluster.
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.EscrowArbitration as Program<EscrowArbitration>;
+
+  const ARBITRATION_FEE = 1_000_000;
+  const ARBITER_STAKE = 0.1 * LAMPORTS_PER_SOL;
+
+  const [configPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    program.programId
+  );
+
+  const [registryPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("arbiter_registry")],
+    program.programId
+  );
+
+  const escrowPda = (buyer: PublicKey, escrowId: number) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), buyer.toBuffer(), new BN(escrowId).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+
+  const milestoneDisputePda = (escrow: PublicKey, milestoneIndex: number) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("milestone_dispute"), escrow.toBuffer(), Buffer.from([milestoneIndex])],
+      program.programId
+    )[0];
+
+  const arbiterPda = (arbiter: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("arbiter"), arbiter.toBuffer()],
+      program.programId
+    )[0];
+
+  type ReasonCode = anchor.IdlTypes<EscrowArbitration>["ReasonCode"];
+  type DisputeDecision = anchor.IdlTypes<EscrowArbitration>["DisputeDecision"];
+
+  type Bank = {
+    context: ProgramTestContext;
+    escrows: Program<EscrowArbitration>;
+    treasury: PublicKey;
+  };
+
+  const fund = (context: ProgramTestContext, lamports = 10 * LAMPORTS_PER_SOL) => {
+    const keypair = Keypair.generate();
+    context.setAccount(keypair.publicKey, {
+      lamports,
+      data: Buffer.alloc(0),
+      owner: SystemProgram.programId,
+      executable: false,
+    });
+    return keypair;
+  };
+
+  // A fresh bank with the program initialized; the bank payer is the config authority
+  const startBank = async (): Promise<Bank> => {
+    const context = await startAnchor(".", [], []);
+    const escrows = new Program<EscrowArbitration>(IDL, program.programId, new BankrunProvider(context));
+    const treasury = fund(context, LAMPORTS_PER_SOL).publicKey;
+
+    await escrows.methods
+      .initialize()
+      .accounts({
+        config: configPda,
+        arbiterRegistry: registryPda,
+        authority: context.payer.publicKey,
+        treasury,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    return { context, escrows, treasury };
+  };
+
+  const balance = async (bank: Bank, address: PublicKey) =>
+    Number(await bank.context.banksClient.getBalance(address));
+
+  // Move to the next slot, for a fresh blockhash and slot hash, and `seconds` ahead
+  const warp = async (bank: Bank, seconds = 0) => {
+    const clock = await bank.context.banksClient.getClock();
+    const slot = clock.slot + BigInt(1);
+    bank.context.warpToSlot(slot);
+    bank.context.setClock(
+      new Clock(
+        slot,
+        clock.epochStartTimestamp,
+        clock.epoch,
+        clock.leaderScheduleEpoch,
+        clock.unixTimestamp + BigInt(seconds)
+      )
+    );
+  };
+
+  // The bank reports program errors by number, e.g. "custom program error: 0x1774"
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    const error = IDL.errors.find((candidate) => candidate.name === code);
+    try {
+      await promise;
+    } catch (err) {
+      expect(String(err)).to.contain(`0x${error.code.toString(16)}`);
+      return;
+    }
+    expect.fail(`expected ${code}`);
+  };
+
+  const nextEscrow = async (bank: Bank, buyer: PublicKey) => {
+    const config = await bank.escrows.account.escrowConfig.fetch(configPda);
+    return escrowPda(buyer, config.escrowCount.toNumber());
+  };
+
+  // A milestone escrow from `buyer` to `seller`, one milestone per amount
+  const createMilestoneEscrow = async (
+    bank: Bank,
+    buyer: Keypair,
+    seller: PublicKey,
+    milestones: { amount: number; releaseTime?: number }[]
+  ) => {
+    const escrow = await nextEscrow(bank, buyer.publicKey);
+    await bank.escrows.methods
+      .createMilestoneEscrow(
+        "Milestone escrow",
+        milestones.map(({ amount, releaseTime }) => ({
+          amount: new BN(amount),
+          releaseTime: releaseTime === undefined ? null : new BN(releaseTime),
+        }))
+      )
+      .accounts({
+        escrow,
+        config: configPda,
+        buyer: buyer.publicKey,
+        seller,
+        treasury: bank.treasury,
+        kycCredential: null,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([buyer])
+      .rpc();
+    return escrow;
+  };
+
+  // A funded arbiter staked into the registry
+  const addArbiter = async (bank: Bank, stake = ARBITER_STAKE) => {
+    const arbiter = fund(bank.context);
+    await bank.escrows.methods
+      .addArbiter(new BN(stake))
+      .accounts({
+        arbiter: arbiterPda(arbiter.publicKey),
+        config: configPda,
+        arbiterRegistry: registryPda,
+        authority: bank.context.payer.publicKey,
+        arbiterAccount: arbiter.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([arbiter])
+      .rpc();
+    return arbiter;
+  };
+
+  // Step slot by slot past the dispute's draw slot, so its hash lands in SlotHashes
+  const warpPastDraw = async (bank: Bank, dispute: PublicKey) => {
+    const { arbiterDrawSlot } = await bank.escrows.account.dispute.fetch(dispute);
+    do {
+      await warp(bank);
+    } while ((await bank.context.banksClient.getClock()).slot <= BigInt(arbiterDrawSlot.toString()));
+  };
+
+  // Assign an arbiter from the full registry, in registry order
+  const assignArbiter = async (bank: Bank, dispute: PublicKey, escrow: PublicKey) => {
+    await warpPastDraw(bank, dispute);
+    const registry = await bank.escrows.account.arbiterRegistry.fetch(registryPda);
+    await bank.escrows.methods
+      .assignArbiter()
+      .accounts({
+        dispute,
+        escrow,
+        arbiterRegistry: registryPda,
+        slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+      })
+      .remainingAccounts(
+        registry.arbiters.map((arbiter) => ({
+          pubkey: arbiterPda(arbiter),
+          isWritable: true,
+          isSigner: false,
+        }))
+      )
+      .rpc();
+    return (await bank.escrows.account.dispute.fetch(dispute)).assignedArbiter;
+  };
+
+  const disputePda = (escrow: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("dispute"), escrow.toBuffer()],
+      program.programId
+    )[0];
+
+  // A SOL escrow from `buyer` to `seller`, optionally presenting a KYC credential
+  const createEscrow = async (
+    bank: Bank,
+    buyer: Keypair,
+    seller: PublicKey,
+    amount: number,
+    kycCredential: PublicKey | null = null
+  ) => {
+    const escrow = await nextEscrow(bank, buyer.publicKey);
+    await bank.escrows.methods
+      .createEscrow(new BN(amount), "Escrow", null)
+      .accounts({
+        escrow,
+        config: configPda,
+        buyer: buyer.publicKey,
+        seller,
+        treasury: bank.treasury,
+        kycCredential,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([buyer])
+      .rpc();
+    return escrow;
+  };
+
+  const createDispute = async (
+    bank: Bank,
+    disputer: Keypair,
+    escrow: PublicKey,
+    reasonCode: ReasonCode = { notDelivered: {} }
+  ) => {
+    const dispute = disputePda(escrow);
+    await bank.escrows.methods
+      .createDispute(reasonCode, "Goods not received")
+      .accounts({
+        dispute,
+        escrow,
+        config: configPda,
+        disputer: disputer.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([disputer])
+      .rpc();
+    return dispute;
+  };
+
+  // Rule on a SOL escrow's dispute as `arbiter`; an appeal also settles the original arbiter
+  const resolveDispute = async (
+    bank: Bank,
+    arbiter: Keypair,
+    escrow: PublicKey,
+    decision: DisputeDecision,
+    {
+      resolutionCode = { notDelivered: {} },
+      originalArbiter = null,
+    }: { resolutionCode?: ReasonCode; originalArbiter?: PublicKey | null } = {}
+  ) => {
+    const { buyer, seller } = await bank.escrows.account.escrow.fetch(escrow);
+    await bank.escrows.methods
+      .resolveDispute(decision, resolutionCode, "Ruling")
+      .accounts({
+        dispute: disputePda(escrow),
+        escrow,
+        arbiter: arbiterPda(arbiter.publicKey),
+        config: configPda,
+        buyer,
+        seller,
+        arbiterAccount: arbiter.publicKey,
+        treasury: bank.treasury,
+        originalArbiter: originalArbiter ? arbiterPda(originalArbiter) : null,
+        escrowTokenAccount: null,
+        buyerTokenAccount: null,
+        sellerTokenAccount: null,
+        tokenProgram: null,
+      })
+      .signers([arbiter])
+      .rpc();
+  };
+
+  const releaseEscrow = (bank: Bank, authority: Keypair, escrow: PublicKey, seller: PublicKey) =>
+    bank.escrows.methods
+      .releaseEscrow()
+      .accounts({ escrow, authority: authority.publicKey, seller })
+      .signers([authority])
+      .rpc();
+
+  describe("milestones", () => {
+    const disputeMilestone = (bank: Bank, disputer: Keypair, escrow: PublicKey, milestoneIndex: number) =>
+      bank.escrows.methods
+        .disputeMilestone(milestoneIndex, { notDelivered: {} }, "Second milestone not delivered")
+        .accounts({
+          dispute: milestoneDisputePda(escrow, milestoneIndex),
+          escrow,
+          config: configPda,
+          disputer: disputer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([disputer])
+        .rpc();
+
+    it("Releases one milestone, then refunds a disputed one", async () => {
+      const bank = await startBank();
+      const buyer = fund(bank.context);
+      const seller = fund(bank.context).publicKey;
+      const arbiter = await addArbiter(bank);
+      const escrow = await createMilestoneEscrow(bank, buyer, seller, [
+        { amount: 0.3 * LAMPORTS_PER_SOL },
+        { amount: 0.7 * LAMPORTS_PER_SOL },
+      ]);
+
+      // Buyer approves the first milestone
+      const sellerBefore = await balance(bank, seller);
+      await bank.escrows.methods
+        .releaseMilestone(0)
+        .accounts({ escrow, authority: buyer.publicKey, seller })
+        .signers([buyer])
+        .rpc();
+      expect(await balance(bank, seller)).to.equal(sellerBefore + 0.3 * LAMPORTS_PER_SOL);
+
+      const partial = await bank.escrows.account.escrow.fetch(escrow);
+      expect(partial.status).to.deep.equal({ active: {} });
+      expect(partial.releasedAmount.toNumber()).to.equal(0.3 * LAMPORTS_PER_SOL);
+      expect(partial.milestones[0].status).to.deep.equal({ released: {} });
+
+      // ...and disputes the second
+      const dispute = milestoneDisputePda(escrow, 1);
+      await disputeMilestone(bank, buyer, escrow, 1);
+      const disputed = await bank.escrows.account.escrow.fetch(escrow);
+      expect(disputed.milestones[1].status).to.deep.equal({ disputed: {} });
+
+      expect((await assignArbiter(bank, dispute, escrow)).toString()).to.equal(arbiter.publicKey.toString());
+
+      const buyerBefore = await balance(bank, buyer.publicKey);
+      await bank.escrows.methods
+        .resolveMilestoneDispute({ favorBuyer: {} }, { notDelivered: {} }, "Never delivered")
+        .accounts({
+          dispute,
+          escrow,
+          arbiter: arbiterPda(arbiter.publicKey),
+          buyer: buyer.publicKey,
+          seller,
+          config: configPda,
+          arbiterAccount: arbiter.publicKey,
+          treasury: bank.treasury,
+        })
+        .signers([arbiter])
+        .rpc();
+
+      // The buyer gets back only the disputed milestone; the seller keeps the first
+      expect(await balance(bank, buyer.publicKey)).to.equal(buyerBefore + 0.7 * LAMPORTS_PER_SOL);
+      expect(await balance(bank, seller)).to.equal(sellerBefore + 0.3 * LAMPORTS_PER_SOL);
+
+      const settled = await bank.escrows.account.escrow.fetch(escrow);
+      expect(settled.status).to.deep.equal({ completed: {} });
+      expect(settled.releasedAmount.toNumber()).to.equal(0.3 * LAMPORTS_PER_SOL);
+      expect(settled.refundedAmount.toNumber()).to.equal(0.7 * LAMPORTS_PER_SOL);
+      expect(settled.milestones[1].status).to.deep.equal({ refunded: {} });
+
+      // Only rent is left behind
+      const account = await bank.context.banksClient.getAccount(escrow);
+      const rent = await bank.context.banksClient.getRent();
+      expect(BigInt(account.lamports)).to.equal(rent.minimumBalance(BigInt(account.data.length)));
+    });
+
+    it("Lets anyone release a milestone once its release time passes", async () => {
+      const bank = await startBank();
+      const buyer = fund(bank.context);
+      const seller = fund(bank.context);
+      const { unixTimestamp } = await bank.context.banksClient.getClock();
+      const escrow = await createMilestoneEscrow(bank, buyer, seller.publicKey, [
+        { amount: 0.2 * LAMPORTS_PER_SOL, releaseTime: Number(unixTimestamp) + 3600 },
+      ]);
+
+      const release = () =>
+        bank.escrows.methods
+          .releaseMilestone(0)
+          .accounts({ escrow, authority: seller.publicKey, seller: seller.publicKey })
+          .signers([seller])
+          .rpc();
+
+      await expectError(release(), "Unauthorized");
+
+      await warp(bank, 3600);
+      const sellerBefore = await balance(bank, seller.publicKey);
+      await release();
+
+      expect(await balance(bank, seller.publicKey)).to.equal(sellerBefore + 0.2 * LAMPORTS_PER_SOL);
+      const settled = await bank.escrows.account.escrow.fetch(escrow);
+      expect(settled.status).to.deep.equal({ completed: {} });
+    });
+
+    it("Rejects releasing a disputed milestone", async () => {
+      const bank = await startBank();
+      const buyer = fund(bank.context);
+      const seller = fund(bank.context).publicKey;
+      const escrow = await createMilestoneEscrow(bank, buyer, seller, [
+        { amount: 0.3 * LAMPORTS_PER_SOL },
+        { amount: 0.7 * LAMPORTS_PER_SOL },
+      ]);
+      await disputeMilestone(bank, buyer, escrow, 1);
+
+      await expectError(
+        bank.escrows.methods
+          .releaseMilestone(1)
+          .accounts({ escrow, authority: buyer.publicKey, seller })
+          .signers([buyer])
+          .rpc(),
+        "InvalidMilestoneStatus"
+      );
+    });
+  });
+
+  describe("drafts", () => {
+    const createDraft = async (bank: Bank, buyer: Keypair, seller: PublicKey, amount: number) => {
+      const escrow = await nextEscrow(bank, buyer.publicKey);
+      await bank.escrows.methods
+        .createDraftEscrow(new BN(amount), "Draft terms", null)
+        .accounts({
+          escrow,
+          config: configPda,
+          buyer: buyer.publicKey,
+          seller,
+          treasury: bank.treasury,
+          kycCredential: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([buyer])
+        .rpc();
+      return escrow;
+    };
+
+    const cancelDraft = (bank: Bank, authority: Keypair, escrow: PublicKey, buyer: PublicKey) =>
+      bank.escrows.methods
+        .cancelDraft()
+        .accounts({ escrow, authority: authority.publicKey, buyer })
+        .signers([authority])
+        .rpc();
+
+    it("Locks funds only once a draft is funded", async () => {
+      const bank = await startBank();
+      const buyer = fund(bank.context);
+      const seller = fund(bank.context).publicKey;
+      const amount = 0.5 * LAMPORTS_PER_SOL;
+
+      const buyerBefore = await balance(bank, buyer.publicKey);
+      const escrow = await createDraft(bank, buyer, seller, amount);
+
+      // The draft costs the buyer its rent and nothing more
+      const rent = await balance(bank, escrow);
+      expect(await balance(bank, buyer.publicKey)).to.equal(buyerBefore - rent);
+      const draft = await bank.escrows.account.escrow.fetch(escrow);
+      expect(draft.status).to.deep.equal({ draft: {} });
+      expect(draft.amount.toNumber()).to.equal(amount);
+
+      await bank.escrows.methods
+        .fundEscrow()
+        .accounts({
+          escrow,
+          config: configPda,
+          buyer: buyer.publicKey,
+          kycCredential: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([buyer])
+        .rpc();
+
+      const active = await bank.escrows.account.escrow.fetch(escrow);
+      expect(active.status).to.deep.equal({ active: {} });
+      expect(await balance(bank, escrow)).to.equal(rent + amount);
+      expect(await balance(bank, buyer.publicKey)).to.equal(buyerBefore - rent - amount);
+
+      const config = await bank.escrows.account.escrowConfig.fetch(configPda);
+      expect(config.totalEscrows.toNumber()).to.equal(1);
+    });
+
+    it("Cancels an unfunded draft without moving funds", async () => {
+      const bank = await startBank();
+      const buyer = fund(bank.context);
+      const seller = fund(bank.context);
+
+      const buyerBefore = await balance(bank, buyer.publicKey);
+      const sellerBefore = await balance(bank, seller.publicKey);
+      const escrow = await createDraft(bank, buyer, seller.publicKey, 0.5 * LAMPORTS_PER_SOL);
+
+      // Either party may discard it; the rent goes back to the buyer
+      await cancelDraft(bank, seller, escrow, buyer.publicKey);
+
+      expect(await bank.escrows.account.escrow.fetchNullable(escrow)).to.be.null;
+      expect(await balance(bank, buyer.publicKey)).to.equal(buyerBefore);
+      expect(await balance(bank, seller.publicKey)).to.equal(sellerBefore);
+      const config = await bank.escrows.account.escrowConfig.fetch(configPda);
+      expect(config.totalEscrows.toNumber()).to.equal(0);
+    });
+
+    it("Rejects cancelling a funded escrow", async () => {
+      const bank = await startBank();
+      const buyer = fund(bank.context);
+      const escrow = await createDraft(bank, buyer, fund(bank.context).publicKey, 0.5 * LAMPORTS_PER_SOL);
+      await bank.escrows.methods
+        .fundEscrow()
+        .accounts({
+          escrow,
+          config: configPda,
+          buyer: buyer.publicKey,
+          kycCredential: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([buyer])
+        .rpc();
+
+      await expectError(cancelDraft(bank, buyer, escrow, buyer.publicKey), "InvalidEscrowStatus");
+    });
+  });
+
+  describe("arbitration fee rebates", () => {
+    it("Rebates part of the fee to a winning buyer the seller disputed", async () => {
+      const bank = await startBank();
+      const buyer = fund(bank.context);
+      const seller = fund(bank.context);
+      const arbiter = await addArbiter(bank);
+      const amount = 0.5 * LAMPORTS_PER_SOL;
+      const escrow = await createEscrow(bank, buyer, seller.publicKey, amount);
+
+      const sellerBefore = await balance(bank, seller.publicKey);
+      const dispute = await createDispute(bank, seller, escrow);
+      expect(await balance(bank, seller.publicKey)).to.be.at.most(sellerBefore - ARBITRATION_FEE);
+      const { feePaid } = await bank.escrows.account.dispute.fetch(dispute);
+      expect(feePaid.toNumber()).to.equal(ARBITRATION_FEE);
+
+      await assignArbiter(bank, dispute, escrow);
+      const buyerBefore = await balance(bank, buyer.publicKey);
+      const treasuryBefore = await balance(bank, bank.treasury);
+      await resolveDispute(bank, arbiter, escrow, { favorBuyer: {} });
+
+      // Default rebate is half the fee; the treasury collects the rest
+      const rebate = ARBITRATION_FEE / 2;
+      expect(await balance(bank, buyer.publicKey)).to.equal(buyerBefore + amount + rebate);
+      expect(await balance(bank, bank.treasury)).to.equal(treasuryBefore + ARBITRATION_FEE - rebate);
+    });
+
+    it("Pays no rebate to a winner who filed the dispute", async () => {
+      const bank = await startBank();
+      const buyer = fund(bank.context);
+      const seller = fund(bank.context).publicKey;
+      const arbiter = await addArbiter(bank);
+      const amount = 0.5 * LAMPORTS_PER_SOL;
+      const escrow = await createEscrow(bank, buyer, seller, amount);
+      const dispute = await createDispute(bank, buyer, escrow);
+      await assignArbiter(bank, dispute, escrow);
+
+      const buyerBefore = await balance(bank, buyer.publicKey);
+      const treasuryBefore = await balance(bank, bank.treasury);
+      await resolveDispute(bank, arbiter, escrow, { favorBuyer: {} });
+
+      expect(await balance(bank, buyer.publicKey)).to.equal(buyerBefore + amount);
+      expect(await balance(bank, bank.treasury)).to.equal(treasuryBefore + ARBITRATION_FEE);
+
+      // The fee doesn't stay behind in the dispute account
+      const account = await bank.context.banksClient.getAccount(dispute);
+      const rent = await bank.context.banksClient.getRent();
+      expect(BigInt(account.lamports)).to.equal(rent.minimumBalance(BigInt(account.data.length)));
+    });
+
+    it("Pays the configured rebate rate", async () => {
+      const bank = await startBank();
+      await bank.escrows.methods
+        .setDisputeFeeRebate(2000)
+        .accounts({ config: configPda, authority: bank.context.payer.publicKey })
+        .rpc();
+      const buyer = fund(bank.context);
+      const seller = fund(bank.context);
+      const arbiter = await addArbiter(bank);
+      const escrow = await createEscrow(bank, buyer, seller.publicKey, 0.5 * LAMPORTS_PER_SOL);
+      const dispute = await createDispute(bank, buyer, escrow);
+      await assignArbiter(bank, dispute, escrow);
+
+      const sellerBefore = await balance(bank, seller.publicKey);
+      await resolveDispute(bank, arbiter, escrow, { favorSeller: {} });
+
+      expect(await balance(bank, seller.publicKey)).to.equal(
+        sellerBefore + 0.5 * LAMPORTS_PER_SOL + ARBITRATION_FEE * 0.2
+      );
+    });
+  });
+
+  describe("reason codes", () => {
+    it("Stores dispute reason codes and counts them per reason", async () => {
+      const bank = await startBank();
+      const buyer = fund(bank.context);
+      const seller = fund(bank.context).publicKey;
+      const reasonCodes: ReasonCode[] = [{ notDelivered: {} }, { fraud: {} }, { fraud: {} }, { other: {} }];
+
+      for (const reasonCode of reasonCodes) {
+        const escrow = await createEscrow(bank, buyer, seller, 0.1 * LAMPORTS_PER_SOL);
+        const dispute = await createDispute(bank, buyer, escrow, reasonCode);
+
+        const account = await bank.escrows.account.dispute.fetch(dispute);
+        expect(account.reasonCode).to.deep.equal(reasonCode);
+        expect(account.reason).to.equal("Goods not received");
+        expect(account.resolutionCode).to.be.null;
+      }
+
+      // Indexed NotDelivered, NotAsDescribed, Fraud, Other
+      const config = await bank.escrows.account.escrowConfig.fetch(configPda);
+      expect(config.totalDisputes.toNumber()).to.equal(4);
+      expect(config.disputesByReason.map((count) => count.toNumber())).to.deep.equal([1, 0, 2, 1]);
+    });
+
+    it("Stores the arbiter's resolution code", async () => {
+      const bank = await startBank();
+      const buyer = fund(bank.context);
+      const arbiter = await addArbiter(bank);
+      const escrow = await createEscrow(bank, buyer, fund(bank.context).publicKey, 0.1 * LAMPORTS_PER_SOL);
+      const dispute = await createDispute(bank, buyer, escrow, { notDelivered: {} });
+      await assignArbiter(bank, dispute, escrow);
+
+      await resolveDispute(bank, arbiter, escrow, { favorSeller: {} }, { resolutionCode: { notAsDescribed: {} } });
+
+      const account = await bank.escrows.account.dispute.fetch(dispute);
+      expect(account.reasonCode).to.deep.equal({ notDelivered: {} });
+      expect(account.resolutionCode).to.deep.equal({ notAsDescribed: {} });
+      expect(account.reasoning).to.equal("Ruling");
+    });
+  });
+
+  describe("escrow creation fee", () => {
+    it("Charges the buyer a configured creation fee into the treasury", async () => {
+      const bank = await startBank();
+      const fee = 5_000_000;
+      await bank.escrows.methods
+        .setEscrowCreationFee(new BN(fee))
+        .accounts({ config: configPda, authority: bank.context.payer.publicKey })
+        .rpc();
+      const buyer = fund(bank.context);
+      const amount = 0.5 * LAMPORTS_PER_SOL;
+
+      const buyerBefore = await balance(bank, buyer.publicKey);
+      const treasuryBefore = await balance(bank, bank.treasury);
+      const escrow = await createEscrow(bank, buyer, fund(bank.context).publicKey, amount);
+
+      const rent = (await balance(bank, escrow)) - amount;
+      expect(await balance(bank, bank.treasury)).to.equal(treasuryBefore + fee);
+      expect(await balance(bank, buyer.publicKey)).to.equal(buyerBefore - amount - rent - fee);
+    });
+
+    it("Charges nothing by default", async () => {
+      const bank = await startBank();
+      const buyer = fund(bank.context);
+      const amount = 0.5 * LAMPORTS_PER_SOL;
+
+      const buyerBefore = await balance(bank, buyer.publicKey);
+      const treasuryBefore = await balance(bank, bank.treasury);
+      const escrow = await createEscrow(bank, buyer, fund(bank.context).publicKey, amount);
+
+      const rent = (await balance(bank, escrow)) - amount;
+      expect(await balance(bank, bank.treasury)).to.equal(treasuryBefore);
+      expect(await balance(bank, buyer.publicKey)).to.equal(buyerBefore - amount - rent);
+    });
+  });
+
+  describe("multiple escrows per buyer", () => {
+    it("Opens two escrows from one buyer and releases each independently", async () => {
+      const bank = await startBank();
+      const buyer = fund(bank.context);
+      const firstSeller = fund(bank.context).publicKey;
+      const secondSeller = fund(bank.context).publicKey;
+
+      const first = await createEscrow(bank, buyer, firstSeller, 0.2 * LAMPORTS_PER_SOL);
+      const second = await createEscrow(bank, buyer, secondSeller, 0.3 * LAMPORTS_PER_SOL);
+      expect(first.toString()).to.equal(escrowPda(buyer.publicKey, 0).toString());
+      expect(second.toString()).to.equal(escrowPda(buyer.publicKey, 1).toString());
+
+      const firstSellerBefore = await balance(bank, firstSeller);
+      const secondSellerBefore = await balance(bank, secondSeller);
+
+      await releaseEscrow(bank, buyer, second, secondSeller);
+
+      expect(await balance(bank, secondSeller)).to.equal(secondSellerBefore + 0.3 * LAMPORTS_PER_SOL);
+      expect(await balance(bank, firstSeller)).to.equal(firstSellerBefore);
+      expect((await bank.escrows.account.escrow.fetch(first)).status).to.deep.equal({ active: {} });
+
+      await releaseEscrow(bank, buyer, first, firstSeller);
+
+      expect(await balance(bank, firstSeller)).to.equal(firstSellerBefore + 0.2 * LAMPORTS_PER_SOL);
+      for (const [escrow, escrowId] of [[first, 0], [second, 1]] as const) {
+        const account = await bank.escrows.account.escrow.fetch(escrow);
+        expect(account.escrowId.toNumber()).to.equal(escrowId);
+        expect(account.status).to.deep.equal({ completed: {} });
+      }
+    });
+  });
+
+  describe("appeals", () => {
+    const APPEAL_DEPOSIT = 0.05 * LAMPORTS_PER_SOL;
+    const ESCROW_AMOUNT = LAMPORTS_PER_SOL;
+
+    // Rewrite an arbiter's reputation so it out-ranks the original arbiter on appeal
+    const setReputation = async (bank: Bank, arbiter: PublicKey, reputation: number) => {
+      const address = arbiterPda(arbiter);
+      const account = await bank.escrows.account.arbiter.fetch(address);
+      const info = await bank.context.banksClient.getAccount(address);
+      const data = Buffer.from(info.data);
+      (await bank.escrows.coder.accounts.encode("Arbiter", { ...account, reputation })).copy(data);
+      bank.context.setAccount(address, { ...info, data });
+    };
+
+    // A dispute ruled for the seller inside an open appeal window, with a senior arbiter
+    // registered to hear the buyer's appeal
+    const appealedDispute = async () => {
+      const bank = await startBank();
+      await bank.escrows.methods
+        .setAppealWindow(new BN(24 * 60 * 60))
+        .accounts({ config: configPda, authority: bank.context.payer.publicKey })
+        .rpc();
+      await bank.escrows.methods
+        .setAppealDeposit(new BN(APPEAL_DEPOSIT))
+        .accounts({ config: configPda, authority: bank.context.payer.publicKey })
+        .rpc();
+
+      const buyer = fund(bank.context);
+      const seller = fund(bank.context).publicKey;
+      const original = await addArbiter(bank);
+      const escrow = await createEscrow(bank, buyer, seller, ESCROW_AMOUNT);
+      const dispute = await createDispute(bank, buyer, escrow);
+      await assignArbiter(bank, dispute, escrow);
+      await resolveDispute(bank, original, escrow, { favorSeller: {} });
+
+      // The ruling is held while it can still be appealed
+      const ruled = await bank.escrows.account.dispute.fetch(dispute);
+      expect(ruled.rulingExecuted).to.be.false;
+
+      const senior = await addArbiter(bank);
+      await setReputation(bank, senior.publicKey, 200);
+
+      const buyerBefore = await balance(bank, buyer.publicKey);
+      const disputeBefore = await balance(bank, dispute);
+      await bank.escrows.methods
+        .appealDispute("Tracking shows the parcel was never shipped")
+        .accounts({
+          dispute,
+          escrow,
+          config: configPda,
+          originalArbiter: arbiterPda(original.publicKey),
+          appellant: buyer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([buyer])
+        .rpc();
+
+      // The deposit is held by the dispute until the appeal is heard
+      expect(await balance(bank, buyer.publicKey)).to.equal(buyerBefore - APPEAL_DEPOSIT);
+      expect(await balance(bank, dispute)).to.equal(disputeBefore + APPEAL_DEPOSIT);
+      const appealed = await bank.escrows.account.dispute.fetch(dispute);
+      expect(appealed.status).to.deep.equal({ appealed: {} });
+      expect(appealed.appealDeposit.toNumber()).to.equal(APPEAL_DEPOSIT);
+      expect(appealed.appellant.toString()).to.equal(buyer.publicKey.toString());
+
+      const assigned = await assignArbiter(bank, dispute, escrow);
+      expect(assigned.toString()).to.equal(senior.publicKey.toString());
+
+      return { bank, buyer, seller, original, senior, escrow, dispute };
+    };
+
+    it("Forfeits the deposit to the treasury when the ruling is upheld", async () => {
+      const { bank, buyer, seller, original, senior, escrow, dispute } = await appealedDispute();
+      const buyerBefore = await balance(bank, buyer.publicKey);
+      const sellerBefore = await balance(bank, seller);
+      const treasuryBefore = await balance(bank, bank.treasury);
+
+      await resolveDispute(bank, senior, escrow, { favorSeller: {} }, { originalArbiter: original.publicKey });
+
+      expect(await balance(bank, bank.treasury)).to.equal(treasuryBefore + APPEAL_DEPOSIT);
+      expect(await balance(bank, buyer.publicKey)).to.equal(buyerBefore);
+      expect(await balance(bank, seller)).to.equal(sellerBefore + ESCROW_AMOUNT);
+
+      const account = await bank.escrows.account.dispute.fetch(dispute);
+      expect(account.appealUpheld).to.be.true;
+      expect(account.appealDeposit.toNumber()).to.equal(0);
+      expect(account.rulingExecuted).to.be.true;
+
+      // The original arbiter keeps their whole stake
+      const originalArbiter = await bank.escrows.account.arbiter.fetch(arbiterPda(original.publicKey));
+      expect(originalArbiter.stake.toNumber()).to.equal(ARBITER_STAKE);
+      expect(originalArbiter.openCases).to.equal(0);
+    });
+
+    it("Returns the deposit to the appellant when the ruling is overturned", async () => {
+      const { bank, buyer, seller, original, senior, escrow, dispute } = await appealedDispute();
+      const buyerBefore = await balance(bank, buyer.publicKey);
+      const sellerBefore = await balance(bank, seller);
+      const treasuryBefore = await balance(bank, bank.treasury);
+
+      await resolveDispute(bank, senior, escrow, { favorBuyer: {} }, { originalArbiter: original.publicKey });
+
+      expect(await balance(bank, buyer.publicKey)).to.equal(buyerBefore + ESCROW_AMOUNT + APPEAL_DEPOSIT);
+      expect(await balance(bank, seller)).to.equal(sellerBefore);
+
+      const account = await bank.escrows.account.dispute.fetch(dispute);
+      expect(account.appealUpheld).to.be.false;
+      expect(account.appealDeposit.toNumber()).to.equal(0);
+
+      // Only the original arbiter's slashed stake (10% by default) reaches the treasury
+      const slash = ARBITER_STAKE / 10;
+      expect(await balance(bank, bank.treasury)).to.equal(treasuryBefore + slash);
+      const originalArbiter = await bank.escrows.account.arbiter.fetch(arbiterPda(original.publicKey));
+      expect(originalArbiter.stake.toNumber()).to.equal(ARBITER_STAKE - slash);
+    });
+
+    it("Rejects an appeal once the window has closed", async () => {
+      const bank = await startBank();
+      await bank.escrows.methods
+        .setAppealWindow(new BN(60))
+        .accounts({ config: configPda, authority: bank.context.payer.publicKey })
+        .rpc();
+      const buyer = fund(bank.context);
+      const original = await addArbiter(bank);
+      const escrow = await createEscrow(bank, buyer, fund(bank.context).publicKey, ESCROW_AMOUNT);
+      const dispute = await createDispute(bank, buyer, escrow);
+      await assignArbiter(bank, dispute, escrow);
+      await resolveDispute(bank, original, escrow, { favorSeller: {} });
+
+      await warp(bank, 120);
+      await expectError(
+        bank.escrows.methods
+          .appealDispute("Too late")
+          .accounts({
+            dispute,
+            escrow,
+            config: configPda,
+            originalArbiter: arbiterPda(original.publicKey),
+            appellant: buyer.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([buyer])
+          .rpc(),
+        "AppealWindowClosed"
+      );
+    });
+  });
+
+  describe("arbiter assignment", () => {
+    it("Assigns a registered arbiter and counts the case against them", async () => {
+      const bank = await startBank();
+      const arbiters = [await addArbiter(bank), await addArbiter(bank)];
+      const buyer = fund(bank.context);
+      const escrow = await createEscrow(bank, buyer, fund(bank.context).publicKey, 0.1 * LAMPORTS_PER_SOL);
+      const dispute = await createDispute(bank, buyer, escrow);
+
+      const assigned = await assignArbiter(bank, dispute, escrow);
+
+      const assignedArbiter = arbiters.find((arbiter) => arbiter.publicKey.equals(assigned));
+      expect(assignedArbiter).to.not.be.undefined;
+      const account = await bank.escrows.account.arbiter.fetch(arbiterPda(assigned));
+      expect(account.openCases).to.equal(1);
+
+      await expectError(assignArbiter(bank, dispute, escrow), "ArbiterAlreadyAssigned");
+    });
+
+    it("Never assigns an arbiter who is a party to the escrow", async () => {
+      const bank = await startBank();
+      const seller = await addArbiter(bank);
+      const arbiter = await addArbiter(bank);
+      const buyer = fund(bank.context);
+      const escrow = await createEscrow(bank, buyer, seller.publicKey, 0.1 * LAMPORTS_PER_SOL);
+      const dispute = await createDispute(bank, buyer, escrow);
+
+      const assigned = await assignArbiter(bank, dispute, escrow);
+      expect(assigned.toString()).to.equal(arbiter.publicKey.toString());
+    });
+
+    it("Rejects a ruling from an arbiter who wasn't assigned", async () => {
+      const bank = await startBank();
+      const arbiters = [await addArbiter(bank), await addArbiter(bank)];
+      const buyer = fund(bank.context);
+      const escrow = await createEscrow(bank, buyer, fund(bank.context).publicKey, 0.1 * LAMPORTS_PER_SOL);
+      const dispute = await createDispute(bank, buyer, escrow);
+
+      // Nobody can rule before an arbiter is assigned
+      await expectError(resolveDispute(bank, arbiters[0], escrow, { favorBuyer: {} }), "ArbiterNotAssigned");
+
+      const assigned = await assignArbiter(bank, dispute, escrow);
+      const other = arbiters.find((arbiter) => !arbiter.publicKey.equals(assigned));
+      await expectError(resolveDispute(bank, other, escrow, { favorBuyer: {} }), "UnauthorizedArbiter");
+
+      const account = await bank.escrows.account.dispute.fetch(dispute);
+      expect(account.status).to.deep.equal({ open: {} });
+    });
+
+    it("Waits for the draw slot set when the dispute was opened", async () => {
+      const bank = await startBank();
+      await addArbiter(bank);
+      const buyer = fund(bank.context);
+      const escrow = await createEscrow(bank, buyer, fund(bank.context).publicKey, 0.1 * LAMPORTS_PER_SOL);
+      const dispute = await createDispute(bank, buyer, escrow);
+
+      const { slot } = await bank.context.banksClient.getClock();
+      const account = await bank.escrows.account.dispute.fetch(dispute);
+      expect(BigInt(account.arbiterDrawSlot.toString()) > slot).to.be.true;
+
+      await warp(bank);
+      await expectError(
+        bank.escrows.methods
+          .assignArbiter()
+          .accounts({
+            dispute,
+            escrow,
+            arbiterRegistry: registryPda,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          })
+          .remainingAccounts((await bank.escrows.account.arbiterRegistry.fetch(registryPda)).arbiters.map(
+            (arbiter) => ({ pubkey: arbiterPda(arbiter), isWritable: true, isSigner: false })
+          ))
+          .rpc(),
+        "ArbiterDrawPending"
+      );
+
+      expect(await assignArbiter(bank, dispute, escrow)).to.not.be.null;
+    });
+
+    it("Rejects a draw from a hand-picked subset of the registry", async () => {
+      const bank = await startBank();
+      const chosen = await addArbiter(bank);
+      await addArbiter(bank);
+      const buyer = fund(bank.context);
+      const escrow = await createEscrow(bank, buyer, fund(bank.context).publicKey, 0.1 * LAMPORTS_PER_SOL);
+      const dispute = await createDispute(bank, buyer, escrow);
+
+      await expectError(
+        bank.escrows.methods
+          .assignArbiter()
+          .accounts({
+            dispute,
+            escrow,
+            arbiterRegistry: registryPda,
+            slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          })
+          .remainingAccounts([{ pubkey: arbiterPda(chosen.publicKey), isWritable: true, isSigner: false }])
+          .rpc(),
+        "InvalidArbiterCandidates"
+      );
+    });
+  });
+
+  describe("arbiter exit", () => {
+    const withdrawStake = (bank: Bank, arbiter: Keypair) =>
+      bank.escrows.methods
+        .withdrawArbiterStake()
+        .accounts({
+          arbiter: arbiterPda(arbiter.publicKey),
+          arbiterRegistry: registryPda,
+          arbiterAccount: arbiter.publicKey,
+        })
+        .signers([arbiter])
+        .rpc();
+
+    it("Refuses to let an arbiter with an open case exit", async () => {
+      const bank = await startBank();
+      const arbiter = await addArbiter(bank);
+      const buyer = fund(bank.context);
+      const escrow = await createEscrow(bank, buyer, fund(bank.context).publicKey, 0.1 * LAMPORTS_PER_SOL);
+      const dispute = await createDispute(bank, buyer, escrow);
+      await assignArbiter(bank, dispute, escrow);
+
+      await expectError(withdrawStake(bank, arbiter), "ArbiterHasOpenCases");
+
+      const account = await bank.escrows.account.arbiter.fetch(arbiterPda(arbiter.publicKey));
+      expect(account.isActive).to.be.true;
+      expect(account.stake.toNumber()).to.equal(ARBITER_STAKE);
+    });
+
+    it("Returns the stake and leaves the registry once the case is resolved", async () => {
+      const bank = await startBank();
+      const arbiter = await addArbiter(bank);
+      const buyer = fund(bank.context);
+      const escrow = await createEscrow(bank, buyer, fund(bank.context).publicKey, 0.1 * LAMPORTS_PER_SOL);
+      const dispute = await createDispute(bank, buyer, escrow);
+      await assignArbiter(bank, dispute, escrow);
+      await resolveDispute(bank, arbiter, escrow, { favorBuyer: {} });
+
+      const walletBefore = await balance(bank, arbiter.publicKey);
+      await withdrawStake(bank, arbiter);
+
+      expect(await balance(bank, arbiter.publicKey)).to.equal(walletBefore + ARBITER_STAKE);
+      const account = await bank.escrows.account.arbiter.fetch(arbiterPda(arbiter.publicKey));
+      expect(account.isActive).to.be.false;
+      expect(account.stake.toNumber()).to.equal(0);
+      expect(account.casesResolved).to.equal(1);
+      const registry = await bank.escrows.account.arbiterRegistry.fetch(registryPda);
+      expect(registry.arbiters).to.be.empty;
+
+      await warp(bank);
+      await expectError(withdrawStake(bank, arbiter), "ArbiterInactive");
+    });
+
+    it("Keeps a held ruling open against the arbiter until it is finalized", async () => {
+      const bank = await startBank();
+      await bank.escrows.methods
+        .setAppealWindow(new BN(60))
+        .accounts({ config: configPda, authority: bank.context.payer.publicKey })
+        .rpc();
+      const arbiter = await addArbiter(bank);
+      const buyer = fund(bank.context);
+      const seller = fund(bank.context).publicKey;
+      const escrow = await createEscrow(bank, buyer, seller, 0.1 * LAMPORTS_PER_SOL);
+      const dispute = await createDispute(bank, buyer, escrow);
+      await assignArbiter(bank, dispute, escrow);
+      await resolveDispute(bank, arbiter, escrow, { favorSeller: {} });
+
+      // The ruling can still be appealed, so the arbiter can't walk away from a slash
+      await expectError(withdrawStake(bank, arbiter), "ArbiterHasOpenCases");
+
+      await warp(bank, 120);
+      await bank.escrows.methods
+        .finalizeDispute()
+        .accounts({
+          dispute,
+          escrow,
+          config: configPda,
+          arbiter: arbiterPda(arbiter.publicKey),
+          buyer: buyer.publicKey,
+          seller,
+          escrowTokenAccount: null,
+          buyerTokenAccount: null,
+          sellerTokenAccount: null,
+          tokenProgram: null,
+        })
+        .rpc();
+
+      await withdrawStake(bank, arbiter);
+      const account = await bank.escrows.account.arbiter.fetch(arbiterPda(arbiter.publicKey));
+      expect(account.isActive).to.be.false;
+    });
+  });
+
+  describe("notification events", () => {
+    // Process a transaction directly on the bank and decode the events from its logs, by name
+    const emittedEvents = async (
+      bank: Bank,
+      builder: { transaction(): Promise<anchor.web3.Transaction> },
+      signers: Keypair[] = []
+    ) => {
+      await warp(bank);
+      const tx = await builder.transaction();
+      tx.recentBlockhash = bank.context.lastBlockhash;
+      tx.feePayer = bank.context.payer.publicKey;
+      tx.sign(bank.context.payer, ...signers);
+      const { logMessages } = await bank.context.banksClient.processTransaction(tx);
+      const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(IDL));
+      return Object.fromEntries([...parser.parseLogs(logMessages)].map((event) => [event.name, event.data]));
+    };
+
+    const expectParties = (
+      event: Record<string, unknown>,
+      buyer: PublicKey,
+      seller: PublicKey,
+      amount: number
+    ) => {
+      expect(event, "event not emitted").to.not.be.undefined;
+      expect((event.buyer as PublicKey).toString()).to.equal(buyer.toString());
+      expect((event.seller as PublicKey).toString()).to.equal(seller.toString());
+      expect((event.amount as BN).toNumber()).to.equal(amount);
+    };
+
+    const createEscrowTx = (bank: Bank, escrow: PublicKey, buyer: PublicKey, seller: PublicKey, amount: number) =>
+      bank.escrows.methods
+        .createEscrow(new BN(amount), "Escrow", null)
+        .accounts({
+          escrow,
+          config: configPda,
+          buyer,
+          seller,
+          treasury: bank.treasury,
+          kycCredential: null,
+          systemProgram: SystemProgram.programId,
+        });
+
+    it("Names both parties when an escrow is created and released", async () => {
+      const bank = await startBank();
+      const buyer = fund(bank.context);
+      const seller = fund(bank.context).publicKey;
+      const amount = 0.5 * LAMPORTS_PER_SOL;
+      const escrow = await nextEscrow(bank, buyer.publicKey);
+
+      const created = await emittedEvents(bank, createEscrowTx(bank, escrow, buyer.publicKey, seller, amount), [buyer]);
+      expectParties(created.EscrowCreated, buyer.publicKey, seller, amount);
+
+      const released = await emittedEvents(
+        bank,
+        bank.escrows.methods.releaseEscrow().accounts({ escrow, authority: buyer.publicKey, seller }),
+        [buyer]
+      );
+      expectParties(released.EscrowReleased, buyer.publicKey, seller, amount);
+    });
+
+    it("Names both parties through a dispute", async () => {
+      const bank = await startBank();
+      const arbiter = await addArbiter(bank);
+      const buyer = fund(bank.context);
+      const seller = fund(bank.context);
+      const amount = 0.5 * LAMPORTS_PER_SOL;
+      const escrow = await createEscrow(bank, buyer, seller.publicKey, amount);
+      const dispute = disputePda(escrow);
+
+      const created = await emittedEvents(
+        bank,
+        bank.escrows.methods.createDispute({ notDelivered: {} }, "Goods not received").accounts({
+          dispute,
+          escrow,
+          config: configPda,
+          disputer: seller.publicKey,
+          systemProgram: SystemProgram.programId,
+        }),
+        [seller]
+      );
+      expectParties(created.DisputeCreated, buyer.publicKey, seller.publicKey, amount);
+
+      await warpPastDraw(bank, dispute);
+      const assigned = await emittedEvents(
+        bank,
+        bank.escrows.methods
+          .assignArbiter()
+          .accounts({ dispute, escrow, arbiterRegistry: registryPda, slotHashes: SYSVAR_SLOT_HASHES_PUBKEY })
+          .remainingAccounts([{ pubkey: arbiterPda(arbiter.publicKey), isWritable: true, isSigner: false }])
+      );
+      expectParties(assigned.ArbiterAssigned, buyer.publicKey, seller.publicKey, amount);
+
+      // The seller filed and lost, so the buyer is rebated half the fee
+      const resolved = await emittedEvents(
+        bank,
+        bank.escrows.methods.resolveDispute({ favorBuyer: {} }, { notDelivered: {} }, "Ruling").accounts({
+          dispute,
+          escrow,
+          arbiter: arbiterPda(arbiter.publicKey),
+          config: configPda,
+          buyer: buyer.publicKey,
+          seller: seller.publicKey,
+          arbiterAccount: arbiter.publicKey,
+          treasury: bank.treasury,
+          originalArbiter: null,
+          escrowTokenAccount: null,
+          buyerTokenAccount: null,
+          sellerTokenAccount: null,
+          tokenProgram: null,
+        }),
+        [arbiter]
+      );
+      expectParties(resolved.DisputeResolved, buyer.publicKey, seller.publicKey, amount);
+      expectParties(resolved.ArbitrationFeePaid, buyer.publicKey, seller.publicKey, ARBITRATION_FEE / 2);
+      expectParties(resolved.DisputeFeeRebated, buyer.publicKey, seller.publicKey, ARBITRATION_FEE / 2);
+    });
+
+    it("Names both parties when a held ruling is appealed or finalized", async () => {
+      const bank = await startBank();
+      await bank.escrows.methods
+        .setAppealWindow(new BN(60))
+        .accounts({ config: configPda, authority: bank.context.payer.publicKey })
+        .rpc();
+      const arbiter = await addArbiter(bank);
+      const buyer = fund(bank.context);
+      const seller = fund(bank.context).publicKey;
+      const amount = 0.5 * LAMPORTS_PER_SOL;
+
+      const appealed = await createEscrow(bank, buyer, seller, amount);
+      await assignArbiter(bank, await createDispute(bank, buyer, appealed), appealed);
+      await resolveDispute(bank, arbiter, appealed, { favorSeller: {} });
+      const finalized = await createEscrow(bank, buyer, seller, amount);
+      await assignArbiter(bank, await createDispute(bank, buyer, finalized), finalized);
+      await resolveDispute(bank, arbiter, finalized, { favorSeller: {} });
+
+      const appeal = await emittedEvents(
+        bank,
+        bank.escrows.methods.appealDispute("Ruling ignored the tracking history").accounts({
+          dispute: disputePda(appealed),
+          escrow: appealed,
+          config: configPda,
+          originalArbiter: arbiterPda(arbiter.publicKey),
+          appellant: buyer.publicKey,
+          systemProgram: SystemProgram.programId,
+        }),
+        [buyer]
+      );
+      expectParties(appeal.DisputeAppealed, buyer.publicKey, seller, amount);
+
+      await warp(bank, 120);
+      const finalize = await emittedEvents(
+        bank,
+        bank.escrows.methods.finalizeDispute().accounts({
+          dispute: disputePda(finalized),
+          escrow: finalized,
+          config: configPda,
+          arbiter: arbiterPda(arbiter.publicKey),
+          buyer: buyer.publicKey,
+          seller,
+          escrowTokenAccount: null,
+          buyerTokenAccount: null,
+          sellerTokenAccount: null,
+          tokenProgram: null,
+        })
+      );
+      expectParties(finalize.DisputeFinalized, buyer.publicKey, seller, amount);
+    });
+
+    it("Names both parties for milestone and draft events", async () => {
+      const bank = await startBank();
+      const buyer = fund(bank.context);
+      const seller = fund(bank.context).publicKey;
+      const escrow = await createMilestoneEscrow(bank, buyer, seller, [
+        { amount: 0.2 * LAMPORTS_PER_SOL },
+        { amount: 0.3 * LAMPORTS_PER_SOL },
+      ]);
+
+      const milestone = await emittedEvents(
+        bank,
+        bank.escrows.methods.releaseMilestone(1).accounts({ escrow, authority: buyer.publicKey, seller }),
+        [buyer]
+      );
+      expectParties(milestone.MilestoneReleased, buyer.publicKey, seller, 0.3 * LAMPORTS_PER_SOL);
+
+      const draft = await nextEscrow(bank, buyer.publicKey);
+      const drafted = await emittedEvents(
+        bank,
+        bank.escrows.methods.createDraftEscrow(new BN(LAMPORTS_PER_SOL), "Draft terms", null).accounts({
+          escrow: draft,
+          config: configPda,
+          buyer: buyer.publicKey,
+          seller,
+          treasury: bank.treasury,
+          kycCredential: null,
+          systemProgram: SystemProgram.programId,
+        }),
+        [buyer]
+      );
+      expectParties(drafted.EscrowDraftCreated, buyer.publicKey, seller, LAMPORTS_PER_SOL);
+
+      const cancelled = await emittedEvents(
+        bank,
+        bank.escrows.methods.cancelDraft().accounts({ escrow: draft, authority: buyer.publicKey, buyer: buyer.publicKey }),
+        [buyer]
+      );
+      expectParties(cancelled.EscrowDraftCancelled, buyer.publicKey, seller, LAMPORTS_PER_SOL);
+    });
+  });
+
+  describe("escrow limits and KYC", () => {
+    const KYC_THRESHOLD = LAMPORTS_PER_SOL;
+
+    // A token account holding `amount` of the KYC SBT mint
+    const setCredential = (bank: Bank, mint: PublicKey, owner: PublicKey, amount = 1) => {
+      const address = Keypair.generate().publicKey;
+      const data = Buffer.alloc(ACCOUNT_SIZE);
+      AccountLayout.encode(
+        {
+          mint,
+          owner,
+          amount: BigInt(amount),
+          delegateOption: 0,
+          delegate: PublicKey.default,
+          state: AccountState.Initialized,
+          isNativeOption: 0,
+          isNative: BigInt(0),
+          delegatedAmount: BigInt(0),
+          closeAuthorityOption: 0,
+          closeAuthority: PublicKey.default,
+        },
+        data
+      );
+      bank.context.setAccount(address, { lamports: LAMPORTS_PER_SOL, data, owner: TOKEN_PROGRAM_ID, executable: false });
+      return address;
+    };
+
+    // A bank requiring KYC above 1 SOL
+    const startKycBank = async () => {
+      const bank = await startBank();
+      const kycMint = Keypair.generate().publicKey;
+      await bank.escrows.methods
+        .setKycRequirement(kycMint, new BN(KYC_THRESHOLD))
+        .accounts({ config: configPda, authority: bank.context.payer.publicKey })
+        .rpc();
+      return { bank, kycMint };
+    };
+
+    it("Creates an escrow at the threshold without KYC", async () => {
+      const { bank } = await startKycBank();
+      const buyer = fund(bank.context);
+
+      const escrow = await createEscrow(bank, buyer, fund(bank.context).publicKey, KYC_THRESHOLD);
+
+      const account = await bank.escrows.account.escrow.fetch(escrow);
+      expect(account.amount.toNumber()).to.equal(KYC_THRESHOLD);
+    });
+
+    it("Rejects a large escrow without KYC", async () => {
+      const { bank } = await startKycBank();
+      const buyer = fund(bank.context);
+
+      await expectError(
+        createEscrow(bank, buyer, fund(bank.context).publicKey, 2 * LAMPORTS_PER_SOL),
+        "KycRequired"
+      );
+    });
+
+    it("Allows a large escrow with a valid KYC SBT", async () => {
+      const { bank, kycMint } = await startKycBank();
+      const buyer = fund(bank.context);
+      const credential = setCredential(bank, kycMint, buyer.publicKey);
+
+      const escrow = await createEscrow(bank, buyer, fund(bank.context).publicKey, 2 * LAMPORTS_PER_SOL, credential);
+
+      const account = await bank.escrows.account.escrow.fetch(escrow);
+      expect(account.amount.toNumber()).to.equal(2 * LAMPORTS_PER_SOL);
+    });
+
+    it("Rejects a credential from another mint or holder", async () => {
+      const { bank, kycMint } = await startKycBank();
+      const buyer = fund(bank.context);
+      const seller = fund(bank.context).publicKey;
+
+      for (const credential of [
+        setCredential(bank, Keypair.generate().publicKey, buyer.publicKey),
+        setCredential(bank, kycMint, seller),
+        setCredential(bank, kycMint, buyer.publicKey, 0),
+      ]) {
+        await expectError(
+          createEscrow(bank, buyer, seller, 2 * LAMPORTS_PER_SOL, credential),
+          "InvalidKycCredential"
+        );
+      }
+    });
+
+    it("Rejects an escrow above the maximum amount, even with KYC", async () => {
+      const { bank, kycMint } = await startKycBank();
+      await bank.escrows.methods
+        .setMaxEscrowAmount(new BN(5 * LAMPORTS_PER_SOL))
+        .accounts({ config: configPda, authority: bank.context.payer.publicKey })
+        .rpc();
+      const buyer = fund(bank.context, 20 * LAMPORTS_PER_SOL);
+      const seller = fund(bank.context).publicKey;
+      const credential = setCredential(bank, kycMint, buyer.publicKey);
+
+      await expectError(
+        createEscrow(bank, buyer, seller, 6 * LAMPORTS_PER_SOL, credential),
+        "EscrowAmountTooLarge"
+      );
+      await createEscrow(bank, buyer, seller, 5 * LAMPORTS_PER_SOL, credential);
+    });
+  });
+});
@@ -122,6 +122,18 @@ pub mod fiat_bridge {
         // Mark transaction as processed
         ctx.accounts.processed_tx.store(1, Ordering::Relaxed);
         
+        // Accrue the deposit reward to the user's ledger for later claiming
+        let reward_ledger = &mut ctx.accounts.reward_ledger;
+        reward_ledger.user = user;
+        reward_ledger.accrued_rewards = reward_ledger.accrued_rewards
+            .checked_add(reward)
+            .ok_or(ErrorCode::MathOverflow)?;
+        reward_ledger.deposit_count = reward_ledger.deposit_count
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        reward_ledger.last_accrued_at = Clock::get()?.unix_timestamp;
+        reward_ledger.bump = ctx.bumps.reward_ledger;
+        
         // Emit event
         emit!(FiatDepositProcessed {
             user,
@@ -135,6 +147,83 @@ pub mod fiat_bridge {
         Ok(())
     }
     
+    // Top up the reward vault that deposit rewards are claimed from (admin only)
+    pub fn fund_reward_vault(
+        ctx: Context<FundRewardVault>,
+        amount: u64,
+    ) -> Result<()> {
+        let transfer_ix = Transfer {
+            from: ctx.accounts.admin_ata.to_account_info(),
+            to: ctx.accounts.reward_vault.to_account_info(),
+            authority: ctx.accounts.admin.to_account_info(),
+        };
+        
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            transfer_ix,
+        );
+        
+        token::transfer(cpi_ctx, amount)?;
+        ctx.accounts.reward_vault.reload()?;
+        
+        emit!(RewardVaultFunded {
+            admin: *ctx.accounts.admin.key,
+            amount,
+            vault_balance: ctx.accounts.reward_vault.amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    // Claim all accrued deposit rewards from the reward vault, zeroing the user's ledger.
+    // Deposit liquidity in the bridge vault is never used for rewards.
+    pub fn claim_bridge_rewards(ctx: Context<ClaimBridgeRewards>) -> Result<()> {
+        let reward_ledger = &mut ctx.accounts.reward_ledger;
+        let amount = reward_ledger.accrued_rewards;
+        
+        if amount > 0 {
+            require!(
+                ctx.accounts.reward_vault.amount >= amount,
+                ErrorCode::InsufficientRewardVault
+            );
+            
+            let transfer_ix = Transfer {
+                from: ctx.accounts.reward_vault.to_account_info(),
+                to: ctx.accounts.user_ata.to_account_info(),
+                authority: ctx.accounts.bridge_state.to_account_info(),
+            };
+            
+            let seeds = &[
+                b"bridge_state".as_ref(),
+                &[ctx.accounts.bridge_state.bump],
+            ];
+            let signer = &[&seeds[..]];
+            
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                transfer_ix,
+                signer,
+            );
+            
+            token::transfer(cpi_ctx, amount)?;
+        }
+        
+        reward_ledger.accrued_rewards = 0;
+        reward_ledger.total_claimed = reward_ledger.total_claimed
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        emit!(BridgeRewardsClaimed {
+            user: reward_ledger.user,
+            amount,
+            total_claimed: reward_ledger.total_claimed,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
     // Withdraw fees (admin only)
     pub fn withdraw_fees(
         ctx: Context<WithdrawFees>,
@@ -209,7 +298,7 @@ pub struct InitializeBridge<'info> {
 
 // Accounts for process_fiat_deposit
 #[derive(Accounts)]
-#[instruction(circle_tx_id: String)]
+#[instruction(amount: u64, user: Pubkey, circle_tx_id: String)]
 pub struct ProcessFiatDeposit<'info> {
     #[account(
         seeds = [b"bridge_state"],
@@ -232,6 +321,15 @@ pub struct ProcessFiatDeposit<'info> {
     )]
     pub processed_tx: AccountLoader<'info, u8>,
     
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = RewardLedger::LEN,
+        seeds = [b"reward_ledger", user.as_ref()],
+        bump,
+    )]
+    pub reward_ledger: Account<'info, RewardLedger>,
+    
     #[account(mut)]
     pub user_ata: Account<'info, TokenAccount>,
     
@@ -242,6 +340,81 @@ pub struct ProcessFiatDeposit<'info> {
     pub system_program: Program<'info, System>,
 }
 
+// Accounts for claim_bridge_rewards
+#[derive(Accounts)]
+pub struct ClaimBridgeRewards<'info> {
+    #[account(
+        seeds = [b"bridge_state"],
+        bump = bridge_state.bump,
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward_ledger", user.key().as_ref()],
+        bump = reward_ledger.bump,
+        has_one = user @ ErrorCode::Unauthorized,
+    )]
+    pub reward_ledger: Account<'info, RewardLedger>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward_vault"],
+        bump,
+        token::mint = bridge_state.usdc_mint,
+        token::authority = bridge_state,
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = bridge_state.usdc_mint,
+        token::authority = user,
+    )]
+    pub user_ata: Account<'info, TokenAccount>,
+    
+    pub user: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+// Accounts for fund_reward_vault
+#[derive(Accounts)]
+pub struct FundRewardVault<'info> {
+    #[account(
+        seeds = [b"bridge_state"],
+        bump = bridge_state.bump,
+        has_one = admin,
+        has_one = usdc_mint,
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    pub usdc_mint: Account<'info, Mint>,
+    
+    #[account(
+        init_if_needed,
+        payer = admin,
+        seeds = [b"reward_vault"],
+        bump,
+        token::mint = usdc_mint,
+        token::authority = bridge_state,
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = usdc_mint,
+        token::authority = admin,
+    )]
+    pub admin_ata: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
 // Accounts for withdraw_fees
 #[derive(Accounts)]
 pub struct WithdrawFees<'info> {
@@ -274,6 +447,21 @@ pub struct BridgeState {
     pub bump: u8,
 }
 
+// Per-user ledger of deposit rewards awaiting claim
+#[account]
+pub struct RewardLedger {
+    pub user: Pubkey,
+    pub accrued_rewards: u64,
+    pub total_claimed: u64,
+    pub deposit_count: u64,
+    pub last_accrued_at: i64,
+    pub bump: u8,
+}
+
+impl RewardLedger {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 8 + 1;
+}
+
 // Events
 #[event]
 pub struct FiatDepositProcessed {
@@ -285,6 +473,22 @@ pub struct FiatDepositProcessed {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct BridgeRewardsClaimed {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub total_claimed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardVaultFunded {
+    pub admin: Pubkey,
+    pub amount: u64,
+    pub vault_balance: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct FeesWithdrawn {
     pub admin: Pubkey,
@@ -301,4 +505,6 @@ pub enum ErrorCode {
     TransactionAlreadyProcessed,
     #[msg("Unauthorized")]
     Unauthorized,
+    #[msg("Reward vault cannot cover the claim")]
+    InsufficientRewardVault,
 }
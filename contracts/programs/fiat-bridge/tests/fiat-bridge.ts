@@ -0,0 +1,222 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, LAMPORTS_PER_SOL, SystemProgram } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createAssociatedTokenAccount,
+  createMint,
+  getAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { FiatBridge } from "../target/types/fiat_bridge";
+import { expect } from "chai";
+
+describe("fiat-bridge", () => {
+  // Configure the client to use the local cluster.
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.FiatBridge as Program<FiatBridge>;
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+  const payer = (provider.wallet as anchor.Wallet).payer;
+
+  const FEE_BASIS_POINTS = 100;
+  const REWARD_BASIS_POINTS = 200;
+  const REWARD_VAULT_FUNDING = 1_000_000;
+
+  const [bridgeStatePda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("bridge_state")],
+    program.programId
+  );
+
+  const rewardLedgerPda = (user: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("reward_ledger"), user.toBuffer()],
+      program.programId
+    )[0];
+
+  const [rewardVaultPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("reward_vault")],
+    program.programId
+  );
+
+  const processedTxPda = (circleTxId: string) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("processed_tx"), Buffer.from(circleTxId)],
+      program.programId
+    )[0];
+
+  const feeVault = Keypair.generate();
+  const bridgeVault = Keypair.generate();
+  let usdcMint: PublicKey;
+
+  const fundedKeypair = async () => {
+    const keypair = Keypair.generate();
+    const signature = await provider.connection.requestAirdrop(keypair.publicKey, LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(signature);
+    return keypair;
+  };
+
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    try {
+      await promise;
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal(code);
+      return;
+    }
+    expect.fail(`expected ${code}`);
+  };
+
+  const tokenBalance = async (address: PublicKey) =>
+    Number((await getAccount(provider.connection, address)).amount);
+
+  // A funded user with an empty USDC account
+  const createUser = async () => {
+    const user = await fundedKeypair();
+    const userAta = await createAssociatedTokenAccount(provider.connection, payer, usdcMint, user.publicKey);
+    return { user, userAta };
+  };
+
+  const processDeposit = (user: PublicKey, userAta: PublicKey, amount: number) => {
+    const circleTxId = Keypair.generate().publicKey.toBase58().slice(0, 16);
+    return program.methods
+      .processFiatDeposit(new BN(amount), user, circleTxId)
+      .accounts({
+        bridgeState: bridgeStatePda,
+        bridgeVault: bridgeVault.publicKey,
+        feeVault: feeVault.publicKey,
+        processedTx: processedTxPda(circleTxId),
+        rewardLedger: rewardLedgerPda(user),
+        userAta,
+        admin: provider.wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  };
+
+  const claimRewards = (user: Keypair, userAta: PublicKey, ledgerOwner = user.publicKey) =>
+    program.methods
+      .claimBridgeRewards()
+      .accounts({
+        bridgeState: bridgeStatePda,
+        rewardLedger: rewardLedgerPda(ledgerOwner),
+        rewardVault: rewardVaultPda,
+        userAta,
+        user: user.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([user])
+      .rpc();
+
+  before(async () => {
+    usdcMint = await createMint(provider.connection, payer, payer.publicKey, null, 6);
+
+    await program.methods
+      .initializeBridge(FEE_BASIS_POINTS, REWARD_BASIS_POINTS)
+      .accounts({
+        bridgeState: bridgeStatePda,
+        admin: provider.wallet.publicKey,
+        usdcMint,
+        feeVault: feeVault.publicKey,
+        bridgeVault: bridgeVault.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([feeVault, bridgeVault])
+      .rpc();
+
+    // Bridge liquidity for deposits
+    await mintTo(provider.connection, payer, usdcMint, bridgeVault.publicKey, payer, 1_000_000_000);
+
+    // Rewards come out of their own vault, funded by the admin
+    const adminAta = await createAssociatedTokenAccount(provider.connection, payer, usdcMint, payer.publicKey);
+    await mintTo(provider.connection, payer, usdcMint, adminAta, payer, REWARD_VAULT_FUNDING);
+    await program.methods
+      .fundRewardVault(new BN(REWARD_VAULT_FUNDING))
+      .accounts({
+        bridgeState: bridgeStatePda,
+        usdcMint,
+        rewardVault: rewardVaultPda,
+        adminAta,
+        admin: provider.wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  });
+
+  describe("reward ledger", () => {
+    it("Accrues rewards across deposits and pays them out on claim", async () => {
+      const { user, userAta } = await createUser();
+
+      await processDeposit(user.publicKey, userAta, 1_000_000);
+      await processDeposit(user.publicKey, userAta, 500_000);
+
+      const accrued = await program.account.rewardLedger.fetch(rewardLedgerPda(user.publicKey));
+      expect(accrued.user.toString()).to.equal(user.publicKey.toString());
+      expect(accrued.accruedRewards.toNumber()).to.equal(30_000);
+      expect(accrued.depositCount.toNumber()).to.equal(2);
+      expect(accrued.totalClaimed.toNumber()).to.equal(0);
+
+      // Deposits pay out net of fees, with no reward inline
+      expect(await tokenBalance(userAta)).to.equal(1_485_000);
+
+      const bridgeLiquidity = await tokenBalance(bridgeVault.publicKey);
+      const rewardFunds = await tokenBalance(rewardVaultPda);
+      await claimRewards(user, userAta);
+
+      // Rewards are paid from the reward vault, leaving deposit liquidity alone
+      expect(await tokenBalance(bridgeVault.publicKey)).to.equal(bridgeLiquidity);
+      expect(await tokenBalance(rewardVaultPda)).to.equal(rewardFunds - 30_000);
+
+      const claimed = await program.account.rewardLedger.fetch(rewardLedgerPda(user.publicKey));
+      expect(claimed.accruedRewards.toNumber()).to.equal(0);
+      expect(claimed.totalClaimed.toNumber()).to.equal(30_000);
+      expect(await tokenBalance(userAta)).to.equal(1_515_000);
+    });
+
+    it("Pays nothing on a second claim", async () => {
+      const { user, userAta } = await createUser();
+      await processDeposit(user.publicKey, userAta, 1_000_000);
+      await claimRewards(user, userAta);
+      const balance = await tokenBalance(userAta);
+
+      // Wait for a fresh blockhash so the identical claim isn't deduplicated
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await claimRewards(user, userAta);
+
+      const ledger = await program.account.rewardLedger.fetch(rewardLedgerPda(user.publicKey));
+      expect(ledger.accruedRewards.toNumber()).to.equal(0);
+      expect(ledger.totalClaimed.toNumber()).to.equal(20_000);
+      expect(await tokenBalance(userAta)).to.equal(balance);
+    });
+
+    it("Rejects a claim the reward vault cannot cover", async () => {
+      const { user, userAta } = await createUser();
+      // A 2% reward on this deposit is more than the reward vault ever held
+      await processDeposit(user.publicKey, userAta, 100_000_000);
+      const bridgeLiquidity = await tokenBalance(bridgeVault.publicKey);
+
+      await expectError(claimRewards(user, userAta), "InsufficientRewardVault");
+
+      const ledger = await program.account.rewardLedger.fetch(rewardLedgerPda(user.publicKey));
+      expect(ledger.accruedRewards.toNumber()).to.equal(2_000_000);
+      expect(ledger.totalClaimed.toNumber()).to.equal(0);
+      expect(await tokenBalance(bridgeVault.publicKey)).to.equal(bridgeLiquidity);
+    });
+
+    it("Rejects claiming another user's ledger", async () => {
+      const owner = await createUser();
+      const other = await createUser();
+      await processDeposit(owner.user.publicKey, owner.userAta, 1_000_000);
+
+      await expectError(
+        claimRewards(other.user, other.userAta, owner.user.publicKey),
+        "ConstraintSeeds"
+      );
+
+      const ledger = await program.account.rewardLedger.fetch(rewardLedgerPda(owner.user.publicKey));
+      expect(ledger.accruedRewards.toNumber()).to.equal(20_000);
+    });
+  });
+});
@@ -1,9 +1,37 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Mint, Token, TokenAccount};
-use switchboard_v2::AggregatorAccountData;
+use switchboard_v2::{AggregatorAccountData, SwitchboardDecimal};
 
 declare_id!("FraudDetection1111111111111111111111111111111");
 
+pub const MAX_BULK_RISK_ADDRESSES: usize = 10;
+pub const MAX_RISK_DESCRIPTION_LEN: usize = 252;
+pub const TRANSACTION_TYPE_COUNT: usize = 6;
+/// Past transaction slots kept per user for burst detection
+pub const RECENT_TX_SLOTS: usize = 8;
+/// Most recent flags kept on a user profile; older ones are dropped
+pub const MAX_STORED_FLAGS: usize = 10;
+/// Flag descriptions stored on a user profile are truncated to this many bytes
+pub const MAX_FLAG_DESCRIPTION_LEN: usize = 100;
+/// Default age after which a price feed result is rejected
+pub const DEFAULT_ORACLE_MAX_STALENESS_SECONDS: i64 = 300;
+/// Default widest accepted price feed confidence interval, in US cents
+pub const DEFAULT_ORACLE_MAX_CONFIDENCE_CENTS: u64 = 100;
+/// Default largest transaction, in USD, allowed without KYC
+pub const DEFAULT_KYC_NONE_LIMIT_USD: u64 = 1000;
+/// Default largest transaction, in USD, before basic KYC users are asked to upgrade
+pub const DEFAULT_KYC_BASIC_LIMIT_USD: u64 = 10000;
+/// Default number of transactions inside `rapid_window_slots` that counts as a rapid burst
+pub const DEFAULT_RAPID_BURST_THRESHOLD: u8 = 2;
+/// Default short velocity window, roughly 30 seconds of slots
+pub const DEFAULT_VELOCITY_WINDOW_SLOTS: u64 = 60;
+/// Default number of transactions allowed inside the short velocity window
+pub const DEFAULT_VELOCITY_BURST_THRESHOLD: u8 = 5;
+/// Risk score added per flag, in `FlagSeverity` order
+pub const FLAG_SEVERITY_RISK_WEIGHTS: [u32; 4] = [1, 5, 15, 50];
+/// Users whose risk score exceeds this are blocked automatically
+pub const AUTO_BLOCK_RISK_SCORE: u32 = 100;
+
 #[program]
 pub mod fraud_detection {
     use super::*;
@@ -14,12 +42,32 @@ pub mod fraud_detection {
         high_value_threshold_usd: u64,
         velocity_threshold: u32,
         max_daily_volume_usd: u64,
+        slots_per_day: u64,
+        rapid_window_slots: u64,
     ) -> Result<()> {
+        require!(
+            slots_per_day > 0 && rapid_window_slots < slots_per_day,
+            FraudDetectionError::InvalidSlotConfiguration
+        );
+
         let compliance_config = &mut ctx.accounts.compliance_config;
         compliance_config.authority = authority;
         compliance_config.high_value_threshold_usd = high_value_threshold_usd;
         compliance_config.velocity_threshold = velocity_threshold;
         compliance_config.max_daily_volume_usd = max_daily_volume_usd;
+        compliance_config.slots_per_day = slots_per_day;
+        compliance_config.rapid_window_slots = rapid_window_slots;
+        compliance_config.rapid_burst_threshold = DEFAULT_RAPID_BURST_THRESHOLD;
+        compliance_config.velocity_window_slots = DEFAULT_VELOCITY_WINDOW_SLOTS;
+        compliance_config.velocity_burst_threshold = DEFAULT_VELOCITY_BURST_THRESHOLD;
+        compliance_config.oracle_max_staleness_seconds = DEFAULT_ORACLE_MAX_STALENESS_SECONDS;
+        compliance_config.oracle_max_confidence_cents = DEFAULT_ORACLE_MAX_CONFIDENCE_CENTS;
+        compliance_config.kyc_none_limit_usd = DEFAULT_KYC_NONE_LIMIT_USD;
+        compliance_config.kyc_basic_limit_usd = DEFAULT_KYC_BASIC_LIMIT_USD;
+        compliance_config.auto_unblock_enabled = false;
+        compliance_config.auto_unblock_cooldown_slots = 0;
+        compliance_config.min_kyc_by_transaction_type = [KYCLevel::None; TRANSACTION_TYPE_COUNT];
+        compliance_config.risk_list_version = 0;
         compliance_config.is_active = true;
         compliance_config.total_flagged_transactions = 0;
         compliance_config.total_blocked_transactions = 0;
@@ -31,6 +79,8 @@ pub mod fraud_detection {
             high_value_threshold_usd,
             velocity_threshold,
             max_daily_volume_usd,
+            slots_per_day,
+            rapid_window_slots,
             slot: compliance_config.last_updated_slot,
         });
 
@@ -53,10 +103,15 @@ pub mod fraud_detection {
         user_profile.daily_transaction_count = 0;
         user_profile.daily_volume_usd = 0;
         user_profile.last_transaction_slot = 0;
+        user_profile.recent_transaction_slots = [0; RECENT_TX_SLOTS];
         user_profile.last_daily_reset_slot = Clock::get()?.slot;
         user_profile.is_flagged = false;
         user_profile.is_blocked = false;
+        user_profile.blocked_at_slot = 0;
+        user_profile.is_critical_block = false;
+        user_profile.last_critical_flag_slot = 0;
         user_profile.flags = Vec::new();
+        user_profile.last_status = TransactionStatus::Approved;
         user_profile.bump = *ctx.bumps.get("user_profile").unwrap();
 
         emit!(UserProfileRegistered {
@@ -75,20 +130,28 @@ pub mod fraud_detection {
         risk_category: RiskCategory,
         risk_level: RiskLevel,
         description: String,
+        effective_from: u64,
+        effective_until: Option<u64>,
     ) -> Result<()> {
         let risk_registry = &mut ctx.accounts.risk_registry;
-        let compliance_config = &ctx.accounts.compliance_config;
+        let compliance_config = &mut ctx.accounts.compliance_config;
 
         require!(
             ctx.accounts.authority.key() == compliance_config.authority,
             FraudDetectionError::UnauthorizedAccess
         );
+        validate_effective_window(effective_from, effective_until)?;
+
+        let list_version = bump_risk_list_version(compliance_config)?;
 
         risk_registry.address = address;
         risk_registry.risk_category = risk_category;
-        risk_level = risk_level;
+        risk_registry.risk_level = risk_level.clone();
         risk_registry.description = description;
-        risk_registry.added_at_slot = Clock::get()?.slot;
+        risk_registry.added_at_slot = compliance_config.last_updated_slot;
+        risk_registry.effective_from = effective_from;
+        risk_registry.effective_until = effective_until;
+        risk_registry.list_version = list_version;
         risk_registry.is_active = true;
         risk_registry.bump = *ctx.bumps.get("risk_registry").unwrap();
 
@@ -96,12 +159,97 @@ pub mod fraud_detection {
             address,
             risk_category,
             risk_level,
+            effective_from,
+            effective_until,
+            list_version,
             slot: risk_registry.added_at_slot,
         });
 
         Ok(())
     }
 
+    /// Register several high-risk addresses at once. Each entry's registry PDA
+    /// (`[b"risk_registry", address]`) must be passed, in order, as a remaining account.
+    pub fn bulk_add_high_risk_addresses<'info>(
+        ctx: Context<'_, '_, '_, 'info, BulkAddHighRiskAddresses<'info>>,
+        entries: Vec<HighRiskAddressInput>,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.compliance_config.authority,
+            FraudDetectionError::UnauthorizedAccess
+        );
+        require!(
+            !entries.is_empty() && entries.len() <= MAX_BULK_RISK_ADDRESSES,
+            FraudDetectionError::InvalidBulkSize
+        );
+        require!(
+            ctx.remaining_accounts.len() == entries.len(),
+            FraudDetectionError::RiskRegistryAccountMismatch
+        );
+
+        let list_version = bump_risk_list_version(&mut ctx.accounts.compliance_config)?;
+        let current_slot = ctx.accounts.compliance_config.last_updated_slot;
+        let rent_lamports = Rent::get()?.minimum_balance(RiskRegistry::LEN);
+
+        for (entry, registry_info) in entries.into_iter().zip(ctx.remaining_accounts.iter()) {
+            require!(
+                entry.description.len() <= MAX_RISK_DESCRIPTION_LEN,
+                FraudDetectionError::DescriptionTooLong
+            );
+            validate_effective_window(entry.effective_from, entry.effective_until)?;
+
+            let (expected_key, bump) = Pubkey::find_program_address(
+                &[b"risk_registry", entry.address.as_ref()],
+                ctx.program_id,
+            );
+            require!(
+                registry_info.key() == expected_key && registry_info.data_is_empty(),
+                FraudDetectionError::RiskRegistryAccountMismatch
+            );
+
+            anchor_lang::system_program::create_account(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::CreateAccount {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: registry_info.clone(),
+                    },
+                    &[&[b"risk_registry", entry.address.as_ref(), &[bump]]],
+                ),
+                rent_lamports,
+                RiskRegistry::LEN as u64,
+                ctx.program_id,
+            )?;
+
+            let risk_registry = RiskRegistry {
+                address: entry.address,
+                risk_category: entry.risk_category.clone(),
+                risk_level: entry.risk_level.clone(),
+                description: entry.description,
+                added_at_slot: current_slot,
+                effective_from: entry.effective_from,
+                effective_until: entry.effective_until,
+                list_version,
+                is_active: true,
+                bump,
+            };
+            let mut data = registry_info.try_borrow_mut_data()?;
+            risk_registry.try_serialize(&mut &mut data[..])?;
+
+            emit!(HighRiskAddressAdded {
+                address: entry.address,
+                risk_category: entry.risk_category,
+                risk_level: entry.risk_level,
+                effective_from: entry.effective_from,
+                effective_until: entry.effective_until,
+                list_version,
+                slot: current_slot,
+            });
+        }
+
+        Ok(())
+    }
+
     pub fn monitor_transaction(
         ctx: Context<MonitorTransaction>,
         amount_lamports: u64,
@@ -109,11 +257,11 @@ pub mod fraud_detection {
         transaction_type: TransactionType,
     ) -> Result<TransactionStatus> {
         let user_profile = &mut ctx.accounts.user_profile;
-        let compliance_config = &ctx.accounts.compliance_config;
+        let compliance_config = &mut ctx.accounts.compliance_config;
         let current_slot = Clock::get()?.slot;
 
-        // Reset daily counters if needed (assuming ~2.5 slots per second, 216,000 slots per day)
-        if current_slot - user_profile.last_daily_reset_slot > 216_000 {
+        // Reset daily counters if needed (slot duration differs per cluster, so this is configured)
+        if current_slot - user_profile.last_daily_reset_slot > compliance_config.slots_per_day {
             user_profile.daily_transaction_count = 0;
             user_profile.daily_volume_usd = 0;
             user_profile.last_daily_reset_slot = current_slot;
@@ -123,36 +271,61 @@ pub mod fraud_detection {
         let usd_amount = get_usd_value_from_oracle(
             &ctx.accounts.price_oracle,
             amount_lamports,
+            compliance_config,
         )?;
 
         // Check if user is already blocked
         if user_profile.is_blocked {
+            record_status_transition(user_profile, TransactionStatus::Blocked, Vec::new(), current_slot);
+            compliance_config.total_blocked_transactions += 1;
+            compliance_config.last_updated_slot = current_slot;
             return Ok(TransactionStatus::Blocked);
         }
 
         let mut flags = Vec::new();
         let mut should_block = false;
 
+        // An actively whitelisted recipient or user skips the high-value, velocity and high-risk
+        // checks; each skipped flag is still emitted for compliance review. The whitelist PDA
+        // derivation is checked by the account constraints.
+        let user = user_profile.user;
+        let whitelisted = ctx.accounts.whitelist.as_ref().map_or(false, |whitelist| {
+            whitelist.is_active && (whitelist.address == recipient || whitelist.address == user)
+        });
+
         // High-value transaction check
         if usd_amount > compliance_config.high_value_threshold_usd {
-            flags.push(FraudFlag {
+            push_flag_unless_whitelisted(&mut flags, FraudFlag {
                 flag_type: FlagType::HighValueTransaction,
                 severity: FlagSeverity::High,
                 description: format!("Transaction amount ${} exceeds threshold ${}", 
                     usd_amount, compliance_config.high_value_threshold_usd),
                 detected_at_slot: current_slot,
-            });
+            }, whitelisted, user, recipient);
         }
 
         // Velocity check
         if user_profile.daily_transaction_count >= compliance_config.velocity_threshold {
-            flags.push(FraudFlag {
+            push_flag_unless_whitelisted(&mut flags, FraudFlag {
                 flag_type: FlagType::HighVelocity,
                 severity: FlagSeverity::Medium,
                 description: format!("Daily transaction count {} exceeds threshold {}", 
                     user_profile.daily_transaction_count, compliance_config.velocity_threshold),
                 detected_at_slot: current_slot,
-            });
+            }, whitelisted, user, recipient);
+        }
+
+        // Short-window velocity check for card-testing style bursts, counting this transaction
+        let window_count = user_profile.transactions_within(current_slot, compliance_config.velocity_window_slots);
+        if window_count > compliance_config.velocity_burst_threshold as usize {
+            push_flag_unless_whitelisted(&mut flags, FraudFlag {
+                flag_type: FlagType::HighVelocity,
+                severity: FlagSeverity::Critical,
+                description: format!("{} transactions within {} slots exceed burst threshold {}",
+                    window_count, compliance_config.velocity_window_slots,
+                    compliance_config.velocity_burst_threshold),
+                detected_at_slot: current_slot,
+            }, whitelisted, user, recipient);
         }
 
         // Daily volume check
@@ -168,50 +341,88 @@ pub mod fraud_detection {
             should_block = true;
         }
 
-        // Check recipient against high-risk registry
-        if let Ok(risk_registry) = ctx.remaining_accounts.get(0) {
-            let risk_data = risk_registry.try_borrow_data()?;
-            if risk_data.len() > 0 {
-                flags.push(FraudFlag {
+        // Check recipient against high-risk registry; only entries effective at this slot apply.
+        // The recipient's registry PDA must always be passed as the first remaining account, so
+        // a risky recipient can't be hidden by omitting it; an empty account means not listed.
+        let registry_info = ctx.remaining_accounts.get(0)
+            .ok_or(FraudDetectionError::RiskRegistryAccountMismatch)?;
+        let (expected_registry, _) = Pubkey::find_program_address(
+            &[b"risk_registry", recipient.as_ref()],
+            ctx.program_id,
+        );
+        require!(
+            registry_info.key() == expected_registry,
+            FraudDetectionError::RiskRegistryAccountMismatch
+        );
+        if !registry_info.data_is_empty() {
+            require!(
+                registry_info.owner == ctx.program_id,
+                FraudDetectionError::RiskRegistryAccountMismatch
+            );
+            let risk_registry = RiskRegistry::try_deserialize(&mut &registry_info.try_borrow_data()?[..])?;
+            if risk_registry.address == recipient && risk_registry.is_effective_at(current_slot) {
+                let flagged = push_flag_unless_whitelisted(&mut flags, FraudFlag {
                     flag_type: FlagType::HighRiskRecipient,
                     severity: FlagSeverity::Critical,
-                    description: "Transaction to high-risk address detected".to_string(),
+                    description: format!("Transaction to high-risk address detected (risk list v{})",
+                        compliance_config.risk_list_version),
                     detected_at_slot: current_slot,
-                });
-                should_block = true;
+                }, whitelisted, user, recipient);
+                should_block |= flagged;
             }
         }
 
-        // Unusual pattern detection (simplified)
-        let time_since_last_tx = current_slot - user_profile.last_transaction_slot;
-        if time_since_last_tx < 10 && user_profile.total_transaction_count > 0 {
+        // Unusual pattern detection: a burst of transactions inside the rapid window,
+        // counting this one and the user's recent transaction slots
+        let burst_size = user_profile.transactions_within(current_slot, compliance_config.rapid_window_slots);
+        if burst_size >= compliance_config.rapid_burst_threshold as usize {
+            let severity = if burst_size - compliance_config.rapid_burst_threshold as usize >= 2 {
+                FlagSeverity::High
+            } else {
+                FlagSeverity::Medium
+            };
             flags.push(FraudFlag {
                 flag_type: FlagType::UnusualPattern,
-                severity: FlagSeverity::Medium,
-                description: "Rapid successive transactions detected".to_string(),
+                severity,
+                description: format!("Rapid successive transactions detected ({} within {} slots)",
+                    burst_size, compliance_config.rapid_window_slots),
                 detected_at_slot: current_slot,
             });
         }
 
+        // Minimum KYC configured for this transaction type, regardless of amount
+        let required_kyc_level = compliance_config.min_kyc_by_transaction_type[transaction_type.index()];
+        if user_profile.kyc_level < required_kyc_level {
+            flags.push(FraudFlag {
+                flag_type: FlagType::KYCRequired,
+                severity: FlagSeverity::High,
+                description: format!("{:?} KYC required for this transaction type", required_kyc_level),
+                detected_at_slot: current_slot,
+            });
+            should_block = true;
+        }
+
         // KYC level checks
         match user_profile.kyc_level {
             KYCLevel::None => {
-                if usd_amount > 1000 {
+                if usd_amount > compliance_config.kyc_none_limit_usd {
                     flags.push(FraudFlag {
                         flag_type: FlagType::KYCRequired,
                         severity: FlagSeverity::High,
-                        description: "KYC required for transactions over $1000".to_string(),
+                        description: format!("KYC required for transactions over ${}",
+                            compliance_config.kyc_none_limit_usd),
                         detected_at_slot: current_slot,
                     });
                     should_block = true;
                 }
             },
             KYCLevel::Basic => {
-                if usd_amount > 10000 {
+                if usd_amount > compliance_config.kyc_basic_limit_usd {
                     flags.push(FraudFlag {
                         flag_type: FlagType::KYCUpgradeRequired,
                         severity: FlagSeverity::Medium,
-                        description: "Enhanced KYC required for transactions over $10,000".to_string(),
+                        description: format!("Enhanced KYC required for transactions over ${}",
+                            compliance_config.kyc_basic_limit_usd),
                         detected_at_slot: current_slot,
                     });
                 }
@@ -227,27 +438,29 @@ pub mod fraud_detection {
         user_profile.daily_transaction_count += 1;
         user_profile.daily_volume_usd += usd_amount;
         user_profile.last_transaction_slot = current_slot;
+        user_profile.recent_transaction_slots.rotate_right(1);
+        user_profile.recent_transaction_slots[0] = current_slot;
 
         // Calculate risk score based on flags
-        let risk_score_increase = flags.iter().map(|flag| {
-            match flag.severity {
-                FlagSeverity::Low => 1,
-                FlagSeverity::Medium => 5,
-                FlagSeverity::High => 15,
-                FlagSeverity::Critical => 50,
-            }
-        }).sum::<u32>();
+        let risk_score_increase = flags.iter().map(|flag| flag.severity.risk_weight()).sum::<u32>();
 
         user_profile.risk_score += risk_score_increase;
 
+        let has_critical_flag = flags.iter().any(|flag| flag.severity == FlagSeverity::Critical);
+        if has_critical_flag {
+            user_profile.last_critical_flag_slot = current_slot;
+        }
+
         // Auto-block if risk score is too high
-        if user_profile.risk_score > 100 {
+        if user_profile.risk_score > AUTO_BLOCK_RISK_SCORE {
             should_block = true;
             user_profile.is_blocked = true;
+            user_profile.blocked_at_slot = current_slot;
+            user_profile.is_critical_block = has_critical_flag;
         }
 
         // Store flags
-        user_profile.flags.extend(flags.clone());
+        user_profile.record_flags(flags.clone());
         if !flags.is_empty() {
             user_profile.is_flagged = true;
         }
@@ -261,6 +474,18 @@ pub mod fraud_detection {
             TransactionStatus::Approved
         };
 
+        let flag_types = flags.iter().map(|flag| flag.flag_type.clone()).collect();
+        record_status_transition(user_profile, status.clone(), flag_types, current_slot);
+
+        // Global counters for compliance dashboards
+        if !flags.is_empty() {
+            compliance_config.total_flagged_transactions += 1;
+        }
+        if status == TransactionStatus::Blocked {
+            compliance_config.total_blocked_transactions += 1;
+        }
+        compliance_config.last_updated_slot = current_slot;
+
         // Create transaction record
         let transaction_record = &mut ctx.accounts.transaction_record;
         transaction_record.user = user_profile.user;
@@ -269,7 +494,7 @@ pub mod fraud_detection {
         transaction_record.amount_usd = usd_amount;
         transaction_record.transaction_type = transaction_type;
         transaction_record.status = status;
-        transaction_record.flags = flags.clone();
+        transaction_record.record_flags(flags.clone());
         transaction_record.processed_at_slot = current_slot;
         transaction_record.bump = *ctx.bumps.get("transaction_record").unwrap();
 
@@ -310,29 +535,38 @@ pub mod fraud_detection {
         );
 
         // Update risk score based on AI analysis
-        user_profile.risk_score = (user_profile.risk_score + ai_risk_score) / 2;
+        let old_risk_score = user_profile.risk_score;
+        user_profile.risk_score = (old_risk_score + ai_risk_score) / 2;
 
         // Add AI-detected anomalies as flags
-        for indicator in anomaly_indicators {
-            user_profile.flags.push(FraudFlag {
-                flag_type: FlagType::AIAnomaly,
-                severity: if ai_risk_score > 75 { FlagSeverity::Critical } 
-                         else if ai_risk_score > 50 { FlagSeverity::High }
-                         else if ai_risk_score > 25 { FlagSeverity::Medium }
-                         else { FlagSeverity::Low },
-                description: indicator,
-                detected_at_slot: Clock::get()?.slot,
-            });
+        let detected_at_slot = Clock::get()?.slot;
+        user_profile.record_flags(anomaly_indicators.into_iter().map(|indicator| FraudFlag {
+            flag_type: FlagType::AIAnomaly,
+            severity: if ai_risk_score > 75 { FlagSeverity::Critical } 
+                     else if ai_risk_score > 50 { FlagSeverity::High }
+                     else if ai_risk_score > 25 { FlagSeverity::Medium }
+                     else { FlagSeverity::Low },
+            description: indicator,
+            detected_at_slot,
+        }));
+
+        if ai_risk_score > 75 {
+            user_profile.last_critical_flag_slot = Clock::get()?.slot;
         }
 
-        // Auto-block if AI risk score is critical
+        // Auto-block if the AI score itself is critical. This deliberately uses the raw AI score,
+        // not the averaged risk_score, so a clean history can't dilute a critical assessment.
         if ai_risk_score > 90 {
+            let slot = Clock::get()?.slot;
             user_profile.is_blocked = true;
+            user_profile.blocked_at_slot = slot;
+            user_profile.is_critical_block = true;
+            record_status_transition(user_profile, TransactionStatus::Blocked, vec![FlagType::AIAnomaly], slot);
         }
 
         emit!(AIRiskScoreUpdated {
             user: user_profile.user,
-            old_risk_score: user_profile.risk_score,
+            old_risk_score,
             new_risk_score: user_profile.risk_score,
             ai_risk_score,
             slot: Clock::get()?.slot,
@@ -379,7 +613,9 @@ pub mod fraud_detection {
         );
 
         user_profile.is_blocked = false;
+        user_profile.is_critical_block = false;
         user_profile.risk_score = user_profile.risk_score / 2; // Reduce risk score
+        record_status_transition(user_profile, TransactionStatus::Approved, Vec::new(), Clock::get()?.slot);
 
         emit!(UserUnblocked {
             user: user_profile.user,
@@ -389,21 +625,395 @@ pub mod fraud_detection {
 
         Ok(())
     }
+
+    /// Clear a user's flags after manual review. Blocking is left alone; see `unblock_user`.
+    pub fn clear_user_flags(
+        ctx: Context<ClearUserFlags>,
+        reset_risk_score: bool,
+    ) -> Result<()> {
+        let user_profile = &mut ctx.accounts.user_profile;
+        let compliance_config = &ctx.accounts.compliance_config;
+
+        require!(
+            ctx.accounts.authority.key() == compliance_config.authority,
+            FraudDetectionError::UnauthorizedAccess
+        );
+
+        let flags_cleared = user_profile.flags.len() as u32;
+        user_profile.flags.clear();
+        user_profile.is_flagged = false;
+        if reset_risk_score {
+            user_profile.risk_score = 0;
+        }
+
+        let slot = Clock::get()?.slot;
+        if !user_profile.is_blocked {
+            record_status_transition(user_profile, TransactionStatus::Approved, Vec::new(), slot);
+        }
+
+        emit!(UserFlagsCleared {
+            user: user_profile.user,
+            cleared_by: ctx.accounts.authority.key(),
+            flags_cleared,
+            risk_score_reset: reset_risk_score,
+            slot,
+        });
+
+        Ok(())
+    }
+
+    pub fn set_auto_unblock_policy(
+        ctx: Context<UpdateComplianceConfig>,
+        enabled: bool,
+        cooldown_slots: u64,
+    ) -> Result<()> {
+        let compliance_config = &mut ctx.accounts.compliance_config;
+
+        require!(
+            ctx.accounts.authority.key() == compliance_config.authority,
+            FraudDetectionError::UnauthorizedAccess
+        );
+
+        compliance_config.auto_unblock_enabled = enabled;
+        compliance_config.auto_unblock_cooldown_slots = cooldown_slots;
+        compliance_config.last_updated_slot = Clock::get()?.slot;
+
+        emit!(AutoUnblockPolicyUpdated {
+            enabled,
+            cooldown_slots,
+            slot: compliance_config.last_updated_slot,
+        });
+
+        Ok(())
+    }
+
+    pub fn set_min_kyc_level(
+        ctx: Context<UpdateComplianceConfig>,
+        transaction_type: TransactionType,
+        min_kyc_level: KYCLevel,
+    ) -> Result<()> {
+        let compliance_config = &mut ctx.accounts.compliance_config;
+
+        require!(
+            ctx.accounts.authority.key() == compliance_config.authority,
+            FraudDetectionError::UnauthorizedAccess
+        );
+
+        compliance_config.min_kyc_by_transaction_type[transaction_type.index()] = min_kyc_level;
+        compliance_config.last_updated_slot = Clock::get()?.slot;
+
+        emit!(MinKycLevelUpdated {
+            transaction_type,
+            min_kyc_level,
+            slot: compliance_config.last_updated_slot,
+        });
+
+        Ok(())
+    }
+
+    /// Configure burst detection: flag when `burst_threshold` transactions (including the
+    /// current one) land within `rapid_window_slots`
+    pub fn set_rapid_pattern_policy(
+        ctx: Context<UpdateComplianceConfig>,
+        rapid_window_slots: u64,
+        burst_threshold: u8,
+    ) -> Result<()> {
+        let compliance_config = &mut ctx.accounts.compliance_config;
+
+        require!(
+            ctx.accounts.authority.key() == compliance_config.authority,
+            FraudDetectionError::UnauthorizedAccess
+        );
+        require!(
+            rapid_window_slots < compliance_config.slots_per_day,
+            FraudDetectionError::InvalidSlotConfiguration
+        );
+        require!(
+            burst_threshold >= 2 && burst_threshold as usize <= RECENT_TX_SLOTS + 1,
+            FraudDetectionError::InvalidBurstThreshold
+        );
+
+        compliance_config.rapid_window_slots = rapid_window_slots;
+        compliance_config.rapid_burst_threshold = burst_threshold;
+        compliance_config.last_updated_slot = Clock::get()?.slot;
+
+        emit!(RapidPatternPolicyUpdated {
+            rapid_window_slots,
+            burst_threshold,
+            slot: compliance_config.last_updated_slot,
+        });
+
+        Ok(())
+    }
+
+    /// Configure the short velocity window: flag `HighVelocity` as critical when more than
+    /// `burst_threshold` transactions (including the current one) land within `window_slots`
+    pub fn set_velocity_burst_policy(
+        ctx: Context<UpdateComplianceConfig>,
+        window_slots: u64,
+        burst_threshold: u8,
+    ) -> Result<()> {
+        let compliance_config = &mut ctx.accounts.compliance_config;
+
+        require!(
+            ctx.accounts.authority.key() == compliance_config.authority,
+            FraudDetectionError::UnauthorizedAccess
+        );
+        require!(
+            window_slots > 0 && window_slots < compliance_config.slots_per_day,
+            FraudDetectionError::InvalidSlotConfiguration
+        );
+        // Only RECENT_TX_SLOTS past transactions are tracked, so a larger threshold could never trip
+        require!(
+            burst_threshold >= 1 && burst_threshold as usize <= RECENT_TX_SLOTS,
+            FraudDetectionError::InvalidVelocityBurstThreshold
+        );
+
+        compliance_config.velocity_window_slots = window_slots;
+        compliance_config.velocity_burst_threshold = burst_threshold;
+        compliance_config.last_updated_slot = Clock::get()?.slot;
+
+        emit!(VelocityBurstPolicyUpdated {
+            window_slots,
+            burst_threshold,
+            slot: compliance_config.last_updated_slot,
+        });
+
+        Ok(())
+    }
+
+    pub fn set_oracle_policy(
+        ctx: Context<UpdateComplianceConfig>,
+        max_staleness_seconds: i64,
+        max_confidence_cents: u64,
+    ) -> Result<()> {
+        let compliance_config = &mut ctx.accounts.compliance_config;
+
+        require!(
+            ctx.accounts.authority.key() == compliance_config.authority,
+            FraudDetectionError::UnauthorizedAccess
+        );
+        require!(
+            max_staleness_seconds > 0 && max_confidence_cents > 0,
+            FraudDetectionError::InvalidOraclePolicy
+        );
+
+        compliance_config.oracle_max_staleness_seconds = max_staleness_seconds;
+        compliance_config.oracle_max_confidence_cents = max_confidence_cents;
+        compliance_config.last_updated_slot = Clock::get()?.slot;
+
+        emit!(OraclePolicyUpdated {
+            max_staleness_seconds,
+            max_confidence_cents,
+            slot: compliance_config.last_updated_slot,
+        });
+
+        Ok(())
+    }
+
+    /// Set the USD amounts above which users without KYC are blocked and users with basic
+    /// KYC are flagged for an upgrade
+    pub fn set_kyc_limits(
+        ctx: Context<UpdateComplianceConfig>,
+        none_limit_usd: u64,
+        basic_limit_usd: u64,
+    ) -> Result<()> {
+        let compliance_config = &mut ctx.accounts.compliance_config;
+
+        require!(
+            ctx.accounts.authority.key() == compliance_config.authority,
+            FraudDetectionError::UnauthorizedAccess
+        );
+        require!(
+            none_limit_usd <= basic_limit_usd,
+            FraudDetectionError::InvalidKycLimits
+        );
+
+        compliance_config.kyc_none_limit_usd = none_limit_usd;
+        compliance_config.kyc_basic_limit_usd = basic_limit_usd;
+        compliance_config.last_updated_slot = Clock::get()?.slot;
+
+        emit!(KycLimitsUpdated {
+            none_limit_usd,
+            basic_limit_usd,
+            slot: compliance_config.last_updated_slot,
+        });
+
+        Ok(())
+    }
+
+    /// Change when an existing risk registry entry applies, or deactivate it outright.
+    /// Bumps the risk list version so past decisions can be tied to the list state at the time.
+    pub fn update_risk_entry_window(
+        ctx: Context<UpdateRiskEntry>,
+        effective_from: u64,
+        effective_until: Option<u64>,
+        is_active: bool,
+    ) -> Result<()> {
+        let risk_registry = &mut ctx.accounts.risk_registry;
+        let compliance_config = &mut ctx.accounts.compliance_config;
+
+        require!(
+            ctx.accounts.authority.key() == compliance_config.authority,
+            FraudDetectionError::UnauthorizedAccess
+        );
+        validate_effective_window(effective_from, effective_until)?;
+
+        let list_version = bump_risk_list_version(compliance_config)?;
+        risk_registry.effective_from = effective_from;
+        risk_registry.effective_until = effective_until;
+        risk_registry.is_active = is_active;
+        risk_registry.list_version = list_version;
+
+        emit!(RiskEntryWindowUpdated {
+            address: risk_registry.address,
+            effective_from,
+            effective_until,
+            is_active,
+            list_version,
+            slot: compliance_config.last_updated_slot,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless unblock for score-threshold blocks once the cooldown has passed.
+    /// Blocks caused by critical flags (sanctions, high-risk recipients, critical AI scores)
+    /// always require the manual `unblock_user` path.
+    pub fn auto_unblock_user(ctx: Context<AutoUnblockUser>) -> Result<()> {
+        let user_profile = &mut ctx.accounts.user_profile;
+        let compliance_config = &ctx.accounts.compliance_config;
+        let current_slot = Clock::get()?.slot;
+
+        require!(compliance_config.auto_unblock_enabled, FraudDetectionError::AutoUnblockDisabled);
+        require!(user_profile.is_blocked, FraudDetectionError::UserNotBlocked);
+        require!(
+            !user_profile.is_critical_block
+                && user_profile.last_critical_flag_slot < user_profile.blocked_at_slot,
+            FraudDetectionError::CriticalBlock
+        );
+        require!(
+            current_slot - user_profile.blocked_at_slot >= compliance_config.auto_unblock_cooldown_slots,
+            FraudDetectionError::AutoUnblockCooldownActive
+        );
+
+        user_profile.is_blocked = false;
+        user_profile.risk_score = user_profile.risk_score / 2; // Same reduction as manual unblock
+        record_status_transition(user_profile, TransactionStatus::Approved, Vec::new(), current_slot);
+
+        emit!(UserUnblocked {
+            user: user_profile.user,
+            reason: "Automatic unblock after cooldown".to_string(),
+            slot: current_slot,
+        });
+
+        Ok(())
+    }
+
+    /// Returns every active compliance parameter in one call. Anchor writes the returned value
+    /// to the transaction return data (`set_return_data`), so other programs can mirror the
+    /// ruleset via CPI: `fraud_detection::cpi::get_compliance_config(cpi_ctx)?.get()`.
+    pub fn get_compliance_config(
+        ctx: Context<GetComplianceConfig>,
+    ) -> Result<ComplianceParameters> {
+        let compliance_config: &ComplianceConfig = &ctx.accounts.compliance_config;
+        Ok(ComplianceParameters::from(compliance_config))
+    }
 }
 
-// Helper function to get USD value from price oracle
+// USD value of `amount_lamports` in whole dollars (rounded down). The feed's price is a
+// decimal (mantissa, scale), applied in integer cents; stale or low-confidence results are rejected.
 fn get_usd_value_from_oracle(
     price_oracle: &AccountInfo,
     amount_lamports: u64,
+    compliance_config: &ComplianceConfig,
 ) -> Result<u64> {
-    let aggregator = AggregatorAccountData::new(price_oracle)?;
-    let price = aggregator.get_result()?.try_into()?;
-    
-    // Convert lamports to SOL, then to USD
-    let sol_amount = amount_lamports as f64 / 1_000_000_000.0;
-    let usd_amount = sol_amount * price;
-    
-    Ok(usd_amount as u64)
+    let aggregator = AggregatorAccountData::new(price_oracle)
+        .map_err(|_| error!(FraudDetectionError::InvalidPriceOracle))?;
+    aggregator
+        .check_staleness(Clock::get()?.unix_timestamp, compliance_config.oracle_max_staleness_seconds)
+        .map_err(|_| error!(FraudDetectionError::InvalidPriceOracle))?;
+    aggregator
+        .check_confidence_interval(SwitchboardDecimal::new(
+            compliance_config.oracle_max_confidence_cents as i128,
+            2,
+        ))
+        .map_err(|_| error!(FraudDetectionError::InvalidPriceOracle))?;
+
+    let price = aggregator.get_result()
+        .map_err(|_| error!(FraudDetectionError::InvalidPriceOracle))?;
+    require!(price.mantissa > 0, FraudDetectionError::InvalidPriceOracle);
+
+    // cents = lamports * mantissa * 100 / (10^9 lamports per SOL * 10^scale)
+    let denominator = 10u128
+        .checked_pow(9 + price.scale)
+        .ok_or(FraudDetectionError::InvalidPriceOracle)?;
+    let usd_cents = (amount_lamports as u128)
+        .checked_mul(price.mantissa as u128)
+        .and_then(|value| value.checked_mul(100))
+        .ok_or(FraudDetectionError::InvalidPriceOracle)?
+        / denominator;
+
+    u64::try_from(usd_cents / 100).map_err(|_| error!(FraudDetectionError::InvalidPriceOracle))
+}
+
+fn validate_effective_window(effective_from: u64, effective_until: Option<u64>) -> Result<()> {
+    if let Some(until) = effective_until {
+        require!(until > effective_from, FraudDetectionError::InvalidEffectiveWindow);
+    }
+    Ok(())
+}
+
+/// Increment the global risk list version, returning the new value.
+fn bump_risk_list_version(compliance_config: &mut ComplianceConfig) -> Result<u64> {
+    compliance_config.risk_list_version += 1;
+    compliance_config.last_updated_slot = Clock::get()?.slot;
+    Ok(compliance_config.risk_list_version)
+}
+
+// Record a flag unless the whitelist suppresses it, in which case the suppression is emitted
+// as an audit trail instead. Returns whether the flag was recorded.
+fn push_flag_unless_whitelisted(
+    flags: &mut Vec<FraudFlag>,
+    flag: FraudFlag,
+    whitelisted: bool,
+    user: Pubkey,
+    recipient: Pubkey,
+) -> bool {
+    if whitelisted {
+        emit!(FlagSuppressedByWhitelist {
+            user,
+            recipient,
+            flag_type: flag.flag_type,
+            slot: flag.detected_at_slot,
+        });
+        return false;
+    }
+
+    flags.push(flag);
+    true
+}
+
+// Emit a single UserStatusTransition event, only when the user's status actually changes
+fn record_status_transition(
+    user_profile: &mut UserProfile,
+    new_status: TransactionStatus,
+    flag_types: Vec<FlagType>,
+    slot: u64,
+) {
+    if user_profile.last_status == new_status {
+        return;
+    }
+
+    let old_status = std::mem::replace(&mut user_profile.last_status, new_status.clone());
+
+    emit!(UserStatusTransition {
+        user: user_profile.user,
+        old_status,
+        new_status,
+        flag_types,
+        slot,
+    });
 }
 
 #[derive(Accounts)]
@@ -449,6 +1059,20 @@ pub struct AddHighRiskAddress<'info> {
     )]
     pub risk_registry: Account<'info, RiskRegistry>,
     #[account(
+        mut,
+        seeds = [b"compliance_config"],
+        bump = compliance_config.bump
+    )]
+    pub compliance_config: Account<'info, ComplianceConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BulkAddHighRiskAddresses<'info> {
+    #[account(
+        mut,
         seeds = [b"compliance_config"],
         bump = compliance_config.bump
     )]
@@ -467,6 +1091,7 @@ pub struct MonitorTransaction<'info> {
     )]
     pub user_profile: Account<'info, UserProfile>,
     #[account(
+        mut,
         seeds = [b"compliance_config"],
         bump = compliance_config.bump
     )]
@@ -481,6 +1106,12 @@ pub struct MonitorTransaction<'info> {
     pub transaction_record: Account<'info, TransactionRecord>,
     /// CHECK: Price oracle account for USD conversion
     pub price_oracle: AccountInfo<'info>,
+    // Whitelist entry for the recipient or the user, if either is whitelisted
+    #[account(
+        seeds = [b"whitelist", whitelist.address.as_ref()],
+        bump = whitelist.bump
+    )]
+    pub whitelist: Option<Account<'info, Whitelist>>,
     #[account(mut)]
     pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -539,12 +1170,102 @@ pub struct UnblockUser<'info> {
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct GetComplianceConfig<'info> {
+    #[account(
+        seeds = [b"compliance_config"],
+        bump = compliance_config.bump
+    )]
+    pub compliance_config: Account<'info, ComplianceConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ClearUserFlags<'info> {
+    #[account(
+        mut,
+        seeds = [b"user_profile", user_profile.user.as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        seeds = [b"compliance_config"],
+        bump = compliance_config.bump
+    )]
+    pub compliance_config: Account<'info, ComplianceConfig>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateComplianceConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"compliance_config"],
+        bump = compliance_config.bump
+    )]
+    pub compliance_config: Account<'info, ComplianceConfig>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRiskEntry<'info> {
+    #[account(
+        mut,
+        seeds = [b"risk_registry", risk_registry.address.as_ref()],
+        bump = risk_registry.bump
+    )]
+    pub risk_registry: Account<'info, RiskRegistry>,
+    #[account(
+        mut,
+        seeds = [b"compliance_config"],
+        bump = compliance_config.bump
+    )]
+    pub compliance_config: Account<'info, ComplianceConfig>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AutoUnblockUser<'info> {
+    #[account(
+        mut,
+        seeds = [b"user_profile", user_profile.user.as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        seeds = [b"compliance_config"],
+        bump = compliance_config.bump
+    )]
+    pub compliance_config: Account<'info, ComplianceConfig>,
+}
+
 #[account]
 pub struct ComplianceConfig {
     pub authority: Pubkey,
     pub high_value_threshold_usd: u64,
     pub velocity_threshold: u32,
     pub max_daily_volume_usd: u64,
+    pub slots_per_day: u64,
+    pub rapid_window_slots: u64,
+    /// Transactions within `rapid_window_slots` that count as a burst
+    pub rapid_burst_threshold: u8,
+    /// Short window for the critical velocity check, in slots
+    pub velocity_window_slots: u64,
+    /// Transactions allowed within `velocity_window_slots` before flagging
+    pub velocity_burst_threshold: u8,
+    /// Oldest accepted price feed result, in seconds
+    pub oracle_max_staleness_seconds: i64,
+    /// Widest accepted price feed confidence interval, in US cents
+    pub oracle_max_confidence_cents: u64,
+    /// Transactions above this USD amount require KYC
+    pub kyc_none_limit_usd: u64,
+    /// Transactions above this USD amount require enhanced KYC
+    pub kyc_basic_limit_usd: u64,
+    pub auto_unblock_enabled: bool,
+    pub auto_unblock_cooldown_slots: u64,
+    /// Minimum KYC level per `TransactionType`, indexed by `TransactionType::index`
+    pub min_kyc_by_transaction_type: [KYCLevel; TRANSACTION_TYPE_COUNT],
+    /// Bumped on every change to the high-risk registry
+    pub risk_list_version: u64,
     pub is_active: bool,
     pub total_flagged_transactions: u64,
     pub total_blocked_transactions: u64,
@@ -553,7 +1274,66 @@ pub struct ComplianceConfig {
 }
 
 impl ComplianceConfig {
-    pub const LEN: usize = 8 + 32 + 8 + 4 + 8 + 1 + 8 + 8 + 8 + 1;
+    pub const LEN: usize = 8 + 32 + 8 + 4 + 8 + 8 + 8 + 1 + 8 + 1 + 8 + 8 + 8 + 8 + 1 + 8 + TRANSACTION_TYPE_COUNT + 8 + 1 + 8 + 8 + 8 + 1;
+}
+
+/// Snapshot of the active ruleset returned by `get_compliance_config`, including the
+/// fixed risk weights applied to flags
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ComplianceParameters {
+    pub authority: Pubkey,
+    pub is_active: bool,
+    pub high_value_threshold_usd: u64,
+    pub velocity_threshold: u32,
+    pub max_daily_volume_usd: u64,
+    pub slots_per_day: u64,
+    pub rapid_window_slots: u64,
+    pub rapid_burst_threshold: u8,
+    pub velocity_window_slots: u64,
+    pub velocity_burst_threshold: u8,
+    pub oracle_max_staleness_seconds: i64,
+    pub oracle_max_confidence_cents: u64,
+    pub kyc_none_limit_usd: u64,
+    pub kyc_basic_limit_usd: u64,
+    pub min_kyc_by_transaction_type: [KYCLevel; TRANSACTION_TYPE_COUNT],
+    pub auto_unblock_enabled: bool,
+    pub auto_unblock_cooldown_slots: u64,
+    pub severity_risk_weights: [u32; 4],
+    pub auto_block_risk_score: u32,
+    pub risk_list_version: u64,
+    pub total_flagged_transactions: u64,
+    pub total_blocked_transactions: u64,
+    pub last_updated_slot: u64,
+}
+
+impl From<&ComplianceConfig> for ComplianceParameters {
+    fn from(config: &ComplianceConfig) -> Self {
+        Self {
+            authority: config.authority,
+            is_active: config.is_active,
+            high_value_threshold_usd: config.high_value_threshold_usd,
+            velocity_threshold: config.velocity_threshold,
+            max_daily_volume_usd: config.max_daily_volume_usd,
+            slots_per_day: config.slots_per_day,
+            rapid_window_slots: config.rapid_window_slots,
+            rapid_burst_threshold: config.rapid_burst_threshold,
+            velocity_window_slots: config.velocity_window_slots,
+            velocity_burst_threshold: config.velocity_burst_threshold,
+            oracle_max_staleness_seconds: config.oracle_max_staleness_seconds,
+            oracle_max_confidence_cents: config.oracle_max_confidence_cents,
+            kyc_none_limit_usd: config.kyc_none_limit_usd,
+            kyc_basic_limit_usd: config.kyc_basic_limit_usd,
+            min_kyc_by_transaction_type: config.min_kyc_by_transaction_type,
+            auto_unblock_enabled: config.auto_unblock_enabled,
+            auto_unblock_cooldown_slots: config.auto_unblock_cooldown_slots,
+            severity_risk_weights: FLAG_SEVERITY_RISK_WEIGHTS,
+            auto_block_risk_score: AUTO_BLOCK_RISK_SCORE,
+            risk_list_version: config.risk_list_version,
+            total_flagged_transactions: config.total_flagged_transactions,
+            total_blocked_transactions: config.total_blocked_transactions,
+            last_updated_slot: config.last_updated_slot,
+        }
+    }
 }
 
 #[account]
@@ -567,15 +1347,43 @@ pub struct UserProfile {
     pub daily_transaction_count: u32,
     pub daily_volume_usd: u64,
     pub last_transaction_slot: u64,
+    /// Slots of the most recent transactions, newest first
+    pub recent_transaction_slots: [u64; RECENT_TX_SLOTS],
     pub last_daily_reset_slot: u64,
     pub is_flagged: bool,
     pub is_blocked: bool,
+    pub blocked_at_slot: u64,
+    pub is_critical_block: bool,
+    pub last_critical_flag_slot: u64,
     pub flags: Vec<FraudFlag>,
+    pub last_status: TransactionStatus,
     pub bump: u8,
 }
 
 impl UserProfile {
-    pub const LEN: usize = 8 + 32 + 64 + 1 + 4 + 8 + 8 + 4 + 8 + 8 + 8 + 1 + 1 + 512 + 1;
+    pub const LEN: usize = 8 + 32 + 64 + 1 + 4 + 8 + 8 + 4 + 8 + 8 + 8 * RECENT_TX_SLOTS + 8 + 1 + 1 + 8 + 1 + 8
+        + 4 + MAX_STORED_FLAGS * FraudFlag::SIZE + 1 + 1;
+
+    /// Append flags, keeping only the most recent `MAX_STORED_FLAGS` so the account never outgrows `LEN`
+    pub fn record_flags(&mut self, flags: impl IntoIterator<Item = FraudFlag>) {
+        for mut flag in flags {
+            flag.truncate_description();
+            self.flags.push(flag);
+        }
+        if self.flags.len() > MAX_STORED_FLAGS {
+            let excess = self.flags.len() - MAX_STORED_FLAGS;
+            self.flags.drain(..excess);
+        }
+    }
+
+    /// Transactions within `window_slots` of `current_slot`, counting the current one
+    pub fn transactions_within(&self, current_slot: u64, window_slots: u64) -> usize {
+        let recorded = (self.total_transaction_count as usize).min(RECENT_TX_SLOTS);
+        1 + self.recent_transaction_slots[..recorded]
+            .iter()
+            .filter(|slot| current_slot - **slot < window_slots)
+            .count()
+    }
 }
 
 #[account]
@@ -585,12 +1393,24 @@ pub struct RiskRegistry {
     pub risk_level: RiskLevel,
     pub description: String,
     pub added_at_slot: u64,
+    /// First slot at which the entry applies
+    pub effective_from: u64,
+    /// Slot at which the entry stops applying (exclusive); `None` means open-ended
+    pub effective_until: Option<u64>,
+    /// Risk list version at which this entry was last changed
+    pub list_version: u64,
     pub is_active: bool,
     pub bump: u8,
 }
 
 impl RiskRegistry {
-    pub const LEN: usize = 8 + 32 + 1 + 1 + 256 + 8 + 1 + 1;
+    pub const LEN: usize = 8 + 32 + 1 + 1 + 256 + 8 + 8 + 9 + 8 + 1 + 1;
+
+    pub fn is_effective_at(&self, slot: u64) -> bool {
+        self.is_active
+            && slot >= self.effective_from
+            && self.effective_until.map_or(true, |until| slot < until)
+    }
 }
 
 #[account]
@@ -607,7 +1427,20 @@ pub struct TransactionRecord {
 }
 
 impl TransactionRecord {
-    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 1 + 1 + 512 + 8 + 1;
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 1 + 1
+        + 4 + MAX_STORED_FLAGS * FraudFlag::SIZE + 8 + 1;
+
+    /// Store the transaction's flags, truncated and capped at `MAX_STORED_FLAGS` so they always fit `LEN`
+    pub fn record_flags(&mut self, flags: impl IntoIterator<Item = FraudFlag>) {
+        self.flags = flags
+            .into_iter()
+            .take(MAX_STORED_FLAGS)
+            .map(|mut flag| {
+                flag.truncate_description();
+                flag
+            })
+            .collect();
+    }
 }
 
 #[account]
@@ -622,13 +1455,23 @@ impl Whitelist {
     pub const LEN: usize = 8 + 32 + 8 + 1 + 1;
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum KYCLevel {
     None,
     Basic,
     Enhanced,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct HighRiskAddressInput {
+    pub address: Pubkey,
+    pub risk_category: RiskCategory,
+    pub risk_level: RiskLevel,
+    pub description: String,
+    pub effective_from: u64,
+    pub effective_until: Option<u64>,
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
 pub enum RiskCategory {
     Sanctions,
@@ -659,6 +1502,19 @@ pub enum TransactionType {
     Other,
 }
 
+impl TransactionType {
+    pub fn index(&self) -> usize {
+        match self {
+            TransactionType::Payment => 0,
+            TransactionType::Transfer => 1,
+            TransactionType::Swap => 2,
+            TransactionType::Bridge => 3,
+            TransactionType::Stake => 4,
+            TransactionType::Other => 5,
+        }
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
 pub enum TransactionStatus {
     Approved,
@@ -686,6 +1542,18 @@ pub enum FlagSeverity {
     Critical,
 }
 
+impl FlagSeverity {
+    pub fn risk_weight(&self) -> u32 {
+        let index = match self {
+            FlagSeverity::Low => 0,
+            FlagSeverity::Medium => 1,
+            FlagSeverity::High => 2,
+            FlagSeverity::Critical => 3,
+        };
+        FLAG_SEVERITY_RISK_WEIGHTS[index]
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct FraudFlag {
     pub flag_type: FlagType,
@@ -694,6 +1562,21 @@ pub struct FraudFlag {
     pub detected_at_slot: u64,
 }
 
+impl FraudFlag {
+    /// Serialized size of a flag whose description fits `MAX_FLAG_DESCRIPTION_LEN`
+    pub const SIZE: usize = 1 + 1 + 4 + MAX_FLAG_DESCRIPTION_LEN + 8;
+
+    fn truncate_description(&mut self) {
+        if self.description.len() > MAX_FLAG_DESCRIPTION_LEN {
+            let mut end = MAX_FLAG_DESCRIPTION_LEN;
+            while !self.description.is_char_boundary(end) {
+                end -= 1;
+            }
+            self.description.truncate(end);
+        }
+    }
+}
+
 // Events
 #[event]
 pub struct ComplianceModuleInitialized {
@@ -701,6 +1584,8 @@ pub struct ComplianceModuleInitialized {
     pub high_value_threshold_usd: u64,
     pub velocity_threshold: u32,
     pub max_daily_volume_usd: u64,
+    pub slots_per_day: u64,
+    pub rapid_window_slots: u64,
     pub slot: u64,
 }
 
@@ -717,6 +1602,19 @@ pub struct HighRiskAddressAdded {
     pub address: Pubkey,
     pub risk_category: RiskCategory,
     pub risk_level: RiskLevel,
+    pub effective_from: u64,
+    pub effective_until: Option<u64>,
+    pub list_version: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RiskEntryWindowUpdated {
+    pub address: Pubkey,
+    pub effective_from: u64,
+    pub effective_until: Option<u64>,
+    pub is_active: bool,
+    pub list_version: u64,
     pub slot: u64,
 }
 
@@ -754,6 +1652,14 @@ pub struct AddressWhitelisted {
     pub slot: u64,
 }
 
+#[event]
+pub struct FlagSuppressedByWhitelist {
+    pub user: Pubkey,
+    pub recipient: Pubkey,
+    pub flag_type: FlagType,
+    pub slot: u64,
+}
+
 #[event]
 pub struct UserUnblocked {
     pub user: Pubkey,
@@ -761,6 +1667,66 @@ pub struct UserUnblocked {
     pub slot: u64,
 }
 
+#[event]
+pub struct UserFlagsCleared {
+    pub user: Pubkey,
+    pub cleared_by: Pubkey,
+    pub flags_cleared: u32,
+    pub risk_score_reset: bool,
+    pub slot: u64,
+}
+
+#[event]
+pub struct UserStatusTransition {
+    pub user: Pubkey,
+    pub old_status: TransactionStatus,
+    pub new_status: TransactionStatus,
+    pub flag_types: Vec<FlagType>,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AutoUnblockPolicyUpdated {
+    pub enabled: bool,
+    pub cooldown_slots: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RapidPatternPolicyUpdated {
+    pub rapid_window_slots: u64,
+    pub burst_threshold: u8,
+    pub slot: u64,
+}
+
+#[event]
+pub struct VelocityBurstPolicyUpdated {
+    pub window_slots: u64,
+    pub burst_threshold: u8,
+    pub slot: u64,
+}
+
+#[event]
+pub struct OraclePolicyUpdated {
+    pub max_staleness_seconds: i64,
+    pub max_confidence_cents: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct KycLimitsUpdated {
+    pub none_limit_usd: u64,
+    pub basic_limit_usd: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct MinKycLevelUpdated {
+    pub transaction_type: TransactionType,
+    pub min_kyc_level: KYCLevel,
+    pub slot: u64,
+}
+
 #[error_code]
 pub enum FraudDetectionError {
     #[msg("Unauthorized access")]
@@ -775,4 +1741,30 @@ pub enum FraudDetectionError {
     KYCRequired,
     #[msg("Invalid price oracle data")]
     InvalidPriceOracle,
+    #[msg("Oracle staleness and confidence limits must be positive")]
+    InvalidOraclePolicy,
+    #[msg("The no-KYC limit must not exceed the basic KYC limit")]
+    InvalidKycLimits,
+    #[msg("Invalid slot configuration")]
+    InvalidSlotConfiguration,
+    #[msg("Burst threshold must be between 2 and the tracked transaction history plus one")]
+    InvalidBurstThreshold,
+    #[msg("Velocity burst threshold must be between 1 and the tracked transaction history")]
+    InvalidVelocityBurstThreshold,
+    #[msg("Auto-unblock is disabled")]
+    AutoUnblockDisabled,
+    #[msg("User is not blocked")]
+    UserNotBlocked,
+    #[msg("Critical blocks require manual review")]
+    CriticalBlock,
+    #[msg("Auto-unblock cooldown has not elapsed")]
+    AutoUnblockCooldownActive,
+    #[msg("Invalid number of addresses for bulk import")]
+    InvalidBulkSize,
+    #[msg("Risk registry account does not match the expected address")]
+    RiskRegistryAccountMismatch,
+    #[msg("Description too long")]
+    DescriptionTooLong,
+    #[msg("Effective-until slot must be after effective-from slot")]
+    InvalidEffectiveWindow,
 }
@@ -0,0 +1,988 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, LAMPORTS_PER_SOL, SystemProgram } from "@solana/web3.js";
+import { Clock, ProgramTestContext, startAnchor } from "solana-bankrun";
+import { BankrunProvider } from "anchor-bankrun";
+import { IDL, FraudDetection } from "../target/types/fraud_detection";
+import { expect } from "chai";
+
+// Every test runs on a fresh bank with a stubbed SOL/USD price feed, so the slot can be moved
+// through the daily, burst and cooldown windows while the feed stays fresh
+describe("fraud-detection", () => {
+  // Configure the client to use the local cluster.
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.FraudDetection as Program<FraudDetection>;
+
+  const SWITCHBOARD_PROGRAM_ID = new PublicKey("SW1TCH7qEPTdLsDHRgPuMQjbQxKdH2aBStViMFnt64f");
+  const SOL_PRICE_CENTS = 10_000;
+  const HIGH_VALUE_THRESHOLD_USD = 10_000;
+  const VELOCITY_THRESHOLD = 10;
+  const MAX_DAILY_VOLUME_USD = 50_000;
+  const SLOTS_PER_DAY = 1_000;
+  const RAPID_WINDOW_SLOTS = 5;
+  // Slots between transactions that trip neither the rapid nor the short velocity window
+  const QUIET_SLOTS = 20;
+
+  const [configPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("compliance_config")],
+    program.programId
+  );
+
+  const userProfilePda = (user: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("user_profile"), user.toBuffer()],
+      program.programId
+    )[0];
+
+  const riskRegistryPda = (address: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("risk_registry"), address.toBuffer()],
+      program.programId
+    )[0];
+
+  const whitelistPda = (address: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("whitelist"), address.toBuffer()],
+      program.programId
+    )[0];
+
+  const transactionRecordPda = (user: PublicKey, slot: number) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("transaction_record"), user.toBuffer(), new BN(slot).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+
+  type KYCLevel = anchor.IdlTypes<FraudDetection>["KYCLevel"];
+  type TransactionType = anchor.IdlTypes<FraudDetection>["TransactionType"];
+
+  type Bank = {
+    context: ProgramTestContext;
+    fraud: Program<FraudDetection>;
+    oracle: PublicKey;
+  };
+
+  // Lamports worth `dollars` at the feed's price
+  const lamportsFor = (dollars: number) => (dollars * LAMPORTS_PER_SOL * 100) / SOL_PRICE_CENTS;
+
+  // A Switchboard V2 aggregator whose latest confirmed round reports SOL_PRICE_CENTS with a
+  // scale of 2, opened at `openedAt`. Offsets follow the packed AggregatorAccountData layout.
+  const setPriceFeed = (context: ProgramTestContext, openedAt: bigint) => {
+    const address = Keypair.generate().publicKey;
+    const data = Buffer.alloc(3851);
+    anchor.BorshAccountsCoder.accountDiscriminator("AggregatorAccountData").copy(data);
+    data.writeUInt32LE(1, 8 + 228); // min_oracle_results
+    const round = 8 + 333; // latest_confirmed_round
+    data.writeUInt32LE(1, round); // num_success
+    data.writeBigInt64LE(openedAt, round + 17); // round_open_timestamp
+    data.writeBigInt64LE(BigInt(SOL_PRICE_CENTS), round + 25); // result mantissa, low half
+    data.writeUInt32LE(2, round + 41); // result scale
+    context.setAccount(address, { lamports: LAMPORTS_PER_SOL, data, owner: SWITCHBOARD_PROGRAM_ID, executable: false });
+    return address;
+  };
+
+  // A fresh bank with the compliance module initialized; the bank payer is the authority
+  const startBank = async ({
+    slotsPerDay = SLOTS_PER_DAY,
+    rapidWindowSlots = RAPID_WINDOW_SLOTS,
+    velocityThreshold = VELOCITY_THRESHOLD,
+  } = {}): Promise<Bank> => {
+    const context = await startAnchor(".", [], []);
+    const fraud = new Program<FraudDetection>(IDL, program.programId, new BankrunProvider(context));
+    const { unixTimestamp } = await context.banksClient.getClock();
+    const oracle = setPriceFeed(context, unixTimestamp);
+
+    await fraud.methods
+      .initializeComplianceModule(
+        context.payer.publicKey,
+        new BN(HIGH_VALUE_THRESHOLD_USD),
+        velocityThreshold,
+        new BN(MAX_DAILY_VOLUME_USD),
+        new BN(slotsPerDay),
+        new BN(rapidWindowSlots)
+      )
+      .accounts({
+        complianceConfig: configPda,
+        authority: context.payer.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    return { context, fraud, oracle };
+  };
+
+  // Move `slots` ahead without moving the clock's timestamp, so the price feed stays fresh
+  const warp = async (bank: Bank, slots = 1) => {
+    const clock = await bank.context.banksClient.getClock();
+    const slot = clock.slot + BigInt(slots);
+    bank.context.warpToSlot(slot);
+    bank.context.setClock(
+      new Clock(slot, clock.epochStartTimestamp, clock.epoch, clock.leaderScheduleEpoch, clock.unixTimestamp)
+    );
+  };
+
+  const currentSlot = async (bank: Bank) => Number((await bank.context.banksClient.getClock()).slot);
+
+  // The bank reports program errors by number, e.g. "custom program error: 0x1770"
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    const error = IDL.errors.find((candidate) => candidate.name === code);
+    try {
+      await promise;
+    } catch (err) {
+      expect(String(err)).to.contain(`0x${error.code.toString(16)}`);
+      return;
+    }
+    expect.fail(`expected ${code}`);
+  };
+
+  const registerUser = async (bank: Bank, kycLevel: KYCLevel = { basic: {} }) => {
+    const user = Keypair.generate().publicKey;
+    await bank.fraud.methods
+      .registerUserProfile(user, "user.sol", kycLevel)
+      .accounts({
+        userProfile: userProfilePda(user),
+        authority: bank.context.payer.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    return user;
+  };
+
+  type MonitorOptions = {
+    slots?: number;
+    recipient?: PublicKey;
+    transactionType?: TransactionType;
+    whitelisted?: PublicKey | null;
+    riskRegistry?: PublicKey;
+  };
+
+  // A monitor_transaction call for a transfer of `dollars` from `user`, `slots` after the
+  // previous one, optionally presenting the whitelist entry of `whitelisted`. The recipient's
+  // registry entry is passed unless another account is given.
+  const monitorTransaction = async (
+    bank: Bank,
+    user: PublicKey,
+    dollars: number,
+    {
+      slots = QUIET_SLOTS,
+      recipient = Keypair.generate().publicKey,
+      transactionType = { payment: {} },
+      whitelisted = null,
+      riskRegistry = riskRegistryPda(recipient),
+    }: MonitorOptions = {}
+  ) => {
+    await warp(bank, slots);
+    const transactionRecord = transactionRecordPda(user, await currentSlot(bank));
+    const builder = bank.fraud.methods
+      .monitorTransaction(new BN(lamportsFor(dollars)), recipient, transactionType)
+      .accounts({
+        userProfile: userProfilePda(user),
+        complianceConfig: configPda,
+        transactionRecord,
+        priceOracle: bank.oracle,
+        whitelist: whitelisted ? whitelistPda(whitelisted) : null,
+        authority: bank.context.payer.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts([{ pubkey: riskRegistry, isWritable: false, isSigner: false }]);
+    return { builder, transactionRecord };
+  };
+
+  // Monitor a transfer and return its transaction record
+  const monitor = async (bank: Bank, user: PublicKey, dollars: number, options: MonitorOptions = {}) => {
+    const { builder, transactionRecord } = await monitorTransaction(bank, user, dollars, options);
+    await builder.rpc();
+    return bank.fraud.account.transactionRecord.fetch(transactionRecord);
+  };
+
+  type RiskCategory = anchor.IdlTypes<FraudDetection>["RiskCategory"];
+  type RiskLevel = anchor.IdlTypes<FraudDetection>["RiskLevel"];
+
+  // List `address` in the high-risk registry, effective from slot 0 unless given a window
+  const addRiskAddress = (
+    bank: Bank,
+    address: PublicKey,
+    {
+      riskCategory = { sanctions: {} },
+      riskLevel = { critical: {} },
+      effectiveFrom = 0,
+      effectiveUntil = null,
+    }: { riskCategory?: RiskCategory; riskLevel?: RiskLevel; effectiveFrom?: number; effectiveUntil?: number | null } = {}
+  ) =>
+    bank.fraud.methods
+      .addHighRiskAddress(
+        address,
+        riskCategory,
+        riskLevel,
+        "Sanctions list entry",
+        new BN(effectiveFrom),
+        effectiveUntil === null ? null : new BN(effectiveUntil)
+      )
+      .accounts({
+        riskRegistry: riskRegistryPda(address),
+        complianceConfig: configPda,
+        authority: bank.context.payer.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+  const whitelistAddress = (bank: Bank, address: PublicKey) =>
+    bank.fraud.methods
+      .whitelistAddress(address)
+      .accounts({
+        whitelist: whitelistPda(address),
+        complianceConfig: configPda,
+        authority: bank.context.payer.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+  const updateRiskScoreAi = (bank: Bank, user: PublicKey, aiRiskScore: number, indicators: string[] = []) =>
+    bank.fraud.methods
+      .updateRiskScoreAi(aiRiskScore, indicators)
+      .accounts({
+        userProfile: userProfilePda(user),
+        complianceConfig: configPda,
+        authority: bank.context.payer.publicKey,
+      });
+
+  const flagTypes = (record: { flags: { flagType: object }[] }) =>
+    record.flags.map((flag) => Object.keys(flag.flagType)[0]);
+
+  // Process a transaction directly on the bank and decode the events from its logs
+  const emittedEvents = async (bank: Bank, builder: { transaction(): Promise<anchor.web3.Transaction> }) => {
+    const tx = await builder.transaction();
+    tx.recentBlockhash = bank.context.lastBlockhash;
+    tx.feePayer = bank.context.payer.publicKey;
+    tx.sign(bank.context.payer);
+    const { logMessages } = await bank.context.banksClient.processTransaction(tx);
+    const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(IDL));
+    return [...parser.parseLogs(logMessages)];
+  };
+
+  describe("slot configuration", () => {
+    it("Resets daily counters once the configured day has passed", async () => {
+      const slotsPerDay = 50;
+      const bank = await startBank({ slotsPerDay });
+      const user = await registerUser(bank);
+      const { lastDailyResetSlot } = await bank.fraud.account.userProfile.fetch(userProfilePda(user));
+      const registeredAt = lastDailyResetSlot.toNumber();
+
+      await monitor(bank, user, 100, { slots: 10 });
+      await monitor(bank, user, 100, { slots: 10 });
+
+      // Exactly one configured day after the reset slot the day hasn't rolled over yet
+      await monitor(bank, user, 100, { slots: registeredAt + slotsPerDay - (await currentSlot(bank)) });
+      let profile = await bank.fraud.account.userProfile.fetch(userProfilePda(user));
+      expect(profile.dailyTransactionCount).to.equal(3);
+      expect(profile.dailyVolumeUsd.toNumber()).to.equal(300);
+
+      await monitor(bank, user, 100, { slots: 1 });
+      profile = await bank.fraud.account.userProfile.fetch(userProfilePda(user));
+      expect(profile.dailyTransactionCount).to.equal(1);
+      expect(profile.dailyVolumeUsd.toNumber()).to.equal(100);
+      expect(profile.lastDailyResetSlot.toNumber()).to.equal(registeredAt + slotsPerDay + 1);
+      expect(profile.totalTransactionCount.toNumber()).to.equal(4);
+    });
+
+    it("Detects rapid transactions only inside the configured window", async () => {
+      const bank = await startBank();
+      const user = await registerUser(bank);
+
+      await monitor(bank, user, 100);
+      const outside = await monitor(bank, user, 100, { slots: RAPID_WINDOW_SLOTS });
+      expect(flagTypes(outside)).to.be.empty;
+      expect(outside.status).to.deep.equal({ approved: {} });
+
+      const inside = await monitor(bank, user, 100, { slots: RAPID_WINDOW_SLOTS - 1 });
+      expect(flagTypes(inside)).to.deep.equal(["unusualPattern"]);
+      expect(inside.status).to.deep.equal({ flagged: {} });
+    });
+
+    it("Rejects a rapid window that isn't shorter than a day", async () => {
+      await expectError(startBank({ slotsPerDay: 10, rapidWindowSlots: 10 }), "InvalidSlotConfiguration");
+    });
+  });
+
+  describe("auto-unblock", () => {
+    const COOLDOWN_SLOTS = 100;
+
+    const enableAutoUnblock = (bank: Bank) =>
+      bank.fraud.methods
+        .setAutoUnblockPolicy(true, new BN(COOLDOWN_SLOTS))
+        .accounts({ complianceConfig: configPda, authority: bank.context.payer.publicKey })
+        .rpc();
+
+    const autoUnblock = (bank: Bank, user: PublicKey) =>
+      bank.fraud.methods
+        .autoUnblockUser()
+        .accounts({ userProfile: userProfilePda(user), complianceConfig: configPda })
+        .rpc();
+
+    it("Unblocks a velocity-blocked user once the cooldown has passed", async () => {
+      // Every transaction is over the daily velocity threshold, a medium flag worth 5 points
+      const bank = await startBank({ velocityThreshold: 0 });
+      const user = await registerUser(bank);
+      for (let i = 0; i < 20; i++) {
+        await monitor(bank, user, 100);
+      }
+      const blocking = await monitor(bank, user, 100);
+      expect(flagTypes(blocking)).to.deep.equal(["highVelocity"]);
+      expect(blocking.status).to.deep.equal({ blocked: {} });
+
+      let profile = await bank.fraud.account.userProfile.fetch(userProfilePda(user));
+      expect(profile.isBlocked).to.be.true;
+      expect(profile.isCriticalBlock).to.be.false;
+      expect(profile.riskScore).to.equal(105);
+
+      // Opt-in only
+      await expectError(autoUnblock(bank, user), "AutoUnblockDisabled");
+      await enableAutoUnblock(bank);
+      await warp(bank);
+      await expectError(autoUnblock(bank, user), "AutoUnblockCooldownActive");
+
+      await warp(bank, COOLDOWN_SLOTS);
+      await autoUnblock(bank, user);
+
+      profile = await bank.fraud.account.userProfile.fetch(userProfilePda(user));
+      expect(profile.isBlocked).to.be.false;
+      expect(profile.riskScore).to.equal(52);
+    });
+
+    it("Never auto-unblocks a sanctions block", async () => {
+      const bank = await startBank();
+      await enableAutoUnblock(bank);
+      const sanctioned = Keypair.generate().publicKey;
+      await addRiskAddress(bank, sanctioned);
+      const user = await registerUser(bank);
+
+      // Each transfer to the sanctioned address is a critical flag worth 50 points
+      for (let i = 0; i < 3; i++) {
+        await monitor(bank, user, 100, { recipient: sanctioned });
+      }
+      const profile = await bank.fraud.account.userProfile.fetch(userProfilePda(user));
+      expect(profile.isBlocked).to.be.true;
+      expect(profile.isCriticalBlock).to.be.true;
+
+      await warp(bank, COOLDOWN_SLOTS);
+      await expectError(autoUnblock(bank, user), "CriticalBlock");
+      expect((await bank.fraud.account.userProfile.fetch(userProfilePda(user))).isBlocked).to.be.true;
+    });
+  });
+
+  describe("bulk risk registry import", () => {
+    const bulkAdd = (bank: Bank, addresses: PublicKey[], registries = addresses.map(riskRegistryPda)) =>
+      bank.fraud.methods
+        .bulkAddHighRiskAddresses(
+          addresses.map((address, index) => ({
+            address,
+            riskCategory: index % 2 === 0 ? { knownScammer: {} } : { mixerService: {} },
+            riskLevel: { high: {} },
+            description: `Imported entry ${index}`,
+            effectiveFrom: new BN(0),
+            effectiveUntil: null,
+          }))
+        )
+        .accounts({
+          complianceConfig: configPda,
+          authority: bank.context.payer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(registries.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false })))
+        .rpc();
+
+    it("Adds several addresses that are each screened afterwards", async () => {
+      const bank = await startBank();
+      const addresses = [0, 1, 2].map(() => Keypair.generate().publicKey);
+
+      await bulkAdd(bank, addresses);
+
+      const config = await bank.fraud.account.complianceConfig.fetch(configPda);
+      expect(config.riskListVersion.toNumber()).to.equal(1);
+      for (const [index, address] of addresses.entries()) {
+        const registry = await bank.fraud.account.riskRegistry.fetch(riskRegistryPda(address));
+        expect(registry.address.toString()).to.equal(address.toString());
+        expect(registry.riskLevel).to.deep.equal({ high: {} });
+        expect(registry.description).to.equal(`Imported entry ${index}`);
+        expect(registry.isActive).to.be.true;
+
+        const user = await registerUser(bank);
+        const record = await monitor(bank, user, 100, { recipient: address });
+        expect(flagTypes(record)).to.deep.equal(["highRiskRecipient"]);
+        expect(record.status).to.deep.equal({ blocked: {} });
+      }
+    });
+
+    it("Rejects registry accounts that don't match the entries", async () => {
+      const bank = await startBank();
+      const addresses = [0, 1].map(() => Keypair.generate().publicKey);
+
+      await expectError(bulkAdd(bank, addresses, [riskRegistryPda(addresses[0])]), "RiskRegistryAccountMismatch");
+      await expectError(bulkAdd(bank, addresses, [...addresses].reverse().map(riskRegistryPda)), "RiskRegistryAccountMismatch");
+      expect(await bank.fraud.account.riskRegistry.fetchNullable(riskRegistryPda(addresses[0]))).to.be.null;
+    });
+
+    it("Rejects an import larger than the bulk limit", async () => {
+      const bank = await startBank();
+      const addresses = Array.from({ length: 11 }, () => Keypair.generate().publicKey);
+
+      // The size is checked before the registry accounts
+      await expectError(bulkAdd(bank, addresses, []), "InvalidBulkSize");
+    });
+  });
+
+  describe("status transitions", () => {
+    // The UserStatusTransition events a monitored transfer emits
+    const transitions = async (bank: Bank, user: PublicKey, dollars: number, options: MonitorOptions = {}) => {
+      const { builder } = await monitorTransaction(bank, user, dollars, options);
+      return (await emittedEvents(bank, builder))
+        .filter((event) => event.name === "UserStatusTransition")
+        .map(({ data }) => ({
+          oldStatus: data.oldStatus,
+          newStatus: data.newStatus,
+          flagTypes: (data.flagTypes as object[]).map((flagType) => Object.keys(flagType)[0]),
+        }));
+    };
+
+    it("Fires only when the user's status actually changes", async () => {
+      const bank = await startBank();
+      const user = await registerUser(bank);
+      const sanctioned = Keypair.generate().publicKey;
+      await addRiskAddress(bank, sanctioned);
+
+      // Users start out approved
+      expect(await transitions(bank, user, 100)).to.be.empty;
+
+      expect(await transitions(bank, user, 15_000)).to.deep.equal([
+        {
+          oldStatus: { approved: {} },
+          newStatus: { flagged: {} },
+          flagTypes: ["highValueTransaction", "kycUpgradeRequired"],
+        },
+      ]);
+      expect(await transitions(bank, user, 15_000)).to.be.empty;
+
+      expect(await transitions(bank, user, 100)).to.deep.equal([
+        { oldStatus: { flagged: {} }, newStatus: { approved: {} }, flagTypes: [] },
+      ]);
+      expect(await transitions(bank, user, 100)).to.be.empty;
+
+      expect(await transitions(bank, user, 100, { recipient: sanctioned })).to.deep.equal([
+        { oldStatus: { approved: {} }, newStatus: { blocked: {} }, flagTypes: ["highRiskRecipient"] },
+      ]);
+      expect(await transitions(bank, user, 100, { recipient: sanctioned })).to.be.empty;
+
+      const profile = await bank.fraud.account.userProfile.fetch(userProfilePda(user));
+      expect(profile.lastStatus).to.deep.equal({ blocked: {} });
+    });
+  });
+
+  describe("minimum KYC per transaction type", () => {
+    it("Blocks a bridge from a user without KYC but allows a small payment", async () => {
+      const bank = await startBank();
+      await bank.fraud.methods
+        .setMinKycLevel({ bridge: {} }, { basic: {} })
+        .accounts({ complianceConfig: configPda, authority: bank.context.payer.publicKey })
+        .rpc();
+      const unverified = await registerUser(bank, { none: {} });
+      const verified = await registerUser(bank, { basic: {} });
+
+      const bridge = await monitor(bank, unverified, 10, { transactionType: { bridge: {} } });
+      expect(flagTypes(bridge)).to.deep.equal(["kycRequired"]);
+      expect(bridge.status).to.deep.equal({ blocked: {} });
+
+      const payment = await monitor(bank, unverified, 10, { transactionType: { payment: {} } });
+      expect(flagTypes(payment)).to.be.empty;
+      expect(payment.status).to.deep.equal({ approved: {} });
+
+      const verifiedBridge = await monitor(bank, verified, 10, { transactionType: { bridge: {} } });
+      expect(verifiedBridge.status).to.deep.equal({ approved: {} });
+
+      const config = await bank.fraud.account.complianceConfig.fetch(configPda);
+      expect(config.minKycByTransactionType[3]).to.deep.equal({ basic: {} });
+      expect(config.minKycByTransactionType[0]).to.deep.equal({ none: {} });
+    });
+  });
+
+  describe("risk list effective windows", () => {
+    it("Applies an entry only within its effective window", async () => {
+      const bank = await startBank();
+      const user = await registerUser(bank);
+      const address = Keypair.generate().publicKey;
+      const start = await currentSlot(bank);
+      await addRiskAddress(bank, address, { effectiveFrom: start + 50, effectiveUntil: start + 100 });
+
+      const before = await monitor(bank, user, 100, { recipient: address });
+      expect(flagTypes(before)).to.be.empty;
+
+      await warp(bank, start + 50 - (await currentSlot(bank)) - QUIET_SLOTS);
+      const during = await monitor(bank, user, 100, { recipient: address });
+      expect(during.processedAtSlot.toNumber()).to.equal(start + 50);
+      expect(flagTypes(during)).to.deep.equal(["highRiskRecipient"]);
+
+      // The window's end is exclusive
+      await warp(bank, start + 100 - (await currentSlot(bank)) - QUIET_SLOTS);
+      const after = await monitor(bank, user, 100, { recipient: address });
+      expect(after.processedAtSlot.toNumber()).to.equal(start + 100);
+      expect(flagTypes(after)).to.be.empty;
+    });
+
+    it("Bumps the list version on every change", async () => {
+      const bank = await startBank();
+      const user = await registerUser(bank);
+      const address = Keypair.generate().publicKey;
+      await addRiskAddress(bank, address);
+      await addRiskAddress(bank, Keypair.generate().publicKey);
+
+      let registry = await bank.fraud.account.riskRegistry.fetch(riskRegistryPda(address));
+      expect(registry.listVersion.toNumber()).to.equal(1);
+
+      // Deactivating the entry stops it applying
+      await bank.fraud.methods
+        .updateRiskEntryWindow(new BN(0), null, false)
+        .accounts({
+          riskRegistry: riskRegistryPda(address),
+          complianceConfig: configPda,
+          authority: bank.context.payer.publicKey,
+        })
+        .rpc();
+
+      registry = await bank.fraud.account.riskRegistry.fetch(riskRegistryPda(address));
+      expect(registry.listVersion.toNumber()).to.equal(3);
+      expect(registry.isActive).to.be.false;
+      const config = await bank.fraud.account.complianceConfig.fetch(configPda);
+      expect(config.riskListVersion.toNumber()).to.equal(3);
+
+      const record = await monitor(bank, user, 100, { recipient: address });
+      expect(flagTypes(record)).to.be.empty;
+    });
+
+    it("Rejects a window that ends before it starts", async () => {
+      const bank = await startBank();
+
+      await expectError(
+        addRiskAddress(bank, Keypair.generate().publicKey, { effectiveFrom: 100, effectiveUntil: 100 }),
+        "InvalidEffectiveWindow"
+      );
+    });
+  });
+
+  describe("rapid bursts", () => {
+    const burstFlag = (record: Awaited<ReturnType<typeof monitor>>) =>
+      record.flags.find((flag) => "unusualPattern" in flag.flagType);
+
+    it("Flags a 2-transaction burst as medium", async () => {
+      const bank = await startBank();
+      const user = await registerUser(bank);
+
+      await monitor(bank, user, 100);
+      const record = await monitor(bank, user, 100, { slots: 1 });
+
+      expect(burstFlag(record).severity).to.deep.equal({ medium: {} });
+      expect(burstFlag(record).description).to.contain("2 within 5 slots");
+    });
+
+    it("Scales severity with the size of the burst", async () => {
+      const bank = await startBank();
+      const user = await registerUser(bank);
+
+      await monitor(bank, user, 100);
+      const severities = [];
+      for (let i = 0; i < 4; i++) {
+        severities.push(burstFlag(await monitor(bank, user, 100, { slots: 1 })).severity);
+      }
+
+      expect(severities).to.deep.equal([{ medium: {} }, { medium: {} }, { high: {} }, { high: {} }]);
+    });
+
+    it("Uses the configured window and threshold", async () => {
+      const bank = await startBank();
+      await bank.fraud.methods
+        .setRapidPatternPolicy(new BN(10), 3)
+        .accounts({ complianceConfig: configPda, authority: bank.context.payer.publicKey })
+        .rpc();
+      const user = await registerUser(bank);
+
+      await monitor(bank, user, 100);
+      expect(burstFlag(await monitor(bank, user, 100, { slots: 4 }))).to.be.undefined;
+      const record = await monitor(bank, user, 100, { slots: 4 });
+      expect(burstFlag(record).severity).to.deep.equal({ medium: {} });
+      expect(burstFlag(record).description).to.contain("3 within 10 slots");
+    });
+  });
+
+  describe("risk registry", () => {
+    it("Stores the risk level it was given", async () => {
+      const bank = await startBank();
+      const critical = Keypair.generate().publicKey;
+      const low = Keypair.generate().publicKey;
+
+      await addRiskAddress(bank, critical, { riskLevel: { critical: {} } });
+      await addRiskAddress(bank, low, { riskCategory: { pep: {} }, riskLevel: { low: {} } });
+
+      const criticalEntry = await bank.fraud.account.riskRegistry.fetch(riskRegistryPda(critical));
+      expect(criticalEntry.riskLevel).to.deep.equal({ critical: {} });
+      expect(criticalEntry.riskCategory).to.deep.equal({ sanctions: {} });
+      const lowEntry = await bank.fraud.account.riskRegistry.fetch(riskRegistryPda(low));
+      expect(lowEntry.riskLevel).to.deep.equal({ low: {} });
+      expect(lowEntry.riskCategory).to.deep.equal({ pep: {} });
+    });
+  });
+
+  describe("whitelist audit trail", () => {
+    it("Records each flag the whitelist suppresses", async () => {
+      const bank = await startBank();
+      const user = await registerUser(bank, { enhanced: {} });
+      const merchant = Keypair.generate().publicKey;
+      await whitelistAddress(bank, merchant);
+
+      const { builder, transactionRecord } = await monitorTransaction(bank, user, 15_000, {
+        recipient: merchant,
+        whitelisted: merchant,
+      });
+      const suppressed = (await emittedEvents(bank, builder)).filter(
+        (event) => event.name === "FlagSuppressedByWhitelist"
+      );
+
+      expect(suppressed).to.have.length(1);
+      expect(suppressed[0].data.flagType).to.deep.equal({ highValueTransaction: {} });
+      expect((suppressed[0].data.recipient as PublicKey).toString()).to.equal(merchant.toString());
+      expect((suppressed[0].data.user as PublicKey).toString()).to.equal(user.toString());
+      expect((suppressed[0].data.slot as BN).toNumber()).to.equal(await currentSlot(bank));
+
+      const record = await bank.fraud.account.transactionRecord.fetch(transactionRecord);
+      expect(record.flags).to.be.empty;
+    });
+
+    it("Records nothing for a transfer that isn't whitelisted", async () => {
+      const bank = await startBank();
+      const user = await registerUser(bank, { enhanced: {} });
+
+      const { builder } = await monitorTransaction(bank, user, 15_000);
+      const events = await emittedEvents(bank, builder);
+
+      expect(events.map((event) => event.name)).to.not.include("FlagSuppressedByWhitelist");
+      expect(events.map((event) => event.name)).to.include("TransactionFlagged");
+    });
+  });
+
+  describe("AI risk scores", () => {
+    it("Reports the score before and after the update", async () => {
+      const bank = await startBank();
+      const user = await registerUser(bank);
+      // High value and an enhanced KYC upgrade, 15 + 5 points
+      await monitor(bank, user, 15_000);
+
+      const [updated] = (await emittedEvents(bank, updateRiskScoreAi(bank, user, 60))).filter(
+        (event) => event.name === "AIRiskScoreUpdated"
+      );
+
+      expect(updated.data.oldRiskScore).to.equal(20);
+      expect(updated.data.newRiskScore).to.equal(40);
+      expect(updated.data.aiRiskScore).to.equal(60);
+      const profile = await bank.fraud.account.userProfile.fetch(userProfilePda(user));
+      expect(profile.riskScore).to.equal(40);
+    });
+
+    it("Blocks on the raw AI score, not the averaged risk score", async () => {
+      const bank = await startBank();
+      const critical = await registerUser(bank);
+      const elevated = await registerUser(bank);
+
+      await updateRiskScoreAi(bank, critical, 95, ["Mixer interaction"]).rpc();
+      await updateRiskScoreAi(bank, elevated, 90, ["Unusual hours"]).rpc();
+
+      // The averaged score stays low, but a critical AI score blocks on its own
+      const criticalProfile = await bank.fraud.account.userProfile.fetch(userProfilePda(critical));
+      expect(criticalProfile.riskScore).to.equal(47);
+      expect(criticalProfile.isBlocked).to.be.true;
+      expect(criticalProfile.isCriticalBlock).to.be.true;
+
+      const elevatedProfile = await bank.fraud.account.userProfile.fetch(userProfilePda(elevated));
+      expect(elevatedProfile.riskScore).to.equal(45);
+      expect(elevatedProfile.isBlocked).to.be.false;
+      expect(elevatedProfile.flags.map((flag) => flag.severity)).to.deep.equal([{ critical: {} }]);
+    });
+  });
+
+  describe("high-risk recipient check", () => {
+    it("Flags a transfer to an active registry entry", async () => {
+      const bank = await startBank();
+      const user = await registerUser(bank);
+      const risky = Keypair.generate().publicKey;
+      await addRiskAddress(bank, risky);
+
+      const record = await monitor(bank, user, 100, { recipient: risky });
+
+      expect(record.flags[0].flagType).to.deep.equal({ highRiskRecipient: {} });
+      expect(record.flags[0].severity).to.deep.equal({ critical: {} });
+      expect(record.status).to.deep.equal({ blocked: {} });
+    });
+
+    it("Approves a clean recipient with no registry entry", async () => {
+      const bank = await startBank();
+      const user = await registerUser(bank);
+
+      const record = await monitor(bank, user, 100);
+
+      expect(record.flags).to.be.empty;
+      expect(record.status).to.deep.equal({ approved: {} });
+    });
+
+    it("Rejects a registry account that isn't the recipient's", async () => {
+      const bank = await startBank();
+      const user = await registerUser(bank);
+      const risky = Keypair.generate().publicKey;
+      await addRiskAddress(bank, risky);
+
+      // Another address's real entry, and an arbitrary account
+      await expectError(monitor(bank, user, 100, { riskRegistry: riskRegistryPda(risky) }), "RiskRegistryAccountMismatch");
+      await expectError(
+        monitor(bank, user, 100, { recipient: risky, riskRegistry: Keypair.generate().publicKey }),
+        "RiskRegistryAccountMismatch"
+      );
+    });
+
+    it("Rejects a spoofed account at the recipient's registry address", async () => {
+      const bank = await startBank();
+      const user = await registerUser(bank);
+      const recipient = Keypair.generate().publicKey;
+      bank.context.setAccount(riskRegistryPda(recipient), {
+        lamports: LAMPORTS_PER_SOL,
+        data: Buffer.alloc(64, 1),
+        owner: SystemProgram.programId,
+        executable: false,
+      });
+
+      await expectError(monitor(bank, user, 100, { recipient }), "RiskRegistryAccountMismatch");
+    });
+  });
+
+  describe("whitelist", () => {
+    it("Approves a whitelisted high-value transfer and flags the same transfer elsewhere", async () => {
+      const bank = await startBank();
+      const user = await registerUser(bank, { enhanced: {} });
+      const merchant = Keypair.generate().publicKey;
+      await whitelistAddress(bank, merchant);
+
+      const whitelisted = await monitor(bank, user, 15_000, { recipient: merchant, whitelisted: merchant });
+      expect(whitelisted.flags).to.be.empty;
+      expect(whitelisted.status).to.deep.equal({ approved: {} });
+
+      const other = await monitor(bank, user, 15_000);
+      expect(flagTypes(other)).to.deep.equal(["highValueTransaction"]);
+      expect(other.status).to.deep.equal({ flagged: {} });
+    });
+
+    it("Skips the high-risk check for a whitelisted user", async () => {
+      const bank = await startBank();
+      const user = await registerUser(bank, { enhanced: {} });
+      const risky = Keypair.generate().publicKey;
+      await addRiskAddress(bank, risky);
+      await whitelistAddress(bank, user);
+
+      const record = await monitor(bank, user, 100, { recipient: risky, whitelisted: user });
+
+      expect(record.flags).to.be.empty;
+      expect(record.status).to.deep.equal({ approved: {} });
+    });
+
+    it("Ignores a whitelist entry for an unrelated address", async () => {
+      const bank = await startBank();
+      const user = await registerUser(bank, { enhanced: {} });
+      const merchant = Keypair.generate().publicKey;
+      await whitelistAddress(bank, merchant);
+
+      const record = await monitor(bank, user, 15_000, { whitelisted: merchant });
+
+      expect(flagTypes(record)).to.deep.equal(["highValueTransaction"]);
+      expect(record.status).to.deep.equal({ flagged: {} });
+    });
+  });
+
+  describe("stored flag cap", () => {
+    const MAX_STORED_FLAGS = 10;
+
+    const recordAnomalies = (bank: Bank, user: PublicKey, indicators: string[]) =>
+      updateRiskScoreAi(bank, user, 20, indicators).rpc();
+
+    it("Keeps only the latest flags after 50 are pushed", async () => {
+      const bank = await startBank();
+      const user = await registerUser(bank, { enhanced: {} });
+
+      await recordAnomalies(bank, user, Array.from({ length: 50 }, (_, i) => `Anomaly ${i + 1}`));
+
+      let profile = await bank.fraud.account.userProfile.fetch(userProfilePda(user));
+      expect(profile.flags.map((flag) => flag.description)).to.deep.equal(
+        Array.from({ length: MAX_STORED_FLAGS }, (_, i) => `Anomaly ${41 + i}`)
+      );
+
+      // The full profile still serializes on the next flagged transaction
+      await monitor(bank, user, 15_000);
+      profile = await bank.fraud.account.userProfile.fetch(userProfilePda(user));
+      expect(profile.flags).to.have.length(MAX_STORED_FLAGS);
+      expect(profile.flags[0].description).to.equal("Anomaly 42");
+      expect(profile.flags[MAX_STORED_FLAGS - 1].flagType).to.deep.equal({ highValueTransaction: {} });
+    });
+
+    it("Truncates long flag descriptions", async () => {
+      const bank = await startBank();
+      const user = await registerUser(bank);
+
+      await recordAnomalies(bank, user, ["x".repeat(300)]);
+
+      const profile = await bank.fraud.account.userProfile.fetch(userProfilePda(user));
+      expect(profile.flags[0].description).to.equal("x".repeat(100));
+    });
+  });
+
+  describe("global counters", () => {
+    it("Counts flagged and blocked transactions", async () => {
+      const bank = await startBank();
+      const verified = await registerUser(bank, { basic: {} });
+      const unverified = await registerUser(bank, { none: {} });
+
+      expect((await monitor(bank, verified, 100)).status).to.deep.equal({ approved: {} });
+      expect((await monitor(bank, verified, 15_000)).status).to.deep.equal({ flagged: {} });
+      expect((await monitor(bank, unverified, 2_000)).status).to.deep.equal({ blocked: {} });
+
+      const config = await bank.fraud.account.complianceConfig.fetch(configPda);
+      expect(config.totalFlaggedTransactions.toNumber()).to.equal(2);
+      expect(config.totalBlockedTransactions.toNumber()).to.equal(1);
+      expect(config.lastUpdatedSlot.toNumber()).to.equal(await currentSlot(bank));
+    });
+  });
+
+  describe("KYC limits", () => {
+    const setKycLimits = (bank: Bank, noneLimitUsd: number, basicLimitUsd: number) =>
+      bank.fraud.methods
+        .setKycLimits(new BN(noneLimitUsd), new BN(basicLimitUsd))
+        .accounts({ complianceConfig: configPda, authority: bank.context.payer.publicKey })
+        .rpc();
+
+    it("Requires KYC above the configured no-KYC limit", async () => {
+      const bank = await startBank();
+      await setKycLimits(bank, 500, 10_000);
+      const user = await registerUser(bank, { none: {} });
+
+      const allowed = await monitor(bank, user, 400);
+      expect(allowed.status).to.deep.equal({ approved: {} });
+
+      const record = await monitor(bank, user, 600);
+      expect(flagTypes(record)).to.deep.equal(["kycRequired"]);
+      expect(record.flags[0].description).to.contain("$500");
+      expect(record.status).to.deep.equal({ blocked: {} });
+    });
+
+    it("Allows the default limit until it is lowered", async () => {
+      const bank = await startBank();
+      const user = await registerUser(bank, { none: {} });
+
+      expect(flagTypes(await monitor(bank, user, 600))).to.be.empty;
+      await setKycLimits(bank, 500, 10_000);
+      expect(flagTypes(await monitor(bank, user, 600))).to.deep.equal(["kycRequired"]);
+    });
+
+    it("Rejects a no-KYC limit above the basic limit", async () => {
+      const bank = await startBank();
+
+      await expectError(setKycLimits(bank, 20_000, 10_000), "InvalidKycLimits");
+    });
+  });
+
+  describe("short velocity window", () => {
+    // Outside the rapid window, so only the velocity checks apply
+    const SPACING = RAPID_WINDOW_SLOTS + 1;
+
+    it("Flags a burst inside the window as critical", async () => {
+      const bank = await startBank();
+      const user = await registerUser(bank);
+
+      // The default allows five transactions within 60 slots
+      for (let i = 0; i < 5; i++) {
+        expect(flagTypes(await monitor(bank, user, 100, { slots: SPACING }))).to.be.empty;
+      }
+      const record = await monitor(bank, user, 100, { slots: SPACING });
+
+      expect(flagTypes(record)).to.deep.equal(["highVelocity"]);
+      expect(record.flags[0].severity).to.deep.equal({ critical: {} });
+      expect(record.flags[0].description).to.contain("6 transactions within 60 slots");
+    });
+
+    it("Uses the configured window and threshold", async () => {
+      const bank = await startBank();
+      await bank.fraud.methods
+        .setVelocityBurstPolicy(new BN(30), 2)
+        .accounts({ complianceConfig: configPda, authority: bank.context.payer.publicKey })
+        .rpc();
+      const user = await registerUser(bank);
+
+      await monitor(bank, user, 100, { slots: 10 });
+      expect(flagTypes(await monitor(bank, user, 100, { slots: 10 }))).to.be.empty;
+      expect(flagTypes(await monitor(bank, user, 100, { slots: 10 }))).to.deep.equal(["highVelocity"]);
+
+      // Once the earlier transactions fall out of the window the burst is over
+      expect(flagTypes(await monitor(bank, user, 100, { slots: 30 }))).to.be.empty;
+    });
+  });
+
+  describe("compliance config view", () => {
+    // Call get_compliance_config and decode its return data
+    const getComplianceConfig = async (bank: Bank) => {
+      const tx = await bank.fraud.methods.getComplianceConfig().accounts({ complianceConfig: configPda }).transaction();
+      tx.recentBlockhash = bank.context.lastBlockhash;
+      tx.feePayer = bank.context.payer.publicKey;
+      tx.sign(bank.context.payer);
+      const { returnData } = await bank.context.banksClient.processTransaction(tx);
+      expect(returnData.programId.toString()).to.equal(program.programId.toString());
+      return new anchor.BorshCoder(IDL).types.decode("ComplianceParameters", Buffer.from(returnData.data));
+    };
+
+    it("Returns the stored parameters after an update", async () => {
+      const bank = await startBank();
+      const authority = bank.context.payer.publicKey;
+      await bank.fraud.methods
+        .setKycLimits(new BN(500), new BN(5_000))
+        .accounts({ complianceConfig: configPda, authority })
+        .rpc();
+      await bank.fraud.methods
+        .setMinKycLevel({ bridge: {} }, { enhanced: {} })
+        .accounts({ complianceConfig: configPda, authority })
+        .rpc();
+      await bank.fraud.methods
+        .setAutoUnblockPolicy(true, new BN(100))
+        .accounts({ complianceConfig: configPda, authority })
+        .rpc();
+      const user = await registerUser(bank);
+      await monitor(bank, user, 15_000);
+
+      const parameters = await getComplianceConfig(bank);
+      const config = await bank.fraud.account.complianceConfig.fetch(configPda);
+
+      expect(parameters.authority.toString()).to.equal(config.authority.toString());
+      expect(parameters.isActive).to.be.true;
+      expect(parameters.highValueThresholdUsd.toNumber()).to.equal(HIGH_VALUE_THRESHOLD_USD);
+      expect(parameters.velocityThreshold).to.equal(VELOCITY_THRESHOLD);
+      expect(parameters.maxDailyVolumeUsd.toNumber()).to.equal(MAX_DAILY_VOLUME_USD);
+      expect(parameters.slotsPerDay.toNumber()).to.equal(SLOTS_PER_DAY);
+      expect(parameters.rapidWindowSlots.toNumber()).to.equal(RAPID_WINDOW_SLOTS);
+      expect(parameters.rapidBurstThreshold).to.equal(config.rapidBurstThreshold);
+      expect(parameters.velocityWindowSlots.toNumber()).to.equal(config.velocityWindowSlots.toNumber());
+      expect(parameters.velocityBurstThreshold).to.equal(config.velocityBurstThreshold);
+      expect(parameters.oracleMaxStalenessSeconds.toNumber()).to.equal(config.oracleMaxStalenessSeconds.toNumber());
+      expect(parameters.oracleMaxConfidenceCents.toNumber()).to.equal(config.oracleMaxConfidenceCents.toNumber());
+      expect(parameters.kycNoneLimitUsd.toNumber()).to.equal(500);
+      expect(parameters.kycBasicLimitUsd.toNumber()).to.equal(5_000);
+      expect(parameters.minKycByTransactionType).to.deep.equal(config.minKycByTransactionType);
+      expect(parameters.minKycByTransactionType[3]).to.deep.equal({ enhanced: {} });
+      expect(parameters.autoUnblockEnabled).to.be.true;
+      expect(parameters.autoUnblockCooldownSlots.toNumber()).to.equal(100);
+      expect(parameters.severityRiskWeights).to.deep.equal([1, 5, 15, 50]);
+      expect(parameters.autoBlockRiskScore).to.equal(100);
+      expect(parameters.riskListVersion.toNumber()).to.equal(0);
+      expect(parameters.totalFlaggedTransactions.toNumber()).to.equal(1);
+      expect(parameters.totalBlockedTransactions.toNumber()).to.equal(0);
+      expect(parameters.lastUpdatedSlot.toNumber()).to.equal(config.lastUpdatedSlot.toNumber());
+    });
+  });
+});
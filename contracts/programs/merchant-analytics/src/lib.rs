@@ -6,6 +6,12 @@ use mpl_token_metadata::state::{DataV2, Creator};
 
 declare_id!("MERCxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx");
 
+/// Default age after which transaction records may be closed (90 days)
+pub const DEFAULT_RETENTION_PERIOD: i64 = 90 * 24 * 60 * 60;
+
+/// Age after which unredeemed loyalty points may be expired (365 days)
+pub const LOYALTY_POINTS_EXPIRY: i64 = 365 * 24 * 60 * 60;
+
 #[program]
 pub mod merchant_analytics {
     use super::*;
@@ -27,6 +33,19 @@ pub mod merchant_analytics {
         Ok(())
     }
 
+    /// Pause or unpause every write instruction (authority only)
+    pub fn set_pause(ctx: Context<SetPause>, is_paused: bool) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.is_paused = is_paused;
+
+        emit!(PauseUpdated {
+            is_paused,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
     /// Register merchant for analytics tracking
     pub fn register_merchant(
         ctx: Context<RegisterMerchant>,
@@ -49,8 +68,12 @@ pub mod merchant_analytics {
         merchant.total_customers = 0;
         merchant.total_transactions = 0;
         merchant.loyalty_points_issued = 0;
+        merchant.loyalty_points_redeemed = 0;
+        merchant.loyalty_points_expired = 0;
         merchant.is_active = true;
         merchant.created_at = Clock::get()?.unix_timestamp;
+        merchant.retention_period = DEFAULT_RETENTION_PERIOD;
+        merchant.transactions_closed = 0;
 
         config.total_merchants += 1;
 
@@ -78,6 +101,7 @@ pub mod merchant_analytics {
         let transaction = &mut ctx.accounts.transaction;
         let config = &mut ctx.accounts.config;
 
+        require!(!config.is_paused, ErrorCode::ProgramPaused);
         require!(merchant.is_active, ErrorCode::MerchantInactive);
         require!(amount > 0, ErrorCode::InvalidAmount);
         require!(transaction_hash.len() <= 100, ErrorCode::HashTooLong);
@@ -85,6 +109,7 @@ pub mod merchant_analytics {
 
         // Initialize transaction record
         transaction.merchant = merchant.key();
+        transaction.payer = ctx.accounts.authority.key();
         transaction.amount = amount;
         transaction.currency = currency;
         transaction.customer_id = customer_id.clone();
@@ -128,6 +153,7 @@ pub mod merchant_analytics {
         let merchant = &mut ctx.accounts.merchant;
         let loyalty_record = &mut ctx.accounts.loyalty_record;
 
+        require!(!ctx.accounts.config.is_paused, ErrorCode::ProgramPaused);
         require!(merchant.is_active, ErrorCode::MerchantInactive);
         require!(points > 0, ErrorCode::InvalidPoints);
         require!(customer_id.len() <= 100, ErrorCode::CustomerIdTooLong);
@@ -163,11 +189,21 @@ pub mod merchant_analytics {
         points_to_redeem: u32,
         reward_description: String,
     ) -> Result<()> {
-        let merchant = &ctx.accounts.merchant;
+        let merchant = &mut ctx.accounts.merchant;
         let redemption = &mut ctx.accounts.redemption;
 
+        require!(!ctx.accounts.config.is_paused, ErrorCode::ProgramPaused);
         require!(merchant.is_active, ErrorCode::MerchantInactive);
+        require!(
+            merchant.owner == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
         require!(points_to_redeem > 0, ErrorCode::InvalidPoints);
+        // Only points still outstanding can be redeemed, so the liability never underflows
+        require!(
+            points_to_redeem as u64 <= merchant.loyalty_liability(),
+            ErrorCode::InsufficientLoyaltyPoints
+        );
         require!(customer_id.len() <= 100, ErrorCode::CustomerIdTooLong);
         require!(reward_description.len() <= 200, ErrorCode::DescriptionTooLong);
 
@@ -179,6 +215,8 @@ pub mod merchant_analytics {
         redemption.status = RedemptionStatus::Pending;
         redemption.redeemed_at = Clock::get()?.unix_timestamp;
 
+        merchant.loyalty_points_redeemed += points_to_redeem as u64;
+
         emit!(LoyaltyPointsRedeemed {
             merchant_id: merchant.key(),
             redemption_id: redemption.key(),
@@ -191,6 +229,44 @@ pub mod merchant_analytics {
         Ok(())
     }
 
+    /// Expire an unredeemed loyalty record once it is older than `LOYALTY_POINTS_EXPIRY`
+    pub fn expire_loyalty_points(ctx: Context<ExpireLoyaltyPoints>) -> Result<()> {
+        let merchant = &mut ctx.accounts.merchant;
+        let loyalty_record = &mut ctx.accounts.loyalty_record;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        require!(!ctx.accounts.config.is_paused, ErrorCode::ProgramPaused);
+        require!(
+            merchant.owner == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            loyalty_record.status == LoyaltyStatus::Active,
+            ErrorCode::InvalidLoyaltyStatus
+        );
+        require!(
+            current_time >= loyalty_record.issued_at + LOYALTY_POINTS_EXPIRY,
+            ErrorCode::LoyaltyPointsNotExpired
+        );
+
+        loyalty_record.status = LoyaltyStatus::Expired;
+        merchant.loyalty_points_expired += loyalty_record.points as u64;
+
+        emit!(LoyaltyPointsExpired {
+            merchant_id: merchant.key(),
+            loyalty_id: loyalty_record.key(),
+            points: loyalty_record.points,
+            timestamp: current_time,
+        });
+
+        Ok(())
+    }
+
+    /// Outstanding loyalty liability: points issued minus redeemed minus expired
+    pub fn get_loyalty_liability(ctx: Context<GetAnalyticsSummary>) -> Result<u64> {
+        Ok(ctx.accounts.merchant.loyalty_liability())
+    }
+
     /// Mint NFT reward for top customers
     pub fn mint_customer_nft(
         ctx: Context<MintCustomerNft>,
@@ -201,6 +277,7 @@ pub mod merchant_analytics {
         let merchant = &ctx.accounts.merchant;
         let nft_reward = &mut ctx.accounts.nft_reward;
 
+        require!(!ctx.accounts.config.is_paused, ErrorCode::ProgramPaused);
         require!(merchant.is_active, ErrorCode::MerchantInactive);
         require!(customer_id.len() <= 100, ErrorCode::CustomerIdTooLong);
         require!(metadata_uri.len() <= 200, ErrorCode::UriTooLong);
@@ -275,6 +352,7 @@ pub mod merchant_analytics {
     ) -> Result<()> {
         let merchant = &mut ctx.accounts.merchant;
 
+        require!(!ctx.accounts.config.is_paused, ErrorCode::ProgramPaused);
         require!(
             merchant.owner == ctx.accounts.authority.key(),
             ErrorCode::Unauthorized
@@ -290,6 +368,116 @@ pub mod merchant_analytics {
 
         Ok(())
     }
+
+    /// Close a merchant account and return its rent to the owner (owner or authority).
+    /// Every loyalty point issued must first be redeemed or expired.
+    pub fn deregister_merchant(ctx: Context<DeregisterMerchant>) -> Result<()> {
+        let merchant = &ctx.accounts.merchant;
+        let config = &mut ctx.accounts.config;
+        let authority = ctx.accounts.authority.key();
+
+        require!(!config.is_paused, ErrorCode::ProgramPaused);
+        require!(
+            merchant.owner == authority || config.authority == authority,
+            ErrorCode::Unauthorized
+        );
+        require!(
+            merchant.loyalty_liability() == 0,
+            ErrorCode::OutstandingLoyaltyLiability
+        );
+
+        config.total_merchants = config.total_merchants.saturating_sub(1);
+
+        emit!(MerchantDeregistered {
+            merchant_id: merchant.key(),
+            owner: merchant.owner,
+            deregistered_by: authority,
+            rent_returned: merchant.to_account_info().lamports(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set how long transaction records are kept before they can be closed
+    pub fn set_retention_period(
+        ctx: Context<UpdateMerchantStatus>,
+        retention_period: i64,
+    ) -> Result<()> {
+        let merchant = &mut ctx.accounts.merchant;
+
+        require!(!ctx.accounts.config.is_paused, ErrorCode::ProgramPaused);
+        require!(
+            merchant.owner == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(retention_period > 0, ErrorCode::InvalidRetentionPeriod);
+
+        merchant.retention_period = retention_period;
+
+        emit!(RetentionPeriodUpdated {
+            merchant_id: merchant.key(),
+            retention_period,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Close transaction records older than the merchant's retention period,
+    /// passed as remaining accounts in (transaction, payer) pairs. Rent goes
+    /// back to whoever paid for the record when it was logged; merchant and
+    /// global aggregates are left untouched.
+    pub fn close_old_transactions<'info>(
+        ctx: Context<'_, '_, '_, 'info, CloseOldTransactions<'info>>,
+    ) -> Result<()> {
+        let merchant = &mut ctx.accounts.merchant;
+
+        require!(!ctx.accounts.config.is_paused, ErrorCode::ProgramPaused);
+        require!(
+            merchant.owner == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            !ctx.remaining_accounts.is_empty() && ctx.remaining_accounts.len() % 2 == 0,
+            ErrorCode::InvalidTransactionAccount
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let mut closed_count: u64 = 0;
+        let mut rent_reclaimed: u64 = 0;
+
+        for pair in ctx.remaining_accounts.chunks(2) {
+            let (account_info, payer) = (&pair[0], &pair[1]);
+            let transaction = Account::<Transaction>::try_from(account_info)
+                .map_err(|_| ErrorCode::InvalidTransactionAccount)?;
+
+            require!(
+                transaction.merchant == merchant.key(),
+                ErrorCode::InvalidTransactionAccount
+            );
+            require!(
+                now >= transaction.timestamp + merchant.retention_period,
+                ErrorCode::TransactionNotExpired
+            );
+            require!(payer.key() == transaction.payer, ErrorCode::RentPayerMismatch);
+
+            rent_reclaimed += account_info.lamports();
+            transaction.close(payer.clone())?;
+            closed_count += 1;
+        }
+
+        merchant.transactions_closed += closed_count;
+
+        emit!(TransactionsClosed {
+            merchant_id: merchant.key(),
+            closed_count,
+            rent_reclaimed,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
 }
 
 #[derive(Accounts)]
@@ -309,6 +497,19 @@ pub struct Initialize<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct SetPause<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, AnalyticsConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct RegisterMerchant<'info> {
     #[account(
@@ -382,6 +583,12 @@ pub struct IssueLoyaltyPoints<'info> {
     )]
     pub merchant: Account<'info, Merchant>,
     
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, AnalyticsConfig>,
+    
     #[account(mut)]
     pub authority: Signer<'info>,
     
@@ -400,17 +607,50 @@ pub struct RedeemLoyaltyPoints<'info> {
     pub redemption: Account<'info, LoyaltyRedemption>,
     
     #[account(
+        mut,
         seeds = [b"merchant", merchant.owner.as_ref()],
         bump
     )]
     pub merchant: Account<'info, Merchant>,
     
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, AnalyticsConfig>,
+    
     #[account(mut)]
     pub authority: Signer<'info>,
     
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct ExpireLoyaltyPoints<'info> {
+    #[account(
+        mut,
+        seeds = [b"loyalty", merchant.key().as_ref()],
+        bump,
+        constraint = loyalty_record.merchant == merchant.key() @ ErrorCode::Unauthorized
+    )]
+    pub loyalty_record: Account<'info, LoyaltyRecord>,
+    
+    #[account(
+        mut,
+        seeds = [b"merchant", merchant.owner.as_ref()],
+        bump
+    )]
+    pub merchant: Account<'info, Merchant>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, AnalyticsConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct MintCustomerNft<'info> {
     #[account(
@@ -438,6 +678,12 @@ pub struct MintCustomerNft<'info> {
     /// CHECK: Recipient account
     pub recipient: AccountInfo<'info>,
     
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, AnalyticsConfig>,
+    
     #[account(mut)]
     pub authority: Signer<'info>,
     
@@ -467,6 +713,58 @@ pub struct UpdateMerchantStatus<'info> {
     )]
     pub merchant: Account<'info, Merchant>,
     
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, AnalyticsConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DeregisterMerchant<'info> {
+    #[account(
+        mut,
+        seeds = [b"merchant", merchant.owner.as_ref()],
+        bump,
+        close = owner
+    )]
+    pub merchant: Account<'info, Merchant>,
+    
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, AnalyticsConfig>,
+    
+    #[account(
+        mut,
+        constraint = owner.key() == merchant.owner @ ErrorCode::Unauthorized
+    )]
+    /// CHECK: Merchant owner receiving the rent
+    pub owner: AccountInfo<'info>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseOldTransactions<'info> {
+    #[account(
+        mut,
+        seeds = [b"merchant", merchant.owner.as_ref()],
+        bump
+    )]
+    pub merchant: Account<'info, Merchant>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, AnalyticsConfig>,
+    
+    #[account(mut)]
     pub authority: Signer<'info>,
 }
 
@@ -495,15 +793,27 @@ pub struct Merchant {
     pub loyalty_points_issued: u64,
     pub is_active: bool,
     pub created_at: i64,
+    pub retention_period: i64,       // Seconds before a transaction record may be closed
+    pub transactions_closed: u64,
+    pub loyalty_points_redeemed: u64,
+    pub loyalty_points_expired: u64,
 }
 
 impl Merchant {
-    pub const INIT_SPACE: usize = 32 + 100 + 1 + 64 + 8 + 8 + 8 + 8 + 1 + 8;
+    pub const INIT_SPACE: usize = 32 + 100 + 1 + 64 + 8 + 8 + 8 + 8 + 1 + 8 + 8 + 8 + 8 + 8;
+
+    /// Outstanding loyalty liability: points issued minus redeemed minus expired
+    pub fn loyalty_liability(&self) -> u64 {
+        self.loyalty_points_issued
+            .saturating_sub(self.loyalty_points_redeemed)
+            .saturating_sub(self.loyalty_points_expired)
+    }
 }
 
 #[account]
 pub struct Transaction {
     pub merchant: Pubkey,
+    pub payer: Pubkey,               // Paid the record's rent; refunded when it is closed
     pub amount: u64,
     pub currency: Currency,
     pub customer_id: Option<String>,
@@ -513,7 +823,7 @@ pub struct Transaction {
 }
 
 impl Transaction {
-    pub const INIT_SPACE: usize = 32 + 8 + 1 + 100 + 100 + 500 + 8;
+    pub const INIT_SPACE: usize = 32 + 32 + 8 + 1 + 100 + 100 + 500 + 8;
 }
 
 #[account]
@@ -611,6 +921,12 @@ pub struct ProgramInitialized {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct PauseUpdated {
+    pub is_paused: bool,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct MerchantRegistered {
     pub merchant_id: Pubkey,
@@ -651,6 +967,14 @@ pub struct LoyaltyPointsRedeemed {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct LoyaltyPointsExpired {
+    pub merchant_id: Pubkey,
+    pub loyalty_id: Pubkey,
+    pub points: u32,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct CustomerNftMinted {
     pub merchant_id: Pubkey,
@@ -668,6 +992,30 @@ pub struct MerchantStatusUpdated {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct MerchantDeregistered {
+    pub merchant_id: Pubkey,
+    pub owner: Pubkey,
+    pub deregistered_by: Pubkey,
+    pub rent_returned: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RetentionPeriodUpdated {
+    pub merchant_id: Pubkey,
+    pub retention_period: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TransactionsClosed {
+    pub merchant_id: Pubkey,
+    pub closed_count: u64,
+    pub rent_reclaimed: u64,
+    pub timestamp: i64,
+}
+
 #[error_code]
 pub enum ErrorCode {
     #[msg("Program is currently paused")]
@@ -686,6 +1034,8 @@ pub enum ErrorCode {
     MetadataTooLong,
     #[msg("Invalid points amount")]
     InvalidPoints,
+    #[msg("Redemption exceeds outstanding loyalty points")]
+    InsufficientLoyaltyPoints,
     #[msg("Customer ID too long")]
     CustomerIdTooLong,
     #[msg("Reason too long")]
@@ -696,4 +1046,18 @@ pub enum ErrorCode {
     UriTooLong,
     #[msg("Unauthorized access")]
     Unauthorized,
+    #[msg("Invalid retention period")]
+    InvalidRetentionPeriod,
+    #[msg("Invalid transaction account")]
+    InvalidTransactionAccount,
+    #[msg("Transaction is within the retention period")]
+    TransactionNotExpired,
+    #[msg("Loyalty record is not active")]
+    InvalidLoyaltyStatus,
+    #[msg("Loyalty points have not expired yet")]
+    LoyaltyPointsNotExpired,
+    #[msg("Merchant still has outstanding loyalty points")]
+    OutstandingLoyaltyLiability,
+    #[msg("Rent must be refunded to the account that paid for the record")]
+    RentPayerMismatch,
 }
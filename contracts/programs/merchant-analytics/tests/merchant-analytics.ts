@@ -0,0 +1,506 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, LAMPORTS_PER_SOL, SystemProgram } from "@solana/web3.js";
+import { Clock, startAnchor } from "solana-bankrun";
+import { BankrunProvider } from "anchor-bankrun";
+import { IDL, MerchantAnalytics } from "../target/types/merchant_analytics";
+import { expect } from "chai";
+
+describe("merchant-analytics", () => {
+  // Configure the client to use the local cluster.
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.MerchantAnalytics as Program<MerchantAnalytics>;
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+
+  const API_KEY = "a".repeat(64);
+
+  const [configPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    program.programId
+  );
+
+  const merchantPda = (owner: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("merchant"), owner.toBuffer()],
+      program.programId
+    )[0];
+
+  const transactionPda = (merchant: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("transaction"), merchant.toBuffer()],
+      program.programId
+    )[0];
+
+  const loyaltyPda = (merchant: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("loyalty"), merchant.toBuffer()],
+      program.programId
+    )[0];
+
+  const redemptionPda = (merchant: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("redemption"), merchant.toBuffer()],
+      program.programId
+    )[0];
+
+  const fundedKeypair = async () => {
+    const keypair = Keypair.generate();
+    const signature = await provider.connection.requestAirdrop(keypair.publicKey, LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(signature);
+    return keypair;
+  };
+
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    try {
+      await promise;
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal(code);
+      return;
+    }
+    expect.fail(`expected ${code}`);
+  };
+
+  const registerMerchant = (owner: Keypair) =>
+    program.methods
+      .registerMerchant(`Merchant ${owner.publicKey.toBase58().slice(0, 8)}`, { retail: {} }, API_KEY)
+      .accounts({
+        merchant: merchantPda(owner.publicKey),
+        config: configPda,
+        owner: owner.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+
+  // A funded owner with a registered merchant
+  const createMerchant = async () => {
+    const owner = await fundedKeypair();
+    await registerMerchant(owner);
+    return { owner, merchant: merchantPda(owner.publicKey) };
+  };
+
+  // Logged by the owner unless another `payer` signs and funds the record
+  const logTransaction = (owner: Keypair, amount: number, payer = owner) => {
+    const merchant = merchantPda(owner.publicKey);
+    return program.methods
+      .logTransaction(new BN(amount), { usdc: {} }, "customer-1", `tx-${amount}`, "{}")
+      .accounts({
+        transaction: transactionPda(merchant),
+        merchant,
+        config: configPda,
+        authority: payer.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([payer])
+      .rpc();
+  };
+
+  const issueLoyaltyPoints = (owner: Keypair, points: number, analytics = program) => {
+    const merchant = merchantPda(owner.publicKey);
+    return analytics.methods
+      .issueLoyaltyPoints("customer-1", points, "Purchase")
+      .accounts({
+        loyaltyRecord: loyaltyPda(merchant),
+        merchant,
+        config: configPda,
+        authority: owner.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+  };
+
+  const redeemLoyaltyPoints = (owner: Keypair, points: number) => {
+    const merchant = merchantPda(owner.publicKey);
+    return program.methods
+      .redeemLoyaltyPoints("customer-1", points, "Free coffee")
+      .accounts({
+        redemption: redemptionPda(merchant),
+        merchant,
+        config: configPda,
+        authority: owner.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+  };
+
+  const expireLoyaltyPoints = (owner: Keypair, analytics = program) => {
+    const merchant = merchantPda(owner.publicKey);
+    return analytics.methods
+      .expireLoyaltyPoints()
+      .accounts({
+        loyaltyRecord: loyaltyPda(merchant),
+        merchant,
+        config: configPda,
+        authority: owner.publicKey,
+      })
+      .signers([owner])
+      .rpc();
+  };
+
+  const loyaltyLiability = (merchant: PublicKey) =>
+    program.methods.getLoyaltyLiability().accounts({ merchant }).view() as Promise<BN>;
+
+  const deregisterMerchant = (owner: PublicKey, authority?: Keypair) =>
+    program.methods
+      .deregisterMerchant()
+      .accounts({
+        merchant: merchantPda(owner),
+        config: configPda,
+        owner,
+        authority: authority ? authority.publicKey : provider.wallet.publicKey,
+      })
+      .signers(authority ? [authority] : [])
+      .rpc();
+
+  const setRetentionPeriod = (owner: Keypair, seconds: number) =>
+    program.methods
+      .setRetentionPeriod(new BN(seconds))
+      .accounts({
+        merchant: merchantPda(owner.publicKey),
+        config: configPda,
+        authority: owner.publicKey,
+      })
+      .signers([owner])
+      .rpc();
+
+  // Each record is passed with the account refunded its rent, the owner by default
+  const closeOldTransactions = (owner: Keypair, transactions: PublicKey[], payer = owner.publicKey) =>
+    program.methods
+      .closeOldTransactions()
+      .accounts({
+        merchant: merchantPda(owner.publicKey),
+        config: configPda,
+        authority: owner.publicKey,
+      })
+      .remainingAccounts(
+        transactions.flatMap((pubkey) => [
+          { pubkey, isWritable: true, isSigner: false },
+          { pubkey: payer, isWritable: true, isSigner: false },
+        ])
+      )
+      .signers([owner])
+      .rpc();
+
+  before(async () => {
+    await program.methods
+      .initialize()
+      .accounts({
+        config: configPda,
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  });
+
+  describe("data retention", () => {
+    it("Closes records past retention, keeping aggregates and refunding rent", async () => {
+      const { owner, merchant } = await createMerchant();
+      await setRetentionPeriod(owner, 1);
+      await logTransaction(owner, 5_000);
+
+      const transaction = transactionPda(merchant);
+      const rent = await provider.connection.getBalance(transaction);
+      const merchantBefore = await program.account.merchant.fetch(merchant);
+      const configBefore = await program.account.analyticsConfig.fetch(configPda);
+      const ownerBalance = await provider.connection.getBalance(owner.publicKey);
+
+      // Let the record age past the one-second retention period
+      await new Promise((resolve) => setTimeout(resolve, 3000));
+      await closeOldTransactions(owner, [transaction]);
+
+      expect(await provider.connection.getAccountInfo(transaction)).to.equal(null);
+      expect(await provider.connection.getBalance(owner.publicKey)).to.equal(ownerBalance + rent);
+
+      const merchantAfter = await program.account.merchant.fetch(merchant);
+      expect(merchantAfter.transactionsClosed.toNumber()).to.equal(1);
+      expect(merchantAfter.totalSales.toNumber()).to.equal(merchantBefore.totalSales.toNumber());
+      expect(merchantAfter.totalTransactions.toNumber()).to.equal(1);
+
+      const configAfter = await program.account.analyticsConfig.fetch(configPda);
+      expect(configAfter.totalTransactions.toNumber()).to.equal(configBefore.totalTransactions.toNumber());
+      expect(configAfter.totalVolume.toNumber()).to.equal(configBefore.totalVolume.toNumber());
+    });
+
+    it("Refunds rent to whoever paid for the record", async () => {
+      const { owner, merchant } = await createMerchant();
+      await setRetentionPeriod(owner, 1);
+      const logger = await fundedKeypair();
+      await logTransaction(owner, 5_000, logger);
+
+      const transaction = transactionPda(merchant);
+      expect((await program.account.transaction.fetch(transaction)).payer.toString()).to.equal(
+        logger.publicKey.toString()
+      );
+      const rent = await provider.connection.getBalance(transaction);
+      const loggerBalance = await provider.connection.getBalance(logger.publicKey);
+
+      await new Promise((resolve) => setTimeout(resolve, 3000));
+      await expectError(closeOldTransactions(owner, [transaction]), "RentPayerMismatch");
+
+      await closeOldTransactions(owner, [transaction], logger.publicKey);
+      expect(await provider.connection.getAccountInfo(transaction)).to.equal(null);
+      expect(await provider.connection.getBalance(logger.publicKey)).to.equal(loggerBalance + rent);
+    });
+
+    it("Keeps records still within the retention period", async () => {
+      const { owner, merchant } = await createMerchant();
+      await logTransaction(owner, 5_000);
+
+      await expectError(closeOldTransactions(owner, [transactionPda(merchant)]), "TransactionNotExpired");
+      expect(await provider.connection.getAccountInfo(transactionPda(merchant))).to.not.equal(null);
+    });
+
+    it("Rejects records belonging to another merchant", async () => {
+      const { owner } = await createMerchant();
+      const other = await createMerchant();
+      await logTransaction(other.owner, 5_000);
+
+      await expectError(
+        closeOldTransactions(owner, [transactionPda(other.merchant)]),
+        "InvalidTransactionAccount"
+      );
+    });
+
+    it("Only lets the merchant owner change the retention period", async () => {
+      const { owner } = await createMerchant();
+
+      await expectError(
+        program.methods
+          .setRetentionPeriod(new BN(1))
+          .accounts({
+            merchant: merchantPda(owner.publicKey),
+            config: configPda,
+            authority: provider.wallet.publicKey,
+          })
+          .rpc(),
+        "Unauthorized"
+      );
+    });
+  });
+
+  describe("loyalty liability", () => {
+    const YEAR = 365 * 24 * 60 * 60;
+
+    it("Reports issued points less redemptions as the liability", async () => {
+      const { owner, merchant } = await createMerchant();
+
+      await issueLoyaltyPoints(owner, 100);
+      expect((await loyaltyLiability(merchant)).toNumber()).to.equal(100);
+
+      await redeemLoyaltyPoints(owner, 40);
+
+      const account = await program.account.merchant.fetch(merchant);
+      expect(account.loyaltyPointsIssued.toNumber()).to.equal(100);
+      expect(account.loyaltyPointsRedeemed.toNumber()).to.equal(40);
+      expect(account.loyaltyPointsExpired.toNumber()).to.equal(0);
+      expect((await loyaltyLiability(merchant)).toNumber()).to.equal(60);
+    });
+
+    it("Rejects redeeming more than the outstanding points", async () => {
+      const { owner } = await createMerchant();
+      await issueLoyaltyPoints(owner, 100);
+
+      await expectError(redeemLoyaltyPoints(owner, 101), "InsufficientLoyaltyPoints");
+    });
+
+    it("Rejects expiring points younger than a year", async () => {
+      const { owner } = await createMerchant();
+      await issueLoyaltyPoints(owner, 100);
+
+      await expectError(expireLoyaltyPoints(owner), "LoyaltyPointsNotExpired");
+    });
+
+    it("Removes expired points from the liability", async () => {
+      // A fresh bank so the clock can move a year ahead
+      const context = await startAnchor(".", [], []);
+      const bankrunProgram = new Program<MerchantAnalytics>(IDL, program.programId, new BankrunProvider(context));
+      await bankrunProgram.methods
+        .initialize()
+        .accounts({
+          config: configPda,
+          authority: context.payer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const owner = Keypair.generate();
+      context.setAccount(owner.publicKey, {
+        lamports: LAMPORTS_PER_SOL,
+        data: Buffer.alloc(0),
+        owner: SystemProgram.programId,
+        executable: false,
+      });
+      const merchant = merchantPda(owner.publicKey);
+      await bankrunProgram.methods
+        .registerMerchant("Expiring Merchant", { retail: {} }, API_KEY)
+        .accounts({
+          merchant,
+          config: configPda,
+          owner: owner.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([owner])
+        .rpc();
+      await issueLoyaltyPoints(owner, 100, bankrunProgram);
+
+      // Move to the next slot, for a fresh blockhash, and a year ahead
+      const clock = await context.banksClient.getClock();
+      const slot = clock.slot + BigInt(1);
+      context.warpToSlot(slot);
+      context.setClock(
+        new Clock(
+          slot,
+          clock.epochStartTimestamp,
+          clock.epoch,
+          clock.leaderScheduleEpoch,
+          clock.unixTimestamp + BigInt(YEAR)
+        )
+      );
+
+      await expireLoyaltyPoints(owner, bankrunProgram);
+
+      const account = await bankrunProgram.account.merchant.fetch(merchant);
+      expect(account.loyaltyPointsIssued.toNumber()).to.equal(100);
+      expect(account.loyaltyPointsExpired.toNumber()).to.equal(100);
+      const liability = await bankrunProgram.methods.getLoyaltyLiability().accounts({ merchant }).view();
+      expect(liability.toNumber()).to.equal(0);
+
+      const record = await bankrunProgram.account.loyaltyRecord.fetch(loyaltyPda(merchant));
+      expect(record.status).to.deep.equal({ expired: {} });
+    });
+  });
+
+  describe("pause", () => {
+    const setPause = (isPaused: boolean, authority?: Keypair) =>
+      program.methods
+        .setPause(isPaused)
+        .accounts({
+          config: configPda,
+          authority: authority ? authority.publicKey : provider.wallet.publicKey,
+        })
+        .signers(authority ? [authority] : [])
+        .rpc();
+
+    // `seeded` holds a loyalty record and a transaction; `fresh` has no records yet
+    let seeded: { owner: Keypair; merchant: PublicKey };
+    let fresh: { owner: Keypair; merchant: PublicKey };
+
+    before(async () => {
+      seeded = await createMerchant();
+      await logTransaction(seeded.owner, 5_000);
+      await issueLoyaltyPoints(seeded.owner, 100);
+      fresh = await createMerchant();
+    });
+
+    it("Blocks every write instruction while paused", async () => {
+      const newOwner = await fundedKeypair();
+      await setPause(true);
+
+      try {
+        expect((await program.account.analyticsConfig.fetch(configPda)).isPaused).to.equal(true);
+
+        await expectError(registerMerchant(newOwner), "ProgramPaused");
+        await expectError(logTransaction(fresh.owner, 5_000), "ProgramPaused");
+        await expectError(issueLoyaltyPoints(fresh.owner, 100), "ProgramPaused");
+        await expectError(redeemLoyaltyPoints(seeded.owner, 10), "ProgramPaused");
+        await expectError(expireLoyaltyPoints(seeded.owner), "ProgramPaused");
+        await expectError(setRetentionPeriod(fresh.owner, 1), "ProgramPaused");
+        await expectError(
+          closeOldTransactions(seeded.owner, [transactionPda(seeded.merchant)]),
+          "ProgramPaused"
+        );
+        await expectError(
+          program.methods
+            .updateMerchantStatus(false)
+            .accounts({ merchant: fresh.merchant, config: configPda, authority: fresh.owner.publicKey })
+            .signers([fresh.owner])
+            .rpc(),
+          "ProgramPaused"
+        );
+        await expectError(deregisterMerchant(fresh.owner.publicKey, fresh.owner), "ProgramPaused");
+      } finally {
+        await setPause(false);
+      }
+
+      const merchant = await program.account.merchant.fetch(seeded.merchant);
+      expect(merchant.totalTransactions.toNumber()).to.equal(1);
+      expect(merchant.loyaltyPointsRedeemed.toNumber()).to.equal(0);
+    });
+
+    it("Still serves reads while paused", async () => {
+      await setPause(true);
+
+      try {
+        const summary = await program.methods
+          .getAnalyticsSummary()
+          .accounts({ merchant: seeded.merchant })
+          .view();
+        expect(summary.totalSales.toNumber()).to.equal(5_000);
+        expect(summary.loyaltyPointsIssued.toNumber()).to.equal(100);
+        expect((await loyaltyLiability(seeded.merchant)).toNumber()).to.equal(100);
+      } finally {
+        await setPause(false);
+      }
+    });
+
+    it("Accepts writes again once unpaused", async () => {
+      await logTransaction(fresh.owner, 7_000);
+
+      const merchant = await program.account.merchant.fetch(fresh.merchant);
+      expect(merchant.totalSales.toNumber()).to.equal(7_000);
+    });
+
+    it("Only lets the config authority pause", async () => {
+      await expectError(setPause(true, await fundedKeypair()), "Unauthorized");
+    });
+  });
+
+  describe("deregistration", () => {
+    it("Closes a clean merchant, refunding rent and decrementing the count", async () => {
+      const { owner, merchant } = await createMerchant();
+      const rent = await provider.connection.getBalance(merchant);
+      const ownerBalance = await provider.connection.getBalance(owner.publicKey);
+      const { totalMerchants } = await program.account.analyticsConfig.fetch(configPda);
+
+      await deregisterMerchant(owner.publicKey, owner);
+
+      expect(await provider.connection.getAccountInfo(merchant)).to.equal(null);
+      expect(await provider.connection.getBalance(owner.publicKey)).to.equal(ownerBalance + rent);
+      const config = await program.account.analyticsConfig.fetch(configPda);
+      expect(config.totalMerchants.toNumber()).to.equal(totalMerchants.toNumber() - 1);
+    });
+
+    it("Lets the config authority deregister, refunding the owner", async () => {
+      const { owner, merchant } = await createMerchant();
+      const rent = await provider.connection.getBalance(merchant);
+      const ownerBalance = await provider.connection.getBalance(owner.publicKey);
+
+      await deregisterMerchant(owner.publicKey);
+
+      expect(await provider.connection.getAccountInfo(merchant)).to.equal(null);
+      expect(await provider.connection.getBalance(owner.publicKey)).to.equal(ownerBalance + rent);
+    });
+
+    it("Rejects deregistration while loyalty points are outstanding", async () => {
+      const { owner, merchant } = await createMerchant();
+      await issueLoyaltyPoints(owner, 100);
+
+      await expectError(deregisterMerchant(owner.publicKey, owner), "OutstandingLoyaltyLiability");
+      expect(await provider.connection.getAccountInfo(merchant)).to.not.equal(null);
+
+      // Redeeming the rest clears the liability
+      await redeemLoyaltyPoints(owner, 100);
+      await deregisterMerchant(owner.publicKey, owner);
+      expect(await provider.connection.getAccountInfo(merchant)).to.equal(null);
+    });
+
+    it("Rejects anyone but the owner or config authority", async () => {
+      const { owner } = await createMerchant();
+
+      await expectError(deregisterMerchant(owner.publicKey, await fundedKeypair()), "Unauthorized");
+    });
+  });
+});
@@ -7,6 +7,9 @@ use anchor_spl::{
 // Program ID needs to be updated after deployment
 declare_id!("MerchantRewards11111111111111111111111111111");
 
+// Maximum number of accruals a user can have vesting at once
+pub const MAX_VESTING_TRANCHES: usize = 16;
+
 #[program]
 pub mod merchant_rewards {
     use super::*;
@@ -16,45 +19,111 @@ pub mod merchant_rewards {
         ctx: Context<InitializeRewardPool>,
         reward_mint: Pubkey,
         kyc_verification_program: Pubkey,
+        cliff_duration: i64,
+        vesting_duration: i64,
     ) -> Result<()> {
+        require!(
+            cliff_duration >= 0 && vesting_duration > 0 && cliff_duration <= vesting_duration,
+            ErrorCode::InvalidVestingSchedule
+        );
+
         let reward_pool = &mut ctx.accounts.reward_pool;
         reward_pool.admin = *ctx.accounts.admin.key;
         reward_pool.reward_mint = reward_mint;
         reward_pool.kyc_verification_program = kyc_verification_program;
+        reward_pool.cliff_duration = cliff_duration;
+        reward_pool.vesting_duration = vesting_duration;
         reward_pool.bump = *ctx.bumps.get("reward_pool").unwrap();
         
         Ok(())
     }
 
-    // Claim merchant rewards (only callable by KYC-verified users)
+    // Accrue rewards for a user, vesting on the pool's cliff + linear schedule
+    pub fn accrue_rewards(
+        ctx: Context<AccrueRewards>,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        let reward_pool = &ctx.accounts.reward_pool;
+        let vesting_account = &mut ctx.accounts.vesting_account;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        if vesting_account.user == Pubkey::default() {
+            vesting_account.user = ctx.accounts.user.key();
+            vesting_account.bump = *ctx.bumps.get("vesting_account").unwrap();
+        }
+
+        // Each accrual vests on its own schedule from now, leaving earlier tranches on theirs
+        vesting_account.settle_vested_tranches(current_time);
+        require!(
+            vesting_account.tranches.len() < MAX_VESTING_TRANCHES,
+            ErrorCode::TooManyVestingTranches
+        );
+        vesting_account.tranches.push(VestingTranche {
+            amount,
+            start_ts: current_time,
+            cliff_duration: reward_pool.cliff_duration,
+            vesting_duration: reward_pool.vesting_duration,
+        });
+        vesting_account.total_amount = vesting_account.total_amount
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        emit!(RewardAccrued {
+            user: vesting_account.user,
+            amount,
+            total_amount: vesting_account.total_amount,
+            timestamp: current_time,
+        });
+
+        Ok(())
+    }
+
+    // Claim vested merchant rewards (only callable by KYC-verified users)
     pub fn claim_rewards(
         ctx: Context<ClaimRewards>,
-        amount: u64,
     ) -> Result<()> {
         // Verify the user has a KYC SBT
         let kyc_verification_account = &ctx.accounts.kyc_verification_account;
         // In a real implementation, you would verify the KYC SBT ownership here
         // This is a simplified example
-        
+
+        // Only the vested, not yet claimed portion can be released
+        let current_time = Clock::get()?.unix_timestamp;
+        let vested_amount = ctx.accounts.vesting_account.vested_amount(current_time)?;
+        let amount = vested_amount
+            .checked_sub(ctx.accounts.vesting_account.claimed_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(amount > 0, ErrorCode::NothingVested);
+        require!(ctx.accounts.reward_vault.amount >= amount, ErrorCode::InsufficientRewards);
+
         // Transfer rewards from pool to user
+        let seeds = &[b"reward_pool".as_ref(), &[ctx.accounts.reward_pool.bump]];
+        let signer = &[&seeds[..]];
+
         let cpi_accounts = Transfer {
             from: ctx.accounts.reward_vault.to_account_info(),
             to: ctx.accounts.user_reward_ata.to_account_info(),
             authority: ctx.accounts.reward_pool.to_account_info(),
         };
         
-        let cpi_ctx = CpiContext::new(
+        let cpi_ctx = CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
             cpi_accounts,
+            signer,
         );
         
         token::transfer(cpi_ctx, amount)?;
+
+        let vesting_account = &mut ctx.accounts.vesting_account;
+        vesting_account.claimed_amount = vested_amount;
         
         // Emit event
         emit!(RewardClaimed {
             user: ctx.accounts.user.key(),
             amount,
-            timestamp: Clock::get()?.unix_timestamp,
+            timestamp: current_time,
         });
         
         Ok(())
@@ -68,7 +137,7 @@ pub struct InitializeRewardPool<'info> {
     #[account(
         init,
         payer = admin,
-        space = 8 + 32 + 32 + 32 + 1,
+        space = 8 + 32 + 32 + 32 + 8 + 8 + 1,
         seeds = [b"reward_pool"],
         bump,
     )]
@@ -79,6 +148,33 @@ pub struct InitializeRewardPool<'info> {
     pub system_program: Program<'info, System>,
 }
 
+// Accounts for accrue_rewards
+#[derive(Accounts)]
+pub struct AccrueRewards<'info> {
+    #[account(
+        seeds = [b"reward_pool"],
+        bump = reward_pool.bump,
+        has_one = admin @ ErrorCode::Unauthorized,
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + 32 + 8 + 8 + 8 + 4 + MAX_VESTING_TRANCHES * (8 + 8 + 8 + 8) + 1,
+        seeds = [b"vesting", user.key().as_ref()],
+        bump,
+    )]
+    pub vesting_account: Account<'info, VestingAccount>,
+
+    /// CHECK: Only used as the vesting account owner
+    pub user: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
 // Accounts for claim_rewards
 #[derive(Accounts)]
 pub struct ClaimRewards<'info> {
@@ -87,6 +183,14 @@ pub struct ClaimRewards<'info> {
         bump = reward_pool.bump,
     )]
     pub reward_pool: Account<'info, RewardPool>,
+
+    #[account(
+        mut,
+        seeds = [b"vesting", user.key().as_ref()],
+        bump = vesting_account.bump,
+        constraint = vesting_account.user == user.key() @ ErrorCode::Unauthorized,
+    )]
+    pub vesting_account: Account<'info, VestingAccount>,
     
     #[account(
         mut,
@@ -118,9 +222,88 @@ pub struct RewardPool {
     pub admin: Pubkey,
     pub reward_mint: Pubkey,
     pub kyc_verification_program: Pubkey,
+    pub cliff_duration: i64,
+    pub vesting_duration: i64,
     pub bump: u8,
 }
 
+// Per-user vesting state for accrued rewards
+#[account]
+pub struct VestingAccount {
+    pub user: Pubkey,
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    pub settled_amount: u64, // Fully vested tranches that have been folded out of `tranches`
+    pub tranches: Vec<VestingTranche>,
+    pub bump: u8,
+}
+
+// One accrual and the cliff + linear schedule it vests on
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct VestingTranche {
+    pub amount: u64,
+    pub start_ts: i64,
+    pub cliff_duration: i64,
+    pub vesting_duration: i64,
+}
+
+impl VestingTranche {
+    // Nothing vests before the cliff, then the amount releases linearly until fully vested
+    pub fn vested_amount(&self, current_time: i64) -> Result<u64> {
+        let elapsed = current_time.saturating_sub(self.start_ts);
+        if elapsed < self.cliff_duration {
+            return Ok(0);
+        }
+        if elapsed >= self.vesting_duration {
+            return Ok(self.amount);
+        }
+
+        let vested = (self.amount as u128)
+            .checked_mul(elapsed as u128)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(self.vesting_duration as u128)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(vested as u64)
+    }
+
+    pub fn is_fully_vested(&self, current_time: i64) -> bool {
+        current_time.saturating_sub(self.start_ts) >= self.vesting_duration
+    }
+}
+
+impl VestingAccount {
+    pub fn vested_amount(&self, current_time: i64) -> Result<u64> {
+        self.tranches.iter().try_fold(self.settled_amount, |total, tranche| {
+            total
+                .checked_add(tranche.vested_amount(current_time)?)
+                .ok_or(ErrorCode::MathOverflow.into())
+        })
+    }
+
+    // Fold fully vested tranches into `settled_amount` to make room for new accruals
+    pub fn settle_vested_tranches(&mut self, current_time: i64) {
+        let mut settled_amount = self.settled_amount;
+        self.tranches.retain(|tranche| {
+            if tranche.is_fully_vested(current_time) {
+                settled_amount += tranche.amount;
+                false
+            } else {
+                true
+            }
+        });
+        self.settled_amount = settled_amount;
+    }
+}
+
+// Event emitted when rewards are accrued for a user
+#[event]
+pub struct RewardAccrued {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub total_amount: u64,
+    pub timestamp: i64,
+}
+
 // Event emitted when rewards are claimed
 #[event]
 pub struct RewardClaimed {
@@ -138,4 +321,14 @@ pub enum ErrorCode {
     InsufficientRewards,
     #[msg("Unauthorized")]
     Unauthorized,
+    #[msg("Invalid vesting schedule")]
+    InvalidVestingSchedule,
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+    #[msg("No vested rewards available to claim")]
+    NothingVested,
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Too many reward accruals are still vesting")]
+    TooManyVestingTranches,
 }
@@ -0,0 +1,254 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, LAMPORTS_PER_SOL, SystemProgram } from "@solana/web3.js";
+import {
+  ACCOUNT_SIZE,
+  AccountLayout,
+  AccountState,
+  MINT_SIZE,
+  MintLayout,
+  TOKEN_PROGRAM_ID,
+  getAssociatedTokenAddressSync,
+} from "@solana/spl-token";
+import { Clock, ProgramTestContext, startAnchor } from "solana-bankrun";
+import { BankrunProvider } from "anchor-bankrun";
+import { IDL, MerchantRewards } from "../target/types/merchant_rewards";
+import { expect } from "chai";
+
+describe("merchant-rewards", () => {
+  // Configure the client to use the local cluster.
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.MerchantRewards as Program<MerchantRewards>;
+
+  const DAY = 24 * 60 * 60;
+  const CLIFF_DURATION = DAY;
+  const VESTING_DURATION = 4 * DAY;
+
+  const [rewardPoolPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("reward_pool")],
+    program.programId
+  );
+
+  const vestingPda = (user: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("vesting"), user.toBuffer()],
+      program.programId
+    )[0];
+
+  const expectRejected = async (promise: Promise<unknown>) => {
+    try {
+      await promise;
+    } catch {
+      return;
+    }
+    expect.fail("expected the transaction to fail");
+  };
+
+  // A fresh bank with a funded reward pool, so the clock can be moved through the schedule
+  const startPool = async () => {
+    const context = await startAnchor(".", [], []);
+    const bankrunProgram = new Program<MerchantRewards>(IDL, program.programId, new BankrunProvider(context));
+
+    const rewardMint = setMint(context);
+    const rewardVault = setTokenAccount(context, rewardMint, rewardPoolPda, 10_000_000);
+
+    await bankrunProgram.methods
+      .initializeRewardPool(rewardMint, Keypair.generate().publicKey, new BN(CLIFF_DURATION), new BN(VESTING_DURATION))
+      .accounts({
+        rewardPool: rewardPoolPda,
+        admin: context.payer.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    return { context, bankrunProgram, rewardMint, rewardVault };
+  };
+
+  const setMint = (context: ProgramTestContext) => {
+    const mint = Keypair.generate().publicKey;
+    const data = Buffer.alloc(MINT_SIZE);
+    MintLayout.encode(
+      {
+        mintAuthorityOption: 0,
+        mintAuthority: PublicKey.default,
+        supply: BigInt(0),
+        decimals: 6,
+        isInitialized: true,
+        freezeAuthorityOption: 0,
+        freezeAuthority: PublicKey.default,
+      },
+      data
+    );
+    context.setAccount(mint, { lamports: LAMPORTS_PER_SOL, data, owner: TOKEN_PROGRAM_ID, executable: false });
+    return mint;
+  };
+
+  const setTokenAccount = (context: ProgramTestContext, mint: PublicKey, owner: PublicKey, amount: number) => {
+    const address = getAssociatedTokenAddressSync(mint, owner, true);
+    const data = Buffer.alloc(ACCOUNT_SIZE);
+    AccountLayout.encode(
+      {
+        mint,
+        owner,
+        amount: BigInt(amount),
+        delegateOption: 0,
+        delegate: PublicKey.default,
+        state: AccountState.Initialized,
+        isNativeOption: 0,
+        isNative: BigInt(0),
+        delegatedAmount: BigInt(0),
+        closeAuthorityOption: 0,
+        closeAuthority: PublicKey.default,
+      },
+      data
+    );
+    context.setAccount(address, { lamports: LAMPORTS_PER_SOL, data, owner: TOKEN_PROGRAM_ID, executable: false });
+    return address;
+  };
+
+  const tokenBalance = async (context: ProgramTestContext, address: PublicKey) => {
+    const account = await context.banksClient.getAccount(address);
+    return Number(AccountLayout.decode(Buffer.from(account.data)).amount);
+  };
+
+  // Move to the next slot, for a fresh blockhash, and `seconds` ahead
+  const warp = async (context: ProgramTestContext, seconds: number) => {
+    const clock = await context.banksClient.getClock();
+    const slot = clock.slot + BigInt(1);
+    context.warpToSlot(slot);
+    context.setClock(
+      new Clock(
+        slot,
+        clock.epochStartTimestamp,
+        clock.epoch,
+        clock.leaderScheduleEpoch,
+        clock.unixTimestamp + BigInt(seconds)
+      )
+    );
+  };
+
+  describe("vesting", () => {
+    it("Releases nothing before the cliff, then linearly until fully vested", async () => {
+      const { context, bankrunProgram, rewardMint, rewardVault } = await startPool();
+      const user = Keypair.generate();
+      context.setAccount(user.publicKey, {
+        lamports: LAMPORTS_PER_SOL,
+        data: Buffer.alloc(0),
+        owner: SystemProgram.programId,
+        executable: false,
+      });
+      const userRewardAta = setTokenAccount(context, rewardMint, user.publicKey, 0);
+
+      await bankrunProgram.methods
+        .accrueRewards(new BN(1_000_000))
+        .accounts({
+          rewardPool: rewardPoolPda,
+          vestingAccount: vestingPda(user.publicKey),
+          user: user.publicKey,
+          admin: context.payer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const claimRewards = () =>
+        bankrunProgram.methods
+          .claimRewards()
+          .accounts({
+            rewardPool: rewardPoolPda,
+            vestingAccount: vestingPda(user.publicKey),
+            rewardVault,
+            user: user.publicKey,
+            userRewardAta,
+            kycVerificationAccount: Keypair.generate().publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([user])
+          .rpc();
+
+      // Just short of the cliff nothing has vested
+      await warp(context, CLIFF_DURATION - 60);
+      await expectRejected(claimRewards());
+      expect(await tokenBalance(context, userRewardAta)).to.equal(0);
+
+      // Halfway through the schedule, half has vested
+      await warp(context, 60 + DAY);
+      await claimRewards();
+      expect(await tokenBalance(context, userRewardAta)).to.equal(500_000);
+
+      // Fully vested, the remainder is released
+      await warp(context, 2 * DAY);
+      await claimRewards();
+      expect(await tokenBalance(context, userRewardAta)).to.equal(1_000_000);
+      expect(await tokenBalance(context, rewardVault)).to.equal(9_000_000);
+
+      const vesting = await bankrunProgram.account.vestingAccount.fetch(vestingPda(user.publicKey));
+      expect(vesting.totalAmount.toNumber()).to.equal(1_000_000);
+      expect(vesting.claimedAmount.toNumber()).to.equal(1_000_000);
+
+      // Nothing is left to claim
+      await warp(context, DAY);
+      await expectRejected(claimRewards());
+    });
+
+    it("Keeps earlier accruals on their own schedule when more rewards accrue", async () => {
+      const { context, bankrunProgram, rewardMint, rewardVault } = await startPool();
+      const user = Keypair.generate();
+      context.setAccount(user.publicKey, {
+        lamports: LAMPORTS_PER_SOL,
+        data: Buffer.alloc(0),
+        owner: SystemProgram.programId,
+        executable: false,
+      });
+      const userRewardAta = setTokenAccount(context, rewardMint, user.publicKey, 0);
+
+      const accrueRewards = (amount: number) =>
+        bankrunProgram.methods
+          .accrueRewards(new BN(amount))
+          .accounts({
+            rewardPool: rewardPoolPda,
+            vestingAccount: vestingPda(user.publicKey),
+            user: user.publicKey,
+            admin: context.payer.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+
+      const claimRewards = () =>
+        bankrunProgram.methods
+          .claimRewards()
+          .accounts({
+            rewardPool: rewardPoolPda,
+            vestingAccount: vestingPda(user.publicKey),
+            rewardVault,
+            user: user.publicKey,
+            userRewardAta,
+            kycVerificationAccount: Keypair.generate().publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([user])
+          .rpc();
+
+      await accrueRewards(1_000_000);
+
+      // Halfway through the first schedule a second accrual starts its own
+      await warp(context, 2 * DAY);
+      await accrueRewards(1_000_000);
+      await claimRewards();
+      expect(await tokenBalance(context, userRewardAta)).to.equal(500_000);
+
+      // The first accrual finishes on time while the second is halfway
+      await warp(context, 2 * DAY);
+      await claimRewards();
+      expect(await tokenBalance(context, userRewardAta)).to.equal(1_500_000);
+
+      await warp(context, 2 * DAY);
+      await claimRewards();
+      expect(await tokenBalance(context, userRewardAta)).to.equal(2_000_000);
+
+      const vesting = await bankrunProgram.account.vestingAccount.fetch(vestingPda(user.publicKey));
+      expect(vesting.totalAmount.toNumber()).to.equal(2_000_000);
+      expect(vesting.claimedAmount.toNumber()).to.equal(2_000_000);
+    });
+  });
+});
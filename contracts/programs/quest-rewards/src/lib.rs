@@ -3,9 +3,13 @@ use anchor_spl::{
     associated_token::AssociatedToken,
     token::{Mint, Token, TokenAccount},
 };
+use mpl_token_metadata::state::TokenMetadataAccount;
 use mpl_bubblegum::{
     program::Bubblegum,
-    state::{metaplex_adapter::MetaplexAdapter, TreeConfig},
+    state::{
+        metaplex_adapter::{MetadataArgs, TokenProgramVersion, TokenStandard},
+        TreeConfig,
+    },
 };
 use spl_account_compression::{
     program::SplAccountCompression,
@@ -15,6 +19,32 @@ use spl_account_compression::{
 
 declare_id!("QuestRewards11111111111111111111111111111111");
 
+/// Metaplex Token Metadata program
+pub const TOKEN_METADATA_PROGRAM_ID: Pubkey =
+    anchor_lang::solana_program::pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+pub const MAX_QUEST_ID_LEN: usize = 32;
+pub const MAX_REGISTRY_QUESTS: usize = 50;
+
+/// Default XP/reputation bounds for Easy, Medium, Hard, and Legendary quests
+pub const DEFAULT_REWARD_BOUNDS: [RewardBounds; 4] = [
+    RewardBounds { min_xp: 10, max_xp: 200, min_reputation: 1, max_reputation: 20 },
+    RewardBounds { min_xp: 100, max_xp: 500, min_reputation: 10, max_reputation: 50 },
+    RewardBounds { min_xp: 300, max_xp: 1500, min_reputation: 30, max_reputation: 150 },
+    RewardBounds { min_xp: 1000, max_xp: 5000, min_reputation: 100, max_reputation: 500 },
+];
+
+pub const ACHIEVEMENT_TYPE_COUNT: usize = 8;
+
+/// Default reputation bonus per achievement type, in `AchievementType` order
+pub const DEFAULT_ACHIEVEMENT_BONUSES: [u64; ACHIEVEMENT_TYPE_COUNT] = [50, 100, 200, 300, 150, 100, 250, 500];
+
+/// Most streak freezes a user can hold at once
+pub const MAX_STREAK_FREEZES: u8 = 3;
+
+/// Fixed-point scale of `UserProfile::decay_factor`
+pub const REPUTATION_DECAY_SCALE: u64 = 1_000_000_000;
+
 #[program]
 pub mod quest_rewards {
     use super::*;
@@ -30,10 +60,17 @@ pub mod quest_rewards {
         user_profile.total_quests_completed = 0;
         user_profile.current_streak = 0;
         user_profile.longest_streak = 0;
+        user_profile.streak_freezes = 0;
         user_profile.last_activity = Clock::get()?.unix_timestamp;
         user_profile.level = 1;
         user_profile.total_xp = 0;
         user_profile.achievements_count = 0;
+        user_profile.achievements_minted = 0;
+        user_profile.achievement_reputation = 0;
+        user_profile.lifetime_reputation = 0;
+        user_profile.season_number = ctx.accounts.quest_registry.season_number;
+        user_profile.decay_factor = REPUTATION_DECAY_SCALE;
+        user_profile.decay_epoch = 0;
         user_profile.bump = *ctx.bumps.get("user_profile").unwrap();
         
         emit!(UserProfileCreated {
@@ -45,6 +82,27 @@ pub mod quest_rewards {
         Ok(())
     }
 
+    pub fn initialize_quest_registry(
+        ctx: Context<InitializeQuestRegistry>,
+    ) -> Result<()> {
+        let quest_registry = &mut ctx.accounts.quest_registry;
+        quest_registry.authority = ctx.accounts.authority.key();
+        quest_registry.active_quest_ids = Vec::new();
+        quest_registry.reward_bounds = DEFAULT_REWARD_BOUNDS;
+        quest_registry.achievement_bonuses = DEFAULT_ACHIEVEMENT_BONUSES;
+        quest_registry.season_number = 1;
+        quest_registry.season_started_at = Clock::get()?.unix_timestamp;
+        quest_registry.season_decay_bps = 0;
+        quest_registry.bump = *ctx.bumps.get("quest_registry").unwrap();
+
+        emit!(QuestRegistryInitialized {
+            authority: quest_registry.authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
     pub fn create_quest(
         ctx: Context<CreateQuest>,
         quest_id: String,
@@ -56,7 +114,29 @@ pub mod quest_rewards {
         requirements: QuestRequirements,
         rewards: QuestRewards,
         duration_hours: u64,
+        repeat_cooldown: Option<i64>,
+        required_collection: Option<Pubkey>,
+        requires_completion_proof: bool,
     ) -> Result<()> {
+        let quest_registry = &mut ctx.accounts.quest_registry;
+        require!(quest_id.len() <= MAX_QUEST_ID_LEN, QuestError::QuestIdTooLong);
+        // Only special quests may be repeated, and only after a positive cooldown
+        if let Some(cooldown) = repeat_cooldown {
+            require!(quest_type == QuestType::Special, QuestError::QuestNotRepeatable);
+            require!(cooldown > 0, QuestError::InvalidCooldown);
+        }
+        require!(
+            quest_registry.reward_bounds[difficulty.index()].contains(&rewards),
+            QuestError::RewardOutOfRange
+        );
+        // Every quest is listed; creation is limited to the registry authority so the
+        // fixed registry slots can't be filled by anyone else
+        require!(
+            quest_registry.active_quest_ids.len() < MAX_REGISTRY_QUESTS,
+            QuestError::QuestRegistryFull
+        );
+        quest_registry.active_quest_ids.push(quest_id.clone());
+
         let quest = &mut ctx.accounts.quest;
         quest.quest_id = quest_id;
         quest.title = title;
@@ -71,19 +151,140 @@ pub mod quest_rewards {
         quest.created_at = Clock::get()?.unix_timestamp;
         quest.expires_at = quest.created_at + (duration_hours as i64 * 3600);
         quest.completions = 0;
+        quest.repeat_cooldown = repeat_cooldown;
+        quest.required_collection = required_collection;
+        quest.requires_completion_proof = requires_completion_proof;
         quest.bump = *ctx.bumps.get("quest").unwrap();
 
         emit!(QuestCreated {
             quest_id: quest.quest_id.clone(),
             creator: quest.creator,
-            quest_type: quest.quest_type,
-            difficulty: quest.difficulty,
+            quest_type: quest.quest_type.clone(),
+            difficulty: quest.difficulty.clone(),
             timestamp: quest.created_at,
         });
 
         Ok(())
     }
 
+    pub fn close_quest(
+        ctx: Context<CloseQuest>,
+    ) -> Result<()> {
+        let quest = &mut ctx.accounts.quest;
+        let quest_registry = &mut ctx.accounts.quest_registry;
+
+        require!(quest.creator == ctx.accounts.creator.key(), QuestError::NotQuestCreator);
+        require!(quest.is_active, QuestError::QuestInactive);
+
+        quest.is_active = false;
+        quest_registry.remove(&quest.quest_id);
+
+        emit!(QuestClosed {
+            quest_id: quest.quest_id.clone(),
+            closed_by: ctx.accounts.creator.key(),
+            expired: false,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn archive_expired_quest(
+        ctx: Context<ArchiveExpiredQuest>,
+    ) -> Result<()> {
+        let quest = &mut ctx.accounts.quest;
+        let quest_registry = &mut ctx.accounts.quest_registry;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        require!(quest.is_active, QuestError::QuestInactive);
+        require!(current_time >= quest.expires_at, QuestError::QuestNotExpired);
+
+        quest.is_active = false;
+        quest_registry.remove(&quest.quest_id);
+
+        emit!(QuestClosed {
+            quest_id: quest.quest_id.clone(),
+            closed_by: ctx.accounts.caller.key(),
+            expired: true,
+            timestamp: current_time,
+        });
+
+        Ok(())
+    }
+
+    pub fn set_reward_bounds(
+        ctx: Context<SetRewardBounds>,
+        difficulty: QuestDifficulty,
+        bounds: RewardBounds,
+    ) -> Result<()> {
+        let quest_registry = &mut ctx.accounts.quest_registry;
+
+        require!(
+            bounds.min_xp <= bounds.max_xp && bounds.min_reputation <= bounds.max_reputation,
+            QuestError::InvalidRewardBounds
+        );
+
+        quest_registry.reward_bounds[difficulty.index()] = bounds.clone();
+
+        emit!(RewardBoundsUpdated {
+            difficulty,
+            bounds,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn set_achievement_bonus(
+        ctx: Context<SetAchievementBonus>,
+        achievement_type: AchievementType,
+        reputation_bonus: u64,
+    ) -> Result<()> {
+        let quest_registry = &mut ctx.accounts.quest_registry;
+
+        let old_bonus = quest_registry.achievement_bonuses[achievement_type.index()];
+        quest_registry.achievement_bonuses[achievement_type.index()] = reputation_bonus;
+
+        emit!(AchievementBonusUpdated {
+            achievement_type,
+            old_bonus,
+            new_bonus: reputation_bonus,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Grant streak freezes to a user (registry authority only), up to `MAX_STREAK_FREEZES` held
+    pub fn grant_streak_freeze(
+        ctx: Context<GrantStreakFreeze>,
+        count: u8,
+    ) -> Result<()> {
+        let user_profile = &mut ctx.accounts.user_profile;
+
+        let streak_freezes = user_profile.streak_freezes.saturating_add(count);
+        require!(
+            count > 0 && streak_freezes <= MAX_STREAK_FREEZES,
+            QuestError::StreakFreezeLimitExceeded
+        );
+        user_profile.streak_freezes = streak_freezes;
+
+        emit!(StreakFreezeGranted {
+            user: user_profile.authority,
+            granted: count,
+            streak_freezes,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn get_active_quests(
+        ctx: Context<GetActiveQuests>,
+    ) -> Result<Vec<String>> {
+        Ok(ctx.accounts.quest_registry.active_quest_ids.clone())
+    }
+
     pub fn start_quest(
         ctx: Context<StartQuest>,
         quest_id: String,
@@ -95,6 +296,22 @@ pub mod quest_rewards {
         require!(quest.is_active, QuestError::QuestInactive);
         require!(current_time < quest.expires_at, QuestError::QuestExpired);
 
+        // NFT-gated quests require the user to hold an NFT from the verified collection
+        if let Some(required_collection) = quest.required_collection {
+            let nft_token_account = ctx.accounts.nft_token_account
+                .as_ref()
+                .ok_or(QuestError::MissingCollectionNft)?;
+            let nft_metadata = ctx.accounts.nft_metadata
+                .as_ref()
+                .ok_or(QuestError::MissingCollectionNft)?;
+            verify_collection_nft(
+                nft_token_account,
+                nft_metadata,
+                &ctx.accounts.user.key(),
+                &required_collection,
+            )?;
+        }
+
         user_quest.user = ctx.accounts.user.key();
         user_quest.quest = quest.key();
         user_quest.quest_id = quest_id;
@@ -102,6 +319,8 @@ pub mod quest_rewards {
         user_quest.progress = QuestProgress::default();
         user_quest.started_at = current_time;
         user_quest.expires_at = quest.expires_at;
+        user_quest.completion_count = 0;
+        user_quest.completion_proof_hash = None;
         user_quest.bump = *ctx.bumps.get("user_quest").unwrap();
 
         emit!(QuestStarted {
@@ -113,9 +332,42 @@ pub mod quest_rewards {
         Ok(())
     }
 
+    /// Restart a completed repeatable quest once its cooldown since the last completion has elapsed
+    pub fn repeat_quest(
+        ctx: Context<RepeatQuest>,
+    ) -> Result<()> {
+        let user_quest = &mut ctx.accounts.user_quest;
+        let quest = &ctx.accounts.quest;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        let cooldown = quest.repeat_cooldown.ok_or(QuestError::QuestNotRepeatable)?;
+        require!(quest.is_active, QuestError::QuestInactive);
+        require!(current_time < quest.expires_at, QuestError::QuestExpired);
+        require!(user_quest.status == QuestStatus::Completed, QuestError::QuestNotCompleted);
+
+        let last_completed_at = user_quest.completed_at.ok_or(QuestError::QuestNotCompleted)?;
+        require!(current_time >= last_completed_at + cooldown, QuestError::QuestOnCooldown);
+
+        user_quest.status = QuestStatus::Active;
+        user_quest.progress = QuestProgress::default();
+        user_quest.started_at = current_time;
+        user_quest.completed_at = None;
+        user_quest.completion_proof_hash = None;
+        user_quest.expires_at = quest.expires_at;
+
+        emit!(QuestStarted {
+            user: ctx.accounts.user.key(),
+            quest_id: user_quest.quest_id.clone(),
+            started_at: current_time,
+        });
+
+        Ok(())
+    }
+
     pub fn update_quest_progress(
         ctx: Context<UpdateQuestProgress>,
         progress_data: QuestProgress,
+        completion_proof_hash: Option<[u8; 32]>,
     ) -> Result<()> {
         let user_quest = &mut ctx.accounts.user_quest;
         let quest = &ctx.accounts.quest;
@@ -123,6 +375,10 @@ pub mod quest_rewards {
 
         require!(user_quest.status == QuestStatus::Active, QuestError::QuestNotActive);
         require!(Clock::get()?.unix_timestamp < user_quest.expires_at, QuestError::QuestExpired);
+        require!(
+            user_profile.season_number == ctx.accounts.quest_registry.season_number,
+            QuestError::SeasonSnapshotPending
+        );
 
         user_quest.progress = progress_data;
 
@@ -136,13 +392,21 @@ pub mod quest_rewards {
         };
 
         if is_completed && user_quest.status == QuestStatus::Active {
+            require!(
+                !quest.requires_completion_proof || completion_proof_hash.is_some(),
+                QuestError::MissingCompletionProof
+            );
+
             user_quest.status = QuestStatus::Completed;
             user_quest.completed_at = Some(Clock::get()?.unix_timestamp);
+            user_quest.completion_count += 1;
+            user_quest.completion_proof_hash = completion_proof_hash;
 
             // Update user profile
             user_profile.total_quests_completed += 1;
             user_profile.total_xp += quest.rewards.xp_reward;
             user_profile.reputation_score += quest.rewards.reputation_points;
+            user_profile.lifetime_reputation += quest.rewards.reputation_points;
             user_profile.last_activity = Clock::get()?.unix_timestamp;
 
             // Level up logic
@@ -161,6 +425,7 @@ pub mod quest_rewards {
                 quest_id: user_quest.quest_id.clone(),
                 xp_earned: quest.rewards.xp_reward,
                 reputation_earned: quest.rewards.reputation_points,
+                completion_proof_hash,
                 completed_at: user_quest.completed_at.unwrap(),
             });
         }
@@ -178,8 +443,18 @@ pub mod quest_rewards {
 
         // Check if it's been more than 24 hours since last activity
         if time_diff > 86400 { // 24 hours in seconds
-            // Check if it's been more than 48 hours (streak broken)
-            if time_diff > 172800 { // 48 hours
+            // A streak freeze covers exactly one missed day (up to 72 hours since last activity)
+            let use_freeze = time_diff > 172800 && time_diff <= 259200 && user_profile.streak_freezes > 0;
+            if use_freeze {
+                // The freeze holds the streak where it was; only a day of activity extends it
+                user_profile.streak_freezes -= 1;
+                emit!(StreakFreezeUsed {
+                    user: ctx.accounts.user.key(),
+                    current_streak: user_profile.current_streak,
+                    streak_freezes: user_profile.streak_freezes,
+                    timestamp: current_time,
+                });
+            } else if time_diff > 172800 { // 48 hours, streak broken
                 user_profile.current_streak = 1; // Reset streak
                 emit!(StreakBroken {
                     user: ctx.accounts.user.key(),
@@ -205,47 +480,248 @@ pub mod quest_rewards {
         Ok(())
     }
 
+    /// Mint an achievement as a compressed NFT owned by the user. The registry authority co-signs
+    /// to attest the user earned it. The quest registry PDA must be the tree delegate (set with
+    /// Bubblegum's `set_tree_delegate`), so only this program mints.
     pub fn mint_compressed_achievement_nft(
         ctx: Context<MintCompressedAchievementNFT>,
         achievement_type: AchievementType,
         metadata_uri: String,
     ) -> Result<()> {
         let user_profile = &mut ctx.accounts.user_profile;
+        require!(
+            user_profile.season_number == ctx.accounts.quest_registry.season_number,
+            QuestError::SeasonSnapshotPending
+        );
         
+        let tree_authority = &ctx.accounts.tree_authority;
+        require!(
+            tree_authority.num_minted < tree_authority.total_mint_capacity,
+            QuestError::AchievementTreeFull
+        );
+
         // Mint compressed NFT using Bubblegum
-        let metadata = MetaplexAdapter {
+        let metadata = MetadataArgs {
             name: format!("{:?} Achievement", achievement_type),
             symbol: "QUEST".to_string(),
-            uri: metadata_uri,
-            creators: vec![],
+            uri: metadata_uri.clone(),
             seller_fee_basis_points: 0,
             primary_sale_happened: true,
             is_mutable: false,
+            edition_nonce: None,
+            token_standard: Some(TokenStandard::NonFungible),
+            collection: None,
+            uses: None,
+            token_program_version: TokenProgramVersion::Original,
+            creators: vec![],
         };
 
-        // This would interact with the Bubblegum program to mint compressed NFT
-        // Implementation depends on the specific Bubblegum version and setup
+        let registry_seeds: &[&[u8]] = &[b"quest_registry", &[ctx.accounts.quest_registry.bump]];
+        mpl_bubblegum::cpi::mint_v1(
+            CpiContext::new_with_signer(
+                ctx.accounts.bubblegum_program.to_account_info(),
+                mpl_bubblegum::cpi::accounts::MintV1 {
+                    tree_authority: ctx.accounts.tree_authority.to_account_info(),
+                    leaf_owner: ctx.accounts.user.to_account_info(),
+                    leaf_delegate: ctx.accounts.user.to_account_info(),
+                    merkle_tree: ctx.accounts.merkle_tree.to_account_info(),
+                    payer: ctx.accounts.user.to_account_info(),
+                    tree_delegate: ctx.accounts.quest_registry.to_account_info(),
+                    log_wrapper: ctx.accounts.log_wrapper.to_account_info(),
+                    compression_program: ctx.accounts.compression_program.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                },
+                &[registry_seeds],
+            ),
+            metadata,
+        )?;
+
+        let reputation_bonus = ctx.accounts.quest_registry.achievement_bonuses[achievement_type.index()];
+
+        // Keep a per-achievement record so the profile counters can be rebuilt
+        let achievement_record = &mut ctx.accounts.achievement_record;
+        achievement_record.user = ctx.accounts.user.key();
+        achievement_record.index = user_profile.achievements_minted;
+        achievement_record.achievement_type = achievement_type.clone();
+        achievement_record.reputation_bonus = reputation_bonus;
+        achievement_record.minted_at = Clock::get()?.unix_timestamp;
+        achievement_record.decay_epoch = user_profile.decay_epoch;
+        achievement_record.decay_factor = user_profile.decay_factor;
+        achievement_record.bump = *ctx.bumps.get("achievement_record").unwrap();
 
+        user_profile.achievements_minted += 1;
         user_profile.achievements_count += 1;
-        user_profile.reputation_score += get_achievement_reputation_bonus(&achievement_type);
+        user_profile.achievement_reputation += reputation_bonus;
+        user_profile.reputation_score += reputation_bonus;
+        user_profile.lifetime_reputation += reputation_bonus;
 
         emit!(AchievementNFTMinted {
             user: ctx.accounts.user.key(),
             achievement_type,
             metadata_uri,
-            reputation_bonus: get_achievement_reputation_bonus(&achievement_type),
+            reputation_bonus,
             timestamp: Clock::get()?.unix_timestamp,
         });
 
         Ok(())
     }
 
+    /// Rebuild a user's achievement count and reputation bonus from their achievement
+    /// records, passed in index order through `remaining_accounts`
+    pub fn recount_achievements(
+        ctx: Context<RecountAchievements>,
+    ) -> Result<()> {
+        let user_profile = &mut ctx.accounts.user_profile;
+        require!(
+            ctx.remaining_accounts.len() == user_profile.achievements_minted as usize,
+            QuestError::AchievementRecordMismatch
+        );
+
+        let mut achievement_reputation: u64 = 0;
+        for (index, record_info) in ctx.remaining_accounts.iter().enumerate() {
+            require!(
+                record_info.owner == ctx.program_id,
+                QuestError::AchievementRecordMismatch
+            );
+            let record = AchievementRecord::try_deserialize(&mut &record_info.try_borrow_data()?[..])?;
+            let (expected_key, _) = Pubkey::find_program_address(
+                &[
+                    b"achievement_record",
+                    user_profile.authority.as_ref(),
+                    &(index as u32).to_le_bytes(),
+                ],
+                ctx.program_id,
+            );
+            require!(
+                record_info.key() == expected_key && record.user == user_profile.authority,
+                QuestError::AchievementRecordMismatch
+            );
+
+            // The bonus granted at mint time, so later bonus changes don't rewrite history, decayed
+            // by every season snapshotted since. A wipe since minting leaves nothing of it.
+            if record.decay_epoch == user_profile.decay_epoch {
+                achievement_reputation += (record.reputation_bonus as u128
+                    * user_profile.decay_factor as u128
+                    / record.decay_factor as u128) as u64;
+            }
+        }
+
+        let old_achievements_count = user_profile.achievements_count;
+        let old_reputation_score = user_profile.reputation_score;
+
+        // Swap the previously credited achievement bonus for the recomputed one
+        user_profile.reputation_score = user_profile.reputation_score
+            .saturating_sub(user_profile.achievement_reputation)
+            + achievement_reputation;
+        user_profile.lifetime_reputation = user_profile.lifetime_reputation
+            .saturating_sub(user_profile.achievement_reputation)
+            + achievement_reputation;
+        user_profile.achievement_reputation = achievement_reputation;
+        user_profile.achievements_count = user_profile.achievements_minted;
+
+        emit!(AchievementsRecounted {
+            user: user_profile.authority,
+            old_achievements_count,
+            achievements_count: user_profile.achievements_count,
+            old_reputation_score,
+            reputation_score: user_profile.reputation_score,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// End the current season and start the next. `decay_bps` is the share of live reputation
+    /// each user loses when their season is snapshotted (10000 zeroes it); lifetime totals are kept.
+    pub fn reset_season(
+        ctx: Context<ResetSeason>,
+        decay_bps: u16,
+    ) -> Result<()> {
+        let quest_registry = &mut ctx.accounts.quest_registry;
+
+        require!(decay_bps <= 10000, QuestError::InvalidDecayRate);
+
+        let ended_season = quest_registry.season_number;
+        quest_registry.season_number += 1;
+        quest_registry.season_started_at = Clock::get()?.unix_timestamp;
+        quest_registry.season_decay_bps = decay_bps;
+
+        emit!(SeasonReset {
+            ended_season,
+            season_number: quest_registry.season_number,
+            decay_bps,
+            timestamp: quest_registry.season_started_at,
+        });
+
+        Ok(())
+    }
+
+    /// Snapshot a user's reputation for the season they were last active in and apply the
+    /// season decay to their live score. Must run once per reset before the user earns more
+    /// reputation; anyone can pay for it.
+    pub fn snapshot_season_reputation(
+        ctx: Context<SnapshotSeasonReputation>,
+    ) -> Result<()> {
+        let quest_registry = &ctx.accounts.quest_registry;
+        let user_profile = &mut ctx.accounts.user_profile;
+        let snapshot = &mut ctx.accounts.reputation_snapshot;
+
+        require!(
+            user_profile.season_number < quest_registry.season_number,
+            QuestError::SeasonAlreadySnapshotted
+        );
+
+        snapshot.user = user_profile.authority;
+        snapshot.season_number = user_profile.season_number;
+        snapshot.reputation_score = user_profile.reputation_score;
+        snapshot.lifetime_reputation = user_profile.lifetime_reputation;
+        snapshot.total_quests_completed = user_profile.total_quests_completed;
+        snapshot.taken_at = Clock::get()?.unix_timestamp;
+        snapshot.bump = *ctx.bumps.get("reputation_snapshot").unwrap();
+
+        // Decay the live score and its achievement portion. decay_factor tracks the cumulative
+        // decay so recount_achievements can apply it to each record's undecayed bonus.
+        let decay_bps = quest_registry.season_decay_bps as u64;
+        user_profile.reputation_score -= user_profile.reputation_score * decay_bps / 10000;
+        user_profile.achievement_reputation -= user_profile.achievement_reputation * decay_bps / 10000;
+        user_profile.decay_factor -= user_profile.decay_factor * decay_bps / 10000;
+        if user_profile.decay_factor == 0 {
+            // Everything earned so far is gone; start a fresh epoch so later bonuses count in full
+            user_profile.decay_epoch += 1;
+            user_profile.decay_factor = REPUTATION_DECAY_SCALE;
+        }
+        user_profile.season_number = quest_registry.season_number;
+
+        emit!(ReputationSnapshotTaken {
+            user: snapshot.user,
+            season_number: snapshot.season_number,
+            season_reputation: snapshot.reputation_score,
+            reputation_score: user_profile.reputation_score,
+            lifetime_reputation: user_profile.lifetime_reputation,
+            timestamp: snapshot.taken_at,
+        });
+
+        Ok(())
+    }
+
+    /// Returns the user's reputation score. Anchor writes the returned value to
+    /// the transaction return data (`set_return_data`), so other programs can gate
+    /// on it via CPI: `quest_rewards::cpi::get_user_reputation(cpi_ctx)?.get()`.
     pub fn get_user_reputation(
         ctx: Context<GetUserReputation>,
     ) -> Result<u64> {
         let user_profile = &ctx.accounts.user_profile;
         Ok(user_profile.reputation_score)
     }
+
+    /// Returns the user's level through return data, same CPI pattern as
+    /// `get_user_reputation`: `quest_rewards::cpi::get_user_level(cpi_ctx)?.get()`.
+    pub fn get_user_level(
+        ctx: Context<GetUserLevel>,
+    ) -> Result<u32> {
+        let user_profile = &ctx.accounts.user_profile;
+        Ok(user_profile.level)
+    }
 }
 
 #[derive(Accounts)]
@@ -259,6 +735,26 @@ pub struct InitializeUserProfile<'info> {
         bump
     )]
     pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        seeds = [b"quest_registry"],
+        bump = quest_registry.bump
+    )]
+    pub quest_registry: Account<'info, QuestRegistry>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeQuestRegistry<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = QuestRegistry::LEN,
+        seeds = [b"quest_registry"],
+        bump
+    )]
+    pub quest_registry: Account<'info, QuestRegistry>,
     #[account(mut)]
     pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -275,11 +771,101 @@ pub struct CreateQuest<'info> {
         bump
     )]
     pub quest: Account<'info, Quest>,
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"quest_registry"],
+        bump = quest_registry.bump
+    )]
+    pub quest_registry: Account<'info, QuestRegistry>,
+    #[account(mut, address = quest_registry.authority @ QuestError::Unauthorized)]
     pub creator: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct SetRewardBounds<'info> {
+    #[account(
+        mut,
+        seeds = [b"quest_registry"],
+        bump = quest_registry.bump,
+        has_one = authority @ QuestError::Unauthorized
+    )]
+    pub quest_registry: Account<'info, QuestRegistry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetAchievementBonus<'info> {
+    #[account(
+        mut,
+        seeds = [b"quest_registry"],
+        bump = quest_registry.bump,
+        has_one = authority @ QuestError::Unauthorized
+    )]
+    pub quest_registry: Account<'info, QuestRegistry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GrantStreakFreeze<'info> {
+    #[account(
+        seeds = [b"quest_registry"],
+        bump = quest_registry.bump,
+        has_one = authority @ QuestError::Unauthorized
+    )]
+    pub quest_registry: Account<'info, QuestRegistry>,
+    #[account(
+        mut,
+        seeds = [b"user_profile", user_profile.authority.as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseQuest<'info> {
+    #[account(
+        mut,
+        seeds = [b"quest", quest.quest_id.as_bytes()],
+        bump = quest.bump
+    )]
+    pub quest: Account<'info, Quest>,
+    #[account(
+        mut,
+        seeds = [b"quest_registry"],
+        bump = quest_registry.bump
+    )]
+    pub quest_registry: Account<'info, QuestRegistry>,
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ArchiveExpiredQuest<'info> {
+    #[account(
+        mut,
+        seeds = [b"quest", quest.quest_id.as_bytes()],
+        bump = quest.bump
+    )]
+    pub quest: Account<'info, Quest>,
+    #[account(
+        mut,
+        seeds = [b"quest_registry"],
+        bump = quest_registry.bump
+    )]
+    pub quest_registry: Account<'info, QuestRegistry>,
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetActiveQuests<'info> {
+    #[account(
+        seeds = [b"quest_registry"],
+        bump = quest_registry.bump
+    )]
+    pub quest_registry: Account<'info, QuestRegistry>,
+}
+
 #[derive(Accounts)]
 #[instruction(quest_id: String)]
 pub struct StartQuest<'info> {
@@ -298,9 +884,28 @@ pub struct StartQuest<'info> {
     pub quest: Account<'info, Quest>,
     #[account(mut)]
     pub user: Signer<'info>,
+    pub nft_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: Optional Metaplex metadata account, validated in verify_collection_nft
+    pub nft_metadata: Option<UncheckedAccount<'info>>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct RepeatQuest<'info> {
+    #[account(
+        mut,
+        seeds = [b"user_quest", user.key().as_ref(), user_quest.quest_id.as_bytes()],
+        bump = user_quest.bump
+    )]
+    pub user_quest: Account<'info, UserQuest>,
+    #[account(
+        seeds = [b"quest", user_quest.quest_id.as_bytes()],
+        bump = quest.bump
+    )]
+    pub quest: Account<'info, Quest>,
+    pub user: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct UpdateQuestProgress<'info> {
     #[account(
@@ -320,6 +925,11 @@ pub struct UpdateQuestProgress<'info> {
         bump = user_profile.bump
     )]
     pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        seeds = [b"quest_registry"],
+        bump = quest_registry.bump
+    )]
+    pub quest_registry: Account<'info, QuestRegistry>,
     pub user: Signer<'info>,
 }
 
@@ -342,17 +952,105 @@ pub struct MintCompressedAchievementNFT<'info> {
         bump = user_profile.bump
     )]
     pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        init,
+        payer = user,
+        space = AchievementRecord::LEN,
+        seeds = [
+            b"achievement_record",
+            user.key().as_ref(),
+            &user_profile.achievements_minted.to_le_bytes()
+        ],
+        bump
+    )]
+    pub achievement_record: Account<'info, AchievementRecord>,
+    #[account(
+        seeds = [b"quest_registry"],
+        bump = quest_registry.bump
+    )]
+    pub quest_registry: Account<'info, QuestRegistry>,
+    #[account(mut)]
     pub user: Signer<'info>,
-    /// CHECK: This is the merkle tree account for compressed NFTs
+    // Registry authority attesting the achievement was earned
+    #[account(address = quest_registry.authority @ QuestError::Unauthorized)]
+    pub authority: Signer<'info>,
+    /// CHECK: Merkle tree for compressed NFTs; validated by Bubblegum against its tree config
+    #[account(mut)]
     pub merkle_tree: UncheckedAccount<'info>,
-    /// CHECK: This is the tree authority for the merkle tree
-    pub tree_authority: UncheckedAccount<'info>,
+    // Bubblegum tree config PDA for `merkle_tree`
+    #[account(
+        mut,
+        seeds = [merkle_tree.key().as_ref()],
+        bump,
+        seeds::program = bubblegum_program.key(),
+        constraint = tree_authority.tree_delegate == quest_registry.key() @ QuestError::InvalidTreeDelegate
+    )]
+    pub tree_authority: Account<'info, TreeConfig>,
     pub bubblegum_program: Program<'info, Bubblegum>,
     pub compression_program: Program<'info, SplAccountCompression>,
     pub log_wrapper: Program<'info, Noop>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct RecountAchievements<'info> {
+    #[account(
+        seeds = [b"quest_registry"],
+        bump = quest_registry.bump,
+        has_one = authority @ QuestError::Unauthorized
+    )]
+    pub quest_registry: Account<'info, QuestRegistry>,
+    #[account(
+        mut,
+        seeds = [b"user_profile", user_profile.authority.as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResetSeason<'info> {
+    #[account(
+        mut,
+        seeds = [b"quest_registry"],
+        bump = quest_registry.bump,
+        has_one = authority @ QuestError::Unauthorized
+    )]
+    pub quest_registry: Account<'info, QuestRegistry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SnapshotSeasonReputation<'info> {
+    #[account(
+        seeds = [b"quest_registry"],
+        bump = quest_registry.bump
+    )]
+    pub quest_registry: Account<'info, QuestRegistry>,
+    #[account(
+        mut,
+        seeds = [b"user_profile", user_profile.authority.as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        init,
+        payer = payer,
+        space = ReputationSnapshot::LEN,
+        seeds = [
+            b"reputation_snapshot",
+            user_profile.authority.as_ref(),
+            &user_profile.season_number.to_le_bytes()
+        ],
+        bump
+    )]
+    pub reputation_snapshot: Account<'info, ReputationSnapshot>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct GetUserReputation<'info> {
     #[account(
@@ -362,6 +1060,15 @@ pub struct GetUserReputation<'info> {
     pub user_profile: Account<'info, UserProfile>,
 }
 
+#[derive(Accounts)]
+pub struct GetUserLevel<'info> {
+    #[account(
+        seeds = [b"user_profile", user_profile.authority.as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+}
+
 #[account]
 pub struct UserProfile {
     pub authority: Pubkey,
@@ -370,15 +1077,53 @@ pub struct UserProfile {
     pub total_quests_completed: u32,
     pub current_streak: u32,
     pub longest_streak: u32,
+    pub streak_freezes: u8,          // Consumed by `update_streak` to bridge one missed day
     pub last_activity: i64,
     pub level: u32,
     pub total_xp: u64,
     pub achievements_count: u32,
+    pub achievements_minted: u32,    // Next achievement record index; never rewritten by recounts
+    pub achievement_reputation: u64, // Portion of reputation_score from achievement bonuses
+    pub lifetime_reputation: u64,    // All reputation ever earned; untouched by season decay
+    pub season_number: u32,          // Season the live reputation_score belongs to
+    pub decay_factor: u64,           // Share of reputation surviving season decay, over REPUTATION_DECAY_SCALE
+    pub decay_epoch: u32,            // Bumped when a decay wipes reputation out and decay_factor restarts
     pub bump: u8,
 }
 
 impl UserProfile {
-    pub const LEN: usize = 8 + 32 + 64 + 8 + 4 + 4 + 4 + 8 + 4 + 8 + 4 + 1;
+    pub const LEN: usize = 8 + 32 + 64 + 8 + 4 + 4 + 4 + 1 + 8 + 4 + 8 + 4 + 4 + 8 + 8 + 4 + 8 + 4 + 1;
+}
+
+#[account]
+pub struct ReputationSnapshot {
+    pub user: Pubkey,
+    pub season_number: u32,
+    pub reputation_score: u64,       // Live score at the end of the season, before decay
+    pub lifetime_reputation: u64,
+    pub total_quests_completed: u32,
+    pub taken_at: i64,
+    pub bump: u8,
+}
+
+impl ReputationSnapshot {
+    pub const LEN: usize = 8 + 32 + 4 + 8 + 8 + 4 + 8 + 1;
+}
+
+#[account]
+pub struct AchievementRecord {
+    pub user: Pubkey,
+    pub index: u32,
+    pub achievement_type: AchievementType,
+    pub reputation_bonus: u64,
+    pub minted_at: i64,
+    pub decay_epoch: u32,            // Owner's decay_epoch at mint
+    pub decay_factor: u64,           // Owner's decay_factor at mint, the base for later decay
+    pub bump: u8,
+}
+
+impl AchievementRecord {
+    pub const LEN: usize = 8 + 32 + 4 + 1 + 8 + 8 + 4 + 8 + 1;
 }
 
 #[account]
@@ -396,11 +1141,38 @@ pub struct Quest {
     pub created_at: i64,
     pub expires_at: i64,
     pub completions: u32,
+    /// Cooldown in seconds before a user may repeat the quest; `None` if not repeatable
+    pub repeat_cooldown: Option<i64>,
+    /// Verified Metaplex collection a user must hold an NFT from to start the quest
+    pub required_collection: Option<Pubkey>,
+    /// Completing the quest requires a hash of the evidence the oracle validated
+    pub requires_completion_proof: bool,
     pub bump: u8,
 }
 
 impl Quest {
-    pub const LEN: usize = 8 + 64 + 128 + 256 + 1 + 1 + 1 + 64 + 64 + 32 + 1 + 8 + 8 + 4 + 1;
+    pub const LEN: usize = 8 + 64 + 128 + 256 + 1 + 1 + 1 + 64 + 64 + 32 + 1 + 8 + 8 + 4 + 9 + 33 + 1 + 1;
+}
+
+#[account]
+pub struct QuestRegistry {
+    pub authority: Pubkey,
+    pub active_quest_ids: Vec<String>,
+    pub reward_bounds: [RewardBounds; 4],
+    pub achievement_bonuses: [u64; ACHIEVEMENT_TYPE_COUNT], // Reputation per achievement type
+    pub season_number: u32,
+    pub season_started_at: i64,
+    pub season_decay_bps: u16,       // Decay applied when each user's last season is snapshotted
+    pub bump: u8,
+}
+
+impl QuestRegistry {
+    pub const LEN: usize = 8 + 32 + 4 + MAX_REGISTRY_QUESTS * (4 + MAX_QUEST_ID_LEN) + 4 * RewardBounds::LEN
+        + 8 * ACHIEVEMENT_TYPE_COUNT + 4 + 8 + 2 + 1;
+
+    pub fn remove(&mut self, quest_id: &str) {
+        self.active_quest_ids.retain(|id| id != quest_id);
+    }
 }
 
 #[account]
@@ -413,11 +1185,14 @@ pub struct UserQuest {
     pub started_at: i64,
     pub completed_at: Option<i64>,
     pub expires_at: i64,
+    pub completion_count: u32,
+    /// Content hash of the evidence validated for the latest completion
+    pub completion_proof_hash: Option<[u8; 32]>,
     pub bump: u8,
 }
 
 impl UserQuest {
-    pub const LEN: usize = 8 + 32 + 32 + 64 + 1 + 64 + 8 + 9 + 8 + 1;
+    pub const LEN: usize = 8 + 32 + 32 + 64 + 1 + 64 + 8 + 9 + 8 + 4 + 33 + 1;
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
@@ -446,6 +1221,17 @@ pub enum QuestDifficulty {
     Legendary,
 }
 
+impl QuestDifficulty {
+    pub fn index(&self) -> usize {
+        match self {
+            QuestDifficulty::Easy => 0,
+            QuestDifficulty::Medium => 1,
+            QuestDifficulty::Hard => 2,
+            QuestDifficulty::Legendary => 3,
+        }
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
 pub enum QuestStatus {
     Active,
@@ -454,7 +1240,7 @@ pub enum QuestStatus {
     Expired,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
 pub enum AchievementType {
     FirstPayment,
     PaymentStreak,
@@ -466,6 +1252,21 @@ pub enum AchievementType {
     CommunityChampion,
 }
 
+impl AchievementType {
+    pub fn index(&self) -> usize {
+        match self {
+            AchievementType::FirstPayment => 0,
+            AchievementType::PaymentStreak => 1,
+            AchievementType::VolumeTrader => 2,
+            AchievementType::QuestMaster => 3,
+            AchievementType::SocialButterfly => 4,
+            AchievementType::TaskCompleter => 5,
+            AchievementType::LoyalCustomer => 6,
+            AchievementType::CommunityChampion => 7,
+        }
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
 pub struct QuestProgress {
     pub payments_made: u32,
@@ -493,6 +1294,23 @@ pub struct QuestRewards {
     pub badge_reward: Option<String>,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct RewardBounds {
+    pub min_xp: u64,
+    pub max_xp: u64,
+    pub min_reputation: u64,
+    pub max_reputation: u64,
+}
+
+impl RewardBounds {
+    pub const LEN: usize = 8 + 8 + 8 + 8;
+
+    pub fn contains(&self, rewards: &QuestRewards) -> bool {
+        (self.min_xp..=self.max_xp).contains(&rewards.xp_reward)
+            && (self.min_reputation..=self.max_reputation).contains(&rewards.reputation_points)
+    }
+}
+
 // Events
 #[event]
 pub struct UserProfileCreated {
@@ -510,6 +1328,35 @@ pub struct QuestCreated {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct QuestRegistryInitialized {
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardBoundsUpdated {
+    pub difficulty: QuestDifficulty,
+    pub bounds: RewardBounds,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AchievementBonusUpdated {
+    pub achievement_type: AchievementType,
+    pub old_bonus: u64,
+    pub new_bonus: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct QuestClosed {
+    pub quest_id: String,
+    pub closed_by: Pubkey,
+    pub expired: bool,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct QuestStarted {
     pub user: Pubkey,
@@ -523,6 +1370,7 @@ pub struct QuestCompleted {
     pub quest_id: String,
     pub xp_earned: u64,
     pub reputation_earned: u64,
+    pub completion_proof_hash: Option<[u8; 32]>,
     pub completed_at: i64,
 }
 
@@ -534,6 +1382,22 @@ pub struct StreakUpdated {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct StreakFreezeGranted {
+    pub user: Pubkey,
+    pub granted: u8,
+    pub streak_freezes: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StreakFreezeUsed {
+    pub user: Pubkey,
+    pub current_streak: u32,
+    pub streak_freezes: u8,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct StreakBroken {
     pub user: Pubkey,
@@ -548,6 +1412,34 @@ pub struct UserLevelUp {
     pub total_xp: u64,
 }
 
+#[event]
+pub struct SeasonReset {
+    pub ended_season: u32,
+    pub season_number: u32,
+    pub decay_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReputationSnapshotTaken {
+    pub user: Pubkey,
+    pub season_number: u32,
+    pub season_reputation: u64,
+    pub reputation_score: u64,
+    pub lifetime_reputation: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AchievementsRecounted {
+    pub user: Pubkey,
+    pub old_achievements_count: u32,
+    pub achievements_count: u32,
+    pub old_reputation_score: u64,
+    pub reputation_score: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct AchievementNFTMinted {
     pub user: Pubkey,
@@ -570,23 +1462,94 @@ pub enum QuestError {
     InvalidRequirements,
     #[msg("Insufficient reputation")]
     InsufficientReputation,
+    #[msg("Quest id too long")]
+    QuestIdTooLong,
+    #[msg("Quest registry is full")]
+    QuestRegistryFull,
+    #[msg("Not the quest creator")]
+    NotQuestCreator,
+    #[msg("Quest has not expired yet")]
+    QuestNotExpired,
+    #[msg("Quest rewards are out of range for its difficulty")]
+    RewardOutOfRange,
+    #[msg("Invalid reward bounds")]
+    InvalidRewardBounds,
+    #[msg("Unauthorized")]
+    Unauthorized,
+    #[msg("Quest is not repeatable")]
+    QuestNotRepeatable,
+    #[msg("Invalid repeat cooldown")]
+    InvalidCooldown,
+    #[msg("Quest has not been completed")]
+    QuestNotCompleted,
+    #[msg("Quest is still on cooldown")]
+    QuestOnCooldown,
+    #[msg("Collection NFT token account and metadata are required for this quest")]
+    MissingCollectionNft,
+    #[msg("Invalid collection NFT metadata account")]
+    InvalidNftMetadata,
+    #[msg("User does not hold an NFT from the required verified collection")]
+    CollectionNotVerified,
+    #[msg("Achievement records do not match the user's minted achievements")]
+    AchievementRecordMismatch,
+    #[msg("This quest requires a completion proof hash")]
+    MissingCompletionProof,
+    #[msg("Decay rate must not exceed 10000 basis points")]
+    InvalidDecayRate,
+    #[msg("User's previous season must be snapshotted first")]
+    SeasonSnapshotPending,
+    #[msg("User's season has already been snapshotted")]
+    SeasonAlreadySnapshotted,
+    #[msg("Streak freeze grant would exceed the maximum a user can hold")]
+    StreakFreezeLimitExceeded,
+    #[msg("Achievement merkle tree is full")]
+    AchievementTreeFull,
+    #[msg("Quest registry is not the delegate of this merkle tree")]
+    InvalidTreeDelegate,
 }
 
 // Helper functions
+// Helper function to check the user holds an NFT whose metadata lists the verified collection
+fn verify_collection_nft(
+    nft_token_account: &Account<TokenAccount>,
+    nft_metadata: &AccountInfo,
+    user: &Pubkey,
+    required_collection: &Pubkey,
+) -> Result<()> {
+    require!(
+        nft_token_account.owner == *user && nft_token_account.amount >= 1,
+        QuestError::CollectionNotVerified
+    );
+    require!(
+        nft_metadata.owner == &TOKEN_METADATA_PROGRAM_ID,
+        QuestError::InvalidNftMetadata
+    );
+
+    let (expected_metadata, _) = Pubkey::find_program_address(
+        &[
+            b"metadata",
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            nft_token_account.mint.as_ref(),
+        ],
+        &TOKEN_METADATA_PROGRAM_ID,
+    );
+    require!(
+        nft_metadata.key() == expected_metadata,
+        QuestError::InvalidNftMetadata
+    );
+
+    let metadata = mpl_token_metadata::state::Metadata::from_account_info(nft_metadata)
+        .map_err(|_| QuestError::InvalidNftMetadata)?;
+    let collection = metadata.collection.ok_or(QuestError::CollectionNotVerified)?;
+    require!(
+        collection.verified && collection.key == *required_collection,
+        QuestError::CollectionNotVerified
+    );
+
+    Ok(())
+}
+
 fn calculate_level(total_xp: u64) -> u32 {
     // Simple level calculation: every 1000 XP = 1 level
     ((total_xp / 1000) + 1) as u32
 }
-
-fn get_achievement_reputation_bonus(achievement_type: &AchievementType) -> u64 {
-    match achievement_type {
-        AchievementType::FirstPayment => 50,
-        AchievementType::PaymentStreak => 100,
-        AchievementType::VolumeTrader => 200,
-        AchievementType::QuestMaster => 300,
-        AchievementType::SocialButterfly => 150,
-        AchievementType::TaskCompleter => 100,
-        AchievementType::LoyalCustomer => 250,
-        AchievementType::CommunityChampion => 500,
-    }
-}
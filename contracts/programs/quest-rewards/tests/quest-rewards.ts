@@ -0,0 +1,1348 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, LAMPORTS_PER_SOL, SystemProgram } from "@solana/web3.js";
+import { createHash } from "crypto";
+import { createAssociatedTokenAccount, createMint, mintTo } from "@solana/spl-token";
+import {
+  PROGRAM_ID as TOKEN_METADATA_PROGRAM_ID,
+  createCreateMasterEditionV3Instruction,
+  createCreateMetadataAccountV3Instruction,
+  createVerifyCollectionInstruction,
+} from "@metaplex-foundation/mpl-token-metadata";
+import {
+  PROGRAM_ID as BUBBLEGUM_PROGRAM_ID,
+  TreeConfig,
+  createCreateTreeInstruction,
+  createSetTreeDelegateInstruction,
+} from "@metaplex-foundation/mpl-bubblegum";
+import {
+  ConcurrentMerkleTreeAccount,
+  SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+  SPL_NOOP_PROGRAM_ID,
+  createAllocTreeIx,
+} from "@solana/spl-account-compression";
+import { Clock, startAnchor } from "solana-bankrun";
+import { BankrunProvider } from "anchor-bankrun";
+import { IDL, QuestRewards } from "../target/types/quest_rewards";
+import { expect } from "chai";
+
+describe("quest-rewards", () => {
+  // Configure the client to use the local cluster.
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.QuestRewards as Program<QuestRewards>;
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+
+  const MAX_REGISTRY_QUESTS = 50;
+  const REPUTATION_DECAY_SCALE = 1_000_000_000;
+  const ACHIEVEMENT_RECORD_LEN = 74;
+
+  const [registryPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("quest_registry")],
+    program.programId
+  );
+
+  const questPda = (questId: string) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("quest"), Buffer.from(questId)],
+      program.programId
+    )[0];
+
+  const userProfilePda = (user: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("user_profile"), user.toBuffer()],
+      program.programId
+    )[0];
+
+  const userQuestPda = (user: PublicKey, questId: string) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("user_quest"), user.toBuffer(), Buffer.from(questId)],
+      program.programId
+    )[0];
+
+  // Returns the bump too, for tests that write records directly
+  const achievementRecordPda = (user: PublicKey, index: number) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("achievement_record"), user.toBuffer(), new BN(index).toArrayLike(Buffer, "le", 4)],
+      program.programId
+    );
+
+  // Quest ids share one validator across tests, so each gets a random suffix
+  const newQuestId = (prefix: string) =>
+    `${prefix}-${Keypair.generate().publicKey.toBase58().slice(0, 8)}`;
+
+  const fundedKeypair = async (sol = 2) => {
+    const keypair = Keypair.generate();
+    const signature = await provider.connection.requestAirdrop(keypair.publicKey, sol * LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(signature);
+    return keypair;
+  };
+
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    try {
+      await promise;
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal(code);
+      return;
+    }
+    expect.fail(`expected ${code}`);
+  };
+
+  type QuestOptions = {
+    questType?: object;
+    difficulty?: object;
+    requirements?: object;
+    xpReward?: number;
+    reputationPoints?: number;
+    durationHours?: number;
+    repeatCooldown?: number | null;
+    requiredCollection?: PublicKey | null;
+    requiresCompletionProof?: boolean;
+  };
+
+  // An Easy payment quest completed by a single payment unless overridden
+  const createQuest = (questId: string, creator?: Keypair, options: QuestOptions = {}) =>
+    program.methods
+      .createQuest(
+        questId,
+        "First payment",
+        "Make a payment with SolanaPay",
+        options.questType ?? { daily: {} },
+        { payment: {} },
+        options.difficulty ?? { easy: {} },
+        options.requirements ?? { paymentCount: { count: 1 } },
+        {
+          xpReward: new BN(options.xpReward ?? 100),
+          reputationPoints: new BN(options.reputationPoints ?? 10),
+          tokenReward: null,
+          nftReward: false,
+          badgeReward: null,
+        },
+        new BN(options.durationHours ?? 24),
+        options.repeatCooldown == null ? null : new BN(options.repeatCooldown),
+        options.requiredCollection ?? null,
+        options.requiresCompletionProof ?? false
+      )
+      .accounts({
+        quest: questPda(questId),
+        questRegistry: registryPda,
+        creator: creator ? creator.publicKey : provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers(creator ? [creator] : [])
+      .rpc();
+
+  const closeQuest = (questId: string, creator?: Keypair) =>
+    program.methods
+      .closeQuest()
+      .accounts({
+        quest: questPda(questId),
+        questRegistry: registryPda,
+        creator: creator ? creator.publicKey : provider.wallet.publicKey,
+      })
+      .signers(creator ? [creator] : [])
+      .rpc();
+
+  const activeQuests = () =>
+    program.methods.getActiveQuests().accounts({ questRegistry: registryPda }).view() as Promise<string[]>;
+
+  const initializeUserProfile = (user: Keypair) =>
+    program.methods
+      .initializeUserProfile(`${user.publicKey.toBase58().slice(0, 8)}.sol`)
+      .accounts({
+        userProfile: userProfilePda(user.publicKey),
+        questRegistry: registryPda,
+        authority: user.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+  const startQuest = (user: Keypair, questId: string) =>
+    program.methods
+      .startQuest(questId)
+      .accounts({
+        userQuest: userQuestPda(user.publicKey, questId),
+        quest: questPda(questId),
+        user: user.publicKey,
+        nftTokenAccount: null,
+        nftMetadata: null,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+  const updateQuestProgress = (
+    user: Keypair,
+    questId: string,
+    paymentsMade: number,
+    completionProofHash: number[] | null = null
+  ) =>
+    program.methods
+      .updateQuestProgress(
+        {
+          paymentsMade,
+          volumeTraded: new BN(0),
+          streakDays: 0,
+          tasksCompleted: 0,
+          socialInteractions: 0,
+        },
+        completionProofHash
+      )
+      .accounts({
+        userQuest: userQuestPda(user.publicKey, questId),
+        quest: questPda(questId),
+        userProfile: userProfilePda(user.publicKey),
+        questRegistry: registryPda,
+        user: user.publicKey,
+      })
+      .signers([user])
+      .rpc();
+
+  // Complete a fresh quest created by the registry authority
+  const completeQuest = async (user: Keypair, options: QuestOptions = {}) => {
+    const questId = newQuestId("quest");
+    await createQuest(questId, undefined, options);
+    await startQuest(user, questId);
+    await updateQuestProgress(user, questId, 1);
+    return questId;
+  };
+
+  const expectRejected = async (promise: Promise<unknown>) => {
+    try {
+      await promise;
+    } catch {
+      return;
+    }
+    expect.fail("expected the transaction to fail");
+  };
+
+  // A fresh bank with its own registry, for tests that move the clock or write accounts directly
+  const startBankrun = async () => {
+    const context = await startAnchor(".", [], []);
+    const bankrunProgram = new Program<QuestRewards>(IDL, program.programId, new BankrunProvider(context));
+    await bankrunProgram.methods
+      .initializeQuestRegistry()
+      .accounts({
+        questRegistry: registryPda,
+        authority: context.payer.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const fundedUser = () => {
+      const keypair = Keypair.generate();
+      context.setAccount(keypair.publicKey, {
+        lamports: 2 * LAMPORTS_PER_SOL,
+        data: Buffer.alloc(0),
+        owner: SystemProgram.programId,
+        executable: false,
+      });
+      return keypair;
+    };
+
+    const initializeProfile = async (user: Keypair) => {
+      await bankrunProgram.methods
+        .initializeUserProfile(`${user.publicKey.toBase58().slice(0, 8)}.sol`)
+        .accounts({
+          userProfile: userProfilePda(user.publicKey),
+          questRegistry: registryPda,
+          authority: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+    };
+
+    return { context, bankrunProgram, fundedUser, initializeProfile };
+  };
+
+  // A private Bubblegum tree delegated to the quest registry, as mint_compressed_achievement_nft expects
+  const createAchievementTree = async (maxDepth: number, maxBufferSize: number, delegate = registryPda) => {
+    const payer = provider.wallet.publicKey;
+    const merkleTree = Keypair.generate();
+    const [treeAuthority] = PublicKey.findProgramAddressSync(
+      [merkleTree.publicKey.toBuffer()],
+      BUBBLEGUM_PROGRAM_ID
+    );
+
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        await createAllocTreeIx(
+          provider.connection,
+          merkleTree.publicKey,
+          payer,
+          { maxDepth, maxBufferSize },
+          0
+        ),
+        createCreateTreeInstruction(
+          {
+            treeAuthority,
+            merkleTree: merkleTree.publicKey,
+            payer,
+            treeCreator: payer,
+            logWrapper: SPL_NOOP_PROGRAM_ID,
+            compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+          },
+          { maxDepth, maxBufferSize, public: false }
+        ),
+        createSetTreeDelegateInstruction({
+          treeAuthority,
+          treeCreator: payer,
+          newTreeDelegate: delegate,
+          merkleTree: merkleTree.publicKey,
+        })
+      ),
+      [merkleTree]
+    );
+    return { merkleTree: merkleTree.publicKey, treeAuthority };
+  };
+
+  const mintAchievement = async (
+    user: Keypair,
+    achievementType: object,
+    tree: { merkleTree: PublicKey; treeAuthority: PublicKey },
+    authority?: Keypair
+  ) => {
+    const { achievementsMinted } = await program.account.userProfile.fetch(userProfilePda(user.publicKey));
+    return program.methods
+      .mintCompressedAchievementNft(achievementType, "https://example.com/achievement.json")
+      .accounts({
+        userProfile: userProfilePda(user.publicKey),
+        achievementRecord: achievementRecordPda(user.publicKey, achievementsMinted)[0],
+        questRegistry: registryPda,
+        user: user.publicKey,
+        authority: authority ? authority.publicKey : provider.wallet.publicKey,
+        merkleTree: tree.merkleTree,
+        treeAuthority: tree.treeAuthority,
+        bubblegumProgram: BUBBLEGUM_PROGRAM_ID,
+        compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+        logWrapper: SPL_NOOP_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers(authority ? [user, authority] : [user])
+      .rpc();
+  };
+
+  before(async () => {
+    await program.methods
+      .initializeQuestRegistry()
+      .accounts({
+        questRegistry: registryPda,
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  });
+
+  describe("quest registry", () => {
+    it("Lists quests created by the registry authority", async () => {
+      const questId = newQuestId("listed");
+
+      await createQuest(questId);
+
+      expect(await activeQuests()).to.include(questId);
+      const quest = await program.account.quest.fetch(questPda(questId));
+      expect(quest.isActive).to.equal(true);
+    });
+
+    it("Removes a quest from the registry when its creator closes it", async () => {
+      const questId = newQuestId("closed");
+      await createQuest(questId);
+
+      await closeQuest(questId);
+
+      expect(await activeQuests()).to.not.include(questId);
+      const quest = await program.account.quest.fetch(questPda(questId));
+      expect(quest.isActive).to.equal(false);
+    });
+
+    it("Removes an expired quest when anyone archives it", async () => {
+      const questId = newQuestId("expired");
+      // A zero-hour quest expires as soon as it is created
+      await createQuest(questId, undefined, { durationHours: 0 });
+      const caller = await fundedKeypair();
+
+      await program.methods
+        .archiveExpiredQuest()
+        .accounts({
+          quest: questPda(questId),
+          questRegistry: registryPda,
+          caller: caller.publicKey,
+        })
+        .signers([caller])
+        .rpc();
+
+      expect(await activeQuests()).to.not.include(questId);
+      const quest = await program.account.quest.fetch(questPda(questId));
+      expect(quest.isActive).to.equal(false);
+    });
+
+    it("Refuses to archive a quest that has not expired", async () => {
+      const questId = newQuestId("live");
+      await createQuest(questId);
+
+      await expectError(
+        program.methods
+          .archiveExpiredQuest()
+          .accounts({
+            quest: questPda(questId),
+            questRegistry: registryPda,
+            caller: provider.wallet.publicKey,
+          })
+          .rpc(),
+        "QuestNotExpired"
+      );
+      expect(await activeQuests()).to.include(questId);
+    });
+
+    it("Only lets the creator close a quest", async () => {
+      const questId = newQuestId("owned");
+      await createQuest(questId);
+
+      await expectError(closeQuest(questId, await fundedKeypair()), "NotQuestCreator");
+      expect(await activeQuests()).to.include(questId);
+    });
+
+    it("Rejects quests from creators other than the registry authority", async () => {
+      const creator = await fundedKeypair();
+      const questId = newQuestId("outsider");
+
+      await expectError(createQuest(questId, creator), "Unauthorized");
+
+      expect(await activeQuests()).to.not.include(questId);
+      expect(await program.account.quest.fetchNullable(questPda(questId))).to.equal(null);
+    });
+
+    it("Rejects listing a quest once the registry is full", async () => {
+      const filler: string[] = [];
+      while ((await activeQuests()).length < MAX_REGISTRY_QUESTS) {
+        const questId = newQuestId("filler");
+        await createQuest(questId);
+        filler.push(questId);
+      }
+
+      try {
+        await expectError(createQuest(newQuestId("overflow")), "QuestRegistryFull");
+      } finally {
+        for (const questId of filler) {
+          await closeQuest(questId);
+        }
+      }
+
+      expect((await activeQuests()).length).to.be.lessThan(MAX_REGISTRY_QUESTS);
+    });
+  });
+
+  describe("reputation and level return data", () => {
+    let user: Keypair;
+
+    before(async () => {
+      user = await fundedKeypair();
+      await initializeUserProfile(user);
+      // 2000 XP puts the user on level 3
+      await completeQuest(user, { difficulty: { legendary: {} }, xpReward: 2000, reputationPoints: 150 });
+    });
+
+    // What a CPI caller reads back with `get()`: the return data Anchor sets for the instruction
+    const returnData = async (signature: string) => {
+      const tx = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const { programId, data } = tx!.meta!.returnData!;
+      expect(programId.toString()).to.equal(program.programId.toString());
+      return Buffer.from(data[0], "base64");
+    };
+
+    it("Returns the user's reputation", async () => {
+      const accounts = { userProfile: userProfilePda(user.publicKey) };
+
+      const reputation = await program.methods.getUserReputation().accounts(accounts).view();
+      expect(reputation.toNumber()).to.equal(150);
+
+      const signature = await program.methods
+        .getUserReputation()
+        .accounts(accounts)
+        .rpc({ commitment: "confirmed" });
+      expect((await returnData(signature)).readBigUInt64LE(0)).to.equal(BigInt(150));
+    });
+
+    it("Returns the user's level", async () => {
+      const accounts = { userProfile: userProfilePda(user.publicKey) };
+
+      const level = await program.methods.getUserLevel().accounts(accounts).view();
+      expect(level).to.equal(3);
+
+      const signature = await program.methods
+        .getUserLevel()
+        .accounts(accounts)
+        .rpc({ commitment: "confirmed" });
+      expect((await returnData(signature)).readUInt32LE(0)).to.equal(3);
+    });
+  });
+
+  describe("difficulty reward bounds", () => {
+    const setRewardBounds = (
+      difficulty: object,
+      bounds: { minXp: number; maxXp: number; minReputation: number; maxReputation: number },
+      authority?: Keypair
+    ) =>
+      program.methods
+        .setRewardBounds(difficulty, {
+          minXp: new BN(bounds.minXp),
+          maxXp: new BN(bounds.maxXp),
+          minReputation: new BN(bounds.minReputation),
+          maxReputation: new BN(bounds.maxReputation),
+        })
+        .accounts({
+          questRegistry: registryPda,
+          authority: authority ? authority.publicKey : provider.wallet.publicKey,
+        })
+        .signers(authority ? [authority] : [])
+        .rpc();
+
+    it("Accepts rewards within the bounds for the quest's difficulty", async () => {
+      const questId = newQuestId("in-range");
+
+      await createQuest(questId, undefined, { difficulty: { easy: {} }, xpReward: 200, reputationPoints: 20 });
+
+      const quest = await program.account.quest.fetch(questPda(questId));
+      expect(quest.rewards.xpReward.toNumber()).to.equal(200);
+      expect(quest.rewards.reputationPoints.toNumber()).to.equal(20);
+    });
+
+    it("Rejects an Easy quest with Legendary rewards", async () => {
+      await expectError(
+        createQuest(newQuestId("too-rich"), undefined, {
+          difficulty: { easy: {} },
+          xpReward: 5000,
+          reputationPoints: 500,
+        }),
+        "RewardOutOfRange"
+      );
+    });
+
+    it("Rejects rewards below the difficulty's minimum", async () => {
+      await expectError(
+        createQuest(newQuestId("too-poor"), undefined, {
+          difficulty: { hard: {} },
+          xpReward: 100,
+          reputationPoints: 30,
+        }),
+        "RewardOutOfRange"
+      );
+    });
+
+    it("Enforces bounds updated by the registry authority", async () => {
+      const registry = await program.account.questRegistry.fetch(registryPda);
+      expect(registry.rewardBounds[0].maxXp.toNumber()).to.equal(200);
+
+      await setRewardBounds({ easy: {} }, { minXp: 10, maxXp: 300, minReputation: 1, maxReputation: 20 });
+      try {
+        await createQuest(newQuestId("raised"), undefined, { difficulty: { easy: {} }, xpReward: 300 });
+      } finally {
+        await setRewardBounds({ easy: {} }, { minXp: 10, maxXp: 200, minReputation: 1, maxReputation: 20 });
+      }
+
+      await expectError(
+        createQuest(newQuestId("restored"), undefined, { difficulty: { easy: {} }, xpReward: 300 }),
+        "RewardOutOfRange"
+      );
+    });
+
+    it("Rejects inverted bounds", async () => {
+      await expectError(
+        setRewardBounds({ medium: {} }, { minXp: 500, maxXp: 100, minReputation: 10, maxReputation: 50 }),
+        "InvalidRewardBounds"
+      );
+    });
+
+    it("Only lets the registry authority change bounds", async () => {
+      await expectError(
+        setRewardBounds(
+          { easy: {} },
+          { minXp: 0, maxXp: 100000, minReputation: 0, maxReputation: 100000 },
+          await fundedKeypair()
+        ),
+        "Unauthorized"
+      );
+    });
+  });
+
+  describe("repeatable special quests", () => {
+    const COOLDOWN = 2;
+
+    const repeatQuest = (user: Keypair, questId: string) =>
+      program.methods
+        .repeatQuest()
+        .accounts({
+          userQuest: userQuestPda(user.publicKey, questId),
+          quest: questPda(questId),
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+    it("Credits rewards again when repeated after the cooldown", async () => {
+      const user = await fundedKeypair();
+      await initializeUserProfile(user);
+      const questId = await completeQuest(user, { questType: { special: {} }, repeatCooldown: COOLDOWN });
+
+      // Let the validator clock move past the cooldown
+      await new Promise((resolve) => setTimeout(resolve, (COOLDOWN + 2) * 1000));
+      await repeatQuest(user, questId);
+
+      const restarted = await program.account.userQuest.fetch(userQuestPda(user.publicKey, questId));
+      expect(restarted.status).to.deep.equal({ active: {} });
+      expect(restarted.completedAt).to.equal(null);
+
+      await updateQuestProgress(user, questId, 1);
+
+      const userQuest = await program.account.userQuest.fetch(userQuestPda(user.publicKey, questId));
+      expect(userQuest.status).to.deep.equal({ completed: {} });
+      expect(userQuest.completionCount).to.equal(2);
+      const profile = await program.account.userProfile.fetch(userProfilePda(user.publicKey));
+      expect(profile.totalQuestsCompleted).to.equal(2);
+      expect(profile.totalXp.toNumber()).to.equal(200);
+      expect(profile.reputationScore.toNumber()).to.equal(20);
+    });
+
+    it("Rejects a repeat before the cooldown has elapsed", async () => {
+      const user = await fundedKeypair();
+      await initializeUserProfile(user);
+      const questId = await completeQuest(user, { questType: { special: {} }, repeatCooldown: 3600 });
+
+      await expectError(repeatQuest(user, questId), "QuestOnCooldown");
+    });
+
+    it("Rejects repeating a quest that is still in progress", async () => {
+      const user = await fundedKeypair();
+      const questId = newQuestId("special");
+      await createQuest(questId, undefined, { questType: { special: {} }, repeatCooldown: COOLDOWN });
+      await startQuest(user, questId);
+
+      await expectError(repeatQuest(user, questId), "QuestNotCompleted");
+    });
+
+    it("Rejects repeating a quest without a cooldown", async () => {
+      const user = await fundedKeypair();
+      await initializeUserProfile(user);
+      const questId = await completeQuest(user, { questType: { special: {} } });
+
+      await expectError(repeatQuest(user, questId), "QuestNotRepeatable");
+    });
+
+    it("Only allows a cooldown on special quests", async () => {
+      await expectError(
+        createQuest(newQuestId("daily"), undefined, { questType: { daily: {} }, repeatCooldown: COOLDOWN }),
+        "QuestNotRepeatable"
+      );
+      await expectError(
+        createQuest(newQuestId("special"), undefined, { questType: { special: {} }, repeatCooldown: 0 }),
+        "InvalidCooldown"
+      );
+    });
+  });
+
+  describe("NFT-gated quests", () => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+
+    const metadataPda = (mint: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+        TOKEN_METADATA_PROGRAM_ID
+      )[0];
+
+    const editionPda = (mint: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("metadata"),
+          TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+          mint.toBuffer(),
+          Buffer.from("edition"),
+        ],
+        TOKEN_METADATA_PROGRAM_ID
+      )[0];
+
+    // Mint a master edition NFT to `owner`, optionally naming (but not verifying) a collection
+    const mintNft = async (owner: PublicKey, collection: PublicKey | null = null) => {
+      const mint = await createMint(provider.connection, payer, payer.publicKey, payer.publicKey, 0);
+      const tokenAccount = await createAssociatedTokenAccount(provider.connection, payer, mint, owner);
+      await mintTo(provider.connection, payer, mint, tokenAccount, payer, 1);
+
+      const metadata = metadataPda(mint);
+      const authorities = {
+        mint,
+        metadata,
+        mintAuthority: payer.publicKey,
+        updateAuthority: payer.publicKey,
+        payer: payer.publicKey,
+      };
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          createCreateMetadataAccountV3Instruction(authorities, {
+            createMetadataAccountArgsV3: {
+              data: {
+                name: "Partner NFT",
+                symbol: "PART",
+                uri: "https://example.com/nft.json",
+                sellerFeeBasisPoints: 0,
+                creators: null,
+                collection: collection ? { key: collection, verified: false } : null,
+                uses: null,
+              },
+              isMutable: true,
+              collectionDetails: null,
+            },
+          }),
+          createCreateMasterEditionV3Instruction(
+            { ...authorities, edition: editionPda(mint) },
+            { createMasterEditionArgs: { maxSupply: 0 } }
+          )
+        )
+      );
+      return { mint, tokenAccount, metadata };
+    };
+
+    const verifyCollection = (nftMetadata: PublicKey, collectionMint: PublicKey) =>
+      provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          createVerifyCollectionInstruction({
+            metadata: nftMetadata,
+            collectionAuthority: payer.publicKey,
+            payer: payer.publicKey,
+            collectionMint,
+            collection: metadataPda(collectionMint),
+            collectionMasterEditionAccount: editionPda(collectionMint),
+          })
+        )
+      );
+
+    const startGatedQuest = (
+      user: Keypair,
+      questId: string,
+      nft: { tokenAccount: PublicKey; metadata: PublicKey } | null
+    ) =>
+      program.methods
+        .startQuest(questId)
+        .accounts({
+          userQuest: userQuestPda(user.publicKey, questId),
+          quest: questPda(questId),
+          user: user.publicKey,
+          nftTokenAccount: nft ? nft.tokenAccount : null,
+          nftMetadata: nft ? nft.metadata : null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+
+    let collectionMint: PublicKey;
+    let questId: string;
+
+    before(async () => {
+      ({ mint: collectionMint } = await mintNft(payer.publicKey));
+      questId = newQuestId("gated");
+      await createQuest(questId, undefined, { requiredCollection: collectionMint });
+    });
+
+    it("Lets a holder of a verified collection NFT start the quest", async () => {
+      const user = await fundedKeypair();
+      const nft = await mintNft(user.publicKey, collectionMint);
+      await verifyCollection(nft.metadata, collectionMint);
+
+      await startGatedQuest(user, questId, nft);
+
+      const userQuest = await program.account.userQuest.fetch(userQuestPda(user.publicKey, questId));
+      expect(userQuest.status).to.deep.equal({ active: {} });
+    });
+
+    it("Rejects a user who passes no NFT", async () => {
+      const user = await fundedKeypair();
+
+      await expectError(startGatedQuest(user, questId, null), "MissingCollectionNft");
+    });
+
+    it("Rejects an NFT whose collection is not verified", async () => {
+      const user = await fundedKeypair();
+      const nft = await mintNft(user.publicKey, collectionMint);
+
+      await expectError(startGatedQuest(user, questId, nft), "CollectionNotVerified");
+    });
+
+    it("Rejects a verified NFT from another collection", async () => {
+      const user = await fundedKeypair();
+      const { mint: otherCollection } = await mintNft(payer.publicKey);
+      const nft = await mintNft(user.publicKey, otherCollection);
+      await verifyCollection(nft.metadata, otherCollection);
+
+      await expectError(startGatedQuest(user, questId, nft), "CollectionNotVerified");
+    });
+
+    it("Rejects a collection NFT held by someone else", async () => {
+      const holder = await fundedKeypair();
+      const user = await fundedKeypair();
+      const nft = await mintNft(holder.publicKey, collectionMint);
+      await verifyCollection(nft.metadata, collectionMint);
+
+      await expectError(startGatedQuest(user, questId, nft), "CollectionNotVerified");
+    });
+  });
+
+  describe("achievement recount", () => {
+    let bank: Awaited<ReturnType<typeof startBankrun>>;
+
+    // Overwrite a program account's data in place, keeping its size
+    const writeAccount = async (address: PublicKey, accountName: string, fields: object, space: number) => {
+      const data = Buffer.alloc(space);
+      (await bank.bankrunProgram.coder.accounts.encode(accountName, fields)).copy(data);
+      bank.context.setAccount(address, {
+        lamports: LAMPORTS_PER_SOL,
+        data,
+        owner: program.programId,
+        executable: false,
+      });
+    };
+
+    // Seed achievement records as if minted, then leave the profile counting only the first
+    const seedDriftedProfile = async (bonuses: number[]) => {
+      const user = bank.fundedUser();
+      await bank.initializeProfile(user);
+
+      const records: PublicKey[] = [];
+      for (const [index, bonus] of bonuses.entries()) {
+        const [record, bump] = achievementRecordPda(user.publicKey, index);
+        await writeAccount(
+          record,
+          "AchievementRecord",
+          {
+            user: user.publicKey,
+            index,
+            achievementType: { firstPayment: {} },
+            reputationBonus: new BN(bonus),
+            mintedAt: new BN(0),
+            decayEpoch: 0,
+            decayFactor: new BN(REPUTATION_DECAY_SCALE),
+            bump,
+          },
+          ACHIEVEMENT_RECORD_LEN
+        );
+        records.push(record);
+      }
+
+      const profileAddress = userProfilePda(user.publicKey);
+      const profile = await bank.bankrunProgram.account.userProfile.fetch(profileAddress);
+      const { data } = (await bank.context.banksClient.getAccount(profileAddress))!;
+      await writeAccount(
+        profileAddress,
+        "UserProfile",
+        {
+          ...profile,
+          achievementsMinted: bonuses.length,
+          achievementsCount: 1,
+          achievementReputation: new BN(bonuses[0]),
+          // 40 reputation earned from quests on top of the one counted bonus
+          reputationScore: new BN(40 + bonuses[0]),
+          lifetimeReputation: new BN(40 + bonuses[0]),
+        },
+        data.length
+      );
+      return { user, records };
+    };
+
+    const recount = (user: PublicKey, records: PublicKey[]) =>
+      bank.bankrunProgram.methods
+        .recountAchievements()
+        .accounts({
+          questRegistry: registryPda,
+          userProfile: userProfilePda(user),
+          authority: bank.context.payer.publicKey,
+        })
+        .remainingAccounts(records.map((pubkey) => ({ pubkey, isWritable: false, isSigner: false })))
+        .rpc();
+
+    before(async () => {
+      bank = await startBankrun();
+    });
+
+    it("Restores the achievement count and reputation from the records", async () => {
+      const { user, records } = await seedDriftedProfile([50, 100, 300]);
+
+      await recount(user.publicKey, records);
+
+      const profile = await bank.bankrunProgram.account.userProfile.fetch(userProfilePda(user.publicKey));
+      expect(profile.achievementsCount).to.equal(3);
+      expect(profile.achievementReputation.toNumber()).to.equal(450);
+      expect(profile.reputationScore.toNumber()).to.equal(40 + 450);
+      expect(profile.lifetimeReputation.toNumber()).to.equal(40 + 450);
+    });
+
+    it("Leaves a correct profile unchanged when recounted again", async () => {
+      const { user, records } = await seedDriftedProfile([150, 250]);
+      await recount(user.publicKey, records);
+      const first = await bank.bankrunProgram.account.userProfile.fetch(userProfilePda(user.publicKey));
+
+      // Refresh the blockhash so the identical transaction isn't deduplicated
+      bank.context.warpToSlot((await bank.context.banksClient.getSlot()) + BigInt(1));
+      await recount(user.publicKey, records);
+
+      const second = await bank.bankrunProgram.account.userProfile.fetch(userProfilePda(user.publicKey));
+      expect(second.achievementsCount).to.equal(first.achievementsCount);
+      expect(second.reputationScore.toNumber()).to.equal(first.reputationScore.toNumber());
+    });
+
+    it("Rejects a recount missing some of the records", async () => {
+      const { user, records } = await seedDriftedProfile([50, 100]);
+
+      await expectRejected(recount(user.publicKey, records.slice(0, 1)));
+    });
+
+    it("Rejects records that belong to another user", async () => {
+      const { user } = await seedDriftedProfile([50, 100]);
+      const { records: otherRecords } = await seedDriftedProfile([50, 100]);
+
+      await expectRejected(recount(user.publicKey, otherRecords));
+    });
+
+    it("Only lets the registry authority recount", async () => {
+      const { user, records } = await seedDriftedProfile([50]);
+      const outsider = bank.fundedUser();
+
+      await expectRejected(
+        bank.bankrunProgram.methods
+          .recountAchievements()
+          .accounts({
+            questRegistry: registryPda,
+            userProfile: userProfilePda(user.publicKey),
+            authority: outsider.publicKey,
+          })
+          .remainingAccounts(records.map((pubkey) => ({ pubkey, isWritable: false, isSigner: false })))
+          .signers([outsider])
+          .rpc()
+      );
+    });
+  });
+
+  describe("completion proofs", () => {
+    const proofHash = (evidence: string) => Array.from(createHash("sha256").update(evidence).digest());
+
+    const emittedEvents = async (signature: string) => {
+      await provider.connection.confirmTransaction(signature, "confirmed");
+      const tx = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+      return Array.from(parser.parseLogs(tx!.meta!.logMessages!));
+    };
+
+    let user: Keypair;
+
+    before(async () => {
+      user = await fundedKeypair();
+      await initializeUserProfile(user);
+    });
+
+    it("Stores and emits the proof hash a quest was completed with", async () => {
+      const questId = newQuestId("proof");
+      await createQuest(questId, undefined, { requiresCompletionProof: true });
+      await startQuest(user, questId);
+      const hash = proofHash("receipt-1");
+
+      const signature = await updateQuestProgress(user, questId, 1, hash);
+
+      const userQuest = await program.account.userQuest.fetch(userQuestPda(user.publicKey, questId));
+      expect(userQuest.status).to.deep.equal({ completed: {} });
+      expect(userQuest.completionProofHash).to.deep.equal(hash);
+
+      const completed = (await emittedEvents(signature)).find((event) => event.name === "QuestCompleted");
+      expect(completed).to.not.equal(undefined);
+      expect(completed!.data.questId).to.equal(questId);
+      expect(completed!.data.completionProofHash).to.deep.equal(hash);
+    });
+
+    it("Rejects completing a proof-required quest without a hash", async () => {
+      const questId = newQuestId("proof");
+      await createQuest(questId, undefined, { requiresCompletionProof: true });
+      await startQuest(user, questId);
+
+      await expectError(updateQuestProgress(user, questId, 1), "MissingCompletionProof");
+
+      const userQuest = await program.account.userQuest.fetch(userQuestPda(user.publicKey, questId));
+      expect(userQuest.status).to.deep.equal({ active: {} });
+      expect(userQuest.completionProofHash).to.equal(null);
+    });
+
+    it("Completes a quest without a required proof when none is given", async () => {
+      const questId = await completeQuest(user);
+
+      const userQuest = await program.account.userQuest.fetch(userQuestPda(user.publicKey, questId));
+      expect(userQuest.status).to.deep.equal({ completed: {} });
+      expect(userQuest.completionProofHash).to.equal(null);
+    });
+  });
+
+  describe("seasons", () => {
+    const reputationSnapshotPda = (user: PublicKey, seasonNumber: number) =>
+      PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("reputation_snapshot"),
+          user.toBuffer(),
+          new BN(seasonNumber).toArrayLike(Buffer, "le", 4),
+        ],
+        program.programId
+      )[0];
+
+    const resetSeason = (decayBps: number, authority?: Keypair) =>
+      program.methods
+        .resetSeason(decayBps)
+        .accounts({
+          questRegistry: registryPda,
+          authority: authority ? authority.publicKey : provider.wallet.publicKey,
+        })
+        .signers(authority ? [authority] : [])
+        .rpc();
+
+    const snapshotSeason = (user: PublicKey, seasonNumber: number) =>
+      program.methods
+        .snapshotSeasonReputation()
+        .accounts({
+          questRegistry: registryPda,
+          userProfile: userProfilePda(user),
+          reputationSnapshot: reputationSnapshotPda(user, seasonNumber),
+          payer: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    const LEGENDARY = { difficulty: { legendary: {} }, xpReward: 1000, reputationPoints: 200 };
+
+    it("Snapshots the season's reputation and decays the live score", async () => {
+      const user = await fundedKeypair();
+      await initializeUserProfile(user);
+      await completeQuest(user, LEGENDARY);
+      const { seasonNumber } = await program.account.questRegistry.fetch(registryPda);
+
+      await resetSeason(5000);
+      await snapshotSeason(user.publicKey, seasonNumber);
+
+      const snapshot = await program.account.reputationSnapshot.fetch(
+        reputationSnapshotPda(user.publicKey, seasonNumber)
+      );
+      expect(snapshot.seasonNumber).to.equal(seasonNumber);
+      expect(snapshot.reputationScore.toNumber()).to.equal(200);
+      expect(snapshot.lifetimeReputation.toNumber()).to.equal(200);
+      expect(snapshot.totalQuestsCompleted).to.equal(1);
+
+      const profile = await program.account.userProfile.fetch(userProfilePda(user.publicKey));
+      expect(profile.seasonNumber).to.equal(seasonNumber + 1);
+      expect(profile.reputationScore.toNumber()).to.equal(100);
+      expect(profile.lifetimeReputation.toNumber()).to.equal(200);
+    });
+
+    it("Keeps lifetime reputation when a season zeroes the live score", async () => {
+      const user = await fundedKeypair();
+      await initializeUserProfile(user);
+      await completeQuest(user, LEGENDARY);
+      const { seasonNumber } = await program.account.questRegistry.fetch(registryPda);
+
+      await resetSeason(10000);
+      await snapshotSeason(user.publicKey, seasonNumber);
+
+      let profile = await program.account.userProfile.fetch(userProfilePda(user.publicKey));
+      expect(profile.reputationScore.toNumber()).to.equal(0);
+      expect(profile.lifetimeReputation.toNumber()).to.equal(200);
+
+      // Reputation earned in the new season adds to both
+      await completeQuest(user, LEGENDARY);
+
+      profile = await program.account.userProfile.fetch(userProfilePda(user.publicKey));
+      expect(profile.reputationScore.toNumber()).to.equal(200);
+      expect(profile.lifetimeReputation.toNumber()).to.equal(400);
+    });
+
+    it("Blocks progress until the user's last season is snapshotted", async () => {
+      const user = await fundedKeypair();
+      await initializeUserProfile(user);
+      const questId = newQuestId("season");
+      await createQuest(questId, undefined, LEGENDARY);
+      await startQuest(user, questId);
+      const { seasonNumber } = await program.account.questRegistry.fetch(registryPda);
+
+      await resetSeason(0);
+
+      await expectError(updateQuestProgress(user, questId, 1), "SeasonSnapshotPending");
+
+      await snapshotSeason(user.publicKey, seasonNumber);
+      await updateQuestProgress(user, questId, 1);
+
+      const profile = await program.account.userProfile.fetch(userProfilePda(user.publicKey));
+      expect(profile.reputationScore.toNumber()).to.equal(200);
+    });
+
+    it("Snapshots each season only once", async () => {
+      const user = await fundedKeypair();
+      await initializeUserProfile(user);
+      const { seasonNumber } = await program.account.questRegistry.fetch(registryPda);
+      await resetSeason(0);
+      await snapshotSeason(user.publicKey, seasonNumber);
+
+      // The profile now belongs to the current season, which has not ended
+      await expectError(snapshotSeason(user.publicKey, seasonNumber + 1), "SeasonAlreadySnapshotted");
+    });
+
+    it("Rejects a decay above 10000 basis points", async () => {
+      await expectError(resetSeason(10001), "InvalidDecayRate");
+    });
+
+    it("Only lets the registry authority reset the season", async () => {
+      await expectError(resetSeason(0, await fundedKeypair()), "Unauthorized");
+    });
+  });
+
+  describe("achievement bonuses", () => {
+    const DEFAULT_BONUSES = [50, 100, 200, 300, 150, 100, 250, 500];
+
+    const setAchievementBonus = (achievementType: object, bonus: number, authority?: Keypair) =>
+      program.methods
+        .setAchievementBonus(achievementType, new BN(bonus))
+        .accounts({
+          questRegistry: registryPda,
+          authority: authority ? authority.publicKey : provider.wallet.publicKey,
+        })
+        .signers(authority ? [authority] : [])
+        .rpc();
+
+    let tree: { merkleTree: PublicKey; treeAuthority: PublicKey };
+
+    before(async () => {
+      tree = await createAchievementTree(5, 8);
+    });
+
+    it("Starts from the previous hardcoded bonuses", async () => {
+      const registry = await program.account.questRegistry.fetch(registryPda);
+      expect(registry.achievementBonuses.map((bonus) => bonus.toNumber())).to.deep.equal(DEFAULT_BONUSES);
+    });
+
+    it("Grants the default bonus for a minted achievement", async () => {
+      const user = await fundedKeypair();
+      await initializeUserProfile(user);
+
+      await mintAchievement(user, { communityChampion: {} }, tree);
+
+      const profile = await program.account.userProfile.fetch(userProfilePda(user.publicKey));
+      expect(profile.reputationScore.toNumber()).to.equal(500);
+      expect(profile.achievementReputation.toNumber()).to.equal(500);
+      const record = await program.account.achievementRecord.fetch(achievementRecordPda(user.publicKey, 0)[0]);
+      expect(record.reputationBonus.toNumber()).to.equal(500);
+    });
+
+    it("Grants the configured bonus once the authority changes it", async () => {
+      const user = await fundedKeypair();
+      await initializeUserProfile(user);
+
+      await setAchievementBonus({ questMaster: {} }, 1234);
+      try {
+        await mintAchievement(user, { questMaster: {} }, tree);
+      } finally {
+        await setAchievementBonus({ questMaster: {} }, DEFAULT_BONUSES[3]);
+      }
+
+      const profile = await program.account.userProfile.fetch(userProfilePda(user.publicKey));
+      expect(profile.reputationScore.toNumber()).to.equal(1234);
+      expect(profile.lifetimeReputation.toNumber()).to.equal(1234);
+
+      // Later changes don't touch what was already minted
+      const record = await program.account.achievementRecord.fetch(achievementRecordPda(user.publicKey, 0)[0]);
+      expect(record.reputationBonus.toNumber()).to.equal(1234);
+    });
+
+    it("Only lets the registry authority change bonuses", async () => {
+      await expectError(setAchievementBonus({ firstPayment: {} }, 100000, await fundedKeypair()), "Unauthorized");
+    });
+  });
+
+  describe("streak freezes", () => {
+    const HOUR = 60 * 60;
+
+    let bank: Awaited<ReturnType<typeof startBankrun>>;
+
+    // Move to the next slot, for a fresh blockhash, and forward the clock
+    const advanceClock = async (seconds: number) => {
+      const clock = await bank.context.banksClient.getClock();
+      const slot = clock.slot + BigInt(1);
+      bank.context.warpToSlot(slot);
+      bank.context.setClock(
+        new Clock(
+          slot,
+          clock.epochStartTimestamp,
+          clock.epoch,
+          clock.leaderScheduleEpoch,
+          clock.unixTimestamp + BigInt(seconds)
+        )
+      );
+    };
+
+    const updateStreak = (user: Keypair) =>
+      bank.bankrunProgram.methods
+        .updateStreak()
+        .accounts({ userProfile: userProfilePda(user.publicKey), user: user.publicKey })
+        .signers([user])
+        .rpc();
+
+    const grantStreakFreeze = (user: PublicKey, count: number) =>
+      bank.bankrunProgram.methods
+        .grantStreakFreeze(count)
+        .accounts({
+          questRegistry: registryPda,
+          userProfile: userProfilePda(user),
+          authority: bank.context.payer.publicKey,
+        })
+        .rpc();
+
+    const profileOf = (user: Keypair) =>
+      bank.bankrunProgram.account.userProfile.fetch(userProfilePda(user.publicKey));
+
+    // A user on a two-day streak
+    const userOnStreak = async () => {
+      const user = bank.fundedUser();
+      await bank.initializeProfile(user);
+      await advanceClock(25 * HOUR);
+      await updateStreak(user);
+      await advanceClock(25 * HOUR);
+      await updateStreak(user);
+      expect((await profileOf(user)).currentStreak).to.equal(2);
+      return user;
+    };
+
+    before(async () => {
+      bank = await startBankrun();
+    });
+
+    it("Uses a freeze to keep the streak across one missed day", async () => {
+      const user = await userOnStreak();
+      await grantStreakFreeze(user.publicKey, 1);
+
+      await advanceClock(50 * HOUR);
+      await updateStreak(user);
+
+      // The freeze bridges the missed day without adding one to the streak
+      const profile = await profileOf(user);
+      expect(profile.currentStreak).to.equal(2);
+      expect(profile.longestStreak).to.equal(2);
+      expect(profile.streakFreezes).to.equal(0);
+
+      // The next day of activity extends it as usual
+      await advanceClock(25 * HOUR);
+      await updateStreak(user);
+      expect((await profileOf(user)).currentStreak).to.equal(3);
+    });
+
+    it("Resets the streak after a missed day without a freeze", async () => {
+      const user = await userOnStreak();
+
+      await advanceClock(50 * HOUR);
+      await updateStreak(user);
+
+      const profile = await profileOf(user);
+      expect(profile.currentStreak).to.equal(1);
+      expect(profile.longestStreak).to.equal(2);
+    });
+
+    it("Keeps the freeze when the absence is longer than one day", async () => {
+      const user = await userOnStreak();
+      await grantStreakFreeze(user.publicKey, 1);
+
+      await advanceClock(80 * HOUR);
+      await updateStreak(user);
+
+      const profile = await profileOf(user);
+      expect(profile.currentStreak).to.equal(1);
+      expect(profile.streakFreezes).to.equal(1);
+    });
+
+    it("Keeps the freeze when no day was missed", async () => {
+      const user = await userOnStreak();
+      await grantStreakFreeze(user.publicKey, 1);
+
+      await advanceClock(25 * HOUR);
+      await updateStreak(user);
+
+      const profile = await profileOf(user);
+      expect(profile.currentStreak).to.equal(3);
+      expect(profile.streakFreezes).to.equal(1);
+    });
+
+    it("Caps the freezes a user can hold", async () => {
+      const user = bank.fundedUser();
+      await bank.initializeProfile(user);
+      await grantStreakFreeze(user.publicKey, 3);
+
+      await advanceClock(1);
+      await expectRejected(grantStreakFreeze(user.publicKey, 1));
+      expect((await profileOf(user)).streakFreezes).to.equal(3);
+    });
+  });
+
+  describe("compressed achievement NFTs", () => {
+    const treeConfig = async (treeAuthority: PublicKey) =>
+      TreeConfig.fromAccountAddress(provider.connection, treeAuthority);
+
+    it("Mints an achievement leaf into the tree for the user", async () => {
+      const tree = await createAchievementTree(3, 8);
+      const user = await fundedKeypair();
+      await initializeUserProfile(user);
+
+      await mintAchievement(user, { firstPayment: {} }, tree);
+
+      const config = await treeConfig(tree.treeAuthority);
+      expect(Number(config.numMinted)).to.equal(1);
+      const merkleTree = await ConcurrentMerkleTreeAccount.fromAccountAddress(
+        provider.connection,
+        tree.merkleTree
+      );
+      expect(merkleTree.tree.rightMostPath.index).to.equal(1);
+
+      const profile = await program.account.userProfile.fetch(userProfilePda(user.publicKey));
+      expect(profile.achievementsMinted).to.equal(1);
+      expect(profile.achievementsCount).to.equal(1);
+      const record = await program.account.achievementRecord.fetch(achievementRecordPda(user.publicKey, 0)[0]);
+      expect(record.user.toString()).to.equal(user.publicKey.toString());
+      expect(record.achievementType).to.deep.equal({ firstPayment: {} });
+    });
+
+    it("Rejects minting into a full tree", async () => {
+      const tree = await createAchievementTree(3, 8);
+      const user = await fundedKeypair(5);
+      await initializeUserProfile(user);
+
+      // A depth-3 tree holds 8 leaves
+      for (let i = 0; i < 8; i++) {
+        await mintAchievement(user, { taskCompleter: {} }, tree);
+      }
+
+      await expectError(mintAchievement(user, { taskCompleter: {} }, tree), "AchievementTreeFull");
+      const profile = await program.account.userProfile.fetch(userProfilePda(user.publicKey));
+      expect(profile.achievementsMinted).to.equal(8);
+    });
+
+    it("Rejects a tree the registry is not the delegate of", async () => {
+      const tree = await createAchievementTree(3, 8, provider.wallet.publicKey);
+      const user = await fundedKeypair();
+      await initializeUserProfile(user);
+
+      await expectError(mintAchievement(user, { firstPayment: {} }, tree), "InvalidTreeDelegate");
+    });
+
+    it("Requires the registry authority to co-sign", async () => {
+      const tree = await createAchievementTree(3, 8);
+      const user = await fundedKeypair();
+      await initializeUserProfile(user);
+
+      await expectError(
+        mintAchievement(user, { firstPayment: {} }, tree, await fundedKeypair()),
+        "Unauthorized"
+      );
+    });
+  });
+});
@@ -4,7 +4,7 @@ use anchor_spl::associated_token::AssociatedToken;
 use mpl_token_metadata::instruction::{create_metadata_accounts_v3, create_master_edition_v3};
 use mpl_token_metadata::state::{DataV2, Creator};
 use solana_program::{
-    program::invoke,
+    program::{invoke, invoke_signed},
     system_instruction,
     native_token::LAMPORTS_PER_SOL,
 };
@@ -26,6 +26,8 @@ pub mod solanapay_payments {
         payment_config.total_volume = 0;
         payment_config.total_transactions = 0;
         payment_config.is_paused = false;
+        payment_config.dispute_timeout = DEFAULT_DISPUTE_TIMEOUT;
+        payment_config.expiry_window = DEFAULT_EXPIRY_WINDOW;
 
         emit!(ProgramInitialized {
             authority: payment_config.authority,
@@ -36,13 +38,24 @@ pub mod solanapay_payments {
         Ok(())
     }
 
-    /// Create escrow payment (SOL or SPL token)
+    /// Create escrow payment (SOL or SPL token).
+    /// With an `idempotency_key`, a marker PDA is created alongside the payment, so a
+    /// client retry with the same key fails instead of opening a duplicate escrow and
+    /// the marker points at the payment that already exists.
+    /// With a `release_condition`, the recipient is paid only once the designated oracle
+    /// attests the condition (see `release_conditional_payment`).
+    /// A `tip_amount` is escrowed alongside the principal and paid to the recipient in full
+    /// on release; the platform fee is computed on the principal only.
     pub fn create_payment(
         ctx: Context<CreatePayment>,
+        payment_id: u64,
         amount: u64,
         payment_type: PaymentType,
         description: String,
         auto_release_time: Option<i64>,
+        idempotency_key: Option<[u8; 32]>,
+        release_condition: Option<ReleaseCondition>,
+        tip_amount: Option<u64>,
     ) -> Result<()> {
         let payment = &mut ctx.accounts.payment;
         let config = &ctx.accounts.payment_config;
@@ -50,13 +63,35 @@ pub mod solanapay_payments {
         require!(!config.is_paused, ErrorCode::ProgramPaused);
         require!(amount > 0, ErrorCode::InvalidAmount);
         require!(description.len() <= 200, ErrorCode::DescriptionTooLong);
+        if let Some(condition) = &release_condition {
+            // Auto-release would bypass the oracle, so the two can't be combined
+            require!(
+                auto_release_time.is_none() && condition.deadline > Clock::get()?.unix_timestamp,
+                ErrorCode::InvalidReleaseCondition
+            );
+        }
 
-        // Calculate fees
+        match (idempotency_key, ctx.accounts.idempotency_marker.as_mut()) {
+            (Some(key), Some(marker)) => {
+                marker.payer = ctx.accounts.payer.key();
+                marker.idempotency_key = key;
+                marker.payment = payment.key();
+                marker.created_at = Clock::get()?.unix_timestamp;
+                marker.bump = ctx.bumps.idempotency_marker;
+            }
+            (None, None) => {}
+            _ => return err!(ErrorCode::IdempotencyMarkerMismatch),
+        }
+
+        // Calculate fees; the tip is fee-exempt
         let platform_fee = amount * config.platform_fee_rate / 10000;
         let net_amount = amount - platform_fee;
+        let tip_amount = tip_amount.unwrap_or(0);
+        let escrowed_amount = amount.checked_add(tip_amount).ok_or(ErrorCode::InvalidAmount)?;
 
         // Initialize payment account
         payment.payer = ctx.accounts.payer.key();
+        payment.payment_id = payment_id;
         payment.recipient = ctx.accounts.recipient.key();
         payment.amount = amount;
         payment.net_amount = net_amount;
@@ -67,6 +102,10 @@ pub mod solanapay_payments {
         payment.created_at = Clock::get()?.unix_timestamp;
         payment.auto_release_time = auto_release_time;
         payment.is_disputed = false;
+        payment.released_amount = 0;
+        payment.fee_charged = false;
+        payment.release_condition = release_condition;
+        payment.tip_amount = tip_amount;
 
         // Handle different payment types
         match payment_type {
@@ -75,7 +114,7 @@ pub mod solanapay_payments {
                 let transfer_instruction = system_instruction::transfer(
                     &ctx.accounts.payer.key(),
                     &payment.key(),
-                    amount,
+                    escrowed_amount,
                 );
                 invoke(
                     &transfer_instruction,
@@ -95,7 +134,7 @@ pub mod solanapay_payments {
                 };
                 let cpi_program = ctx.accounts.token_program.to_account_info();
                 let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-                token::transfer(cpi_ctx, amount)?;
+                token::transfer(cpi_ctx, escrowed_amount)?;
             }
         }
 
@@ -104,6 +143,7 @@ pub mod solanapay_payments {
             payer: payment.payer,
             recipient: payment.recipient,
             amount,
+            tip_amount,
             payment_type,
             timestamp: payment.created_at,
         });
@@ -111,8 +151,320 @@ pub mod solanapay_payments {
         Ok(())
     }
 
+    /// Create a reusable invoice template for a merchant
+    pub fn create_payment_template(
+        ctx: Context<CreatePaymentTemplate>,
+        template_id: String,
+        amount: u64,
+        payment_type: PaymentType,
+        mint: Option<Pubkey>,
+        description: String,
+        auto_release_delay: Option<i64>,
+    ) -> Result<()> {
+        require!(template_id.len() <= MAX_TEMPLATE_ID_LEN, ErrorCode::TemplateIdTooLong);
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(description.len() <= 200, ErrorCode::DescriptionTooLong);
+        require!(
+            auto_release_delay.map_or(true, |delay| delay > 0),
+            ErrorCode::InvalidAutoReleaseDelay
+        );
+        // SOL invoices have no mint, token invoices must name one
+        require!(
+            (payment_type == PaymentType::Sol) == mint.is_none(),
+            ErrorCode::InvalidTemplateMint
+        );
+
+        let template = &mut ctx.accounts.template;
+        template.merchant = ctx.accounts.merchant.key();
+        template.template_id = template_id;
+        template.amount = amount;
+        template.payment_type = payment_type.clone();
+        template.mint = mint;
+        template.description = description;
+        template.auto_release_delay = auto_release_delay;
+        template.payments_created = 0;
+        template.created_at = Clock::get()?.unix_timestamp;
+        template.bump = ctx.bumps.template;
+
+        emit!(PaymentTemplateCreated {
+            template: template.key(),
+            merchant: template.merchant,
+            template_id: template.template_id.clone(),
+            amount,
+            payment_type,
+            timestamp: template.created_at,
+        });
+
+        Ok(())
+    }
+
+    /// Create escrow payment from a merchant template, optionally overriding the amount
+    pub fn create_payment_from_template(
+        ctx: Context<CreatePaymentFromTemplate>,
+        payment_id: u64,
+        amount_override: Option<u64>,
+    ) -> Result<()> {
+        let payment = &mut ctx.accounts.payment;
+        let template = &mut ctx.accounts.template;
+        let config = &ctx.accounts.payment_config;
+
+        require!(!config.is_paused, ErrorCode::ProgramPaused);
+
+        let amount = amount_override.unwrap_or(template.amount);
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        let clock = Clock::get()?;
+
+        // Calculate fees
+        let platform_fee = amount * config.platform_fee_rate as u64 / 10000;
+        let net_amount = amount - platform_fee;
+
+        // Initialize payment account from template defaults
+        payment.payer = ctx.accounts.payer.key();
+        payment.payment_id = payment_id;
+        payment.recipient = template.merchant;
+        payment.amount = amount;
+        payment.net_amount = net_amount;
+        payment.platform_fee = platform_fee;
+        payment.payment_type = template.payment_type.clone();
+        payment.status = PaymentStatus::Pending;
+        payment.description = template.description.clone();
+        payment.created_at = clock.unix_timestamp;
+        payment.auto_release_time = template.auto_release_delay
+            .map(|delay| clock.unix_timestamp + delay);
+        payment.is_disputed = false;
+        payment.released_amount = 0;
+        payment.fee_charged = false;
+        payment.tip_amount = 0;
+
+        match template.payment_type {
+            PaymentType::Sol => {
+                // Transfer SOL to escrow
+                let transfer_instruction = system_instruction::transfer(
+                    &ctx.accounts.payer.key(),
+                    &payment.key(),
+                    amount,
+                );
+                invoke(
+                    &transfer_instruction,
+                    &[
+                        ctx.accounts.payer.to_account_info(),
+                        payment.to_account_info(),
+                        ctx.accounts.system_program.to_account_info(),
+                    ],
+                )?;
+            }
+            PaymentType::Usdc | PaymentType::Token => {
+                let payer_token_account = ctx.accounts.payer_token_account.as_ref()
+                    .ok_or(ErrorCode::MissingTokenAccount)?;
+                let escrow_token_account = ctx.accounts.escrow_token_account.as_ref()
+                    .ok_or(ErrorCode::MissingTokenAccount)?;
+                let token_program = ctx.accounts.token_program.as_ref()
+                    .ok_or(ErrorCode::MissingTokenAccount)?;
+
+                require!(
+                    Some(escrow_token_account.mint) == template.mint,
+                    ErrorCode::InvalidTemplateMint
+                );
+
+                // Transfer SPL tokens to escrow
+                let cpi_accounts = Transfer {
+                    from: payer_token_account.to_account_info(),
+                    to: escrow_token_account.to_account_info(),
+                    authority: ctx.accounts.payer.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new(token_program.to_account_info(), cpi_accounts);
+                token::transfer(cpi_ctx, amount)?;
+            }
+        }
+
+        template.payments_created += 1;
+
+        emit!(PaymentCreated {
+            payment_id: payment.key(),
+            payer: payment.payer,
+            recipient: payment.recipient,
+            amount,
+            tip_amount: 0,
+            payment_type: payment.payment_type.clone(),
+            timestamp: payment.created_at,
+        });
+
+        Ok(())
+    }
+
     /// Release payment from escrow
     pub fn release_payment(ctx: Context<ReleasePayment>) -> Result<()> {
+        let payment = &ctx.accounts.payment;
+
+        require!(
+            payment.status == PaymentStatus::Pending,
+            ErrorCode::InvalidPaymentStatus
+        );
+        require!(!payment.is_disputed, ErrorCode::PaymentDisputed);
+
+        // Check authorization (payer, recipient, or auto-release; a conditional payment can only
+        // be released early by its payer, otherwise it waits for the oracle)
+        let clock = Clock::get()?;
+        require!(
+            payment.is_release_authorized(ctx.accounts.authority.key(), clock.unix_timestamp),
+            ErrorCode::Unauthorized
+        );
+
+        complete_release(ctx)
+    }
+
+    /// Release a conditional payment on the designated oracle's attestation. The oracle signs
+    /// this instruction over the payment's condition id, before the condition deadline.
+    pub fn release_conditional_payment(
+        ctx: Context<ReleasePayment>,
+        condition_id: [u8; 32],
+    ) -> Result<()> {
+        let payment = &ctx.accounts.payment;
+        let clock = Clock::get()?;
+
+        require!(
+            payment.status == PaymentStatus::Pending,
+            ErrorCode::InvalidPaymentStatus
+        );
+        require!(!payment.is_disputed, ErrorCode::PaymentDisputed);
+
+        let condition = payment.release_condition.clone()
+            .ok_or(ErrorCode::NotConditionalPayment)?;
+        require!(
+            condition.oracle == ctx.accounts.authority.key(),
+            ErrorCode::UnauthorizedOracle
+        );
+        require!(condition.condition_id == condition_id, ErrorCode::ConditionMismatch);
+        require!(
+            clock.unix_timestamp <= condition.deadline,
+            ErrorCode::ConditionDeadlinePassed
+        );
+
+        emit!(ConditionAttested {
+            payment_id: payment.key(),
+            oracle: condition.oracle,
+            condition_id,
+            timestamp: clock.unix_timestamp,
+        });
+
+        complete_release(ctx)
+    }
+
+    /// Release several SOL escrows in one transaction. Each payment is passed in remaining
+    /// accounts as a `[payment, recipient]` pair; ineligible payments are skipped and reported
+    /// in `PaymentsBatchReleased` instead of failing the whole batch.
+    pub fn release_payments_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, ReleasePaymentsBatch<'info>>,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.payment_config;
+        let batch_size = ctx.remaining_accounts.len() / 2;
+
+        require!(
+            ctx.remaining_accounts.len() % 2 == 0,
+            ErrorCode::BatchAccountMismatch
+        );
+        require!(
+            batch_size > 0 && batch_size <= MAX_BATCH_RELEASE,
+            ErrorCode::InvalidBatchSize
+        );
+
+        let clock = Clock::get()?;
+        let authority = ctx.accounts.authority.key();
+        let mut results = Vec::with_capacity(batch_size);
+
+        for accounts in ctx.remaining_accounts.chunks(2) {
+            let payment_info = &accounts[0];
+            let recipient_info = &accounts[1];
+
+            require!(
+                payment_info.owner == ctx.program_id,
+                ErrorCode::BatchAccountMismatch
+            );
+            let mut payment = Payment::try_deserialize(&mut &payment_info.try_borrow_data()?[..])?;
+            require!(
+                recipient_info.key() == payment.recipient,
+                ErrorCode::RecipientAccountMismatch
+            );
+
+            // Same authorization rule as `release_payment`
+            let is_authorized = payment.is_release_authorized(authority, clock.unix_timestamp);
+
+            let skip_reason = if payment.is_disputed || payment.status == PaymentStatus::Disputed {
+                Some(BatchSkipReason::Disputed)
+            } else if payment.status != PaymentStatus::Pending {
+                Some(BatchSkipReason::NotPending)
+            } else if payment.payment_type != PaymentType::Sol {
+                Some(BatchSkipReason::UnsupportedPaymentType)
+            } else if !is_authorized {
+                Some(BatchSkipReason::NotReleasable)
+            } else {
+                None
+            };
+
+            if let Some(reason) = skip_reason {
+                results.push(BatchReleaseResult {
+                    payment_id: payment_info.key(),
+                    released_amount: 0,
+                    skip_reason: Some(reason),
+                });
+                continue;
+            }
+
+            let micro_reward = payment.amount / 1000;
+            config.micro_reward_pool += micro_reward;
+
+            let remaining = payment.net_amount - payment.released_amount;
+            let fee_due = if payment.fee_charged { 0 } else { payment.platform_fee };
+
+            payment.status = PaymentStatus::Completed;
+            payment.completed_at = Some(clock.unix_timestamp);
+            payment.released_amount = payment.net_amount;
+            payment.fee_charged = true;
+
+            **payment_info.try_borrow_mut_lamports()? -= remaining + payment.tip_amount + fee_due;
+            **recipient_info.try_borrow_mut_lamports()? += remaining + payment.tip_amount;
+            **ctx.accounts.treasury.to_account_info().try_borrow_mut_lamports()? += fee_due;
+
+            payment.try_serialize(&mut &mut payment_info.try_borrow_mut_data()?[..])?;
+
+            config.total_volume += payment.amount;
+            config.total_transactions += 1;
+
+            emit!(PaymentReleased {
+                version: PAYMENT_RELEASED_EVENT_VERSION,
+                payment_id: payment_info.key(),
+                recipient: payment.recipient,
+                amount: remaining,
+                tip_amount: payment.tip_amount,
+                gross_amount: payment.amount,
+                net_amount: payment.net_amount,
+                platform_fee: payment.platform_fee,
+                micro_reward,
+                timestamp: clock.unix_timestamp,
+            });
+
+            results.push(BatchReleaseResult {
+                payment_id: payment_info.key(),
+                released_amount: remaining + payment.tip_amount,
+                skip_reason: None,
+            });
+        }
+
+        let released_count = results.iter().filter(|r| r.skip_reason.is_none()).count() as u32;
+        emit!(PaymentsBatchReleased {
+            released_count,
+            skipped_count: results.len() as u32 - released_count,
+            results,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Release part of an escrowed payment, keeping the rest locked.
+    /// The platform fee is taken on the first partial release only.
+    pub fn release_partial_payment(ctx: Context<ReleasePayment>, amount: u64) -> Result<()> {
         let payment = &mut ctx.accounts.payment;
         let config = &mut ctx.accounts.payment_config;
 
@@ -120,105 +472,586 @@ pub mod solanapay_payments {
             payment.status == PaymentStatus::Pending,
             ErrorCode::InvalidPaymentStatus
         );
+        require!(!payment.is_disputed, ErrorCode::PaymentDisputed);
+        require!(
+            payment.payer == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(
+            amount <= payment.net_amount - payment.released_amount,
+            ErrorCode::ReleaseExceedsRemaining
+        );
 
-        // Check authorization (payer, recipient, or auto-release)
         let clock = Clock::get()?;
-        let is_authorized = payment.payer == ctx.accounts.authority.key() ||
-            payment.recipient == ctx.accounts.authority.key() ||
-            (payment.auto_release_time.is_some() && 
-             clock.unix_timestamp >= payment.auto_release_time.unwrap());
+        let fee_due = if payment.fee_charged { 0 } else { payment.platform_fee };
+
+        payment.released_amount += amount;
+        payment.fee_charged = true;
+        let fully_released = payment.released_amount == payment.net_amount;
+        if fully_released {
+            payment.status = PaymentStatus::Completed;
+            payment.completed_at = Some(clock.unix_timestamp);
+        }
+        // The tip goes out with the final release
+        let tip_released = if fully_released { payment.tip_amount } else { 0 };
+        let payout = amount + tip_released;
+
+        match payment.payment_type {
+            PaymentType::Sol => {
+                **payment.to_account_info().try_borrow_mut_lamports()? -= payout;
+                **ctx.accounts.recipient.to_account_info().try_borrow_mut_lamports()? += payout;
+
+                if fee_due > 0 {
+                    **payment.to_account_info().try_borrow_mut_lamports()? -= fee_due;
+                    **ctx.accounts.treasury.to_account_info().try_borrow_mut_lamports()? += fee_due;
+                }
+            }
+            PaymentType::Usdc | PaymentType::Token => {
+                let escrow_token_account = ctx.accounts.escrow_token_account.as_ref()
+                    .ok_or(ErrorCode::MissingTokenAccount)?;
+                let recipient_token_account = ctx.accounts.recipient_token_account.as_ref()
+                    .ok_or(ErrorCode::MissingTokenAccount)?;
+                let token_program = ctx.accounts.token_program.as_ref()
+                    .ok_or(ErrorCode::MissingTokenAccount)?;
+                require!(
+                    recipient_token_account.mint == escrow_token_account.mint,
+                    ErrorCode::TokenMintMismatch
+                );
+
+                let seeds = &[b"payment", payment.payer.as_ref(), &payment.payment_id.to_le_bytes(), &[ctx.bumps.payment]];
+                let signer = &[&seeds[..]];
+
+                let cpi_accounts = Transfer {
+                    from: escrow_token_account.to_account_info(),
+                    to: recipient_token_account.to_account_info(),
+                    authority: payment.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer);
+                token::transfer(cpi_ctx, payout)?;
+
+                if fee_due > 0 {
+                    let treasury_token_account = ctx.accounts.treasury_token_account.as_ref()
+                        .ok_or(ErrorCode::MissingTokenAccount)?;
+                    require!(
+                        treasury_token_account.mint == escrow_token_account.mint,
+                        ErrorCode::TokenMintMismatch
+                    );
+                    let cpi_accounts = Transfer {
+                        from: escrow_token_account.to_account_info(),
+                        to: treasury_token_account.to_account_info(),
+                        authority: payment.to_account_info(),
+                    };
+                    let cpi_ctx = CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer);
+                    token::transfer(cpi_ctx, fee_due)?;
+                }
+            }
+        }
+
+        // Count the payment in global stats once it is fully released
+        if fully_released {
+            config.micro_reward_pool += payment.amount / 1000;
+            config.total_volume += payment.amount;
+            config.total_transactions += 1;
+        }
+
+        emit!(PaymentPartiallyReleased {
+            payment_id: payment.key(),
+            recipient: payment.recipient,
+            amount,
+            tip_amount: tip_released,
+            released_total: payment.released_amount,
+            fee_charged: fee_due,
+            timestamp: clock.unix_timestamp,
+        });
 
-        require!(is_authorized, ErrorCode::Unauthorized);
+        Ok(())
+    }
 
-        // Calculate micro-rewards (0.1% of payment goes to reward pool)
-        let micro_reward = payment.amount / 1000;
-        config.micro_reward_pool += micro_reward;
+    /// Dispute a payment
+    pub fn dispute_payment(ctx: Context<DisputePayment>, reason: String) -> Result<()> {
+        let payment = &mut ctx.accounts.payment;
 
-        // Update payment status
-        payment.status = PaymentStatus::Completed;
+        require!(
+            payment.status == PaymentStatus::Pending,
+            ErrorCode::InvalidPaymentStatus
+        );
+        require!(
+            payment.payer == ctx.accounts.disputer.key() ||
+            payment.recipient == ctx.accounts.disputer.key(),
+            ErrorCode::Unauthorized
+        );
+        // A second dispute would reset disputed_at and push back the stale-dispute refund
+        require!(!payment.is_disputed, ErrorCode::PaymentDisputed);
+        require!(reason.len() <= 500, ErrorCode::ReasonTooLong);
+
+        payment.is_disputed = true;
+        payment.dispute_reason = Some(reason.clone());
+        payment.disputed_at = Some(Clock::get()?.unix_timestamp);
+
+        emit!(PaymentDisputed {
+            payment_id: payment.key(),
+            disputer: ctx.accounts.disputer.key(),
+            reason,
+            timestamp: payment.disputed_at.unwrap(),
+        });
+
+        Ok(())
+    }
+
+    /// Resolve a disputed payment (authority only): refund the escrow to the payer
+    /// without a fee, or release it to the recipient as a normal completion.
+    pub fn resolve_dispute(ctx: Context<ResolveDispute>, refund_to_payer: bool) -> Result<()> {
+        let payment = &mut ctx.accounts.payment;
+        let config = &mut ctx.accounts.payment_config;
+        let clock = Clock::get()?;
+
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(
+            payment.status == PaymentStatus::Pending && payment.is_disputed,
+            ErrorCode::PaymentNotDisputed
+        );
+
+        // Anything already released (and a fee charged by a partial release) has left escrow
+        let fee_paid = if payment.fee_charged { payment.platform_fee } else { 0 };
+        let remaining = payment.net_amount - payment.released_amount;
+        let (payer_amount, recipient_amount, fee_due) = if refund_to_payer {
+            (payment.amount - payment.released_amount - fee_paid + payment.tip_amount, 0, 0)
+        } else {
+            (0, remaining + payment.tip_amount, payment.platform_fee - fee_paid)
+        };
+
+        payment.is_disputed = false;
         payment.completed_at = Some(clock.unix_timestamp);
+        if refund_to_payer {
+            payment.status = PaymentStatus::Cancelled;
+        } else {
+            payment.status = PaymentStatus::Completed;
+            payment.released_amount = payment.net_amount;
+            payment.fee_charged = true;
+        }
 
-        // Transfer funds based on payment type
         match payment.payment_type {
             PaymentType::Sol => {
-                // Transfer SOL to recipient
-                **payment.to_account_info().try_borrow_mut_lamports()? -= payment.net_amount;
-                **ctx.accounts.recipient.to_account_info().try_borrow_mut_lamports()? += payment.net_amount;
+                **payment.to_account_info().try_borrow_mut_lamports()? -= payer_amount + recipient_amount + fee_due;
+                **ctx.accounts.payer.to_account_info().try_borrow_mut_lamports()? += payer_amount;
+                **ctx.accounts.recipient.to_account_info().try_borrow_mut_lamports()? += recipient_amount;
+                **ctx.accounts.treasury.to_account_info().try_borrow_mut_lamports()? += fee_due;
+            }
+            PaymentType::Usdc | PaymentType::Token => {
+                let escrow_token_account = ctx.accounts.escrow_token_account.as_ref()
+                    .ok_or(ErrorCode::MissingTokenAccount)?;
+                let token_program = ctx.accounts.token_program.as_ref()
+                    .ok_or(ErrorCode::MissingTokenAccount)?;
+
+                let seeds = &[b"payment", payment.payer.as_ref(), &payment.payment_id.to_le_bytes(), &[ctx.bumps.payment]];
+                let signer = &[&seeds[..]];
 
-                // Transfer platform fee to treasury
-                **payment.to_account_info().try_borrow_mut_lamports()? -= payment.platform_fee;
-                **ctx.accounts.treasury.to_account_info().try_borrow_mut_lamports()? += payment.platform_fee;
+                let transfers = [
+                    (payer_amount, ctx.accounts.payer_token_account.as_ref()),
+                    (recipient_amount, ctx.accounts.recipient_token_account.as_ref()),
+                    (fee_due, ctx.accounts.treasury_token_account.as_ref()),
+                ];
+                for (amount, destination) in transfers {
+                    if amount == 0 {
+                        continue;
+                    }
+                    let destination = destination.ok_or(ErrorCode::MissingTokenAccount)?;
+                    let cpi_accounts = Transfer {
+                        from: escrow_token_account.to_account_info(),
+                        to: destination.to_account_info(),
+                        authority: payment.to_account_info(),
+                    };
+                    let cpi_ctx = CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer);
+                    token::transfer(cpi_ctx, amount)?;
+                }
+            }
+        }
+
+        if !refund_to_payer {
+            config.micro_reward_pool += payment.amount / 1000;
+            config.total_volume += payment.amount;
+            config.total_transactions += 1;
+        }
+
+        emit!(DisputeResolved {
+            payment_id: payment.key(),
+            refunded_to_payer: refund_to_payer,
+            amount: payer_amount + recipient_amount,
+            resolved_by: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Refund a disputed payment to the payer once the dispute timeout has passed.
+    /// Permissionless so funds cannot stay frozen if the authority never resolves it.
+    pub fn resolve_stale_dispute(ctx: Context<ResolveStaleDispute>) -> Result<()> {
+        let payment = &mut ctx.accounts.payment;
+        let config = &ctx.accounts.payment_config;
+        let clock = Clock::get()?;
+
+        require!(
+            payment.status == PaymentStatus::Pending && payment.is_disputed,
+            ErrorCode::PaymentNotDisputed
+        );
+
+        let disputed_at = payment.disputed_at.ok_or(ErrorCode::PaymentNotDisputed)?;
+        require!(
+            clock.unix_timestamp >= disputed_at + config.dispute_timeout,
+            ErrorCode::DisputeTimeoutNotReached
+        );
+
+        // Refund everything still escrowed, tip included (partial releases and a charged fee
+        // have already left)
+        let fee_paid = if payment.fee_charged { payment.platform_fee } else { 0 };
+        let refund_amount = payment.amount - payment.released_amount - fee_paid + payment.tip_amount;
+
+        // Same outcome as resolve_dispute refunding the payer
+        payment.status = PaymentStatus::Cancelled;
+        payment.completed_at = Some(clock.unix_timestamp);
+
+        // Refund the remaining amount to the payer
+        match payment.payment_type {
+            PaymentType::Sol => {
+                **payment.to_account_info().try_borrow_mut_lamports()? -= refund_amount;
+                **ctx.accounts.payer.to_account_info().try_borrow_mut_lamports()? += refund_amount;
             }
             PaymentType::Usdc | PaymentType::Token => {
-                // Transfer tokens to recipient
+                let escrow_token_account = ctx.accounts.escrow_token_account.as_ref()
+                    .ok_or(ErrorCode::MissingTokenAccount)?;
+                let payer_token_account = ctx.accounts.payer_token_account.as_ref()
+                    .ok_or(ErrorCode::MissingTokenAccount)?;
+                let token_program = ctx.accounts.token_program.as_ref()
+                    .ok_or(ErrorCode::MissingTokenAccount)?;
+
                 let cpi_accounts = Transfer {
-                    from: ctx.accounts.escrow_token_account.to_account_info(),
-                    to: ctx.accounts.recipient_token_account.to_account_info(),
+                    from: escrow_token_account.to_account_info(),
+                    to: payer_token_account.to_account_info(),
                     authority: payment.to_account_info(),
                 };
-                let cpi_program = ctx.accounts.token_program.to_account_info();
-                let seeds = &[b"payment", payment.payer.as_ref(), &[ctx.bumps.payment]];
+                let seeds = &[b"payment", payment.payer.as_ref(), &payment.payment_id.to_le_bytes(), &[ctx.bumps.payment]];
                 let signer = &[&seeds[..]];
-                let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-                token::transfer(cpi_ctx, payment.net_amount)?;
+                let cpi_ctx = CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer);
+                token::transfer(cpi_ctx, refund_amount)?;
+            }
+        }
+
+        emit!(StaleDisputeRefunded {
+            payment_id: payment.key(),
+            payer: payment.payer,
+            amount: refund_amount,
+            resolved_by: ctx.accounts.caller.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Reclaim an escrow that was never released or disputed once the expiry window
+    /// has passed. The payer gets the remaining escrow back and the account's rent.
+    pub fn expire_payment(ctx: Context<ExpirePayment>) -> Result<()> {
+        let payment = &mut ctx.accounts.payment;
+        let config = &ctx.accounts.payment_config;
+        let clock = Clock::get()?;
+
+        require!(payment.status == PaymentStatus::Pending, ErrorCode::InvalidPaymentStatus);
+        require!(!payment.is_disputed, ErrorCode::PaymentDisputed);
+        // A conditional payment is refundable once its condition deadline passes unattested
+        let expires_at = match &payment.release_condition {
+            Some(condition) => condition.deadline,
+            None => payment.created_at + config.expiry_window,
+        };
+        require!(clock.unix_timestamp > expires_at, ErrorCode::PaymentNotExpired);
+
+        // Partial releases (and the fee they charged) have already left escrow; the tip is returned
+        let fee_paid = if payment.fee_charged { payment.platform_fee } else { 0 };
+        let reclaimed_amount = payment.amount - payment.released_amount - fee_paid + payment.tip_amount;
+
+        payment.status = PaymentStatus::Cancelled;
+        payment.completed_at = Some(clock.unix_timestamp);
+
+        match payment.payment_type {
+            PaymentType::Sol => {
+                **payment.to_account_info().try_borrow_mut_lamports()? -= reclaimed_amount;
+                **ctx.accounts.payer.to_account_info().try_borrow_mut_lamports()? += reclaimed_amount;
+            }
+            PaymentType::Usdc | PaymentType::Token => {
+                let escrow_token_account = ctx.accounts.escrow_token_account.as_ref()
+                    .ok_or(ErrorCode::MissingTokenAccount)?;
+                let payer_token_account = ctx.accounts.payer_token_account.as_ref()
+                    .ok_or(ErrorCode::MissingTokenAccount)?;
+                let token_program = ctx.accounts.token_program.as_ref()
+                    .ok_or(ErrorCode::MissingTokenAccount)?;
 
-                // Transfer platform fee to treasury
                 let cpi_accounts = Transfer {
-                    from: ctx.accounts.escrow_token_account.to_account_info(),
-                    to: ctx.accounts.treasury_token_account.to_account_info(),
+                    from: escrow_token_account.to_account_info(),
+                    to: payer_token_account.to_account_info(),
                     authority: payment.to_account_info(),
                 };
-                let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-                token::transfer(cpi_ctx, payment.platform_fee)?;
+                let seeds = &[b"payment", payment.payer.as_ref(), &payment.payment_id.to_le_bytes(), &[ctx.bumps.payment]];
+                let signer = &[&seeds[..]];
+                let cpi_ctx = CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer);
+                token::transfer(cpi_ctx, reclaimed_amount)?;
             }
         }
 
-        // Update global stats
-        config.total_volume += payment.amount;
-        config.total_transactions += 1;
+        // Whatever lamports remain are rent, returned to the payer when the account closes
+        let rent_returned = payment.to_account_info().lamports();
 
-        emit!(PaymentReleased {
+        emit!(PaymentExpired {
             payment_id: payment.key(),
-            recipient: payment.recipient,
-            amount: payment.net_amount,
+            payer: payment.payer,
+            reclaimed_amount,
+            rent_returned,
             timestamp: clock.unix_timestamp,
         });
 
         Ok(())
     }
 
-    /// Dispute a payment
-    pub fn dispute_payment(ctx: Context<DisputePayment>, reason: String) -> Result<()> {
-        let payment = &mut ctx.accounts.payment;
+    /// Update how long an undisputed escrow may sit before the payer can reclaim it
+    pub fn update_expiry_window(ctx: Context<UpdateConfig>, expiry_window: i64) -> Result<()> {
+        let config = &mut ctx.accounts.payment_config;
+
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(expiry_window > 0, ErrorCode::InvalidExpiryWindow);
+
+        config.expiry_window = expiry_window;
+
+        emit!(ExpiryWindowUpdated {
+            expiry_window,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Update how long a dispute may stay unresolved before it can be auto-refunded
+    pub fn update_dispute_timeout(ctx: Context<UpdateConfig>, dispute_timeout: i64) -> Result<()> {
+        let config = &mut ctx.accounts.payment_config;
+
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(dispute_timeout > 0, ErrorCode::InvalidDisputeTimeout);
+
+        config.dispute_timeout = dispute_timeout;
+
+        emit!(DisputeTimeoutUpdated {
+            dispute_timeout,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Update the platform fee and/or cashback rates (authority only); omitted rates are left unchanged
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        platform_fee_rate: Option<u16>,
+        cashback_rate: Option<u16>,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.payment_config;
+
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+
+        let old_platform_fee_rate = config.platform_fee_rate;
+        let old_cashback_rate = config.cashback_rate;
+
+        if let Some(rate) = platform_fee_rate {
+            // Capped well below 100% so a compromised authority can't confiscate payments
+            require!(rate <= MAX_PLATFORM_FEE_RATE, ErrorCode::InvalidFeeRate);
+            config.platform_fee_rate = rate;
+        }
+        if let Some(rate) = cashback_rate {
+            require!(rate <= 10000, ErrorCode::InvalidFeeRate);
+            config.cashback_rate = rate;
+        }
+
+        emit!(ConfigUpdated {
+            old_platform_fee_rate,
+            new_platform_fee_rate: config.platform_fee_rate,
+            old_cashback_rate,
+            new_cashback_rate: config.cashback_rate,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Create a recurring SOL subscription billed every `interval_seconds`.
+    /// The payer pre-funds the subscription account with `fund_subscription`.
+    pub fn create_subscription(
+        ctx: Context<CreateSubscription>,
+        amount: u64,
+        interval_seconds: i64,
+        max_cycles: u32,
+    ) -> Result<()> {
+        let subscription = &mut ctx.accounts.subscription;
+        let config = &ctx.accounts.payment_config;
+
+        require!(!config.is_paused, ErrorCode::ProgramPaused);
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(interval_seconds > 0, ErrorCode::InvalidSubscriptionInterval);
+        require!(max_cycles > 0, ErrorCode::InvalidSubscriptionCycles);
+
+        let now = Clock::get()?.unix_timestamp;
+
+        subscription.payer = ctx.accounts.payer.key();
+        subscription.recipient = ctx.accounts.recipient.key();
+        subscription.amount = amount;
+        subscription.interval_seconds = interval_seconds;
+        subscription.next_charge_at = now;
+        subscription.cycles_remaining = max_cycles;
+        subscription.cycles_charged = 0;
+        subscription.is_active = true;
+        subscription.created_at = now;
+        subscription.bump = ctx.bumps.subscription;
+
+        emit!(SubscriptionCreated {
+            subscription_id: subscription.key(),
+            payer: subscription.payer,
+            recipient: subscription.recipient,
+            amount,
+            interval_seconds,
+            max_cycles,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Add SOL to a subscription's escrow to cover future charges
+    pub fn fund_subscription(ctx: Context<FundSubscription>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(ctx.accounts.subscription.is_active, ErrorCode::SubscriptionInactive);
+
+        let transfer_instruction = system_instruction::transfer(
+            &ctx.accounts.payer.key(),
+            &ctx.accounts.subscription.key(),
+            amount,
+        );
+        invoke(
+            &transfer_instruction,
+            &[
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.subscription.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Charge one due subscription cycle. Permissionless so anyone can crank it.
+    pub fn charge_subscription(ctx: Context<ChargeSubscription>) -> Result<()> {
+        let subscription = &mut ctx.accounts.subscription;
+        let config = &mut ctx.accounts.payment_config;
+        let clock = Clock::get()?;
+
+        require!(!config.is_paused, ErrorCode::ProgramPaused);
+        require!(
+            subscription.is_active && subscription.cycles_remaining > 0,
+            ErrorCode::SubscriptionInactive
+        );
+        require!(
+            clock.unix_timestamp >= subscription.next_charge_at,
+            ErrorCode::SubscriptionNotDue
+        );
+
+        // Never dip into the rent-exempt reserve; an underfunded cycle fails as a whole
+        let subscription_info = subscription.to_account_info();
+        let rent_reserve = Rent::get()?.minimum_balance(subscription_info.data_len());
+        let available = subscription_info.lamports().saturating_sub(rent_reserve);
+        require!(available >= subscription.amount, ErrorCode::SubscriptionUnderfunded);
+
+        let platform_fee = subscription.amount * config.platform_fee_rate as u64 / 10000;
+        let net_amount = subscription.amount - platform_fee;
+
+        **subscription_info.try_borrow_mut_lamports()? -= subscription.amount;
+        **ctx.accounts.recipient.to_account_info().try_borrow_mut_lamports()? += net_amount;
+        **ctx.accounts.treasury.to_account_info().try_borrow_mut_lamports()? += platform_fee;
+
+        subscription.cycles_charged += 1;
+        subscription.cycles_remaining -= 1;
+        subscription.next_charge_at += subscription.interval_seconds;
+        if subscription.cycles_remaining == 0 {
+            subscription.is_active = false;
+        }
+
+        config.total_volume += subscription.amount;
+        config.total_transactions += 1;
+
+        emit!(SubscriptionCharged {
+            subscription_id: subscription.key(),
+            recipient: subscription.recipient,
+            amount: net_amount,
+            cycles_remaining: subscription.cycles_remaining,
+            next_charge_at: subscription.next_charge_at,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Cancel a subscription; unspent escrow and rent return to the payer
+    pub fn cancel_subscription(ctx: Context<CancelSubscription>) -> Result<()> {
+        let subscription = &ctx.accounts.subscription;
+
+        emit!(SubscriptionCancelled {
+            subscription_id: subscription.key(),
+            payer: subscription.payer,
+            cycles_charged: subscription.cycles_charged,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
 
-        require!(
-            payment.status == PaymentStatus::Pending,
-            ErrorCode::InvalidPaymentStatus
-        );
-        require!(
-            payment.payer == ctx.accounts.disputer.key() ||
-            payment.recipient == ctx.accounts.disputer.key(),
-            ErrorCode::Unauthorized
-        );
-        require!(reason.len() <= 500, ErrorCode::ReasonTooLong);
+    /// Deposit SOL into the reward vault that backs `micro_reward_pool`
+    pub fn fund_reward_vault(ctx: Context<FundRewardVault>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
 
-        payment.is_disputed = true;
-        payment.dispute_reason = Some(reason.clone());
-        payment.disputed_at = Some(Clock::get()?.unix_timestamp);
+        let transfer_instruction = system_instruction::transfer(
+            &ctx.accounts.funder.key(),
+            &ctx.accounts.reward_vault.key(),
+            amount,
+        );
+        invoke(
+            &transfer_instruction,
+            &[
+                ctx.accounts.funder.to_account_info(),
+                ctx.accounts.reward_vault.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
 
-        emit!(PaymentDisputed {
-            payment_id: payment.key(),
-            disputer: ctx.accounts.disputer.key(),
-            reason,
-            timestamp: payment.disputed_at.unwrap(),
+        emit!(RewardVaultFunded {
+            funder: ctx.accounts.funder.key(),
+            amount,
+            vault_balance: ctx.accounts.reward_vault.lamports(),
+            timestamp: Clock::get()?.unix_timestamp,
         });
 
         Ok(())
     }
 
-    /// Distribute micro-rewards to users
-    pub fn distribute_micro_rewards(
-        ctx: Context<DistributeMicroRewards>,
+    /// Pay micro rewards in SOL from the reward vault. Recipient accounts are passed
+    /// as remaining accounts, in the same order as `recipients`.
+    pub fn distribute_micro_rewards<'info>(
+        ctx: Context<'_, '_, '_, 'info, DistributeMicroRewards<'info>>,
         recipients: Vec<Pubkey>,
         amounts: Vec<u64>,
     ) -> Result<()> {
@@ -230,13 +1063,50 @@ pub mod solanapay_payments {
         );
         require!(recipients.len() == amounts.len(), ErrorCode::MismatchedArrays);
         require!(recipients.len() <= 10, ErrorCode::TooManyRecipients);
+        require!(
+            ctx.remaining_accounts.len() == recipients.len(),
+            ErrorCode::RecipientAccountMismatch
+        );
 
         let total_distribution: u64 = amounts.iter().sum();
         require!(
             total_distribution <= config.micro_reward_pool,
             ErrorCode::InsufficientRewardPool
         );
+        require!(
+            total_distribution <= ctx.accounts.reward_vault.lamports(),
+            ErrorCode::RewardVaultUnderfunded
+        );
+
+        let vault_seeds: &[&[u8]] = &[b"reward_vault", &[ctx.bumps.reward_vault]];
+        for ((recipient, amount), recipient_account) in recipients
+            .iter()
+            .zip(amounts.iter())
+            .zip(ctx.remaining_accounts.iter())
+        {
+            require!(
+                recipient_account.key() == *recipient,
+                ErrorCode::RecipientAccountMismatch
+            );
+
+            let transfer_instruction = system_instruction::transfer(
+                &ctx.accounts.reward_vault.key(),
+                recipient,
+                *amount,
+            );
+            invoke_signed(
+                &transfer_instruction,
+                &[
+                    ctx.accounts.reward_vault.to_account_info(),
+                    recipient_account.clone(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+                &[vault_seeds],
+            )?;
+        }
 
+        // Only reached once every transfer succeeded; any failure aborts the whole
+        // instruction, so the pool is never decremented for rewards that weren't paid
         config.micro_reward_pool -= total_distribution;
 
         emit!(MicroRewardsDistributed {
@@ -359,36 +1229,452 @@ pub mod solanapay_payments {
     }
 }
 
+// Pay out everything still escrowed to the recipient and any outstanding fee to the treasury;
+// callers have already authorized the release
+fn complete_release(ctx: Context<ReleasePayment>) -> Result<()> {
+    let payment = &mut ctx.accounts.payment;
+    let config = &mut ctx.accounts.payment_config;
+    let clock = Clock::get()?;
+
+    // Calculate micro-rewards (0.1% of payment goes to reward pool)
+    let micro_reward = payment.amount / 1000;
+    config.micro_reward_pool += micro_reward;
+
+    // Release whatever is still locked; the fee may already have been taken by a partial release.
+    // The tip is paid on top, fee-exempt.
+    let remaining = payment.net_amount - payment.released_amount;
+    let fee_due = if payment.fee_charged { 0 } else { payment.platform_fee };
+    let payout = remaining + payment.tip_amount;
+
+    // Update payment status
+    payment.status = PaymentStatus::Completed;
+    payment.completed_at = Some(clock.unix_timestamp);
+    payment.released_amount = payment.net_amount;
+    payment.fee_charged = true;
+
+    // Transfer funds based on payment type
+    match payment.payment_type {
+        PaymentType::Sol => {
+            // Transfer SOL to recipient
+            **payment.to_account_info().try_borrow_mut_lamports()? -= payout;
+            **ctx.accounts.recipient.to_account_info().try_borrow_mut_lamports()? += payout;
+
+            // Transfer platform fee to treasury
+            **payment.to_account_info().try_borrow_mut_lamports()? -= fee_due;
+            **ctx.accounts.treasury.to_account_info().try_borrow_mut_lamports()? += fee_due;
+        }
+        PaymentType::Usdc | PaymentType::Token => {
+            // Transfer tokens to recipient
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                to: ctx.accounts.recipient_token_account.to_account_info(),
+                authority: payment.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let seeds = &[b"payment", payment.payer.as_ref(), &payment.payment_id.to_le_bytes(), &[ctx.bumps.payment]];
+            let signer = &[&seeds[..]];
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            token::transfer(cpi_ctx, payout)?;
+
+            // Transfer platform fee to treasury
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                to: ctx.accounts.treasury_token_account.to_account_info(),
+                authority: payment.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            if fee_due > 0 {
+                token::transfer(cpi_ctx, fee_due)?;
+            }
+        }
+    }
+
+    // Update global stats
+    config.total_volume += payment.amount;
+    config.total_transactions += 1;
+
+    emit!(PaymentReleased {
+        version: PAYMENT_RELEASED_EVENT_VERSION,
+        payment_id: payment.key(),
+        recipient: payment.recipient,
+        amount: remaining,
+        tip_amount: payment.tip_amount,
+        gross_amount: payment.amount,
+        net_amount: payment.net_amount,
+        platform_fee: payment.platform_fee,
+        micro_reward,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PaymentConfig::INIT_SPACE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub payment_config: Account<'info, PaymentConfig>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Treasury account for collecting fees
+    pub treasury: AccountInfo<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(
+    payment_id: u64,
+    amount: u64,
+    payment_type: PaymentType,
+    description: String,
+    auto_release_time: Option<i64>,
+    idempotency_key: Option<[u8; 32]>,
+    release_condition: Option<ReleaseCondition>,
+    tip_amount: Option<u64>
+)]
+pub struct CreatePayment<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Payment::INIT_SPACE,
+        seeds = [b"payment", payer.key().as_ref(), payment_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub payment: Account<'info, Payment>,
+    
+    // Required when an idempotency key is supplied; `init` rejects a reused key
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + IdempotencyMarker::INIT_SPACE,
+        seeds = [b"idempotency", payer.key().as_ref(), idempotency_key.unwrap_or_default().as_ref()],
+        bump
+    )]
+    pub idempotency_marker: Option<Account<'info, IdempotencyMarker>>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub payment_config: Account<'info, PaymentConfig>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    /// CHECK: Payment recipient
+    pub recipient: AccountInfo<'info>,
+    
+    // Optional token accounts for SPL token payments
+    #[account(mut)]
+    pub payer_token_account: Option<Account<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub escrow_token_account: Option<Account<'info, TokenAccount>>,
+    
+    pub token_program: Option<Program<'info, Token>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(template_id: String)]
+pub struct CreatePaymentTemplate<'info> {
+    #[account(
+        init,
+        payer = merchant,
+        space = 8 + PaymentTemplate::INIT_SPACE,
+        seeds = [b"template", merchant.key().as_ref(), template_id.as_bytes()],
+        bump
+    )]
+    pub template: Account<'info, PaymentTemplate>,
+    
+    #[account(mut)]
+    pub merchant: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(payment_id: u64)]
+pub struct CreatePaymentFromTemplate<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Payment::INIT_SPACE,
+        seeds = [b"payment", payer.key().as_ref(), payment_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub payment: Account<'info, Payment>,
+    
+    #[account(
+        mut,
+        seeds = [b"template", template.merchant.as_ref(), template.template_id.as_bytes()],
+        bump = template.bump
+    )]
+    pub template: Account<'info, PaymentTemplate>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub payment_config: Account<'info, PaymentConfig>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    // Optional token accounts for SPL token payments
+    #[account(mut)]
+    pub payer_token_account: Option<Account<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub escrow_token_account: Option<Account<'info, TokenAccount>>,
+    
+    pub token_program: Option<Program<'info, Token>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReleasePayment<'info> {
+    #[account(
+        mut,
+        seeds = [b"payment", payment.payer.as_ref(), payment.payment_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub payment: Account<'info, Payment>,
+    
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub payment_config: Account<'info, PaymentConfig>,
+    
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        constraint = recipient.key() == payment.recipient @ ErrorCode::Unauthorized
+    )]
+    /// CHECK: Payment recipient
+    pub recipient: AccountInfo<'info>,
+    
+    #[account(mut, address = payment_config.treasury)]
+    /// CHECK: Treasury account
+    pub treasury: AccountInfo<'info>,
+    
+    // Optional token accounts for SPL token payments
+    #[account(
+        mut,
+        constraint = escrow_token_account.owner == payment.key() @ ErrorCode::Unauthorized
+    )]
+    pub escrow_token_account: Option<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        constraint = recipient_token_account.owner == payment.recipient @ ErrorCode::Unauthorized
+    )]
+    pub recipient_token_account: Option<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        constraint = treasury_token_account.owner == payment_config.treasury @ ErrorCode::Unauthorized
+    )]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+    
+    pub token_program: Option<Program<'info, Token>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReleasePaymentsBatch<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub payment_config: Account<'info, PaymentConfig>,
+    
+    pub authority: Signer<'info>,
+    
+    #[account(mut, address = payment_config.treasury)]
+    /// CHECK: Treasury account
+    pub treasury: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DisputePayment<'info> {
+    #[account(
+        mut,
+        seeds = [b"payment", payment.payer.as_ref(), payment.payment_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub payment: Account<'info, Payment>,
+    
+    pub disputer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveStaleDispute<'info> {
+    #[account(
+        mut,
+        seeds = [b"payment", payment.payer.as_ref(), payment.payment_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub payment: Account<'info, Payment>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub payment_config: Account<'info, PaymentConfig>,
+    
+    #[account(
+        mut,
+        constraint = payer.key() == payment.payer @ ErrorCode::Unauthorized
+    )]
+    /// CHECK: Original payer receiving the refund
+    pub payer: AccountInfo<'info>,
+    
+    pub caller: Signer<'info>,
+    
+    // Optional token accounts for SPL token payments
+    #[account(mut)]
+    pub escrow_token_account: Option<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        constraint = payer_token_account.owner == payment.payer @ ErrorCode::Unauthorized
+    )]
+    pub payer_token_account: Option<Account<'info, TokenAccount>>,
+    
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+#[derive(Accounts)]
+pub struct ExpirePayment<'info> {
+    #[account(
+        mut,
+        seeds = [b"payment", payment.payer.as_ref(), payment.payment_id.to_le_bytes().as_ref()],
+        bump,
+        close = payer
+    )]
+    pub payment: Account<'info, Payment>,
+    
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub payment_config: Account<'info, PaymentConfig>,
+    
+    #[account(
+        mut,
+        constraint = payer.key() == payment.payer @ ErrorCode::Unauthorized
+    )]
+    pub payer: Signer<'info>,
+    
+    // Optional token accounts for SPL token payments
+    #[account(mut)]
+    pub escrow_token_account: Option<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        constraint = payer_token_account.owner == payment.payer @ ErrorCode::Unauthorized
+    )]
+    pub payer_token_account: Option<Account<'info, TokenAccount>>,
+    
+    pub token_program: Option<Program<'info, Token>>,
+}
+
 #[derive(Accounts)]
-pub struct Initialize<'info> {
+pub struct ResolveDispute<'info> {
     #[account(
-        init,
-        payer = authority,
-        space = 8 + PaymentConfig::INIT_SPACE,
+        mut,
+        seeds = [b"payment", payment.payer.as_ref(), payment.payment_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub payment: Account<'info, Payment>,
+    
+    #[account(
+        mut,
         seeds = [b"config"],
         bump
     )]
     pub payment_config: Account<'info, PaymentConfig>,
     
-    #[account(mut)]
     pub authority: Signer<'info>,
     
-    /// CHECK: Treasury account for collecting fees
+    #[account(
+        mut,
+        constraint = payer.key() == payment.payer @ ErrorCode::Unauthorized
+    )]
+    /// CHECK: Original payer
+    pub payer: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        constraint = recipient.key() == payment.recipient @ ErrorCode::Unauthorized
+    )]
+    /// CHECK: Payment recipient
+    pub recipient: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        constraint = treasury.key() == payment_config.treasury @ ErrorCode::Unauthorized
+    )]
+    /// CHECK: Treasury account
     pub treasury: AccountInfo<'info>,
     
-    pub system_program: Program<'info, System>,
+    // Optional token accounts for SPL token payments
+    #[account(mut)]
+    pub escrow_token_account: Option<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        constraint = payer_token_account.owner == payment.payer @ ErrorCode::Unauthorized
+    )]
+    pub payer_token_account: Option<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        constraint = recipient_token_account.owner == payment.recipient @ ErrorCode::Unauthorized
+    )]
+    pub recipient_token_account: Option<Account<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+    
+    pub token_program: Option<Program<'info, Token>>,
 }
 
 #[derive(Accounts)]
-pub struct CreatePayment<'info> {
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub payment_config: Account<'info, PaymentConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateSubscription<'info> {
     #[account(
         init,
         payer = payer,
-        space = 8 + Payment::INIT_SPACE,
-        seeds = [b"payment", payer.key().as_ref()],
+        space = 8 + Subscription::INIT_SPACE,
+        seeds = [b"subscription", payer.key().as_ref(), recipient.key().as_ref()],
         bump
     )]
-    pub payment: Account<'info, Payment>,
+    pub subscription: Account<'info, Subscription>,
     
     #[account(
         seeds = [b"config"],
@@ -399,28 +1685,37 @@ pub struct CreatePayment<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     
-    /// CHECK: Payment recipient
+    /// CHECK: Subscription recipient
     pub recipient: AccountInfo<'info>,
     
-    // Optional token accounts for SPL token payments
-    #[account(mut)]
-    pub payer_token_account: Option<Account<'info, TokenAccount>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundSubscription<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription", subscription.payer.as_ref(), subscription.recipient.as_ref()],
+        bump = subscription.bump,
+        has_one = payer @ ErrorCode::Unauthorized
+    )]
+    pub subscription: Account<'info, Subscription>,
     
     #[account(mut)]
-    pub escrow_token_account: Option<Account<'info, TokenAccount>>,
+    pub payer: Signer<'info>,
     
-    pub token_program: Option<Program<'info, Token>>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct ReleasePayment<'info> {
+pub struct ChargeSubscription<'info> {
     #[account(
         mut,
-        seeds = [b"payment", payment.payer.as_ref()],
-        bump
+        seeds = [b"subscription", subscription.payer.as_ref(), subscription.recipient.as_ref()],
+        bump = subscription.bump,
+        has_one = recipient @ ErrorCode::Unauthorized
     )]
-    pub payment: Account<'info, Payment>,
+    pub subscription: Account<'info, Subscription>,
     
     #[account(
         mut,
@@ -429,40 +1724,48 @@ pub struct ReleasePayment<'info> {
     )]
     pub payment_config: Account<'info, PaymentConfig>,
     
-    pub authority: Signer<'info>,
-    
     #[account(mut)]
-    /// CHECK: Payment recipient
+    /// CHECK: Subscription recipient, checked against the subscription
     pub recipient: AccountInfo<'info>,
     
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = treasury.key() == payment_config.treasury @ ErrorCode::Unauthorized
+    )]
     /// CHECK: Treasury account
     pub treasury: AccountInfo<'info>,
     
-    // Optional token accounts for SPL token payments
-    #[account(mut)]
-    pub escrow_token_account: Option<Account<'info, TokenAccount>>,
-    
-    #[account(mut)]
-    pub recipient_token_account: Option<Account<'info, TokenAccount>>,
+    pub cranker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelSubscription<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription", subscription.payer.as_ref(), subscription.recipient.as_ref()],
+        bump = subscription.bump,
+        has_one = payer @ ErrorCode::Unauthorized,
+        close = payer
+    )]
+    pub subscription: Account<'info, Subscription>,
     
     #[account(mut)]
-    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
-    
-    pub token_program: Option<Program<'info, Token>>,
-    pub system_program: Program<'info, System>,
+    pub payer: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct DisputePayment<'info> {
+pub struct FundRewardVault<'info> {
     #[account(
         mut,
-        seeds = [b"payment", payment.payer.as_ref()],
+        seeds = [b"reward_vault"],
         bump
     )]
-    pub payment: Account<'info, Payment>,
-    
-    pub disputer: Signer<'info>,
+    pub reward_vault: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
@@ -475,6 +1778,15 @@ pub struct DistributeMicroRewards<'info> {
     pub payment_config: Account<'info, PaymentConfig>,
     
     pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward_vault"],
+        bump
+    )]
+    pub reward_vault: SystemAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
@@ -538,15 +1850,33 @@ pub struct PaymentConfig {
     pub total_volume: u64,           // Total payment volume processed
     pub total_transactions: u64,     // Total number of transactions
     pub is_paused: bool,             // Emergency pause flag
+    pub dispute_timeout: i64,        // Seconds before an unresolved dispute can be auto-refunded
+    pub expiry_window: i64,          // Seconds before an undisputed escrow can be reclaimed by the payer
 }
 
 impl PaymentConfig {
-    pub const INIT_SPACE: usize = 32 + 32 + 2 + 2 + 8 + 8 + 8 + 1;
+    pub const INIT_SPACE: usize = 32 + 32 + 2 + 2 + 8 + 8 + 8 + 1 + 8 + 8;
 }
 
+/// Default dispute timeout (14 days)
+pub const DEFAULT_DISPUTE_TIMEOUT: i64 = 14 * 24 * 60 * 60;
+
+/// Default escrow expiry window (30 days)
+pub const DEFAULT_EXPIRY_WINDOW: i64 = 30 * 24 * 60 * 60;
+
+/// Maximum platform fee the authority can set (10%)
+pub const MAX_PLATFORM_FEE_RATE: u16 = 1000;
+
+/// Maximum number of payments `release_payments_batch` will process
+pub const MAX_BATCH_RELEASE: usize = 10;
+
+/// Schema version of `PaymentReleased`; bumped to 2 for the fee breakdown and 3 for the tip
+pub const PAYMENT_RELEASED_EVENT_VERSION: u8 = 3;
+
 #[account]
 pub struct Payment {
     pub payer: Pubkey,
+    pub payment_id: u64,             // Payer-chosen nonce so one payer can hold many escrows
     pub recipient: Pubkey,
     pub amount: u64,
     pub net_amount: u64,
@@ -560,10 +1890,88 @@ pub struct Payment {
     pub is_disputed: bool,
     pub dispute_reason: Option<String>,
     pub disputed_at: Option<i64>,
+    pub released_amount: u64,        // Portion of net_amount already paid to the recipient
+    pub fee_charged: bool,           // Platform fee already sent to treasury
+    pub release_condition: Option<ReleaseCondition>, // Oracle attestation required to pay out
+    pub tip_amount: u64,             // Escrowed on top of `amount`, paid to the recipient fee-free
 }
 
 impl Payment {
-    pub const INIT_SPACE: usize = 32 + 32 + 8 + 8 + 8 + 1 + 1 + 200 + 8 + 9 + 9 + 1 + 500 + 9;
+    pub const INIT_SPACE: usize = 32 + 8 + 32 + 8 + 8 + 8 + 1 + 1 + 200 + 8 + 9 + 9 + 1 + 500 + 9 + 8 + 1
+        + 1 + ReleaseCondition::SIZE + 8;
+
+    /// Whether `authority` may release the payment at `now`: the payer always can; the recipient
+    /// or auto-release only when no oracle condition is attached
+    pub fn is_release_authorized(&self, authority: Pubkey, now: i64) -> bool {
+        self.payer == authority || (self.release_condition.is_none() && (
+            self.recipient == authority ||
+            self.auto_release_time.map_or(false, |release_time| now >= release_time)
+        ))
+    }
+}
+
+/// External fact a conditional payment waits on, attested by a designated oracle
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub struct ReleaseCondition {
+    pub oracle: Pubkey,              // Only this key can attest the condition
+    pub condition_id: [u8; 32],      // e.g. hash of a delivery/shipment identifier
+    pub deadline: i64,               // Unattested after this, the payer can reclaim the escrow
+}
+
+impl ReleaseCondition {
+    pub const SIZE: usize = 32 + 32 + 8;
+}
+
+#[account]
+pub struct PaymentTemplate {
+    pub merchant: Pubkey,
+    pub template_id: String,
+    pub amount: u64,                     // Default invoice amount
+    pub payment_type: PaymentType,
+    pub mint: Option<Pubkey>,            // Required token mint (None for SOL)
+    pub description: String,
+    pub auto_release_delay: Option<i64>, // Seconds after creation before auto-release
+    pub payments_created: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl PaymentTemplate {
+    pub const INIT_SPACE: usize = 32 + (4 + MAX_TEMPLATE_ID_LEN) + 8 + 1 + 33 + (4 + 200) + 9 + 8 + 8 + 1;
+}
+
+/// Maximum length of a merchant template identifier
+pub const MAX_TEMPLATE_ID_LEN: usize = 32;
+
+#[account]
+pub struct IdempotencyMarker {
+    pub payer: Pubkey,
+    pub idempotency_key: [u8; 32],
+    pub payment: Pubkey,             // Payment created under this key
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl IdempotencyMarker {
+    pub const INIT_SPACE: usize = 32 + 32 + 32 + 8 + 1;
+}
+
+#[account]
+pub struct Subscription {
+    pub payer: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,                 // Lamports charged per cycle
+    pub interval_seconds: i64,
+    pub next_charge_at: i64,
+    pub cycles_remaining: u32,
+    pub cycles_charged: u32,
+    pub is_active: bool,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl Subscription {
+    pub const INIT_SPACE: usize = 32 + 32 + 8 + 8 + 8 + 4 + 4 + 1 + 8 + 1;
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
@@ -581,6 +1989,21 @@ pub enum PaymentStatus {
     Cancelled,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum BatchSkipReason {
+    NotPending,
+    Disputed,
+    NotReleasable,              // Caller can't release yet (see `Payment::is_release_authorized`)
+    UnsupportedPaymentType,     // Token escrows must go through `release_payment`
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BatchReleaseResult {
+    pub payment_id: Pubkey,
+    pub released_amount: u64,
+    pub skip_reason: Option<BatchSkipReason>,
+}
+
 #[event]
 pub struct ProgramInitialized {
     pub authority: Pubkey,
@@ -594,15 +2017,61 @@ pub struct PaymentCreated {
     pub payer: Pubkey,
     pub recipient: Pubkey,
     pub amount: u64,
+    pub tip_amount: u64,
+    pub payment_type: PaymentType,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PaymentTemplateCreated {
+    pub template: Pubkey,
+    pub merchant: Pubkey,
+    pub template_id: String,
+    pub amount: u64,
     pub payment_type: PaymentType,
     pub timestamp: i64,
 }
 
+/// Fee breakdown satisfies `net_amount + platform_fee == gross_amount`; `micro_reward`
+/// is the share credited to the micro-reward pool on release
 #[event]
 pub struct PaymentReleased {
+    pub version: u8,
+    pub payment_id: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,          // Released to the recipient by this instruction
+    pub tip_amount: u64,      // Paid to the recipient on top of `amount`, outside the fee breakdown
+    pub gross_amount: u64,
+    pub net_amount: u64,
+    pub platform_fee: u64,
+    pub micro_reward: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConditionAttested {
+    pub payment_id: Pubkey,
+    pub oracle: Pubkey,
+    pub condition_id: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PaymentsBatchReleased {
+    pub released_count: u32,
+    pub skipped_count: u32,
+    pub results: Vec<BatchReleaseResult>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PaymentPartiallyReleased {
     pub payment_id: Pubkey,
     pub recipient: Pubkey,
     pub amount: u64,
+    pub tip_amount: u64,      // Non-zero only on the release that completes the payment
+    pub released_total: u64,
+    pub fee_charged: u64,
     pub timestamp: i64,
 }
 
@@ -614,6 +2083,91 @@ pub struct PaymentDisputed {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct DisputeResolved {
+    pub payment_id: Pubkey,
+    pub refunded_to_payer: bool,
+    pub amount: u64,
+    pub resolved_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StaleDisputeRefunded {
+    pub payment_id: Pubkey,
+    pub payer: Pubkey,
+    pub amount: u64,
+    pub resolved_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub old_platform_fee_rate: u16,
+    pub new_platform_fee_rate: u16,
+    pub old_cashback_rate: u16,
+    pub new_cashback_rate: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PaymentExpired {
+    pub payment_id: Pubkey,
+    pub payer: Pubkey,
+    pub reclaimed_amount: u64,
+    pub rent_returned: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ExpiryWindowUpdated {
+    pub expiry_window: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DisputeTimeoutUpdated {
+    pub dispute_timeout: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SubscriptionCreated {
+    pub subscription_id: Pubkey,
+    pub payer: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub interval_seconds: i64,
+    pub max_cycles: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SubscriptionCharged {
+    pub subscription_id: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub cycles_remaining: u32,
+    pub next_charge_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SubscriptionCancelled {
+    pub subscription_id: Pubkey,
+    pub payer: Pubkey,
+    pub cycles_charged: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardVaultFunded {
+    pub funder: Pubkey,
+    pub amount: u64,
+    pub vault_balance: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct MicroRewardsDistributed {
     pub total_amount: u64,
@@ -658,8 +2212,62 @@ pub enum ErrorCode {
     TooManyRecipients,
     #[msg("Insufficient reward pool")]
     InsufficientRewardPool,
+    #[msg("Reward vault does not hold enough SOL for this distribution")]
+    RewardVaultUnderfunded,
     #[msg("Not eligible for cashback")]
     IneligibleForCashback,
     #[msg("Invalid fee rate")]
     InvalidFeeRate,
+    #[msg("Payment is not disputed")]
+    PaymentNotDisputed,
+    #[msg("Dispute timeout has not been reached")]
+    DisputeTimeoutNotReached,
+    #[msg("Invalid dispute timeout")]
+    InvalidDisputeTimeout,
+    #[msg("Required token account not provided")]
+    MissingTokenAccount,
+    #[msg("Template ID too long")]
+    TemplateIdTooLong,
+    #[msg("Invalid auto-release delay")]
+    InvalidAutoReleaseDelay,
+    #[msg("Template mint does not match payment type or token account")]
+    InvalidTemplateMint,
+    #[msg("Token account mint does not match the escrow")]
+    TokenMintMismatch,
+    #[msg("Payment is disputed")]
+    PaymentDisputed,
+    #[msg("Release amount exceeds remaining escrowed amount")]
+    ReleaseExceedsRemaining,
+    #[msg("Invalid subscription interval")]
+    InvalidSubscriptionInterval,
+    #[msg("Invalid subscription cycle count")]
+    InvalidSubscriptionCycles,
+    #[msg("Subscription is not active")]
+    SubscriptionInactive,
+    #[msg("Subscription charge is not due yet")]
+    SubscriptionNotDue,
+    #[msg("Subscription escrow is underfunded")]
+    SubscriptionUnderfunded,
+    #[msg("Recipient accounts do not match the recipient list")]
+    RecipientAccountMismatch,
+    #[msg("Payment has not reached its expiry window")]
+    PaymentNotExpired,
+    #[msg("Invalid expiry window")]
+    InvalidExpiryWindow,
+    #[msg("Idempotency key and marker account must be supplied together")]
+    IdempotencyMarkerMismatch,
+    #[msg("Invalid batch size")]
+    InvalidBatchSize,
+    #[msg("Batch accounts must be payment and recipient pairs")]
+    BatchAccountMismatch,
+    #[msg("Release condition needs a future deadline and no auto-release time")]
+    InvalidReleaseCondition,
+    #[msg("Payment has no release condition")]
+    NotConditionalPayment,
+    #[msg("Signer is not the payment's condition oracle")]
+    UnauthorizedOracle,
+    #[msg("Attested condition does not match the payment's condition")]
+    ConditionMismatch,
+    #[msg("Release condition deadline has passed")]
+    ConditionDeadlinePassed,
 }
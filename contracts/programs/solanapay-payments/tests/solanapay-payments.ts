@@ -0,0 +1,775 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, LAMPORTS_PER_SOL, SystemProgram } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createAssociatedTokenAccount,
+  createMint,
+  getAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { Clock, startAnchor } from "solana-bankrun";
+import { BankrunProvider } from "anchor-bankrun";
+import { IDL, SolanapayPayments } from "../target/types/solanapay_payments";
+import { expect } from "chai";
+
+describe("solanapay-payments", () => {
+  // Configure the client to use the local cluster.
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.SolanapayPayments as Program<SolanapayPayments>;
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+
+  const DISPUTE_TIMEOUT = 14 * 24 * 60 * 60;
+
+  const [configPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    program.programId
+  );
+
+  const paymentPda = (payer: PublicKey, paymentId: number) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("payment"), payer.toBuffer(), new BN(paymentId).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+
+  const idempotencyPda = (payer: PublicKey, idempotencyKey: number[]) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("idempotency"), payer.toBuffer(), Buffer.from(idempotencyKey)],
+      program.programId
+    )[0];
+
+  const templatePda = (merchant: PublicKey, templateId: string) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("template"), merchant.toBuffer(), Buffer.from(templateId)],
+      program.programId
+    )[0];
+
+  let treasury: Keypair;
+
+  const fundedKeypair = async () => {
+    const keypair = Keypair.generate();
+    const signature = await provider.connection.requestAirdrop(keypair.publicKey, LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(signature);
+    return keypair;
+  };
+
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    try {
+      await promise;
+    } catch (err) {
+      expect((err as anchor.AnchorError).error.errorCode.code).to.equal(code);
+      return;
+    }
+    expect.fail(`expected ${code}`);
+  };
+
+  const expectRejected = async (promise: Promise<unknown>) => {
+    try {
+      await promise;
+    } catch {
+      return;
+    }
+    expect.fail("expected the transaction to fail");
+  };
+
+  const balance = (address: PublicKey) => provider.connection.getBalance(address);
+
+  // Events emitted by a confirmed transaction
+  const emittedEvents = async (signature: string) => {
+    await provider.connection.confirmTransaction(signature, "confirmed");
+    const tx = await provider.connection.getTransaction(signature, { commitment: "confirmed" });
+    const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+    return [...parser.parseLogs(tx.meta.logMessages)];
+  };
+
+  // A SOL escrow from `payer` to `recipient`
+  const createPayment = (
+    payer: Keypair,
+    recipient: PublicKey,
+    paymentId: number,
+    amount: number,
+    {
+      idempotencyKey = null,
+      releaseCondition = null,
+      tip = null,
+    }: {
+      idempotencyKey?: number[] | null;
+      releaseCondition?: { oracle: PublicKey; conditionId: number[]; deadline: BN } | null;
+      tip?: number | null;
+    } = {},
+    payments = program
+  ) =>
+    payments.methods
+      .createPayment(
+        new BN(paymentId),
+        new BN(amount),
+        { sol: {} },
+        "Escrow payment",
+        null,
+        idempotencyKey,
+        releaseCondition,
+        tip === null ? null : new BN(tip)
+      )
+      .accounts({
+        payment: paymentPda(payer.publicKey, paymentId),
+        idempotencyMarker: idempotencyKey ? idempotencyPda(payer.publicKey, idempotencyKey) : null,
+        paymentConfig: configPda,
+        payer: payer.publicKey,
+        recipient,
+        payerTokenAccount: null,
+        escrowTokenAccount: null,
+        tokenProgram: null,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([payer])
+      .rpc();
+
+  const releasePayment = (authority: Keypair, payer: PublicKey, paymentId: number, recipient: PublicKey) =>
+    program.methods
+      .releasePayment()
+      .accounts({
+        payment: paymentPda(payer, paymentId),
+        paymentConfig: configPda,
+        authority: authority.publicKey,
+        recipient,
+        treasury: treasury.publicKey,
+        escrowTokenAccount: null,
+        recipientTokenAccount: null,
+        treasuryTokenAccount: null,
+        tokenProgram: null,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+
+  const disputePayment = (disputer: Keypair, payment: PublicKey, payments = program) =>
+    payments.methods
+      .disputePayment("Item not delivered")
+      .accounts({ payment, disputer: disputer.publicKey })
+      .signers([disputer])
+      .rpc();
+
+  const resolveStaleDispute = (caller: Keypair, payer: PublicKey, paymentId: number, payments = program) =>
+    payments.methods
+      .resolveStaleDispute()
+      .accounts({
+        payment: paymentPda(payer, paymentId),
+        paymentConfig: configPda,
+        payer,
+        caller: caller.publicKey,
+        escrowTokenAccount: null,
+        payerTokenAccount: null,
+        tokenProgram: null,
+      })
+      .signers([caller])
+      .rpc();
+
+  // A fresh bank with the program initialized, so the clock can be moved
+  const startBank = async () => {
+    const context = await startAnchor(".", [], []);
+    const bankrunProgram = new Program<SolanapayPayments>(IDL, program.programId, new BankrunProvider(context));
+    await bankrunProgram.methods
+      .initialize()
+      .accounts({
+        paymentConfig: configPda,
+        authority: context.payer.publicKey,
+        treasury: Keypair.generate().publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const fund = () => {
+      const keypair = Keypair.generate();
+      context.setAccount(keypair.publicKey, {
+        lamports: LAMPORTS_PER_SOL,
+        data: Buffer.alloc(0),
+        owner: SystemProgram.programId,
+        executable: false,
+      });
+      return keypair;
+    };
+
+    // Move to the next slot, for a fresh blockhash, and `seconds` ahead
+    const warp = async (seconds: number) => {
+      const clock = await context.banksClient.getClock();
+      const slot = clock.slot + BigInt(1);
+      context.warpToSlot(slot);
+      context.setClock(
+        new Clock(
+          slot,
+          clock.epochStartTimestamp,
+          clock.epoch,
+          clock.leaderScheduleEpoch,
+          clock.unixTimestamp + BigInt(seconds)
+        )
+      );
+    };
+
+    return { context, bankrunProgram, fund, warp };
+  };
+
+  before(async () => {
+    treasury = await fundedKeypair();
+
+    await program.methods
+      .initialize()
+      .accounts({
+        paymentConfig: configPda,
+        authority: provider.wallet.publicKey,
+        treasury: treasury.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  });
+
+  describe("stale dispute refunds", () => {
+    it("Rejects a refund before the dispute timeout", async () => {
+      const payer = await fundedKeypair();
+      const recipient = Keypair.generate().publicKey;
+      await createPayment(payer, recipient, 0, 0.1 * LAMPORTS_PER_SOL);
+      await disputePayment(payer, paymentPda(payer.publicKey, 0));
+
+      const caller = await fundedKeypair();
+      await expectError(resolveStaleDispute(caller, payer.publicKey, 0), "DisputeTimeoutNotReached");
+    });
+
+    it("Rejects a second dispute of the same payment", async () => {
+      const payer = await fundedKeypair();
+      const recipient = await fundedKeypair();
+      await createPayment(payer, recipient.publicKey, 0, 0.1 * LAMPORTS_PER_SOL);
+      const payment = paymentPda(payer.publicKey, 0);
+      await disputePayment(payer, payment);
+      const { disputedAt } = await program.account.payment.fetch(payment);
+
+      await expectError(disputePayment(recipient, payment), "PaymentDisputed");
+      await expectError(disputePayment(payer, payment), "PaymentDisputed");
+
+      const account = await program.account.payment.fetch(payment);
+      expect(account.disputedAt.toNumber()).to.equal(disputedAt.toNumber());
+    });
+
+    it("Rejects a refund of an undisputed payment", async () => {
+      const payer = await fundedKeypair();
+      await createPayment(payer, Keypair.generate().publicKey, 0, 0.1 * LAMPORTS_PER_SOL);
+
+      const caller = await fundedKeypair();
+      await expectError(resolveStaleDispute(caller, payer.publicKey, 0), "PaymentNotDisputed");
+    });
+
+    it("Lets anyone refund the payer once the timeout has passed", async () => {
+      const { context, bankrunProgram, fund, warp } = await startBank();
+      const payer = fund();
+      const recipient = fund();
+      const amount = 0.1 * LAMPORTS_PER_SOL;
+      const payment = paymentPda(payer.publicKey, 0);
+
+      await createPayment(payer, recipient.publicKey, 0, amount, {}, bankrunProgram);
+      await disputePayment(recipient, payment, bankrunProgram);
+
+      // Still frozen just short of the timeout
+      await warp(DISPUTE_TIMEOUT - 60);
+      const caller = fund();
+      await expectRejected(resolveStaleDispute(caller, payer.publicKey, 0, bankrunProgram));
+
+      await warp(60);
+      const before = await context.banksClient.getBalance(payer.publicKey);
+      await resolveStaleDispute(caller, payer.publicKey, 0, bankrunProgram);
+
+      // The payer gets the full escrow back, fee included
+      const after = await context.banksClient.getBalance(payer.publicKey);
+      expect(Number(after - before)).to.equal(amount);
+
+      const account = await bankrunProgram.account.payment.fetch(payment);
+      expect(account.status).to.deep.equal({ cancelled: {} });
+      expect(account.completedAt).to.not.be.null;
+    });
+  });
+
+  describe("payment templates", () => {
+    const TEMPLATE_ID = "monthly-invoice";
+    const TEMPLATE_AMOUNT = 0.2 * LAMPORTS_PER_SOL;
+    const AUTO_RELEASE_DELAY = 7 * 24 * 60 * 60;
+
+    const createTemplate = (merchant: Keypair) =>
+      program.methods
+        .createPaymentTemplate(
+          TEMPLATE_ID,
+          new BN(TEMPLATE_AMOUNT),
+          { sol: {} },
+          null,
+          "Monthly retainer",
+          new BN(AUTO_RELEASE_DELAY)
+        )
+        .accounts({
+          template: templatePda(merchant.publicKey, TEMPLATE_ID),
+          merchant: merchant.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([merchant])
+        .rpc();
+
+    const createFromTemplate = (payer: Keypair, template: PublicKey, paymentId: number, amountOverride: number | null) =>
+      program.methods
+        .createPaymentFromTemplate(new BN(paymentId), amountOverride === null ? null : new BN(amountOverride))
+        .accounts({
+          payment: paymentPda(payer.publicKey, paymentId),
+          template,
+          paymentConfig: configPda,
+          payer: payer.publicKey,
+          payerTokenAccount: null,
+          escrowTokenAccount: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([payer])
+        .rpc();
+
+    it("Creates payments from a template with its defaults and an amount override", async () => {
+      const merchant = await fundedKeypair();
+      const payer = await fundedKeypair();
+      const template = templatePda(merchant.publicKey, TEMPLATE_ID);
+      await createTemplate(merchant);
+
+      const created = await program.account.paymentTemplate.fetch(template);
+      expect(created.merchant.toString()).to.equal(merchant.publicKey.toString());
+      expect(created.amount.toNumber()).to.equal(TEMPLATE_AMOUNT);
+      expect(created.mint).to.be.null;
+      expect(created.paymentsCreated.toNumber()).to.equal(0);
+
+      await createFromTemplate(payer, template, 0, null);
+      await createFromTemplate(payer, template, 1, 0.05 * LAMPORTS_PER_SOL);
+
+      const first = await program.account.payment.fetch(paymentPda(payer.publicKey, 0));
+      expect(first.recipient.toString()).to.equal(merchant.publicKey.toString());
+      expect(first.amount.toNumber()).to.equal(TEMPLATE_AMOUNT);
+      expect(first.description).to.equal("Monthly retainer");
+      expect(first.paymentType).to.deep.equal({ sol: {} });
+      expect(first.autoReleaseTime.toNumber()).to.equal(first.createdAt.toNumber() + AUTO_RELEASE_DELAY);
+
+      // The override replaces only the amount
+      const second = await program.account.payment.fetch(paymentPda(payer.publicKey, 1));
+      expect(second.recipient.toString()).to.equal(merchant.publicKey.toString());
+      expect(second.amount.toNumber()).to.equal(0.05 * LAMPORTS_PER_SOL);
+      expect(second.description).to.equal("Monthly retainer");
+      expect(second.autoReleaseTime.toNumber()).to.equal(second.createdAt.toNumber() + AUTO_RELEASE_DELAY);
+
+      const used = await program.account.paymentTemplate.fetch(template);
+      expect(used.paymentsCreated.toNumber()).to.equal(2);
+    });
+
+    it("Rejects a SOL template with a mint", async () => {
+      const merchant = await fundedKeypair();
+
+      await expectError(
+        program.methods
+          .createPaymentTemplate(TEMPLATE_ID, new BN(TEMPLATE_AMOUNT), { sol: {} }, Keypair.generate().publicKey, "Monthly retainer", null)
+          .accounts({
+            template: templatePda(merchant.publicKey, TEMPLATE_ID),
+            merchant: merchant.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([merchant])
+          .rpc(),
+        "InvalidTemplateMint"
+      );
+    });
+  });
+
+  describe("concurrent payments", () => {
+    it("Releases the middle of three payments from one payer", async () => {
+      const payer = await fundedKeypair();
+      const recipient = Keypair.generate().publicKey;
+      const amount = 0.1 * LAMPORTS_PER_SOL;
+
+      for (const paymentId of [0, 1, 2]) {
+        await createPayment(payer, recipient, paymentId, amount);
+      }
+
+      const treasuryBefore = await balance(treasury.publicKey);
+      await releasePayment(payer, payer.publicKey, 1, recipient);
+
+      const released = await program.account.payment.fetch(paymentPda(payer.publicKey, 1));
+      expect(released.paymentId.toNumber()).to.equal(1);
+      expect(released.status).to.deep.equal({ completed: {} });
+      expect(await balance(recipient)).to.equal(released.netAmount.toNumber());
+      expect(await balance(treasury.publicKey)).to.equal(treasuryBefore + released.platformFee.toNumber());
+
+      for (const paymentId of [0, 2]) {
+        const pending = await program.account.payment.fetch(paymentPda(payer.publicKey, paymentId));
+        expect(pending.paymentId.toNumber()).to.equal(paymentId);
+        expect(pending.status).to.deep.equal({ pending: {} });
+      }
+    });
+  });
+
+  describe("idempotency keys", () => {
+    const idempotencyKey = (fill: number) => Array(32).fill(fill);
+
+    it("Records the payment created under a key", async () => {
+      const payer = await fundedKeypair();
+      const key = idempotencyKey(1);
+      await createPayment(payer, Keypair.generate().publicKey, 0, 0.1 * LAMPORTS_PER_SOL, { idempotencyKey: key });
+
+      const marker = await program.account.idempotencyMarker.fetch(idempotencyPda(payer.publicKey, key));
+      expect(marker.payer.toString()).to.equal(payer.publicKey.toString());
+      expect(marker.idempotencyKey).to.deep.equal(key);
+      expect(marker.payment.toString()).to.equal(paymentPda(payer.publicKey, 0).toString());
+    });
+
+    it("Rejects a retry with the same key without creating a duplicate", async () => {
+      const payer = await fundedKeypair();
+      const recipient = Keypair.generate().publicKey;
+      const key = idempotencyKey(2);
+      await createPayment(payer, recipient, 0, 0.1 * LAMPORTS_PER_SOL, { idempotencyKey: key });
+
+      // A retry under a new payment id is still caught by the existing marker
+      const payerBalance = await balance(payer.publicKey);
+      await expectRejected(createPayment(payer, recipient, 1, 0.1 * LAMPORTS_PER_SOL, { idempotencyKey: key }));
+
+      expect(await program.account.payment.fetchNullable(paymentPda(payer.publicKey, 1))).to.be.null;
+      expect(await balance(payer.publicKey)).to.equal(payerBalance);
+      const marker = await program.account.idempotencyMarker.fetch(idempotencyPda(payer.publicKey, key));
+      expect(marker.payment.toString()).to.equal(paymentPda(payer.publicKey, 0).toString());
+    });
+
+    it("Creates a new payment under a different key", async () => {
+      const payer = await fundedKeypair();
+      const recipient = Keypair.generate().publicKey;
+      await createPayment(payer, recipient, 0, 0.1 * LAMPORTS_PER_SOL, { idempotencyKey: idempotencyKey(3) });
+      await createPayment(payer, recipient, 1, 0.1 * LAMPORTS_PER_SOL, { idempotencyKey: idempotencyKey(4) });
+
+      const marker = await program.account.idempotencyMarker.fetch(idempotencyPda(payer.publicKey, idempotencyKey(4)));
+      expect(marker.payment.toString()).to.equal(paymentPda(payer.publicKey, 1).toString());
+      const payment = await program.account.payment.fetch(paymentPda(payer.publicKey, 1));
+      expect(payment.status).to.deep.equal({ pending: {} });
+    });
+  });
+
+  describe("release fee breakdown", () => {
+    it("Emits a breakdown that sums back to the gross amount", async () => {
+      const payer = await fundedKeypair();
+      const recipient = Keypair.generate().publicKey;
+      const amount = 0.2 * LAMPORTS_PER_SOL;
+      await createPayment(payer, recipient, 0, amount);
+
+      const signature = await releasePayment(payer, payer.publicKey, 0, recipient);
+
+      const released = (await emittedEvents(signature)).find((event) => event.name === "PaymentReleased");
+      expect(released).to.not.be.undefined;
+      const data = released.data;
+      expect(data.version).to.equal(3);
+      expect(data.paymentId.toString()).to.equal(paymentPda(payer.publicKey, 0).toString());
+      expect(data.grossAmount.toNumber()).to.equal(amount);
+      expect(data.netAmount.toNumber() + data.platformFee.toNumber()).to.equal(data.grossAmount.toNumber());
+      expect(data.platformFee.toNumber()).to.equal(amount * 250 / 10000);
+      expect(data.microReward.toNumber()).to.equal(amount / 1000);
+      expect(data.amount.toNumber()).to.equal(data.netAmount.toNumber());
+      expect(data.tipAmount.toNumber()).to.equal(0);
+
+      // The event matches what actually moved
+      expect(await balance(recipient)).to.equal(data.amount.toNumber());
+    });
+  });
+
+  describe("batch release", () => {
+    const releaseBatch = (authority: Keypair, pairs: [PublicKey, PublicKey][]) =>
+      program.methods
+        .releasePaymentsBatch()
+        .accounts({
+          paymentConfig: configPda,
+          authority: authority.publicKey,
+          treasury: treasury.publicKey,
+        })
+        .remainingAccounts(
+          pairs.flatMap(([payment, recipient]) => [
+            { pubkey: payment, isWritable: true, isSigner: false },
+            { pubkey: recipient, isWritable: true, isSigner: false },
+          ])
+        )
+        .signers([authority])
+        .rpc();
+
+    it("Releases eligible payments and skips a disputed one", async () => {
+      const payer = await fundedKeypair();
+      const recipients = [0, 1, 2].map(() => Keypair.generate().publicKey);
+      const amount = 0.1 * LAMPORTS_PER_SOL;
+      for (const paymentId of [0, 1, 2]) {
+        await createPayment(payer, recipients[paymentId], paymentId, amount);
+      }
+      await disputePayment(payer, paymentPda(payer.publicKey, 1));
+
+      const signature = await releaseBatch(
+        payer,
+        [0, 1, 2].map((paymentId) => [paymentPda(payer.publicKey, paymentId), recipients[paymentId]])
+      );
+
+      const batch = (await emittedEvents(signature)).find((event) => event.name === "PaymentsBatchReleased");
+      expect(batch.data.releasedCount).to.equal(2);
+      expect(batch.data.skippedCount).to.equal(1);
+
+      const netAmount = amount - amount * 250 / 10000;
+      const results = batch.data.results;
+      expect(results.map((result) => result.paymentId.toString())).to.deep.equal(
+        [0, 1, 2].map((paymentId) => paymentPda(payer.publicKey, paymentId).toString())
+      );
+      expect(results[0].releasedAmount.toNumber()).to.equal(netAmount);
+      expect(results[0].skipReason).to.be.null;
+      expect(results[1].releasedAmount.toNumber()).to.equal(0);
+      expect(results[1].skipReason).to.deep.equal({ disputed: {} });
+      expect(results[2].releasedAmount.toNumber()).to.equal(netAmount);
+      expect(results[2].skipReason).to.be.null;
+
+      for (const paymentId of [0, 2]) {
+        const payment = await program.account.payment.fetch(paymentPda(payer.publicKey, paymentId));
+        expect(payment.status).to.deep.equal({ completed: {} });
+        expect(await balance(recipients[paymentId])).to.equal(netAmount);
+      }
+      const disputed = await program.account.payment.fetch(paymentPda(payer.publicKey, 1));
+      expect(disputed.status).to.deep.equal({ pending: {} });
+      expect(disputed.isDisputed).to.be.true;
+      expect(await balance(recipients[1])).to.equal(0);
+    });
+
+    it("Rejects an empty batch", async () => {
+      const payer = await fundedKeypair();
+      await expectError(releaseBatch(payer, []), "InvalidBatchSize");
+    });
+  });
+
+  describe("conditional release", () => {
+    const CONDITION_ID = Array(32).fill(7);
+
+    const releaseConditional = (
+      oracle: Keypair,
+      payer: PublicKey,
+      paymentId: number,
+      recipient: PublicKey,
+      conditionId = CONDITION_ID
+    ) =>
+      program.methods
+        .releaseConditionalPayment(conditionId)
+        .accounts({
+          payment: paymentPda(payer, paymentId),
+          paymentConfig: configPda,
+          authority: oracle.publicKey,
+          recipient,
+          treasury: treasury.publicKey,
+          escrowTokenAccount: null,
+          recipientTokenAccount: null,
+          treasuryTokenAccount: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([oracle])
+        .rpc();
+
+    // A conditional payment attested by `oracle` within the next hour
+    const createConditionalPayment = async (oracle: PublicKey) => {
+      const payer = await fundedKeypair();
+      const recipient = await fundedKeypair();
+      const deadline = new BN(Math.floor(Date.now() / 1000) + 3600);
+      await createPayment(payer, recipient.publicKey, 0, 0.1 * LAMPORTS_PER_SOL, {
+        releaseCondition: { oracle, conditionId: CONDITION_ID, deadline },
+      });
+      return { payer, recipient };
+    };
+
+    it("Releases on the oracle's attestation", async () => {
+      const oracle = await fundedKeypair();
+      const { payer, recipient } = await createConditionalPayment(oracle.publicKey);
+      const recipientBefore = await balance(recipient.publicKey);
+
+      const signature = await releaseConditional(oracle, payer.publicKey, 0, recipient.publicKey);
+
+      const payment = await program.account.payment.fetch(paymentPda(payer.publicKey, 0));
+      expect(payment.status).to.deep.equal({ completed: {} });
+      expect(await balance(recipient.publicKey)).to.equal(recipientBefore + payment.netAmount.toNumber());
+
+      const attested = (await emittedEvents(signature)).find((event) => event.name === "ConditionAttested");
+      expect(attested.data.oracle.toString()).to.equal(oracle.publicKey.toString());
+      expect(attested.data.conditionId).to.deep.equal(CONDITION_ID);
+    });
+
+    it("Rejects an attestation from another signer", async () => {
+      const oracle = await fundedKeypair();
+      const forger = await fundedKeypair();
+      const { payer, recipient } = await createConditionalPayment(oracle.publicKey);
+
+      await expectError(releaseConditional(forger, payer.publicKey, 0, recipient.publicKey), "UnauthorizedOracle");
+    });
+
+    it("Rejects an attestation over another condition", async () => {
+      const oracle = await fundedKeypair();
+      const { payer, recipient } = await createConditionalPayment(oracle.publicKey);
+
+      await expectError(
+        releaseConditional(oracle, payer.publicKey, 0, recipient.publicKey, Array(32).fill(8)),
+        "ConditionMismatch"
+      );
+    });
+
+    it("Keeps the recipient from releasing without an attestation", async () => {
+      const oracle = await fundedKeypair();
+      const { payer, recipient } = await createConditionalPayment(oracle.publicKey);
+
+      await expectError(releasePayment(recipient, payer.publicKey, 0, recipient.publicKey), "Unauthorized");
+    });
+
+    it("Lets the payer reclaim the escrow once the deadline passes unattested", async () => {
+      const { context, bankrunProgram, fund, warp } = await startBank();
+      const oracle = fund();
+      const payer = fund();
+      const recipient = fund();
+      const amount = 0.1 * LAMPORTS_PER_SOL;
+      const payment = paymentPda(payer.publicKey, 0);
+
+      const { unixTimestamp } = await context.banksClient.getClock();
+      const deadline = new BN((unixTimestamp + BigInt(3600)).toString());
+      await createPayment(
+        payer,
+        recipient.publicKey,
+        0,
+        amount,
+        { releaseCondition: { oracle: oracle.publicKey, conditionId: CONDITION_ID, deadline } },
+        bankrunProgram
+      );
+
+      const expirePayment = () =>
+        bankrunProgram.methods
+          .expirePayment()
+          .accounts({
+            payment,
+            paymentConfig: configPda,
+            payer: payer.publicKey,
+            escrowTokenAccount: null,
+            payerTokenAccount: null,
+            tokenProgram: null,
+          })
+          .signers([payer])
+          .rpc();
+
+      await expectRejected(expirePayment());
+
+      await warp(3601);
+      const before = await context.banksClient.getBalance(payer.publicKey);
+      const escrowed = await context.banksClient.getBalance(payment);
+      await expirePayment();
+
+      // The escrow and the account's rent both go back to the payer
+      expect(Number((await context.banksClient.getBalance(payer.publicKey)) - before)).to.equal(Number(escrowed));
+      expect(Number(escrowed)).to.be.greaterThan(amount);
+      expect(await bankrunProgram.account.payment.fetchNullable(payment)).to.be.null;
+    });
+  });
+
+  describe("tips", () => {
+    const AMOUNT = 0.2 * LAMPORTS_PER_SOL;
+    const TIP = 0.03 * LAMPORTS_PER_SOL;
+    const FEE = AMOUNT * 250 / 10000;
+
+    it("Pays a SOL tip to the recipient in full, outside the fee", async () => {
+      const payer = await fundedKeypair();
+      const recipient = Keypair.generate().publicKey;
+      const payerBefore = await balance(payer.publicKey);
+      await createPayment(payer, recipient, 0, AMOUNT, { tip: TIP });
+
+      const payment = await program.account.payment.fetch(paymentPda(payer.publicKey, 0));
+      expect(payment.tipAmount.toNumber()).to.equal(TIP);
+      expect(payment.platformFee.toNumber()).to.equal(FEE);
+      expect(payment.netAmount.toNumber()).to.equal(AMOUNT - FEE);
+
+      // Principal and tip are both escrowed, on top of the payment account's rent
+      const rent = await provider.connection.getMinimumBalanceForRentExemption(
+        (await provider.connection.getAccountInfo(paymentPda(payer.publicKey, 0))).data.length
+      );
+      expect(await balance(payer.publicKey)).to.equal(payerBefore - AMOUNT - TIP - rent);
+
+      const treasuryBefore = await balance(treasury.publicKey);
+      await releasePayment(payer, payer.publicKey, 0, recipient);
+
+      expect(await balance(recipient)).to.equal(AMOUNT - FEE + TIP);
+      expect(await balance(treasury.publicKey)).to.equal(treasuryBefore + FEE);
+    });
+
+    it("Pays a token tip to the recipient in full, outside the fee", async () => {
+      const wallet = (provider.wallet as anchor.Wallet).payer;
+      const payer = await fundedKeypair();
+      const recipient = Keypair.generate().publicKey;
+      const payment = paymentPda(payer.publicKey, 0);
+
+      const mint = await createMint(provider.connection, wallet, wallet.publicKey, null, 6);
+      const payerTokenAccount = await createAssociatedTokenAccount(provider.connection, wallet, mint, payer.publicKey);
+      const escrowTokenAccount = await createAssociatedTokenAccount(
+        provider.connection,
+        wallet,
+        mint,
+        payment,
+        undefined,
+        undefined,
+        undefined,
+        true
+      );
+      const recipientTokenAccount = await createAssociatedTokenAccount(provider.connection, wallet, mint, recipient);
+      const treasuryTokenAccount = await createAssociatedTokenAccount(provider.connection, wallet, mint, treasury.publicKey);
+      await mintTo(provider.connection, wallet, mint, payerTokenAccount, wallet, AMOUNT + TIP);
+
+      const tokenBalance = async (address: PublicKey) =>
+        Number((await getAccount(provider.connection, address)).amount);
+
+      await program.methods
+        .createPayment(new BN(0), new BN(AMOUNT), { usdc: {} }, "Escrow payment", null, null, null, new BN(TIP))
+        .accounts({
+          payment,
+          idempotencyMarker: null,
+          paymentConfig: configPda,
+          payer: payer.publicKey,
+          recipient,
+          payerTokenAccount,
+          escrowTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([payer])
+        .rpc();
+
+      expect(await tokenBalance(escrowTokenAccount)).to.equal(AMOUNT + TIP);
+
+      await program.methods
+        .releasePayment()
+        .accounts({
+          payment,
+          paymentConfig: configPda,
+          authority: payer.publicKey,
+          recipient,
+          treasury: treasury.publicKey,
+          escrowTokenAccount,
+          recipientTokenAccount,
+          treasuryTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([payer])
+        .rpc();
+
+      expect(await tokenBalance(recipientTokenAccount)).to.equal(AMOUNT - FEE + TIP);
+      expect(await tokenBalance(treasuryTokenAccount)).to.equal(FEE);
+      expect(await tokenBalance(escrowTokenAccount)).to.equal(0);
+    });
+  });
+
+  describe("disputed payments", () => {
+    it("Keeps a disputed payment from being released", async () => {
+      const payer = await fundedKeypair();
+      const recipient = await fundedKeypair();
+      await createPayment(payer, recipient.publicKey, 0, 0.1 * LAMPORTS_PER_SOL);
+      await disputePayment(payer, paymentPda(payer.publicKey, 0));
+
+      await expectError(releasePayment(payer, payer.publicKey, 0, recipient.publicKey), "PaymentDisputed");
+      await expectError(releasePayment(recipient, payer.publicKey, 0, recipient.publicKey), "PaymentDisputed");
+
+      const account = await program.account.payment.fetch(paymentPda(payer.publicKey, 0));
+      expect(account.status).to.deep.equal({ pending: {} });
+    });
+  });
+});
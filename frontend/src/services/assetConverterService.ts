@@ -17,7 +17,7 @@ const ASSET_CONVERSIONS = {
   // Wrapped USDT to USDC
   'USDT': {
     targetToken: 'USDC',
-    conversionRate: 1000000, // 1:1 ratio for stablecoins
+    conversionRate: 1000000000, // 1:1 ratio for stablecoins
     minAmount: 1,     // $1 minimum
     maxAmount: 100000, // $100k maximum
     description: 'Convert Tether USD to USD Coin'
@@ -25,7 +25,7 @@ const ASSET_CONVERSIONS = {
   // Wrapped USDC to native USDC
   'wUSDC': {
     targetToken: 'USDC',
-    conversionRate: 1000000, // 1:1 ratio
+    conversionRate: 1000000000, // 1:1 ratio
     minAmount: 1,
     maxAmount: 100000,
     description: 'Convert Wrapped USDC to native USDC'
@@ -127,7 +127,7 @@ class AssetConverterService {
     }
 
     // Calculate conversion
-    const conversionRate = config.conversionRate / 1e9 // Normalize from lamports
+    const conversionRate = config.conversionRate / 1e9 // Whole-token rate, 1e9 = 1:1
     const targetAmount = request.amount * conversionRate
     
     // Calculate fees (0.25% conversion fee)
@@ -212,6 +212,18 @@ class AssetConverterService {
         true
       )
 
+      // Per-user conversion history ring buffer
+      const [userHistory] = PublicKey.findProgramAddressSync(
+        [Buffer.from('conversion_history'), wallet.publicKey.toBuffer()],
+        this.programId
+      )
+
+      // Per-user daily conversion limit tracker
+      const [userLimit] = PublicKey.findProgramAddressSync(
+        [Buffer.from('user_limit'), wallet.publicKey.toBuffer()],
+        this.programId
+      )
+
       // Convert amount to proper decimals
       const decimals = request.sourceToken === 'WETH' ? 18 : 6
       const amount = new BN(request.amount * Math.pow(10, decimals))
@@ -229,6 +241,8 @@ class AssetConverterService {
           sourceVault,
           targetVault,
           adminFeeAccount,
+          userHistory,
+          userLimit,
           user: wallet.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
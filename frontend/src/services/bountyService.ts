@@ -3,6 +3,7 @@ import { AnchorProvider, Program, Wallet, BN } from '@project-serum/anchor';
 import { TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID, getAssociatedTokenAddress } from '@solana/spl-token';
 
 export interface BountyData {
+  bounty_id: number;
   creator: PublicKey;
   title: string;
   description: string;
@@ -15,7 +16,7 @@ export interface BountyData {
   status: 'Open' | 'Completed' | 'Cancelled' | 'Expired';
   created_at: number;
   completed_at: number;
-  winner?: PublicKey;
+  winners: PublicKey[];
   submissions_count: number;
 }
 
@@ -29,6 +30,22 @@ export interface SubmissionData {
   review_notes: string;
 }
 
+export interface RatingSummary {
+  count: number;
+  total: number;
+  average_rating: number; // Hundredths, e.g. 450 = 4.50
+}
+
+export interface WorkerStats {
+  worker: PublicKey;
+  submissions_made: number;
+  approvals: number;
+  rejections: number;
+  reputation_score: number;
+  last_activity: number;
+  ratings: RatingSummary;
+}
+
 export interface BountyConfig {
   authority: PublicKey;
   platform_fee_bps: number;
@@ -90,18 +107,19 @@ class BountyService {
 
     try {
       const creator = this.program.provider.publicKey!;
-      const currentTimestamp = Math.floor(Date.now() / 1000);
-      
-      const [bountyPDA] = PublicKey.findProgramAddressSync(
-        [Buffer.from('bounty'), creator.toBuffer(), new BN(currentTimestamp).toArrayLike(Buffer, 'le', 8)],
-        this.programId
-      );
 
       const [bountyConfigPDA] = PublicKey.findProgramAddressSync(
         [Buffer.from('bounty_config')],
         this.programId
       );
 
+      // Bounty PDAs are indexed by the global bounty counter
+      const bountyConfig = await this.program.account.bountyConfig.fetch(bountyConfigPDA);
+      const [bountyPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from('bounty'), creator.toBuffer(), new BN(bountyConfig.bountyCount).toArrayLike(Buffer, 'le', 8)],
+        this.programId
+      );
+
       const escrowTokenAccount = await getAssociatedTokenAddress(
         rewardMint,
         bountyPDA,
@@ -149,7 +167,8 @@ class BountyService {
   async submitWork(
     bountyPDA: PublicKey,
     submissionData: string,
-    submissionHash: string
+    submissionHash: string,
+    declaredSkills: string[] | null = null
   ): Promise<string> {
     if (!this.program) throw new Error('Program not initialized');
 
@@ -161,11 +180,25 @@ class BountyService {
         this.programId
       );
 
+      const [workerStatsPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from('worker_stats'), worker.toBuffer()],
+        this.programId
+      );
+
+      // One marker per (bounty, SHA-256 of the submission hash); a duplicate submission fails
+      const hashDigest = await crypto.subtle.digest('SHA-256', new TextEncoder().encode(submissionHash));
+      const [submissionHashMarkerPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from('submission_hash'), bountyPDA.toBuffer(), Buffer.from(hashDigest)],
+        this.programId
+      );
+
       const tx = await this.program.methods
-        .submitWork(submissionData, submissionHash)
+        .submitWork(submissionData, submissionHash, declaredSkills)
         .accounts({
           bounty: bountyPDA,
           submission: submissionPDA,
+          submissionHashMarker: submissionHashMarkerPDA,
+          workerStats: workerStatsPDA,
           worker,
           systemProgram: SystemProgram.programId,
         })
@@ -178,6 +211,77 @@ class BountyService {
     }
   }
 
+  /**
+   * Push back the deadline of an open bounty (creator only)
+   */
+  async extendDeadline(bountyPDA: PublicKey, newDeadline: number): Promise<string> {
+    if (!this.program) throw new Error('Program not initialized');
+
+    try {
+      const tx = await this.program.methods
+        .extendDeadline(new BN(newDeadline))
+        .accounts({
+          bounty: bountyPDA,
+          creator: this.program.provider.publicKey,
+        })
+        .rpc();
+
+      return tx;
+    } catch (error) {
+      console.error('Failed to extend deadline:', error);
+      throw error;
+    }
+  }
+
+  /**
+   * Rate the other party of a completed bounty (1-5). The creator rates a winner;
+   * a winner rates the creator, passing their own key as `worker`.
+   */
+  async rateCounterparty(
+    bountyPDA: PublicKey,
+    worker: PublicKey,
+    rating: number,
+    feedback: string
+  ): Promise<string> {
+    if (!this.program) throw new Error('Program not initialized');
+
+    try {
+      const rater = this.program.provider.publicKey!;
+      const bounty = await this.program.account.bounty.fetch(bountyPDA);
+
+      const [ratingRecordPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from('rating'), bountyPDA.toBuffer(), rater.toBuffer(), worker.toBuffer()],
+        this.programId
+      );
+      const [creatorStatsPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from('creator_stats'), (bounty.creator as PublicKey).toBuffer()],
+        this.programId
+      );
+      const [workerStatsPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from('worker_stats'), worker.toBuffer()],
+        this.programId
+      );
+
+      const tx = await this.program.methods
+        .rateCounterparty(rating, feedback)
+        .accounts({
+          bounty: bountyPDA,
+          ratingRecord: ratingRecordPDA,
+          creatorStats: creatorStatsPDA,
+          workerStats: workerStatsPDA,
+          worker,
+          rater,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      return tx;
+    } catch (error) {
+      console.error('Failed to rate counterparty:', error);
+      throw error;
+    }
+  }
+
   /**
    * Approve submission and mint NFT proof
    */
@@ -204,6 +308,11 @@ class BountyService {
       const submission = await this.program.account.submission.fetch(submissionPDA);
       const worker = submission.worker;
 
+      const [workerStatsPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from('worker_stats'), worker.toBuffer()],
+        this.programId
+      );
+
       const escrowTokenAccount = await getAssociatedTokenAddress(
         rewardMint,
         bountyPDA,
@@ -243,6 +352,7 @@ class BountyService {
           bounty: bountyPDA,
           submission: submissionPDA,
           bountyConfig: bountyConfigPDA,
+          workerStats: workerStatsPDA,
           escrowTokenAccount,
           workerTokenAccount,
           platformFeeAccount,
@@ -350,6 +460,25 @@ class BountyService {
     }
   }
 
+  /**
+   * Get a worker's reputation, or null if they have never submitted
+   */
+  async getWorkerReputation(workerPubkey: PublicKey): Promise<WorkerStats | null> {
+    if (!this.program) throw new Error('Program not initialized');
+
+    try {
+      const [workerStatsPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from('worker_stats'), workerPubkey.toBuffer()],
+        this.programId
+      );
+      const workerStats = await this.program.account.workerStats.fetch(workerStatsPDA);
+      return workerStats as WorkerStats;
+    } catch (error) {
+      console.error('Failed to get worker reputation:', error);
+      return null;
+    }
+  }
+
   /**
    * Get bounty statistics
    */
@@ -68,6 +68,7 @@ export interface UserProfile {
   joinedAt: number;
   lastActivity: number;
   isActive: boolean;
+  isPrivate: boolean;
 }
 
 export interface LeaderboardConfig {
@@ -91,6 +92,7 @@ export interface UserRankInfo {
   rewardsEarned: number;
   badgesCount: number;
   estimatedRank: number;
+  isPrivate: boolean;
 }
 
 export interface Achievement {
@@ -336,6 +338,7 @@ export class CommunityLeaderboardService {
         joinedAt: Date.now(),
         lastActivity: Date.now(),
         isActive: true,
+        isPrivate: false,
       };
     } catch (error) {
       console.error('Error fetching user profile:', error);
@@ -394,6 +397,7 @@ export class CommunityLeaderboardService {
             joinedAt: Date.now() - (90 * 24 * 60 * 60 * 1000),
             lastActivity: Date.now() - (2 * 60 * 60 * 1000),
             isActive: true,
+            isPrivate: false,
           },
           score: 15750,
           tier: UserTier.Platinum,
@@ -414,6 +418,7 @@ export class CommunityLeaderboardService {
             joinedAt: Date.now() - (75 * 24 * 60 * 60 * 1000),
             lastActivity: Date.now() - (1 * 60 * 60 * 1000),
             isActive: true,
+            isPrivate: false,
           },
           score: 12400,
           tier: UserTier.Gold,
@@ -437,6 +442,21 @@ export class CommunityLeaderboardService {
         return null;
       }
 
+      // Private profiles only expose their tier as a rank band
+      if (userProfile.isPrivate) {
+        return {
+          userId: userPublicKey,
+          contributionScore: 0,
+          tier: userProfile.tier,
+          totalTransactions: 0,
+          tasksCompleted: 0,
+          rewardsEarned: 0,
+          badgesCount: 0,
+          estimatedRank: 0,
+          isPrivate: true,
+        };
+      }
+
       // Mock rank calculation - in real implementation, this would be indexed
       return {
         userId: userPublicKey,
@@ -447,6 +467,7 @@ export class CommunityLeaderboardService {
         rewardsEarned: userProfile.rewardsEarned,
         badgesCount: userProfile.badges.length,
         estimatedRank: Math.floor(Math.random() * 1000) + 1, // Mock rank
+        isPrivate: false,
       };
     } catch (error) {
       console.error('Error fetching user rank:', error);
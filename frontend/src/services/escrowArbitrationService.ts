@@ -1,6 +1,7 @@
-import { Connection, PublicKey, Transaction, SystemProgram, LAMPORTS_PER_SOL } from '@solana/web3.js';
+import { Connection, PublicKey, Transaction, SystemProgram, LAMPORTS_PER_SOL, SYSVAR_SLOT_HASHES_PUBKEY } from '@solana/web3.js';
 import { Program, AnchorProvider, web3, BN, IdlAccounts } from '@project-serum/anchor';
 import { WalletContextState } from '@solana/wallet-adapter-react';
+import { getAssociatedTokenAddressSync, TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID } from '@solana/spl-token';
 import { EscrowArbitration } from '../types/escrow_arbitration';
 
 export interface EscrowData {
@@ -54,6 +55,35 @@ export enum DisputeDecision {
   FavorSeller = 'FavorSeller'
 }
 
+export enum ReasonCode {
+  NotDelivered = 'NotDelivered',
+  NotAsDescribed = 'NotAsDescribed',
+  Fraud = 'Fraud',
+  Other = 'Other'
+}
+
+// Anchor encodes unit enum variants as `{ camelCaseVariant: {} }`
+const toReasonCodeArg = (code: ReasonCode) => ({
+  [code.charAt(0).toLowerCase() + code.slice(1)]: {}
+});
+
+// Events that carry the escrow's buyer and seller, so a notifier can route them without lookups
+const PARTY_EVENTS = [
+  'EscrowCreated',
+  'EscrowDraftCreated',
+  'EscrowDraftCancelled',
+  'EscrowReleased',
+  'MilestoneReleased',
+  'DisputeCreated',
+  'ArbiterAssigned',
+  'DisputeResolved',
+  'DisputeAppealed',
+  'AppealResolved',
+  'DisputeFinalized',
+  'ArbitrationFeePaid',
+  'DisputeFeeRebated',
+];
+
 export class EscrowArbitrationService {
   private connection: Connection;
   private program: Program<EscrowArbitration>;
@@ -86,9 +116,9 @@ export class EscrowArbitrationService {
   }
 
   // Get escrow PDA
-  private getEscrowPDA(buyer: PublicKey): [PublicKey, number] {
+  private getEscrowPDA(buyer: PublicKey, escrowId: BN): [PublicKey, number] {
     return PublicKey.findProgramAddressSync(
-      [Buffer.from('escrow'), buyer.toBuffer()],
+      [Buffer.from('escrow'), buyer.toBuffer(), escrowId.toArrayLike(Buffer, 'le', 8)],
       this.programId
     );
   }
@@ -109,6 +139,22 @@ export class EscrowArbitrationService {
     );
   }
 
+  // Get arbiter registry PDA
+  private getArbiterRegistryPDA(): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from('arbiter_registry')],
+      this.programId
+    );
+  }
+
+  // Get per-mint escrow limit PDA
+  private getMintLimitPDA(mint: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from('mint_limit'), mint.toBuffer()],
+      this.programId
+    );
+  }
+
   // Initialize the program
   async initializeProgram(
     authority: PublicKey,
@@ -120,6 +166,7 @@ export class EscrowArbitrationService {
       .initialize()
       .accounts({
         config: configPDA,
+        arbiterRegistry: this.getArbiterRegistryPDA()[0],
         authority,
         treasury,
         systemProgram: SystemProgram.programId,
@@ -137,8 +184,9 @@ export class EscrowArbitrationService {
     description: string,
     autoReleaseTime?: number
   ): Promise<string> {
-    const [escrowPDA] = this.getEscrowPDA(buyer);
     const [configPDA] = this.getConfigPDA();
+    const config = await this.program.account.escrowConfig.fetch(configPDA);
+    const [escrowPDA] = this.getEscrowPDA(buyer, config.escrowCount);
 
     const amountLamports = new BN(amount * LAMPORTS_PER_SOL);
     const autoReleaseTimeBN = autoReleaseTime ? new BN(autoReleaseTime) : null;
@@ -150,6 +198,8 @@ export class EscrowArbitrationService {
         config: configPDA,
         buyer,
         seller,
+        treasury: config.treasury,
+        kycCredential: this.getKycCredential(config, buyer, amountLamports),
         systemProgram: SystemProgram.programId,
       })
       .rpc();
@@ -157,6 +207,95 @@ export class EscrowArbitrationService {
     return tx;
   }
 
+  // Buyer's KYC SBT account, only passed when the escrow is above the configured KYC threshold
+  private getKycCredential(config: any, buyer: PublicKey, amount: BN): PublicKey | null {
+    if (config.kycThreshold.isZero() || amount.lte(config.kycThreshold)) {
+      return null;
+    }
+    return getAssociatedTokenAddressSync(config.kycMint, buyer);
+  }
+
+  // Create escrow holding SPL tokens (amount in base units of the mint)
+  async createTokenEscrow(
+    buyer: PublicKey,
+    seller: PublicKey,
+    mint: PublicKey,
+    amount: BN,
+    description: string,
+    autoReleaseTime?: number
+  ): Promise<string> {
+    const [configPDA] = this.getConfigPDA();
+    const config = await this.program.account.escrowConfig.fetch(configPDA);
+    const [escrowPDA] = this.getEscrowPDA(buyer, config.escrowCount);
+    const autoReleaseTimeBN = autoReleaseTime ? new BN(autoReleaseTime) : null;
+    const [mintLimitPDA] = this.getMintLimitPDA(mint);
+    const mintLimit = await this.program.account.mintEscrowLimit.fetchNullable(mintLimitPDA);
+
+    const tx = await this.program.methods
+      .createTokenEscrow(amount, description, autoReleaseTimeBN)
+      .accounts({
+        escrow: escrowPDA,
+        escrowTokenAccount: getAssociatedTokenAddressSync(mint, escrowPDA, true),
+        config: configPDA,
+        buyer,
+        buyerTokenAccount: getAssociatedTokenAddressSync(mint, buyer),
+        seller,
+        mint,
+        mintLimit: mintLimitPDA,
+        treasury: config.treasury,
+        kycCredential: mintLimit
+          ? this.getKycCredential({ kycThreshold: mintLimit.kycThreshold, kycMint: config.kycMint }, buyer, amount)
+          : null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    return tx;
+  }
+
+  // Release token escrow to the seller
+  async releaseTokenEscrow(
+    escrowId: PublicKey,
+    authority: PublicKey
+  ): Promise<string> {
+    const escrow = await this.program.account.escrow.fetch(escrowId);
+
+    const tx = await this.program.methods
+      .releaseTokenEscrow()
+      .accounts({
+        escrow: escrowId,
+        authority,
+        escrowTokenAccount: getAssociatedTokenAddressSync(escrow.mint, escrowId, true),
+        sellerTokenAccount: getAssociatedTokenAddressSync(escrow.mint, escrow.seller),
+        mint: escrow.mint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    return tx;
+  }
+
+  // Token accounts a ruling needs to settle a token escrow; all null for SOL escrows
+  private async getEscrowTokenAccounts(escrowId: PublicKey) {
+    const escrow = await this.program.account.escrow.fetch(escrowId);
+    if (!escrow.mint) {
+      return {
+        escrowTokenAccount: null,
+        buyerTokenAccount: null,
+        sellerTokenAccount: null,
+        tokenProgram: null,
+      };
+    }
+    return {
+      escrowTokenAccount: getAssociatedTokenAddressSync(escrow.mint, escrowId, true),
+      buyerTokenAccount: getAssociatedTokenAddressSync(escrow.mint, escrow.buyer),
+      sellerTokenAccount: getAssociatedTokenAddressSync(escrow.mint, escrow.seller),
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+  }
+
   // Release escrow
   async releaseEscrow(
     escrowId: PublicKey,
@@ -179,13 +318,14 @@ export class EscrowArbitrationService {
   async createDispute(
     escrowId: PublicKey,
     disputer: PublicKey,
-    reason: string
+    reason: string,
+    reasonCode: ReasonCode = ReasonCode.Other
   ): Promise<string> {
     const [disputePDA] = this.getDisputePDA(escrowId);
     const [configPDA] = this.getConfigPDA();
 
     const tx = await this.program.methods
-      .createDispute(reason)
+      .createDispute(toReasonCodeArg(reasonCode), reason)
       .accounts({
         dispute: disputePDA,
         escrow: escrowId,
@@ -214,6 +354,7 @@ export class EscrowArbitrationService {
       .accounts({
         arbiter: arbiterPDA,
         config: configPDA,
+        arbiterRegistry: this.getArbiterRegistryPDA()[0],
         authority,
         arbiterAccount,
         systemProgram: SystemProgram.programId,
@@ -223,6 +364,22 @@ export class EscrowArbitrationService {
     return tx;
   }
 
+  // Deactivate an arbiter with no open cases and return their stake
+  async withdrawArbiterStake(arbiterAccount: PublicKey): Promise<string> {
+    const [arbiterPDA] = this.getArbiterPDA(arbiterAccount);
+
+    const tx = await this.program.methods
+      .withdrawArbiterStake()
+      .accounts({
+        arbiter: arbiterPDA,
+        arbiterRegistry: this.getArbiterRegistryPDA()[0],
+        arbiterAccount,
+      })
+      .rpc();
+
+    return tx;
+  }
+
   // Resolve dispute
   async resolveDispute(
     disputeId: PublicKey,
@@ -231,20 +388,112 @@ export class EscrowArbitrationService {
     buyer: PublicKey,
     seller: PublicKey,
     decision: DisputeDecision,
-    reasoning: string
+    reasoning: string,
+    resolutionCode: ReasonCode = ReasonCode.Other
   ): Promise<string> {
     const decisionEnum = decision === DisputeDecision.FavorBuyer 
       ? { favorBuyer: {} } 
       : { favorSeller: {} };
+    const [configPDA] = this.getConfigPDA();
+    const config = await this.program.account.escrowConfig.fetch(configPDA);
+    const arbiter = await this.program.account.arbiter.fetch(arbiterId);
+    const dispute = await this.program.account.dispute.fetch(disputeId);
+    // Needed so an overturning appellate ruling can slash the original arbiter
+    const originalArbiter = dispute.originalArbiter
+      ? this.getArbiterPDA(dispute.originalArbiter)[0]
+      : null;
+    const tokenAccounts = await this.getEscrowTokenAccounts(escrowId);
 
     const tx = await this.program.methods
-      .resolveDispute(decisionEnum, reasoning)
+      .resolveDispute(decisionEnum, toReasonCodeArg(resolutionCode), reasoning)
       .accounts({
         dispute: disputeId,
         escrow: escrowId,
         arbiter: arbiterId,
+        config: configPDA,
+        buyer,
+        seller,
+        arbiterAccount: arbiter.pubkey,
+        treasury: config.treasury,
+        originalArbiter,
+        ...tokenAccounts,
+      })
+      .rpc();
+
+    return tx;
+  }
+
+  // Appeal a resolved dispute within the appeal window, posting the configured deposit
+  async appealDispute(
+    disputeId: PublicKey,
+    escrowId: PublicKey,
+    appellant: PublicKey,
+    reason: string
+  ): Promise<string> {
+    const [configPDA] = this.getConfigPDA();
+    const dispute = await this.program.account.dispute.fetch(disputeId);
+    const [originalArbiterPDA] = this.getArbiterPDA(dispute.assignedArbiter);
+
+    const tx = await this.program.methods
+      .appealDispute(reason)
+      .accounts({
+        dispute: disputeId,
+        escrow: escrowId,
+        config: configPDA,
+        originalArbiter: originalArbiterPDA,
+        appellant,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    return tx;
+  }
+
+  // Assign a stake- and reputation-weighted random arbiter to a dispute
+  async assignArbiter(disputeId: PublicKey, escrowId: PublicKey): Promise<string> {
+    // The program samples from the whole registry, so pass every entry in registry order
+    const [registryPDA] = this.getArbiterRegistryPDA();
+    const registry = await this.program.account.arbiterRegistry.fetch(registryPDA);
+    const candidates = (registry.arbiters as PublicKey[])
+      // Writable so the picked arbiter's open case count can be updated
+      .map((pubkey) => ({ pubkey: this.getArbiterPDA(pubkey)[0], isWritable: true, isSigner: false }));
+
+    const tx = await this.program.methods
+      .assignArbiter()
+      .accounts({
+        dispute: disputeId,
+        escrow: escrowId,
+        arbiterRegistry: registryPDA,
+        slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+      })
+      .remainingAccounts(candidates)
+      .rpc();
+
+    return tx;
+  }
+
+  // Execute a ruling after its appeal window closed without an appeal
+  async finalizeDispute(
+    disputeId: PublicKey,
+    escrowId: PublicKey,
+    buyer: PublicKey,
+    seller: PublicKey
+  ): Promise<string> {
+    const [configPDA] = this.getConfigPDA();
+    const tokenAccounts = await this.getEscrowTokenAccounts(escrowId);
+    const dispute = await this.program.account.dispute.fetch(disputeId);
+    const [arbiterPDA] = this.getArbiterPDA(dispute.assignedArbiter);
+
+    const tx = await this.program.methods
+      .finalizeDispute()
+      .accounts({
+        dispute: disputeId,
+        escrow: escrowId,
+        config: configPDA,
+        arbiter: arbiterPDA,
         buyer,
         seller,
+        ...tokenAccounts,
       })
       .rpc();
 
@@ -398,6 +647,20 @@ export class EscrowArbitrationService {
     return this.program.addEventListener('DisputeCreated', callback);
   }
 
+  // Subscribe to every escrow and dispute event that involves a party, as buyer or seller
+  subscribeToPartyNotifications(
+    party: PublicKey,
+    callback: (eventName: string, event: any) => void
+  ): number[] {
+    return PARTY_EVENTS.map((eventName) =>
+      this.program.addEventListener(eventName, (event: any) => {
+        if (event.buyer.equals(party) || event.seller.equals(party)) {
+          callback(eventName, event);
+        }
+      })
+    );
+  }
+
   // Unsubscribe from events
   unsubscribeFromEvents(listenerId: number): void {
     this.program.removeEventListener(listenerId);
@@ -153,6 +153,19 @@ class FraudDetectionService {
         this.programId
       );
 
+      // The recipient's risk registry PDA is always passed; it may not exist if they aren't listed
+      const [riskRegistryPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from('risk_registry'), recipient.toBuffer()],
+        this.programId
+      );
+
+      // Pass the recipient's whitelist entry when there is one, so known-good recipients aren't flagged
+      const [whitelistPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from('whitelist'), recipient.toBuffer()],
+        this.programId
+      );
+      const whitelist = (await this.connection.getAccountInfo(whitelistPDA)) ? whitelistPDA : null;
+
       // Get price oracle account (would be configured)
       const priceOracle = new PublicKey('So11111111111111111111111111111111111111112'); // SOL price feed
 
@@ -167,9 +180,13 @@ class FraudDetectionService {
           complianceConfig: complianceConfigPDA,
           transactionRecord: transactionRecordPDA,
           priceOracle: priceOracle,
+          whitelist,
           authority: this.program.provider.publicKey,
           systemProgram: SystemProgram.programId,
         })
+        .remainingAccounts([
+          { pubkey: riskRegistryPDA, isWritable: false, isSigner: false },
+        ])
         .rpc();
 
       // Fetch the transaction record to get the result
@@ -248,7 +265,9 @@ class FraudDetectionService {
     address: PublicKey,
     riskCategory: 'Sanctions' | 'PEP' | 'HighRiskJurisdiction' | 'KnownScammer' | 'MixerService' | 'DarknetMarket' | 'Ransomware' | 'Other',
     riskLevel: 'Low' | 'Medium' | 'High' | 'Critical',
-    description: string
+    description: string,
+    effectiveFromSlot: number,
+    effectiveUntilSlot: number | null = null
   ): Promise<string> {
     if (!this.program) throw new Error('Program not initialized');
 
@@ -268,7 +287,9 @@ class FraudDetectionService {
           address,
           { [riskCategory.toLowerCase()]: {} },
           { [riskLevel.toLowerCase()]: {} },
-          description
+          description,
+          new BN(effectiveFromSlot),
+          effectiveUntilSlot === null ? null : new BN(effectiveUntilSlot)
         )
         .accounts({
           riskRegistry: riskRegistryPDA,
@@ -352,6 +373,39 @@ class FraudDetectionService {
     }
   }
 
+  /**
+   * Clear a user's fraud flags after manual review (admin only)
+   */
+  async clearUserFlags(userPubkey: PublicKey, resetRiskScore: boolean): Promise<string> {
+    if (!this.program) throw new Error('Program not initialized');
+
+    try {
+      const [userProfilePDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from('user_profile'), userPubkey.toBuffer()],
+        this.programId
+      );
+
+      const [complianceConfigPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from('compliance_config')],
+        this.programId
+      );
+
+      const tx = await this.program.methods
+        .clearUserFlags(resetRiskScore)
+        .accounts({
+          userProfile: userProfilePDA,
+          complianceConfig: complianceConfigPDA,
+          authority: this.program.provider.publicKey,
+        })
+        .rpc();
+
+      return tx;
+    } catch (error) {
+      console.error('Failed to clear user flags:', error);
+      throw error;
+    }
+  }
+
   /**
    * Analyze transaction with AI anomaly detection
    */
@@ -476,7 +530,7 @@ class FraudDetectionService {
    */
   subscribeToFraudEvents(
     callback: (event: {
-      type: 'TransactionFlagged' | 'TransactionBlocked' | 'UserBlocked' | 'AIRiskScoreUpdated';
+      type: 'TransactionFlagged' | 'TransactionBlocked' | 'UserBlocked' | 'AIRiskScoreUpdated' | 'FlagSuppressedByWhitelist';
       data: any;
     }) => void
   ): () => void {
@@ -514,6 +568,15 @@ class FraudDetectionService {
     });
     eventListeners.push(blockedListener);
 
+    // Listen for flags skipped because a party is whitelisted
+    const suppressedListener = this.program.addEventListener('FlagSuppressedByWhitelist', (event) => {
+      callback({
+        type: 'FlagSuppressedByWhitelist',
+        data: event
+      });
+    });
+    eventListeners.push(suppressedListener);
+
     // Return cleanup function
     return () => {
       eventListeners.forEach(listener => {
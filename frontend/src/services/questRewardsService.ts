@@ -12,6 +12,7 @@ export interface UserProfile {
   totalQuestsCompleted: number;
   currentStreak: number;
   longestStreak: number;
+  streakFreezes: number;
   lastActivity: number;
   level: number;
   totalXp: number;
@@ -197,6 +198,7 @@ class QuestRewardsService {
         totalQuestsCompleted: Math.floor(Math.random() * 50),
         currentStreak: Math.floor(Math.random() * 10),
         longestStreak: Math.floor(Math.random() * 20),
+        streakFreezes: 0,
         lastActivity: Date.now() / 1000,
         level: Math.floor(Math.random() * 10) + 1,
         totalXp: Math.floor(Math.random() * 10000),
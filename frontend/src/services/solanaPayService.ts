@@ -86,9 +86,10 @@ class SolanaPayService {
       const provider = await this.initializeProgram(wallet)
       const recipientPubkey = new PublicKey(request.recipient)
       
-      // Create payment account PDA
+      // Create payment account PDA (a per-payment nonce lets one payer hold many escrows)
+      const paymentNonce = new BN(Date.now())
       const [paymentPDA] = await PublicKey.findProgramAddress(
-        [Buffer.from('payment'), wallet.publicKey.toBuffer()],
+        [Buffer.from('payment'), wallet.publicKey.toBuffer(), paymentNonce.toArrayLike(Buffer, 'le', 8)],
         SOLANAPAY_PROGRAM_ID
       )
 